@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccessType {
     Unknown,
     Lookup,
@@ -5,6 +6,10 @@ pub enum AccessType {
     Index
 }
 
+impl AccessType {
+    pub const ALL: [AccessType; 4] = [AccessType::Unknown, AccessType::Lookup, AccessType::Scan, AccessType::Index];
+}
+
 impl Default for AccessType {
     fn default() -> Self {
         AccessType::Unknown
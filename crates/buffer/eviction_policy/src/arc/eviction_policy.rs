@@ -0,0 +1,187 @@
+#[cfg(feature = "tracing")]
+use tracy_client::span;
+
+use buffer_common::{AccessType, FrameId};
+use pages::PageId;
+use crate::arc::ARCOptions;
+use crate::EvictionPolicy;
+use crate::lists::{FrameLists, GhostList};
+use crate::traits::EvictionPolicyCreator;
+
+// Frames that were accessed once since they were loaded (recency)
+const T1: usize = 0;
+
+// Frames that were accessed more than once (frequency)
+const T2: usize = 1;
+
+/**
+ * ARCEvictionPolicy implements the ARC (Adaptive Replacement Cache) replacement policy.
+ *
+ * Frames are split between T1 (pages accessed once) and T2 (pages accessed again), both ordered by LRU.
+ * The page ids of frames evicted from T1 and T2 are remembered in the B1 and B2 ghost lists.
+ *
+ * Loading a page that is in B1 means T1 was too small, so the target size of T1 grows,
+ * loading a page that is in B2 means T2 was too small, so the target size of T1 shrinks.
+ * Eviction takes from T1 when it is larger than its target, otherwise from T2.
+ */
+#[derive(Debug)]
+pub struct ARCEvictionPolicy {
+    frames: FrameLists,
+
+    b1: GhostList,
+    b2: GhostList,
+
+    // Target size of T1
+    p: usize,
+
+    num_frames: usize,
+}
+
+impl ARCEvictionPolicy {
+    fn assert_valid_frame_id(&self, frame_id: FrameId) {
+        assert!(self.frames.is_valid_frame_id(frame_id));
+    }
+
+    /// Target size of T1, only used in tests
+    #[cfg(test)]
+    pub(crate) fn get_target_t1_size(&self) -> usize {
+        self.p
+    }
+
+    fn evict_from(&mut self, list: usize) -> Option<FrameId> {
+        let evicted = self.frames.pop_least_recently_used(list)?;
+
+        if let Some(page_id) = evicted.page_id {
+            if list == T1 {
+                self.b1.push(page_id);
+            } else {
+                self.b2.push(page_id);
+            }
+        }
+
+        self.trim_ghost_lists();
+
+        Some(evicted.frame_id)
+    }
+
+    /// Keep T1 + B1 within the number of frames and all the lists within twice the number of frames
+    fn trim_ghost_lists(&mut self) {
+        while self.frames.len(T1) + self.b1.len() > self.num_frames && self.b1.pop_oldest().is_some() {}
+
+        while self.frames.len(T1) + self.frames.len(T2) + self.b1.len() + self.b2.len() > 2 * self.num_frames && self.b2.pop_oldest().is_some() {}
+    }
+
+    /// Adapt the target size of T1 by the ghost list the page was in
+    ///
+    /// @return true if the page was in one of the ghost lists
+    fn adapt(&mut self, page_id: PageId) -> bool {
+        let b1_len = self.b1.len();
+        let b2_len = self.b2.len();
+
+        if self.b1.remove(page_id) {
+            let delta = (b2_len / b1_len).max(1);
+            self.p = (self.p + delta).min(self.num_frames);
+
+            return true;
+        }
+
+        if self.b2.remove(page_id) {
+            let delta = (b1_len / b2_len).max(1);
+            self.p = self.p.saturating_sub(delta);
+
+            return true;
+        }
+
+        false
+    }
+}
+
+impl EvictionPolicyCreator for ARCEvictionPolicy {
+    type Options = ARCOptions;
+
+    fn new(num_frames: usize, _options: ARCOptions) -> Self {
+        Self {
+            frames: FrameLists::new(num_frames, 2),
+            b1: GhostList::new(num_frames),
+            b2: GhostList::new(num_frames),
+            p: 0,
+            num_frames,
+        }
+    }
+}
+
+impl EvictionPolicy for ARCEvictionPolicy {
+    /// Evict the least recently used evictable frame of T1 if T1 is larger than its target size, otherwise of T2.
+    /// When the chosen list has no evictable frame, the other list is used
+    fn evict(&mut self) -> Option<FrameId> {
+        #[cfg(feature = "tracing")]
+        let _evict = span!("Evict");
+
+        let (first, second) = if self.frames.len(T1) > self.p { (T1, T2) } else { (T2, T1) };
+
+        self.evict_from(first).or_else(|| self.evict_from(second))
+    }
+
+    fn record_access(&mut self, frame_id: FrameId, _access_type: AccessType) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        if self.frames.get_list(frame_id).is_some() {
+            self.frames.move_to_front(frame_id, T2);
+
+            return;
+        }
+
+        let page_id = self.frames.take_next_page(frame_id);
+        let was_evicted_recently = page_id.is_some_and(|page_id| self.adapt(page_id));
+
+        self.frames.insert(frame_id, if was_evicted_recently { T2 } else { T1 }, page_id);
+        self.trim_ghost_lists();
+    }
+
+    fn record_new_page(&mut self, frame_id: FrameId, page_id: PageId) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        self.frames.set_next_page(frame_id, page_id);
+    }
+
+    fn set_evictable(&mut self, frame_id: FrameId, set_evictable: bool) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        unsafe { self.set_evictable_unchecked(frame_id, set_evictable) }
+    }
+
+    unsafe fn set_evictable_unchecked(&mut self, frame_id: FrameId, set_evictable: bool) {
+        self.assert_valid_frame_id(frame_id);
+
+        self.frames.set_evictable(frame_id, set_evictable);
+    }
+
+    /// Remove the frame without remembering its page, the page was deleted so it won't be loaded again
+    fn remove(&mut self, frame_id: FrameId) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        self.frames.remove_if_evictable(frame_id);
+    }
+
+    fn resize(&mut self, num_frames: usize) {
+        self.frames.resize(num_frames);
+        self.b1.set_capacity(num_frames);
+        self.b2.set_capacity(num_frames);
+        self.p = self.p.min(num_frames);
+        self.num_frames = num_frames;
+
+        self.trim_ghost_lists();
+    }
+
+    fn size(&self) -> usize {
+        self.frames.evictable_frames()
+    }
+}
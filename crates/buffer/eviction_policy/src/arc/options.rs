@@ -0,0 +1,9 @@
+/// ARC adapts itself to the workload, the options exist so all the policies are created the same way
+#[derive(Clone, Default)]
+pub struct ARCOptions {}
+
+impl ARCOptions {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
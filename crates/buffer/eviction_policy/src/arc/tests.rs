@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use buffer_common::AccessType;
+    use crate::{ARCEvictionPolicy, ARCOptions, EvictionPolicy};
+    use crate::traits::EvictionPolicyCreator;
+
+    fn load_page(policy: &mut ARCEvictionPolicy, frame_id: i32, page_id: i32) {
+        policy.record_new_page(frame_id, page_id);
+        policy.record_access(frame_id, AccessType::default());
+        policy.set_evictable(frame_id, true);
+    }
+
+    #[test]
+    fn sample() {
+        let mut arc = ARCEvictionPolicy::new(4, ARCOptions::new());
+
+        for frame_id in 0..4 {
+            load_page(&mut arc, frame_id, frame_id + 10);
+        }
+
+        // Frame 0 is accessed again so it moves to T2
+        arc.record_access(0, AccessType::default());
+        assert_eq!(arc.size(), 4);
+
+        // T1 is larger than its target, the least recently used frame of T1 is evicted and remembered in B1
+        assert_eq!(arc.evict(), Some(1));
+        assert_eq!(arc.get_target_t1_size(), 0);
+
+        // Page 11 is in B1, so T1 target size grows and the page goes to T2
+        load_page(&mut arc, 1, 11);
+        assert_eq!(arc.get_target_t1_size(), 1);
+
+        assert_eq!(arc.evict(), Some(2));
+
+        // T1 is at its target size, so the least recently used frame of T2 is evicted and remembered in B2
+        assert_eq!(arc.evict(), Some(0));
+
+        // Page 10 is in B2, so T1 target size shrinks
+        load_page(&mut arc, 0, 10);
+        assert_eq!(arc.get_target_t1_size(), 0);
+
+        assert_eq!(arc.evict(), Some(3));
+        assert_eq!(arc.evict(), Some(1));
+        assert_eq!(arc.evict(), Some(0));
+        assert_eq!(arc.evict(), None);
+    }
+
+    #[test]
+    fn should_not_evict_pinned_frames() {
+        let mut arc = ARCEvictionPolicy::new(3, ARCOptions::new());
+
+        load_page(&mut arc, 0, 10);
+        load_page(&mut arc, 1, 11);
+        arc.set_evictable(0, false);
+        assert_eq!(arc.size(), 1);
+
+        // Removed frames are not evicted
+        arc.remove(1);
+        assert_eq!(arc.size(), 0);
+        assert_eq!(arc.evict(), None);
+
+        arc.set_evictable(0, true);
+        assert_eq!(arc.evict(), Some(0));
+
+        // Invalid frames are ignored
+        arc.record_access(10, AccessType::default());
+        arc.set_evictable(10, true);
+        assert_eq!(arc.size(), 0);
+    }
+
+    #[test]
+    fn resize_should_track_new_frames_and_drop_removed_frames() {
+        let mut arc = ARCEvictionPolicy::new(2, ARCOptions::default());
+
+        load_page(&mut arc, 0, 0);
+        load_page(&mut arc, 1, 1);
+
+        arc.resize(4);
+
+        load_page(&mut arc, 3, 3);
+        assert_eq!(arc.size(), 3);
+
+        assert_eq!(arc.evict(), Some(0));
+        assert_eq!(arc.evict(), Some(1));
+        assert_eq!(arc.evict(), Some(3));
+
+        arc.resize(1);
+
+        load_page(&mut arc, 0, 4);
+        assert_eq!(arc.size(), 1);
+        assert_eq!(arc.evict(), Some(0));
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(feature = "tracing")]
+use tracy_client::span;
+
+use buffer_common::{AccessType, FrameId};
+use crate::clock::ClockOptions;
+use crate::EvictionPolicy;
+use crate::traits::EvictionPolicyCreator;
+
+#[derive(Debug, Clone, Default)]
+struct ClockFrame {
+    present: bool,
+    evictable: bool,
+
+    // Second chance bit, set on access and cleared when the hand passes the frame
+    referenced: bool,
+}
+
+/**
+ * ClockEvictionPolicy implements the CLOCK (second chance) replacement policy.
+ *
+ * The frames are arranged in a circle and a hand moves over them when looking for a victim.
+ * A frame that was accessed since the hand last passed it gets a second chance (its reference bit is cleared),
+ * the first evictable frame without the reference bit is evicted.
+ */
+#[derive(Debug)]
+pub struct ClockEvictionPolicy {
+    frames: Vec<ClockFrame>,
+
+    hand: usize,
+
+    // Tracks the number of evictable frames
+    evictable_frames: usize,
+}
+
+impl ClockEvictionPolicy {
+    fn is_valid_frame_id(&self, frame_id: FrameId) -> bool {
+        frame_id >= 0 && self.frames.len() > frame_id as usize
+    }
+
+    fn assert_valid_frame_id(&self, frame_id: FrameId) {
+        assert!(self.is_valid_frame_id(frame_id));
+    }
+}
+
+impl EvictionPolicyCreator for ClockEvictionPolicy {
+    type Options = ClockOptions;
+
+    fn new(num_frames: usize, _options: ClockOptions) -> Self {
+        Self {
+            frames: vec![ClockFrame::default(); num_frames],
+            hand: 0,
+            evictable_frames: 0,
+        }
+    }
+}
+
+impl EvictionPolicy for ClockEvictionPolicy {
+    /// Move the hand until reaching an evictable frame without the reference bit,
+    /// clearing the reference bit of the evictable frames on the way
+    ///
+    /// returns: Option<FrameId> `None` if no frame to evict or `Some(FrameId)` with the frame that
+    ///          got evicted
+    ///
+    fn evict(&mut self) -> Option<FrameId> {
+        #[cfg(feature = "tracing")]
+        let _evict = span!("Evict");
+
+        if self.evictable_frames == 0 {
+            return None;
+        }
+
+        // There is an evictable frame, so it is found in at most 2 rounds
+        loop {
+            let frame_id = self.hand;
+            self.hand = (self.hand + 1) % self.frames.len();
+
+            let frame = &mut self.frames[frame_id];
+
+            if !frame.present || !frame.evictable {
+                continue;
+            }
+
+            if frame.referenced {
+                frame.referenced = false;
+                continue;
+            }
+
+            *frame = ClockFrame::default();
+            self.evictable_frames -= 1;
+
+            return Some(frame_id as FrameId);
+        }
+    }
+
+    fn record_access(&mut self, frame_id: FrameId, _access_type: AccessType) {
+        if !self.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        let frame = &mut self.frames[frame_id as usize];
+
+        // New frame is not evictable by default
+        frame.present = true;
+        frame.referenced = true;
+    }
+
+    fn set_evictable(&mut self, frame_id: FrameId, set_evictable: bool) {
+        if !self.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        unsafe { self.set_evictable_unchecked(frame_id, set_evictable) }
+    }
+
+    unsafe fn set_evictable_unchecked(&mut self, frame_id: FrameId, set_evictable: bool) {
+        self.assert_valid_frame_id(frame_id);
+
+        let frame = &mut self.frames[frame_id as usize];
+
+        // if missing - nothing to do, if evictable status is the same - Nothing to change
+        if !frame.present || frame.evictable == set_evictable {
+            return;
+        }
+
+        frame.evictable = set_evictable;
+
+        if set_evictable {
+            self.evictable_frames += 1;
+        } else {
+            self.evictable_frames -= 1;
+        }
+    }
+
+    fn remove(&mut self, frame_id: FrameId) {
+        if !self.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        let frame = &mut self.frames[frame_id as usize];
+
+        if !frame.present || !frame.evictable {
+            return;
+        }
+
+        *frame = ClockFrame::default();
+        self.evictable_frames -= 1;
+    }
+
+    fn resize(&mut self, num_frames: usize) {
+        debug_assert!(self.frames.iter().skip(num_frames).all(|frame| !frame.present), "removed frames must not be tracked");
+
+        self.frames.resize(num_frames, ClockFrame::default());
+
+        if self.hand >= num_frames {
+            self.hand = 0;
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.evictable_frames
+    }
+}
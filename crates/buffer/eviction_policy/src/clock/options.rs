@@ -0,0 +1,9 @@
+/// CLOCK has no tuning, the options exist so all the policies are created the same way
+#[derive(Clone, Default)]
+pub struct ClockOptions {}
+
+impl ClockOptions {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
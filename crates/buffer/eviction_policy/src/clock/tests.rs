@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use buffer_common::AccessType;
+    use crate::{ClockEvictionPolicy, ClockOptions, EvictionPolicy};
+    use crate::traits::EvictionPolicyCreator;
+
+    #[test]
+    fn sample() {
+        let mut clock = ClockEvictionPolicy::new(5, ClockOptions::new());
+
+        for frame_id in 0..5 {
+            clock.record_access(frame_id, AccessType::default());
+        }
+
+        for frame_id in 0..4 {
+            clock.set_evictable(frame_id, true);
+        }
+        assert_eq!(clock.size(), 4);
+
+        // All the frames are referenced, so the hand clears all of them and evicts the first evictable frame
+        assert_eq!(clock.evict(), Some(0));
+        assert_eq!(clock.size(), 3);
+
+        // Frame 1 gets a second chance
+        clock.record_access(1, AccessType::default());
+        assert_eq!(clock.evict(), Some(2));
+        assert_eq!(clock.evict(), Some(3));
+        assert_eq!(clock.evict(), Some(1));
+        assert_eq!(clock.size(), 0);
+
+        // Frame 4 is not evictable
+        assert_eq!(clock.evict(), None);
+
+        clock.set_evictable(4, true);
+        assert_eq!(clock.evict(), Some(4));
+        assert_eq!(clock.evict(), None);
+    }
+
+    #[test]
+    fn remove_should_only_remove_evictable_frames() {
+        let mut clock = ClockEvictionPolicy::new(3, ClockOptions::new());
+
+        clock.record_access(0, AccessType::default());
+        clock.record_access(1, AccessType::default());
+        clock.set_evictable(0, true);
+
+        clock.remove(1);
+        clock.remove(2);
+        assert_eq!(clock.size(), 1);
+
+        clock.remove(0);
+        assert_eq!(clock.size(), 0);
+        assert_eq!(clock.evict(), None);
+
+        // Invalid frames are ignored
+        clock.record_access(10, AccessType::default());
+        clock.set_evictable(10, true);
+        assert_eq!(clock.size(), 0);
+    }
+
+    #[test]
+    fn resize_should_track_new_frames_and_drop_removed_frames() {
+        let mut clock = ClockEvictionPolicy::new(2, ClockOptions::new());
+
+        for frame_id in 0..2 {
+            clock.record_access(frame_id, AccessType::default());
+            clock.set_evictable(frame_id, true);
+        }
+
+        clock.resize(4);
+
+        clock.record_access(3, AccessType::default());
+        clock.set_evictable(3, true);
+        assert_eq!(clock.size(), 3);
+
+        assert_eq!(clock.evict(), Some(0));
+        assert_eq!(clock.evict(), Some(1));
+        assert_eq!(clock.evict(), Some(3));
+
+        // The hand is past the removed frames
+        clock.resize(1);
+
+        clock.record_access(0, AccessType::default());
+        clock.set_evictable(0, true);
+        assert_eq!(clock.size(), 1);
+        assert_eq!(clock.evict(), Some(0));
+    }
+}
@@ -1,23 +1,29 @@
-use crate::{EvictionPolicy, LRUKEvictionPolicy};
+use crate::{ARCEvictionPolicy, ARCOptions, ClockEvictionPolicy, ClockOptions, EvictionPolicy, LRUKEvictionPolicy, TwoQEvictionPolicy, TwoQOptions};
 use crate::lru_k::LRUKOptions;
 use crate::traits::EvictionPolicyCreator;
 
+#[derive(Clone)]
 pub enum EvictionPoliciesTypes {
     #[allow(non_camel_case_types)]
-    LRU_K(LRUKOptions)
+    LRU_K(LRUKOptions),
+    CLOCK(ClockOptions),
+    #[allow(non_camel_case_types)]
+    TWO_Q(TwoQOptions),
+    ARC(ARCOptions),
 }
 
 impl EvictionPoliciesTypes {
     pub fn create_policy(self, number_of_frames: usize) -> Box<dyn EvictionPolicy> {
-        Box::new(
-            match self {
-                EvictionPoliciesTypes::LRU_K(options) => LRUKEvictionPolicy::new(number_of_frames, options)
-            }
-        )
+        match self {
+            EvictionPoliciesTypes::LRU_K(options) => Box::new(LRUKEvictionPolicy::new(number_of_frames, options)),
+            EvictionPoliciesTypes::CLOCK(options) => Box::new(ClockEvictionPolicy::new(number_of_frames, options)),
+            EvictionPoliciesTypes::TWO_Q(options) => Box::new(TwoQEvictionPolicy::new(number_of_frames, options)),
+            EvictionPoliciesTypes::ARC(options) => Box::new(ARCEvictionPolicy::new(number_of_frames, options)),
+        }
     }
 
-    pub fn get_creator(self) -> Box<dyn FnOnce(usize) -> Box<dyn EvictionPolicy>> {
-        Box::new(|number_of_frames| self.create_policy(number_of_frames))
+    pub fn get_creator(self) -> Box<dyn Fn(usize) -> Box<dyn EvictionPolicy>> {
+        Box::new(move |number_of_frames| self.clone().create_policy(number_of_frames))
     }
 }
 
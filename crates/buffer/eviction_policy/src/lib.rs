@@ -1,7 +1,14 @@
 mod traits;
 mod lru_k;
+mod clock;
+mod two_q;
+mod arc;
+mod lists;
 mod eviction_policies_types;
 
 pub use traits::{EvictionPolicy, EvictionPolicyCreator};
 pub use lru_k::{LRUKEvictionPolicy, LRUKOptions};
+pub use clock::{ClockEvictionPolicy, ClockOptions};
+pub use two_q::{TwoQEvictionPolicy, TwoQOptions};
+pub use arc::{ARCEvictionPolicy, ARCOptions};
 pub use eviction_policies_types::EvictionPoliciesTypes;
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use buffer_common::FrameId;
+use pages::PageId;
+
+#[derive(Debug, Clone)]
+struct FrameEntry {
+    list: usize,
+
+    // Position of the frame in its list, frames with lower value were used less recently
+    last_use: u64,
+
+    evictable: bool,
+
+    page_id: Option<PageId>,
+}
+
+/// Frame that was taken out of its list
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EvictedFrame {
+    pub(crate) frame_id: FrameId,
+
+    pub(crate) list: usize,
+
+    /// The page in the frame if it was recorded by `set_next_page`
+    pub(crate) page_id: Option<PageId>,
+}
+
+/// Frames of a policy that keeps every frame in one of multiple lists ordered by use (like 2Q and ARC)
+///
+/// Each list keeps its evictable frames ordered so the least recently used evictable frame is found without scanning
+/// the frames that are pinned
+#[derive(Debug)]
+pub(crate) struct FrameLists {
+    frames: Vec<Option<FrameEntry>>,
+
+    // The page that the frame is about to hold, set before the frame first access
+    next_pages: Vec<Option<PageId>>,
+
+    // Evictable frames of each list ordered by their last use
+    evictable: Vec<BTreeMap<u64, FrameId>>,
+
+    // Number of frames in each list including the frames that are not evictable
+    lengths: Vec<usize>,
+
+    evictable_frames: usize,
+
+    use_counter: u64,
+}
+
+impl FrameLists {
+    pub(crate) fn new(number_of_frames: usize, number_of_lists: usize) -> Self {
+        FrameLists {
+            frames: vec![None; number_of_frames],
+            next_pages: vec![None; number_of_frames],
+            evictable: vec![BTreeMap::new(); number_of_lists],
+            lengths: vec![0; number_of_lists],
+            evictable_frames: 0,
+            use_counter: 0,
+        }
+    }
+
+    /// Change the number of frames, the removed frames must not be tracked
+    pub(crate) fn resize(&mut self, number_of_frames: usize) {
+        debug_assert!(self.frames.iter().skip(number_of_frames).all(|entry| entry.is_none()), "removed frames must not be tracked");
+
+        self.frames.resize(number_of_frames, None);
+        self.next_pages.resize(number_of_frames, None);
+    }
+
+    pub(crate) fn is_valid_frame_id(&self, frame_id: FrameId) -> bool {
+        frame_id >= 0 && (frame_id as usize) < self.frames.len()
+    }
+
+    /// @return the list of the frame or None if the frame is not tracked
+    pub(crate) fn get_list(&self, frame_id: FrameId) -> Option<usize> {
+        self.frames[frame_id as usize].as_ref().map(|entry| entry.list)
+    }
+
+    pub(crate) fn set_next_page(&mut self, frame_id: FrameId, page_id: PageId) {
+        self.next_pages[frame_id as usize] = Some(page_id);
+    }
+
+    pub(crate) fn take_next_page(&mut self, frame_id: FrameId) -> Option<PageId> {
+        self.next_pages[frame_id as usize].take()
+    }
+
+    /// Add a non evictable frame as the most recently used frame of the list
+    pub(crate) fn insert(&mut self, frame_id: FrameId, list: usize, page_id: Option<PageId>) {
+        debug_assert!(self.frames[frame_id as usize].is_none(), "frame {} is already tracked", frame_id);
+
+        self.use_counter += 1;
+        self.lengths[list] += 1;
+
+        self.frames[frame_id as usize] = Some(FrameEntry {
+            list,
+            last_use: self.use_counter,
+            evictable: false,
+            page_id,
+        });
+    }
+
+    /// Move the frame to be the most recently used frame of the list
+    pub(crate) fn move_to_front(&mut self, frame_id: FrameId, list: usize) {
+        self.use_counter += 1;
+        let use_counter = self.use_counter;
+
+        let entry = self.frames[frame_id as usize].as_mut().expect("frame must be tracked");
+
+        if entry.evictable {
+            self.evictable[entry.list].remove(&entry.last_use);
+            self.evictable[list].insert(use_counter, frame_id);
+        }
+
+        self.lengths[entry.list] -= 1;
+        self.lengths[list] += 1;
+
+        entry.list = list;
+        entry.last_use = use_counter;
+    }
+
+    /// @return true if the evictable state changed
+    pub(crate) fn set_evictable(&mut self, frame_id: FrameId, evictable: bool) -> bool {
+        let Some(entry) = self.frames[frame_id as usize].as_mut() else {
+            return false;
+        };
+
+        if entry.evictable == evictable {
+            return false;
+        }
+
+        entry.evictable = evictable;
+
+        if evictable {
+            self.evictable[entry.list].insert(entry.last_use, frame_id);
+            self.evictable_frames += 1;
+        } else {
+            self.evictable[entry.list].remove(&entry.last_use);
+            self.evictable_frames -= 1;
+        }
+
+        true
+    }
+
+    /// Take the least recently used evictable frame of the list
+    pub(crate) fn pop_least_recently_used(&mut self, list: usize) -> Option<EvictedFrame> {
+        let (_, frame_id) = self.evictable[list].pop_first()?;
+
+        self.evictable_frames -= 1;
+        self.lengths[list] -= 1;
+
+        let entry = self.frames[frame_id as usize].take().expect("evictable frame must be tracked");
+
+        Some(EvictedFrame {
+            frame_id,
+            list,
+            page_id: entry.page_id,
+        })
+    }
+
+    /// Remove the frame if it is evictable
+    pub(crate) fn remove_if_evictable(&mut self, frame_id: FrameId) -> Option<EvictedFrame> {
+        if !self.frames[frame_id as usize].as_ref().is_some_and(|entry| entry.evictable) {
+            return None;
+        }
+
+        let entry = self.frames[frame_id as usize].take().expect("frame must be tracked");
+
+        self.evictable[entry.list].remove(&entry.last_use);
+        self.evictable_frames -= 1;
+        self.lengths[entry.list] -= 1;
+
+        Some(EvictedFrame {
+            frame_id,
+            list: entry.list,
+            page_id: entry.page_id,
+        })
+    }
+
+    /// Number of frames in the list including the frames that are not evictable
+    pub(crate) fn len(&self, list: usize) -> usize {
+        self.lengths[list]
+    }
+
+    pub(crate) fn has_evictable(&self, list: usize) -> bool {
+        !self.evictable[list].is_empty()
+    }
+
+    pub(crate) fn evictable_frames(&self) -> usize {
+        self.evictable_frames
+    }
+}
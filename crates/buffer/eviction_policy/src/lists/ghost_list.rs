@@ -0,0 +1,74 @@
+use std::collections::{BTreeMap, HashMap};
+use pages::PageId;
+
+/// History of recently evicted pages (without their data), the oldest pages are dropped when the list is full
+#[derive(Debug)]
+pub(crate) struct GhostList {
+    capacity: usize,
+
+    // Pages by the order they were added
+    order: BTreeMap<u64, PageId>,
+
+    pages: HashMap<PageId, u64>,
+
+    counter: u64,
+}
+
+impl GhostList {
+    pub(crate) fn new(capacity: usize) -> Self {
+        GhostList {
+            capacity,
+            order: BTreeMap::new(),
+            pages: HashMap::with_capacity(capacity),
+            counter: 0,
+        }
+    }
+
+    /// Change the number of pages to remember, the oldest pages are dropped if there are too many
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+
+        while self.len() > self.capacity {
+            self.pop_oldest();
+        }
+    }
+
+    pub(crate) fn push(&mut self, page_id: PageId) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.remove(page_id);
+
+        self.counter += 1;
+        self.order.insert(self.counter, page_id);
+        self.pages.insert(page_id, self.counter);
+
+        while self.len() > self.capacity {
+            self.pop_oldest();
+        }
+    }
+
+    /// @return true if the page was in the list
+    pub(crate) fn remove(&mut self, page_id: PageId) -> bool {
+        match self.pages.remove(&page_id) {
+            Some(counter) => {
+                self.order.remove(&counter);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn pop_oldest(&mut self) -> Option<PageId> {
+        let (_, page_id) = self.order.pop_first()?;
+        self.pages.remove(&page_id);
+
+        Some(page_id)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.pages.len()
+    }
+}
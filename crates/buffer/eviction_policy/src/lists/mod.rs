@@ -0,0 +1,5 @@
+mod frame_lists;
+mod ghost_list;
+
+pub(crate) use frame_lists::FrameLists;
+pub(crate) use ghost_list::GhostList;
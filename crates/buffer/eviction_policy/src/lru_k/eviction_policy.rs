@@ -237,6 +237,23 @@ impl EvictionPolicy for LRUKEvictionPolicy {
         self.evictable_frames -= removed as usize;
     }
 
+    fn resize(&mut self, num_frames: usize) {
+        self.store.resize(num_frames);
+        self.replacer_size = num_frames;
+    }
+
+    /// The last k accesses of the frame
+    fn get_access_history(&self, frame_id: FrameId) -> Vec<u64> {
+        if !self.is_valid_frame_id(frame_id) {
+            return vec![];
+        }
+
+        self.store
+            .get_existing_node(frame_id)
+            .map(|node| node.get_history().map(|&record| record as u64).collect())
+            .unwrap_or_default()
+    }
+
     /// Replacer's size, which tracks the number of evictable frames.
     ///
     /// returns: isize the number of evictable frames
@@ -106,6 +106,11 @@ impl LRUKNode {
         }
     }
 
+    /// The recorded accesses, from the least recent
+    pub(super) fn get_history(&self) -> impl Iterator<Item = &HistoryRecord> {
+        self.history.iter()
+    }
+
     #[inline]
     pub(super) fn is_evictable(&self) -> bool {
         self.heap_pos != NO_HEAP_POS
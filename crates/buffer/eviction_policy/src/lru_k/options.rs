@@ -2,6 +2,7 @@
 /// lookback window for lru-k replacer
 pub const LRUK_REPLACER_K: usize = 10;
 
+#[derive(Clone)]
 pub struct LRUKOptions {
     pub(super) k: usize,
 }
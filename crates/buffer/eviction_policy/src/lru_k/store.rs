@@ -25,6 +25,8 @@ pub(super) struct Store {
     next_frame_to_evict_heap: Vec<FrameId>,
 
     all: Vec<LRUKNode>,
+
+    k: usize,
     _not_sync: PhantomData<std::cell::Cell<()>>,
 }
 
@@ -38,6 +40,7 @@ impl Store {
             can_use: BitVec::from_elem(capacity, false),
             next_frame_to_evict_heap: Vec::with_capacity(capacity),
             all: vec![LRUKNode::create_invalid(k); capacity],
+            k,
             _not_sync: PhantomData::default(),
         }
     }
@@ -53,6 +56,19 @@ impl Store {
         frames
     }
 
+    /// Change the number of frames, the removed frames must not exist
+    pub fn resize(&mut self, capacity: usize) {
+        debug_assert!((capacity..self.all.len()).all(|frame_id| !self.can_use[frame_id]), "removed frames must not exist");
+
+        if capacity > self.all.len() {
+            self.can_use.grow(capacity - self.all.len(), false);
+        } else {
+            self.can_use.truncate(capacity);
+        }
+
+        self.all.resize(capacity, LRUKNode::create_invalid(self.k));
+    }
+
     pub fn add_non_evictable_node(&mut self, frame_id: FrameId, history_access_counter: &mut HistoryRecordProducer) {
         let node = &mut self.all[frame_id as usize];
         node.reuse(history_access_counter);
@@ -86,6 +102,14 @@ impl Store {
         self.all[frame_id as usize].is_evictable()
     }
 
+    pub fn get_existing_node(&self, frame_id: FrameId) -> Option<&LRUKNode> {
+        if !self.can_use[frame_id as usize] {
+            return None;
+        }
+
+        Some(&self.all[frame_id as usize])
+    }
+
     #[inline(always)]
     pub fn get_node(&mut self, frame_id: FrameId) -> Option<&mut LRUKNode> {
         if !self.can_use[frame_id as usize] {
@@ -253,4 +253,51 @@ mod tests {
 
         assert!(lru_replacer.lock().size() <= 1); // Either evicted or recorded
     }
+
+    #[test]
+    fn resize_should_track_new_frames_and_drop_removed_frames() {
+        let mut lru_replacer = LRUKEvictionPolicy::new(2, LRUKOptions::new(2));
+
+        for frame_id in 0..2 {
+            lru_replacer.record_access(frame_id, AccessType::default());
+            lru_replacer.set_evictable(frame_id, true);
+        }
+
+        lru_replacer.resize(4);
+
+        lru_replacer.record_access(3, AccessType::default());
+        lru_replacer.set_evictable(3, true);
+        assert_eq!(lru_replacer.size(), 3);
+
+        assert_eq!(lru_replacer.evict(), Some(0));
+        assert_eq!(lru_replacer.evict(), Some(1));
+        assert_eq!(lru_replacer.evict(), Some(3));
+
+        lru_replacer.resize(1);
+
+        lru_replacer.record_access(0, AccessType::default());
+        lru_replacer.set_evictable(0, true);
+        assert_eq!(lru_replacer.size(), 1);
+        assert_eq!(lru_replacer.evict(), Some(0));
+    }
+
+    #[test]
+    fn access_history_should_keep_the_last_k_accesses_in_order() {
+        let mut lru_replacer = LRUKEvictionPolicy::new(3, LRUKOptions::new(2));
+
+        // Accesses: 0, 1, 0, 0
+        lru_replacer.record_access(0, AccessType::default());
+        lru_replacer.record_access(1, AccessType::default());
+        lru_replacer.record_access(0, AccessType::default());
+        lru_replacer.record_access(0, AccessType::default());
+
+        assert_eq!(lru_replacer.get_access_history(0), vec![3, 4]);
+        assert_eq!(lru_replacer.get_access_history(1), vec![2]);
+        assert_eq!(lru_replacer.get_access_history(2), Vec::<u64>::new(), "missing frame should not have history");
+        assert_eq!(lru_replacer.get_access_history(5), Vec::<u64>::new(), "invalid frame should not have history");
+
+        lru_replacer.set_evictable(1, true);
+        assert_eq!(lru_replacer.evict(), Some(1));
+        assert_eq!(lru_replacer.get_access_history(1), Vec::<u64>::new(), "evicted frame should not have history");
+    }
 }
@@ -1,4 +1,5 @@
 use buffer_common::{AccessType, FrameId};
+use pages::PageId;
 
 pub trait EvictionPolicyCreator {
     /// The specific eviction policy options
@@ -44,6 +45,21 @@ pub trait EvictionPolicy: Send {
     ///
     fn record_access(&mut self, frame_id: FrameId, access_type: AccessType);
 
+    /// Record that the frame is about to hold a page that was not in the buffer pool,
+    /// this is called before the first access of the frame.
+    ///
+    /// Policies that keep history of evicted pages use it to detect a page that is needed again
+    /// shortly after it was evicted, the default implementation ignores it.
+    ///
+    /// If frame id is invalid nothing is done
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_id`: id of the frame that the page is loaded into
+    /// * `page_id`: id of the page that is loaded
+    ///
+    fn record_new_page(&mut self, _frame_id: FrameId, _page_id: PageId) {}
+
     /// Toggle whether a frame is evictable or non-evictable. This function also
     /// controls replacer's size. Note that size is equal to number of evictable entries.
     ///
@@ -94,6 +110,33 @@ pub trait EvictionPolicy: Send {
     ///
     fn remove(&mut self, frame_id: FrameId);
 
+    /// Change the number of frames the policy will be required to store.
+    ///
+    /// When shrinking, the removed frames (frame id that is not smaller than `num_frames`)
+    /// must not be tracked by the policy anymore, the caller should `remove` them first.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_frames`: the new number of frames
+    ///
+    fn resize(&mut self, num_frames: usize);
+
+    /// Get the access history of the frame, from the least recent access.
+    ///
+    /// The values only order the accesses between the frames of the same policy,
+    /// it is used to restore the hot pages after restart by replaying the accesses in that order.
+    /// The default implementation has no history.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_id`: id of the frame to get the history of
+    ///
+    /// returns: Vec<u64> the access history, empty if the frame is missing or the policy does not keep history
+    ///
+    fn get_access_history(&self, _frame_id: FrameId) -> Vec<u64> {
+        vec![]
+    }
+
     /// Replacer's size, which tracks the number of evictable frames.
     ///
     /// returns: isize the number of evictable frames
@@ -0,0 +1,141 @@
+#[cfg(feature = "tracing")]
+use tracy_client::span;
+
+use buffer_common::{AccessType, FrameId};
+use pages::PageId;
+use crate::EvictionPolicy;
+use crate::lists::{FrameLists, GhostList};
+use crate::traits::EvictionPolicyCreator;
+use crate::two_q::TwoQOptions;
+
+// FIFO of frames that were loaded and not accessed again
+const A1_IN: usize = 0;
+
+// LRU of frames that were accessed again
+const AM: usize = 1;
+
+/**
+ * TwoQEvictionPolicy implements the 2Q replacement policy.
+ *
+ * New pages enter the A1in FIFO queue, accesses while in A1in are treated as correlated and don't promote the page.
+ * Pages evicted from A1in are remembered in the A1out queue (only their page id),
+ * a page that is loaded again while in A1out is hot and goes to the Am LRU queue.
+ *
+ * Frames are evicted from A1in while it is larger than its target size, otherwise from Am.
+ * This keeps scans from flushing the hot pages out of the buffer pool.
+ */
+#[derive(Debug)]
+pub struct TwoQEvictionPolicy {
+    frames: FrameLists,
+
+    a1_out: GhostList,
+
+    a1_in_size: usize,
+
+    options: TwoQOptions,
+}
+
+impl TwoQEvictionPolicy {
+    fn assert_valid_frame_id(&self, frame_id: FrameId) {
+        assert!(self.frames.is_valid_frame_id(frame_id));
+    }
+
+    fn evict_from(&mut self, list: usize) -> Option<FrameId> {
+        let evicted = self.frames.pop_least_recently_used(list)?;
+
+        if let (A1_IN, Some(page_id)) = (evicted.list, evicted.page_id) {
+            self.a1_out.push(page_id);
+        }
+
+        Some(evicted.frame_id)
+    }
+}
+
+impl EvictionPolicyCreator for TwoQEvictionPolicy {
+    type Options = TwoQOptions;
+
+    fn new(num_frames: usize, options: TwoQOptions) -> Self {
+        Self {
+            frames: FrameLists::new(num_frames, 2),
+            a1_out: GhostList::new(num_frames * options.a1_out_percent / 100),
+            a1_in_size: (num_frames * options.a1_in_percent / 100).max(1),
+            options,
+        }
+    }
+}
+
+impl EvictionPolicy for TwoQEvictionPolicy {
+    /// Evict the oldest evictable frame of A1in if it is over its target size, otherwise the least recently used of Am.
+    /// When the chosen queue has no evictable frame, the other queue is used
+    fn evict(&mut self) -> Option<FrameId> {
+        #[cfg(feature = "tracing")]
+        let _evict = span!("Evict");
+
+        let (first, second) = if self.frames.len(A1_IN) > self.a1_in_size || !self.frames.has_evictable(AM) {
+            (A1_IN, AM)
+        } else {
+            (AM, A1_IN)
+        };
+
+        self.evict_from(first).or_else(|| self.evict_from(second))
+    }
+
+    fn record_access(&mut self, frame_id: FrameId, _access_type: AccessType) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        match self.frames.get_list(frame_id) {
+            // Correlated access, the frame stays in its place in the FIFO
+            Some(A1_IN) => {}
+            Some(_) => self.frames.move_to_front(frame_id, AM),
+            None => {
+                let page_id = self.frames.take_next_page(frame_id);
+                let was_evicted_recently = page_id.is_some_and(|page_id| self.a1_out.remove(page_id));
+
+                self.frames.insert(frame_id, if was_evicted_recently { AM } else { A1_IN }, page_id);
+            }
+        }
+    }
+
+    fn record_new_page(&mut self, frame_id: FrameId, page_id: PageId) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        self.frames.set_next_page(frame_id, page_id);
+    }
+
+    fn set_evictable(&mut self, frame_id: FrameId, set_evictable: bool) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        unsafe { self.set_evictable_unchecked(frame_id, set_evictable) }
+    }
+
+    unsafe fn set_evictable_unchecked(&mut self, frame_id: FrameId, set_evictable: bool) {
+        self.assert_valid_frame_id(frame_id);
+
+        self.frames.set_evictable(frame_id, set_evictable);
+    }
+
+    /// Remove the frame without remembering its page, the page was deleted so it won't be loaded again
+    fn remove(&mut self, frame_id: FrameId) {
+        if !self.frames.is_valid_frame_id(frame_id) {
+            return;
+        }
+
+        self.frames.remove_if_evictable(frame_id);
+    }
+
+    fn resize(&mut self, num_frames: usize) {
+        self.frames.resize(num_frames);
+        self.a1_out.set_capacity(num_frames * self.options.a1_out_percent / 100);
+        self.a1_in_size = (num_frames * self.options.a1_in_percent / 100).max(1);
+    }
+
+    fn size(&self) -> usize {
+        self.frames.evictable_frames()
+    }
+}
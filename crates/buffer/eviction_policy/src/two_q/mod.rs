@@ -0,0 +1,6 @@
+mod tests;
+mod eviction_policy;
+mod options;
+
+pub use eviction_policy::TwoQEvictionPolicy;
+pub use options::TwoQOptions;
@@ -0,0 +1,34 @@
+/// Size of the A1in queue out of the number of frames, as recommended by the 2Q paper
+pub const TWO_Q_A1_IN_PERCENT: usize = 25;
+
+/// Number of evicted pages the A1out queue remembers out of the number of frames, as recommended by the 2Q paper
+pub const TWO_Q_A1_OUT_PERCENT: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct TwoQOptions {
+    pub(super) a1_in_percent: usize,
+    pub(super) a1_out_percent: usize,
+}
+
+impl TwoQOptions {
+    /// # Arguments
+    ///
+    /// * `a1_in_percent`: the size of the FIFO queue of pages that were accessed once, as percent of the frames
+    /// * `a1_out_percent`: the number of pages evicted from the FIFO queue to remember, as percent of the frames
+    ///
+    pub fn new(a1_in_percent: usize, a1_out_percent: usize) -> Self {
+        Self {
+            a1_in_percent,
+            a1_out_percent,
+        }
+    }
+}
+
+impl Default for TwoQOptions {
+    fn default() -> Self {
+        Self {
+            a1_in_percent: TWO_Q_A1_IN_PERCENT,
+            a1_out_percent: TWO_Q_A1_OUT_PERCENT,
+        }
+    }
+}
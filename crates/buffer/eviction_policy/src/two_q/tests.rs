@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use buffer_common::AccessType;
+    use crate::{EvictionPolicy, TwoQEvictionPolicy, TwoQOptions};
+    use crate::traits::EvictionPolicyCreator;
+
+    fn load_page(policy: &mut TwoQEvictionPolicy, frame_id: i32, page_id: i32) {
+        policy.record_new_page(frame_id, page_id);
+        policy.record_access(frame_id, AccessType::default());
+        policy.set_evictable(frame_id, true);
+    }
+
+    #[test]
+    fn page_loaded_again_after_eviction_should_survive_scan() {
+        // A1in target size is 1 frame and A1out remembers 2 pages
+        let mut two_q = TwoQEvictionPolicy::new(4, TwoQOptions::default());
+
+        for frame_id in 0..4 {
+            load_page(&mut two_q, frame_id, frame_id + 10);
+        }
+        assert_eq!(two_q.size(), 4);
+
+        // A1in is over its target size, so the oldest frame is evicted
+        assert_eq!(two_q.evict(), Some(0));
+
+        // Page 10 is remembered in A1out, so it is hot and goes to Am
+        load_page(&mut two_q, 0, 10);
+
+        assert_eq!(two_q.evict(), Some(1));
+        assert_eq!(two_q.evict(), Some(2));
+
+        // A1in is at its target size, so the frame is taken from Am
+        assert_eq!(two_q.evict(), Some(0));
+        assert_eq!(two_q.evict(), Some(3));
+        assert_eq!(two_q.evict(), None);
+        assert_eq!(two_q.size(), 0);
+    }
+
+    #[test]
+    fn correlated_access_should_not_promote_frame() {
+        let mut two_q = TwoQEvictionPolicy::new(4, TwoQOptions::default());
+
+        load_page(&mut two_q, 0, 10);
+        load_page(&mut two_q, 1, 11);
+
+        // Accessing the frame while it is in A1in keeps its place in the FIFO
+        two_q.record_access(0, AccessType::default());
+
+        assert_eq!(two_q.evict(), Some(0));
+        assert_eq!(two_q.evict(), Some(1));
+    }
+
+    #[test]
+    fn should_not_evict_pinned_frames() {
+        let mut two_q = TwoQEvictionPolicy::new(3, TwoQOptions::default());
+
+        load_page(&mut two_q, 0, 10);
+        load_page(&mut two_q, 1, 11);
+        two_q.set_evictable(0, false);
+        assert_eq!(two_q.size(), 1);
+
+        // Removed frames are not evicted
+        two_q.remove(1);
+        assert_eq!(two_q.size(), 0);
+        assert_eq!(two_q.evict(), None);
+
+        two_q.set_evictable(0, true);
+        assert_eq!(two_q.evict(), Some(0));
+
+        // Invalid frames are ignored
+        two_q.record_access(10, AccessType::default());
+        two_q.set_evictable(10, true);
+        assert_eq!(two_q.size(), 0);
+    }
+
+    #[test]
+    fn resize_should_track_new_frames_and_drop_removed_frames() {
+        let mut two_q = TwoQEvictionPolicy::new(2, TwoQOptions::default());
+
+        load_page(&mut two_q, 0, 0);
+        load_page(&mut two_q, 1, 1);
+
+        two_q.resize(4);
+
+        load_page(&mut two_q, 3, 3);
+        assert_eq!(two_q.size(), 3);
+
+        assert_eq!(two_q.evict(), Some(0));
+        assert_eq!(two_q.evict(), Some(1));
+        assert_eq!(two_q.evict(), Some(3));
+
+        two_q.resize(1);
+
+        load_page(&mut two_q, 0, 4);
+        assert_eq!(two_q.size(), 1);
+        assert_eq!(two_q.evict(), Some(0));
+    }
+}
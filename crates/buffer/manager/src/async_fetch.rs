@@ -0,0 +1,184 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use buffer_common::AccessType;
+use common::{Future, Promise};
+use pages::{Page, PageAndReadGuard, PageAndWriteGuard, PageId};
+use crate::{errors, BufferPoolManager, PageReadGuard, PageWriteGuard};
+
+/// Result of a page request that is running in the background
+///
+/// Dropping the future without waiting releases the page once the request is finished
+#[must_use = "if unused the requested page is released once fetched"]
+pub struct PageFuture<T> {
+    // Taken when waited for, so drop knows whether the request must still be completed
+    request: Option<PageRequest<T>>,
+}
+
+enum PageRequest<T> {
+    /// Request that already finished
+    Ready(T),
+
+    /// Request that runs in a background thread
+    Thread(Future<T>),
+
+    /// Request that did not start, it runs in the thread that waits for it
+    Deferred(Box<dyn FnOnce() -> T>),
+
+    /// Page read that was scheduled to the disk scheduler, the page is installed in its frame by the thread that waits for it,
+    /// or earlier by a thread that needs a frame after the read finished
+    Read {
+        is_ready: Box<dyn Fn() -> bool>,
+        complete: Box<dyn FnOnce() -> T>,
+    },
+}
+
+impl<T> PageFuture<T> {
+    /// Run the request in a new thread, only for long running requests as every request takes a thread
+    pub(super) fn spawn<F: FnOnce() -> T + Send + 'static>(request: F) -> Self where T: Send + 'static {
+        let promise = Promise::new();
+        let future = promise.get_future();
+
+        thread::Builder::new()
+            .name("Buffer Pool Page Request".to_string())
+            .spawn(move || promise.set_value(request()))
+            .expect("should spawn page request thread");
+
+        Self::from_request(PageRequest::Thread(future))
+    }
+
+    /// Future of a request that finished without waiting for the disk
+    pub(super) fn ready(value: T) -> Self {
+        Self::from_request(PageRequest::Ready(value))
+    }
+
+    /// Future of a request that is done when waited for, dropping it without waiting does not run the request
+    fn deferred<F: FnOnce() -> T + 'static>(request: F) -> Self {
+        Self::from_request(PageRequest::Deferred(Box::new(request)))
+    }
+
+    fn from_request(request: PageRequest<T>) -> Self {
+        PageFuture {
+            request: Some(request),
+        }
+    }
+
+    /// Block until the request is finished
+    pub fn wait(mut self) -> T {
+        match self.request.take().unwrap() {
+            PageRequest::Ready(value) => value,
+            PageRequest::Thread(future) => future.wait(),
+            PageRequest::Deferred(request) => request(),
+            PageRequest::Read { complete, .. } => complete(),
+        }
+    }
+
+    /// @return whether the request finished and `wait` will not block
+    pub fn is_ready(&self) -> bool {
+        match self.request.as_ref().unwrap() {
+            PageRequest::Ready(_) => true,
+            PageRequest::Thread(future) => future.wait_for(Duration::ZERO),
+            PageRequest::Deferred(_) => false,
+            PageRequest::Read { is_ready, .. } => is_ready(),
+        }
+    }
+}
+
+impl<T> Drop for PageFuture<T> {
+    fn drop(&mut self) {
+        // The page of a scheduled read stays pinned for the request
+        if let Some(PageRequest::Read { complete, .. }) = self.request.take() {
+            drop(complete());
+        }
+    }
+}
+
+impl BufferPoolManager {
+    /// Fetch page with read guard, the page is read by the disk scheduler without blocking the calling thread
+    pub(super) fn fetch_page_with_read_guard_async(self: &Arc<Self>, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageReadGuard<'static>, errors::FetchPageError>> {
+        self.fetch_page_async(page_id, access_type, BufferPoolManager::fetch_page_with_read_guard, |bpm, page| {
+            PageReadGuard::new(bpm, PageAndReadGuard::from(page))
+        })
+    }
+
+    /// Fetch page with write guard, the page is read by the disk scheduler without blocking the calling thread
+    pub(super) fn fetch_page_with_write_guard_async(self: &Arc<Self>, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageWriteGuard<'static>, errors::FetchPageError>> {
+        self.fetch_page_async(page_id, access_type, BufferPoolManager::fetch_page_with_write_guard, |bpm, page| {
+            PageWriteGuard::new(bpm, PageAndWriteGuard::from(page))
+        })
+    }
+
+    /// Schedule reading the page from disk and return a future that installs it in its frame
+    ///
+    /// Only pages that need to be read into a frame that can be replaced without flushing are scheduled,
+    /// the rest are fetched when the future is waited for
+    ///
+    /// # Arguments
+    ///
+    /// * `page_id`: The page id to fetch
+    /// * `access_type`: For leaderboard
+    /// * `fetch`: Fetch the page in the waiting thread when the read was not scheduled
+    /// * `create_guard`: Create the guard of the pinned page after the read is completed
+    ///
+    fn fetch_page_async<G: 'static>(
+        self: &Arc<Self>,
+        page_id: PageId,
+        access_type: AccessType,
+        fetch: fn(&Arc<Self>, PageId, AccessType) -> Result<G, errors::FetchPageError>,
+        create_guard: fn(Arc<Self>, Page) -> G,
+    ) -> PageFuture<Result<G, errors::FetchPageError>> {
+        let bpm = self.clone();
+        let fetch_in_waiting_thread = move || fetch(&bpm, page_id, access_type);
+
+        let mut inner = self.inner.lock();
+
+        // The page is already in the buffer pool or is loading by another request
+        if inner.page_table.contains_key(&page_id) || self.pending_fetch_requests.lock().contains_key(&page_id) {
+            return PageFuture::deferred(fetch_in_waiting_thread);
+        }
+
+        let fetch_result = Promise::new();
+        let fetch_result_future = fetch_result.get_future();
+
+        let Some(page) = self.schedule_page_read(&mut inner, page_id, access_type, Some(fetch_result)) else {
+            // The replaced page must be flushed first, which is done with the fetch
+            return PageFuture::deferred(fetch_in_waiting_thread);
+        };
+
+        self.metrics.record_miss(&access_type);
+
+        if matches!(access_type, AccessType::Scan) {
+            self.read_ahead_stats.record_miss();
+        }
+
+        let loaded = self.pending_fetch_requests.lock().get(&page_id).cloned().expect("scheduled read should be pending");
+
+        drop(inner);
+
+        let is_ready_bpm = self.clone();
+        let bpm = self.clone();
+
+        PageFuture::from_request(PageRequest::Read {
+            is_ready: Box::new(move || {
+                is_ready_bpm.reap_finished_read_aheads(&mut is_ready_bpm.inner.lock());
+
+                loaded.wait_for(Duration::ZERO)
+            }),
+            complete: Box::new(move || {
+                // Complete the read unless another thread already did
+                drop(bpm.lock_inner_without_read_ahead(page_id));
+
+                fetch_result_future.wait()?;
+
+                // The page was kept pinned for this request
+                let page_guard = create_guard(bpm.clone(), page);
+
+                if matches!(access_type, AccessType::Scan) {
+                    bpm.read_ahead(page_id);
+                }
+
+                Ok(page_guard)
+            }),
+        })
+    }
+}
@@ -0,0 +1,233 @@
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+#[cfg(feature = "tracing")]
+use tracy_client::span;
+use buffer_common::AccessType;
+use pages::PageId;
+use crate::BufferPoolManager;
+
+/// How often the background writer runs, like Postgres `bgwriter_delay`
+pub const BACKGROUND_WRITER_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Maximum number of pages to write in each round, like Postgres `bgwriter_lru_maxpages`
+pub const BACKGROUND_WRITER_MAX_PAGES_PER_ROUND: usize = 100;
+
+/// Percent of the frames that the background writer tries to keep free or clean and unpinned
+pub const BACKGROUND_WRITER_CLEAN_FRAMES_PERCENT: usize = 25;
+
+pub struct BackgroundWriterOptions {
+    interval: Duration,
+    max_pages_per_round: usize,
+    clean_frames_percent: usize,
+}
+
+impl BackgroundWriterOptions {
+    /// # Arguments
+    ///
+    /// * `interval`: the time to wait between rounds
+    /// * `max_pages_per_round`: the maximum number of pages to write in each round
+    /// * `clean_frames_percent`: the percent of the frames to keep ready for eviction without a write
+    ///
+    pub fn new(interval: Duration, max_pages_per_round: usize, clean_frames_percent: usize) -> Self {
+        Self {
+            interval,
+            max_pages_per_round,
+            clean_frames_percent,
+        }
+    }
+}
+
+impl Default for BackgroundWriterOptions {
+    fn default() -> Self {
+        Self {
+            interval: BACKGROUND_WRITER_INTERVAL,
+            max_pages_per_round: BACKGROUND_WRITER_MAX_PAGES_PER_ROUND,
+            clean_frames_percent: BACKGROUND_WRITER_CLEAN_FRAMES_PERCENT,
+        }
+    }
+}
+
+/// Counters of the background writer
+#[derive(Default)]
+pub struct BackgroundWriterStats {
+    pages_written: AtomicUsize,
+    rounds: AtomicUsize,
+}
+
+impl BackgroundWriterStats {
+    /// Number of dirty pages that were written by the background writer
+    pub fn get_pages_written(&self) -> usize {
+        self.pages_written.load(Ordering::Relaxed)
+    }
+
+    /// Number of rounds the background writer completed
+    pub fn get_rounds(&self) -> usize {
+        self.rounds.load(Ordering::Relaxed)
+    }
+}
+
+/// Running background writer thread
+pub(super) struct BackgroundWriter {
+    stop_sender: Sender<()>,
+
+    thread: JoinHandle<()>,
+}
+
+impl BufferPoolManager {
+    /// Start a thread that writes dirty and unpinned pages in the background,
+    /// so frames can be evicted without waiting for a write
+    ///
+    /// The thread does not keep the buffer pool alive, it is stopped when the buffer pool is dropped.
+    /// If the background writer is already running it is restarted with the new options
+    ///
+    /// # Arguments
+    ///
+    /// * `options`: the rate of the background writer
+    ///
+    pub fn start_background_writer(self: &Arc<Self>, options: BackgroundWriterOptions) {
+        self.stop_background_writer();
+
+        let (stop_sender, stop_receiver) = channel::<()>();
+        let bpm: Weak<Self> = Arc::downgrade(self);
+
+        let thread = thread::Builder::new()
+            .name("Buffer Pool Background Writer".to_string())
+            .spawn(move || {
+                // Stop when requested or when the buffer pool is dropped
+                while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(options.interval) {
+                    let Some(bpm) = bpm.upgrade() else {
+                        break;
+                    };
+
+                    bpm.write_dirty_pages(&options);
+                }
+            })
+            .expect("should create background writer thread");
+
+        *self.background_writer.lock() = Some(BackgroundWriter {
+            stop_sender,
+            thread,
+        });
+    }
+
+    /// Stop the background writer and wait for the current round to finish, nothing is done if it is not running
+    pub fn stop_background_writer(&self) {
+        let Some(BackgroundWriter { stop_sender, thread }) = self.background_writer.lock().take() else {
+            return;
+        };
+
+        // The receiver is dropped if the thread already stopped
+        let _ = stop_sender.send(());
+
+        // The buffer pool can be dropped by the background writer itself when it holds the last reference
+        if thread.thread().id() != thread::current().id() {
+            thread.join().expect("background writer should not panic");
+        }
+    }
+
+    pub fn get_background_writer_stats(&self) -> &BackgroundWriterStats {
+        &self.background_writer_stats
+    }
+
+    /// Write dirty and unpinned pages until enough frames can be evicted without a write
+    ///
+    /// returns: usize the number of pages that were written
+    ///
+    pub(super) fn write_dirty_pages(&self, options: &BackgroundWriterOptions) -> usize {
+        #[cfg(feature = "tracing")]
+        let _write_dirty_pages = span!("[background writer] write dirty pages");
+
+        let pages_to_write: Vec<PageId> = {
+            let inner = self.inner.lock();
+
+            let clean_frames_target = self.pool_size.load(Ordering::Relaxed) * options.clean_frames_percent / 100;
+
+            let mut clean_frames = inner.free_list.len();
+            let mut dirty_pages = vec![];
+
+            for (&page_id, &frame_id) in &inner.page_table {
+                let page = &inner.pages[frame_id as usize];
+
+                if page.is_pinned() {
+                    continue;
+                }
+
+                if page.is_dirty() {
+                    dirty_pages.push(page_id);
+                } else {
+                    clean_frames += 1;
+                }
+            }
+
+            let pages_to_write = clean_frames_target.saturating_sub(clean_frames).min(options.max_pages_per_round);
+            dirty_pages.truncate(pages_to_write);
+
+            dirty_pages
+        };
+
+        let pages_written = pages_to_write
+            .into_iter()
+            .filter(|&page_id| self.write_unpinned_dirty_page(page_id))
+            .count();
+
+        self.background_writer_stats.pages_written.fetch_add(pages_written, Ordering::Relaxed);
+        self.background_writer_stats.rounds.fetch_add(1, Ordering::Relaxed);
+
+        pages_written
+    }
+
+    /// Write the page if it is still dirty and no one uses it
+    ///
+    /// Unlike `flush_page` the page is skipped if it is pinned, so the background writer never waits for page latches
+    ///
+    /// returns: bool whether the page was written
+    ///
+    fn write_unpinned_dirty_page(&self, page_id: PageId) -> bool {
+        let mut inner = self.lock_inner_without_read_ahead(page_id);
+
+        let Some(&frame_id) = inner.page_table.get(&page_id) else {
+            return false;
+        };
+
+        let page = inner.pages[frame_id as usize].clone();
+
+        if page.is_pinned() || !page.is_dirty() {
+            return false;
+        }
+
+        // Avoid evicting in the middle, no one holds the page latch as the page was not pinned
+        inner.eviction_policy.set_evictable(frame_id, false);
+        page.pin();
+
+        let page_guard = page.read();
+
+        // release all locks as we don't want to hold the entire lock while writing the log and the page to disk
+        let disk_scheduler = inner.disk_scheduler.clone();
+        drop(inner);
+
+        // Write ahead log rule, like Postgres the log is flushed up to the page LSN before writing the page
+        let write_page_result = self.flush_log_for_page(page_guard.deref()).and_then(|_| {
+            let (write_page_result, _) = disk_scheduler.write_page_to_disk(page_guard.deref(), || {});
+
+            write_page_result
+        });
+
+        // Keep the page dirty if the write failed so it will be written again later
+        if write_page_result.is_ok() {
+            page.set_is_dirty(false);
+            self.metrics.record_disk_write(&AccessType::Unknown);
+        }
+
+        drop(page_guard);
+
+        self.unpin_page(page_id, AccessType::Unknown);
+
+        write_page_result.is_ok()
+    }
+}
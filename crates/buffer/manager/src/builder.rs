@@ -1,26 +1,37 @@
 use crate::manager::InnerBufferPoolManager;
-use crate::{BufferPoolManager};
+use crate::scan_ring::ScanRing;
+use crate::metrics::BufferPoolMetrics;
+use crate::{BackgroundWriterStats, BufferPoolManager, ReadAheadStats, ShardedBufferPoolManager};
 use disk_storage::{DiskManager, DiskManagerUnlimitedMemory, DiskScheduler};
-use eviction_policy::{EvictionPoliciesTypes, EvictionPolicy, EvictionPolicyCreator, LRUKEvictionPolicy, LRUKOptions};
-use pages::AtomicPageId;
+use eviction_policy::{ARCOptions, ClockOptions, EvictionPoliciesTypes, EvictionPolicy, EvictionPolicyCreator, LRUKEvictionPolicy, LRUKOptions, TwoQOptions};
 use parking_lot::Mutex;
 use recovery_log_manager::LogManager;
-use std::collections::{HashMap, LinkedList};
+use std::collections::{HashMap, HashSet, LinkedList};
 use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
 
 
 #[cfg(feature = "statistics")]
 use crate::BufferPoolManagerStats;
+#[cfg(feature = "guard_tracking")]
+use crate::guard_tracking::GuardTracker;
 
 pub struct BufferPoolManagerBuilder {
     pool_size: usize,
 
-    disk_scheduler: DiskScheduler,
+    disk_scheduler: Arc<DiskScheduler>,
 
     // Eviction policy creator, it gets the pool size and return the eviction policy
-    eviction_policy_creator: Box<dyn FnOnce(usize) -> Box<dyn EvictionPolicy>>,
+    eviction_policy_creator: Box<dyn Fn(usize) -> Box<dyn EvictionPolicy>>,
 
     log_manager: Option<Arc<LogManager>>,
+
+    read_ahead_window: usize,
+
+    scan_ring_size: usize,
+
+    #[cfg(feature = "guard_tracking")]
+    detect_lock_order_inversions: bool,
 }
 
 impl BufferPoolManagerBuilder {
@@ -37,12 +48,16 @@ impl BufferPoolManagerBuilder {
     }
 
     pub fn with_arc_disk_manager<D: DiskManager>(mut self, disk_manager: Arc<D>) -> Self {
-        self.disk_scheduler = DiskScheduler::new(disk_manager);
+        self.disk_scheduler = Arc::new(DiskScheduler::new(disk_manager));
 
         self
     }
 
-    pub fn with_disk_scheduler(mut self, disk_scheduler: DiskScheduler) -> Self {
+    pub fn with_disk_scheduler(self, disk_scheduler: DiskScheduler) -> Self {
+        self.with_arc_disk_scheduler(Arc::new(disk_scheduler))
+    }
+
+    pub fn with_arc_disk_scheduler(mut self, disk_scheduler: Arc<DiskScheduler>) -> Self {
         self.disk_scheduler = disk_scheduler;
 
         self
@@ -51,15 +66,61 @@ impl BufferPoolManagerBuilder {
     // ################# Eviction Policies #####################
 
     pub fn with_lru_k_eviction_policy(self, k: usize) -> Self {
-        self.with_eviction_policy_creator(EvictionPoliciesTypes::LRU_K(LRUKOptions::new(k)).get_creator())
+        self.with_eviction_policy(EvictionPoliciesTypes::LRU_K(LRUKOptions::new(k)))
+    }
+
+    pub fn with_clock_eviction_policy(self) -> Self {
+        self.with_eviction_policy(EvictionPoliciesTypes::CLOCK(ClockOptions::new()))
+    }
+
+    pub fn with_two_q_eviction_policy(self, options: TwoQOptions) -> Self {
+        self.with_eviction_policy(EvictionPoliciesTypes::TWO_Q(options))
+    }
+
+    pub fn with_arc_eviction_policy(self) -> Self {
+        self.with_eviction_policy(EvictionPoliciesTypes::ARC(ARCOptions::new()))
+    }
+
+    pub fn with_eviction_policy(self, policy: EvictionPoliciesTypes) -> Self {
+        self.with_eviction_policy_creator(policy.get_creator())
     }
 
-    pub fn with_eviction_policy_creator<Creator: FnOnce(usize) -> Box<dyn EvictionPolicy> + 'static>(mut self, creator: Creator) -> Self {
+    pub fn with_eviction_policy_creator<Creator: Fn(usize) -> Box<dyn EvictionPolicy> + 'static>(mut self, creator: Creator) -> Self {
         self.eviction_policy_creator = Box::new(creator);
 
         self
     }
 
+    // ################# Read Ahead #####################
+
+    /// Read ahead the next `window` pages when a page is fetched for scan, 0 to disable read-ahead
+    pub fn with_read_ahead_window(mut self, window: usize) -> Self {
+        self.read_ahead_window = window;
+
+        self
+    }
+
+    // ################# Scan Ring #####################
+
+    /// Confine the pages that scans load to a ring of `size` frames, so scans won't evict the hot pages, 0 to disable
+    ///
+    /// The ring should be larger than the read-ahead window, as the pages being read ahead are pinned
+    pub fn with_scan_ring_size(mut self, size: usize) -> Self {
+        self.scan_ring_size = size;
+
+        self
+    }
+
+    // ################# Guard Tracking #####################
+
+    /// Record page latches that are taken in inconsistent order, they are listed by `get_lock_order_inversions`
+    #[cfg(feature = "guard_tracking")]
+    pub fn with_lock_order_detection(mut self, enabled: bool) -> Self {
+        self.detect_lock_order_inversions = enabled;
+
+        self
+    }
+
     pub fn with_log_manager(mut self, log_manager: Option<Arc<LogManager>>) -> Self {
         self.log_manager = log_manager;
 
@@ -67,51 +128,101 @@ impl BufferPoolManagerBuilder {
     }
 
     pub fn build(self) -> BufferPoolManager {
+        self.create_buffer_pool(self.pool_size, self.read_ahead_window, self.scan_ring_size)
+    }
+
+    pub fn build_arc(self) -> Arc<BufferPoolManager> {
+        Arc::new(self.build())
+    }
+
+    /// Build a buffer pool that is split to shards, each with its own part of the frames
+    ///
+    /// Read-ahead is disabled in every shard, as the pages after a scanned page belong to other shards
+    ///
+    /// # Arguments
+    ///
+    /// * `number_of_shards`: the number of independent buffer pools to split the frames between
+    ///
+    pub fn build_sharded(self, number_of_shards: usize) -> ShardedBufferPoolManager {
+        assert!(number_of_shards > 0, "must have at least 1 shard");
+        assert!(self.pool_size >= number_of_shards, "each shard must have at least 1 frame");
+
+        let shards = (0..number_of_shards)
+            .map(|shard_index| {
+                // The first shards get the remaining frames
+                let pool_size = self.pool_size / number_of_shards + (shard_index < self.pool_size % number_of_shards) as usize;
+
+                Arc::new(self.create_buffer_pool(pool_size, 0, self.scan_ring_size.div_ceil(number_of_shards)))
+            })
+            .collect();
+
+        ShardedBufferPoolManager::new(shards, self.disk_scheduler)
+    }
+
+    pub fn build_sharded_arc(self, number_of_shards: usize) -> Arc<ShardedBufferPoolManager> {
+        Arc::new(self.build_sharded(number_of_shards))
+    }
+
+    fn create_buffer_pool(&self, pool_size: usize, read_ahead_window: usize, scan_ring_size: usize) -> BufferPoolManager {
         // Initially, every page is in the free list.
         let mut free_list = LinkedList::new();
 
-        for i in 0..self.pool_size {
+        for i in 0..pool_size {
             free_list.push_back(i as i32)
         }
 
         BufferPoolManager {
-            next_page_id: AtomicPageId::new(0),
-            pool_size: self.pool_size,
+            pool_size: AtomicUsize::new(pool_size),
 
-            log_manager: self.log_manager,
+            log_manager: self.log_manager.clone(),
 
             inner: Mutex::new(InnerBufferPoolManager {
 
                 // we allocate a consecutive memory space for the buffer pool
-                pages: Vec::with_capacity(self.pool_size),
+                pages: Vec::with_capacity(pool_size),
 
-                eviction_policy: (self.eviction_policy_creator)(self.pool_size),
+                eviction_policy: (self.eviction_policy_creator)(pool_size),
 
-                page_table: HashMap::with_capacity(self.pool_size),
+                page_table: HashMap::with_capacity(pool_size),
                 free_list,
 
-                disk_scheduler: Arc::new(self.disk_scheduler),
+                disk_scheduler: self.disk_scheduler.clone(),
+
+                read_ahead_requests: HashMap::new(),
+                read_ahead_pages: HashSet::new(),
+
+                scan_ring: ScanRing::new(scan_ring_size),
             }),
 
             pending_fetch_requests: Mutex::new(HashMap::new()),
 
+            read_ahead_window,
+            read_ahead_stats: ReadAheadStats::default(),
+
+            background_writer: Mutex::new(None),
+            background_writer_stats: BackgroundWriterStats::default(),
+            metrics: BufferPoolMetrics::default(),
+
+            #[cfg(feature = "guard_tracking")]
+            guard_tracker: GuardTracker::new(self.detect_lock_order_inversions),
+
             #[cfg(feature = "statistics")]
             stats: BufferPoolManagerStats::default(),
         }
     }
-
-    pub fn build_arc(self) -> Arc<BufferPoolManager> {
-        Arc::new(self.build())
-    }
 }
 
 impl Default for BufferPoolManagerBuilder {
     fn default() -> Self {
         Self {
             pool_size: 4096,
-            disk_scheduler: DiskScheduler::new(Arc::new(DiskManagerUnlimitedMemory::default())),
+            disk_scheduler: Arc::new(DiskScheduler::new(Arc::new(DiskManagerUnlimitedMemory::default()))),
             eviction_policy_creator: Box::new(|number_of_frames: usize| Box::new(LRUKEvictionPolicy::new(number_of_frames, LRUKOptions::default()))),
             log_manager: None,
+            read_ahead_window: 0,
+            scan_ring_size: 0,
+            #[cfg(feature = "guard_tracking")]
+            detect_lock_order_inversions: false,
         }
     }
 }
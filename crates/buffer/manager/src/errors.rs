@@ -1,5 +1,6 @@
 use pages::PageId;
 use error_utils::{Error};
+use disk_storage::errors::DiskError;
 
 #[derive(thiserror::Error, Debug, PartialEq, Clone)]
 #[error("all available frames are used and not evictable")]
@@ -13,6 +14,12 @@ pub(super) struct InvalidPageId;
 pub enum NewPageError {
     #[error("all frames are used and not evictable")]
     NoAvailableFrameFound,
+
+    #[error("failed to flush the replaced page to disk")]
+    DiskError(#[from] DiskError),
+
+    #[error("failed to allocate page on disk")]
+    AllocatePageError(#[source] DiskError),
 }
 
 impl From<NoAvailableFrameFound> for NewPageError {
@@ -28,6 +35,9 @@ pub enum FetchPageError {
 
     #[error("all frames are used and not evictable")]
     NoAvailableFrameFound,
+
+    #[error("failed to read page from disk")]
+    DiskError(#[from] DiskError),
 }
 
 impl From<InvalidPageId> for FetchPageError {
@@ -50,6 +60,9 @@ pub enum DeletePageError {
 
     #[error("Page {0} is not evictable")]
     PageIsNotEvictable(PageId),
+
+    #[error("failed to deallocate page on disk")]
+    DiskError(#[from] DiskError),
 }
 
 impl From<InvalidPageId> for DeletePageError {
@@ -58,6 +71,18 @@ impl From<InvalidPageId> for DeletePageError {
     }
 }
 
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum ResizeError {
+    #[error("buffer pool must have at least 1 frame")]
+    InvalidPoolSize,
+
+    #[error("Page {0} is pinned in a removed frame")]
+    PageIsPinned(PageId),
+
+    #[error("failed to flush a page of a removed frame to disk")]
+    DiskError(#[from] DiskError),
+}
+
 // This is mostly used for consumers that just want to say that there were a problem with
 // the buffer pool
 #[derive(thiserror::Error, Debug, PartialEq, Clone)]
@@ -0,0 +1,156 @@
+use std::backtrace::Backtrace;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::thread::ThreadId;
+use parking_lot::Mutex;
+use pages::PageId;
+use crate::BufferPoolManager;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageGuardKind {
+    Read,
+    Write,
+}
+
+/// Page guard that was not dropped yet, it holds a pin and a latch on the page
+#[derive(Debug, Clone)]
+pub struct PageGuardHolder {
+    pub page_id: PageId,
+
+    pub kind: PageGuardKind,
+
+    pub thread_id: ThreadId,
+
+    pub thread_name: Option<String>,
+
+    /// Where the guard was created
+    pub backtrace: Arc<Backtrace>,
+}
+
+/// A page latch that was taken while holding another page latch, when before that the latches were taken in the opposite order.
+///
+/// Threads that take the latches concurrently in different orders can deadlock
+#[derive(Debug, Clone)]
+pub struct LockOrderInversion {
+    /// The latch that the thread held
+    pub held_page_id: PageId,
+
+    /// The latch that the thread took while holding `held_page_id`
+    pub acquired_page_id: PageId,
+
+    pub thread_id: ThreadId,
+
+    /// Where the latch of `acquired_page_id` was taken
+    pub backtrace: Arc<Backtrace>,
+}
+
+/// Track the page guards that were not dropped yet, and the order their latches are taken
+#[derive(Default)]
+pub(super) struct GuardTracker {
+    next_guard_id: AtomicU64,
+
+    guards: Mutex<HashMap<u64, PageGuardHolder>>,
+
+    detect_lock_order_inversions: bool,
+
+    /// For each page, the pages whose latches were taken while holding its latch
+    lock_order: Mutex<HashMap<PageId, HashSet<PageId>>>,
+
+    inversions: Mutex<Vec<LockOrderInversion>>,
+}
+
+impl GuardTracker {
+    pub(super) fn new(detect_lock_order_inversions: bool) -> Self {
+        GuardTracker {
+            detect_lock_order_inversions,
+            ..GuardTracker::default()
+        }
+    }
+
+    /// Record a new guard of the current thread
+    ///
+    /// returns: u64 the id to untrack the guard with when it is dropped
+    ///
+    pub(super) fn track(&self, page_id: PageId, kind: PageGuardKind) -> u64 {
+        let guard_id = self.next_guard_id.fetch_add(1, Ordering::Relaxed);
+
+        let current_thread = thread::current();
+
+        let holder = PageGuardHolder {
+            page_id,
+            kind,
+            thread_id: current_thread.id(),
+            thread_name: current_thread.name().map(|name| name.to_string()),
+            backtrace: Arc::new(Backtrace::force_capture()),
+        };
+
+        let mut guards = self.guards.lock();
+
+        if self.detect_lock_order_inversions {
+            let held_page_ids: Vec<PageId> = guards
+                .values()
+                .filter(|guard| guard.thread_id == holder.thread_id && guard.page_id != page_id)
+                .map(|guard| guard.page_id)
+                .collect();
+
+            self.record_lock_order(&held_page_ids, &holder);
+        }
+
+        guards.insert(guard_id, holder);
+
+        guard_id
+    }
+
+    pub(super) fn untrack(&self, guard_id: u64) {
+        self.guards.lock().remove(&guard_id);
+    }
+
+    /// Only inversions between 2 pages are detected, longer cycles are not
+    fn record_lock_order(&self, held_page_ids: &[PageId], holder: &PageGuardHolder) {
+        let mut lock_order = self.lock_order.lock();
+
+        for &held_page_id in held_page_ids {
+            let taken_in_opposite_order = lock_order
+                .get(&holder.page_id)
+                .is_some_and(|taken_after| taken_after.contains(&held_page_id));
+
+            if taken_in_opposite_order {
+                let mut inversions = self.inversions.lock();
+
+                let already_reported = inversions
+                    .iter()
+                    .any(|inversion| inversion.held_page_id == held_page_id && inversion.acquired_page_id == holder.page_id);
+
+                if !already_reported {
+                    inversions.push(LockOrderInversion {
+                        held_page_id,
+                        acquired_page_id: holder.page_id,
+                        thread_id: holder.thread_id,
+                        backtrace: holder.backtrace.clone(),
+                    });
+                }
+            }
+
+            lock_order.entry(held_page_id).or_default().insert(holder.page_id);
+        }
+    }
+}
+
+impl BufferPoolManager {
+    /// Get the page guards that were not dropped yet ordered by page id, each holds a pin and a latch on its page
+    pub fn get_page_guard_holders(&self) -> Vec<PageGuardHolder> {
+        let mut holders: Vec<PageGuardHolder> = self.guard_tracker.guards.lock().values().cloned().collect();
+
+        holders.sort_by_key(|holder| holder.page_id);
+
+        holders
+    }
+
+    /// Get the page latches that were taken in inconsistent order,
+    /// empty unless lock order detection was enabled in the builder
+    pub fn get_lock_order_inversions(&self) -> Vec<LockOrderInversion> {
+        self.guard_tracker.inversions.lock().clone()
+    }
+}
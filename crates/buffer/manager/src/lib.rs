@@ -8,9 +8,26 @@ mod traits;
 #[cfg(test)]
 mod multi_threads_tests;
 mod builder;
+mod read_ahead;
+mod scan_ring;
+mod background_writer;
+mod sharded;
+mod metrics;
+mod resize;
+mod async_fetch;
+mod warm_up;
+#[cfg(feature = "guard_tracking")]
+mod guard_tracking;
 
 pub use manager::BufferPoolManager;
+pub use sharded::ShardedBufferPoolManager;
 pub use page_guards::*;
 pub use manager_stats::BufferPoolManagerStats;
+pub use read_ahead::ReadAheadStats;
+pub use metrics::{AccessTypeMetrics, BufferPoolMetricsSnapshot};
+pub use async_fetch::PageFuture;
+#[cfg(feature = "guard_tracking")]
+pub use guard_tracking::{LockOrderInversion, PageGuardHolder, PageGuardKind};
+pub use background_writer::{BackgroundWriterOptions, BackgroundWriterStats, BACKGROUND_WRITER_INTERVAL, BACKGROUND_WRITER_MAX_PAGES_PER_ROUND, BACKGROUND_WRITER_CLEAN_FRAMES_PERCENT};
 
 pub use traits::BufferPool;
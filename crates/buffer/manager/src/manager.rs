@@ -1,38 +1,41 @@
 use parking_lot::{Mutex, MutexGuard};
-use std::collections::{HashMap, LinkedList};
+use std::collections::{HashMap, HashSet, LinkedList};
 use std::ops::Deref;
-use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use common::config::{INVALID_LSN, LSN};
 use common::{SharedFuture, SharedPromise};
 use disk_storage::{DiskScheduler};
-use pages::{Page, PageAndGuard, PageAndReadGuard, PageAndWriteGuard, AtomicPageId, PageId, INVALID_PAGE_ID};
+use disk_storage::errors::{DiskError, WriteAndReadError};
+use pages::{Page, PageAndGuard, PageAndReadGuard, PageAndWriteGuard, PageId, UnderlyingPage, INVALID_PAGE_ID};
 
 #[cfg(feature = "tracing")]
 use tracy_client::span;
 use buffer_common::{AccessType, FrameId};
 use eviction_policy::{EvictionPolicy};
 use recovery_log_manager::LogManager;
-use crate::{errors, BufferPool, PageReadGuard, PageWriteGuard};
+use crate::{errors, BufferPool, PageFuture, PageReadGuard, PageWriteGuard};
 use crate::builder::BufferPoolManagerBuilder;
+use crate::read_ahead::{ReadAheadRequest, ReadAheadStats};
+use crate::scan_ring::ScanRing;
+use crate::background_writer::{BackgroundWriter, BackgroundWriterStats};
+use crate::metrics::BufferPoolMetrics;
 
 #[cfg(feature = "statistics")]
 use crate::BufferPoolManagerStats;
+#[cfg(feature = "guard_tracking")]
+use crate::guard_tracking::GuardTracker;
 
 ///
 /// BufferPoolManager reads disk pages to and from its internal buffer pool.
 ///
 pub struct BufferPoolManager {
-    /// The next page id to be allocated
-    pub(super) next_page_id: AtomicPageId,
-
     /// Number of pages in the buffer pool
-    /// This will not change after initial set
-    pub(super) pool_size: usize,
+    /// This only changes by `resize` while holding the inner latch
+    pub(super) pool_size: AtomicUsize,
 
 
-    /// Pointer to the log manager. Please ignore this for P1.
-    // LogManager *log_manager_ __attribute__((__unused__));
-    #[allow(unused)]
+    /// The log manager of the write ahead log, dirty pages are written only after the log up to their page LSN is persisted
     pub(super) log_manager: Option<Arc<LogManager>>,
 
     pub(super) inner: Mutex<InnerBufferPoolManager>,
@@ -40,6 +43,23 @@ pub struct BufferPoolManager {
     /// Pending fetch requests from disk
     pub(super) pending_fetch_requests: Mutex<HashMap<PageId, SharedFuture<()>>>,
 
+    /// Number of pages after a scanned page to read ahead, 0 to disable read-ahead
+    pub(super) read_ahead_window: usize,
+
+    pub(super) read_ahead_stats: ReadAheadStats,
+
+    /// Thread that writes dirty pages in the background, when started
+    pub(super) background_writer: Mutex<Option<BackgroundWriter>>,
+
+    pub(super) background_writer_stats: BackgroundWriterStats,
+
+    /// Counters of the buffer pool activity by access type
+    pub(super) metrics: BufferPoolMetrics,
+
+    #[cfg(feature = "guard_tracking")]
+    /// The page guards that were not dropped yet
+    pub(super) guard_tracker: GuardTracker,
+
     #[cfg(feature = "statistics")]
     /// Statistics on buffer pool
     pub(super) stats: BufferPoolManagerStats,
@@ -72,6 +92,15 @@ pub(super) struct InnerBufferPoolManager {
     /// This is inside `Arc` to allow dropping `inner` when calling disk scheduler
     /// It is our responsibility to not leave dangling scheduler outside the mutex guard
     pub(super) disk_scheduler: Arc<DiskScheduler>,
+
+    /// Pages that are being read ahead or fetched in the background
+    pub(super) read_ahead_requests: HashMap<PageId, ReadAheadRequest>,
+
+    /// Pages that were read ahead and were not fetched for scan yet
+    pub(super) read_ahead_pages: HashSet<PageId>,
+
+    /// Frames that scans replace instead of evicting from the entire pool
+    pub(super) scan_ring: ScanRing,
 }
 
 impl BufferPoolManager {
@@ -85,19 +114,84 @@ impl BufferPoolManager {
     ///
     /// * `inner`:
     ///
-    /// returns: PageId page id of the allocated page
+    /// returns: Result<PageId, DiskError> page id of the allocated page, deallocated pages are reused first
     ///
-    fn allocate_page(&self) -> PageId {
-        self.next_page_id.fetch_add(1, Ordering::SeqCst)
+    fn allocate_page(&self, inner: &InnerBufferPoolManager) -> Result<PageId, DiskError> {
+        inner.disk_scheduler.allocate_page()
     }
 
     /**
      * @brief Deallocate a page on disk. Caller should acquire the latch before calling this function.
+     * @param disk_scheduler the disk scheduler of the buffer pool
      * @param page_id id of the page to deallocate
      */
-    fn deallocate_page(&self, _page_id: PageId) {
-        // This is a no-nop right now without a more complex data structure to track deallocated pages
-        // TODO - call disk scheduler to deallocate the page
+    fn deallocate_page(&self, disk_scheduler: &DiskScheduler, page_id: PageId) -> Result<(), DiskError> {
+        disk_scheduler.deallocate_page(page_id)
+    }
+
+    /// Get the log manager that the changes of the pages are logged to, `None` if logging is disabled
+    pub fn get_log_manager(&self) -> Option<&Arc<LogManager>> {
+        self.log_manager.as_ref()
+    }
+
+    /// The dirty pages with their recovery LSN, the LSN of the first logged change that is not on disk, for the checkpoint
+    ///
+    /// The buffer pool is not blocked while collecting, only the latch of one page is taken at a time.
+    /// Pages that only have changes that were not logged are skipped as recovery can't redo them anyway
+    ///
+    /// returns: Vec<(PageId, LSN)> the dirty page table
+    ///
+    pub fn get_dirty_page_table(&self) -> Vec<(PageId, LSN)> {
+        let dirty_pages: Vec<(PageId, Page)> = {
+            let inner = self.inner.lock();
+
+            inner.page_table
+                .iter()
+                .map(|(&page_id, &frame_id)| (page_id, inner.pages[frame_id as usize].clone()))
+                .filter(|(_, page)| page.is_dirty())
+                .collect()
+        };
+
+        dirty_pages
+            .into_iter()
+            .filter_map(|(page_id, page)| {
+                // Wait for a writer that is in the middle of a change, it sets the recovery LSN before releasing the latch
+                let page_guard = page.read();
+
+                // The frame was reused for another page, the page was written when it was evicted
+                if page_guard.get_page_id() != page_id {
+                    return None;
+                }
+
+                let rec_lsn = page.get_rec_lsn();
+
+                (rec_lsn != INVALID_LSN).then_some((page_id, rec_lsn))
+            })
+            .collect()
+    }
+
+    /// Persist the log up to the page LSN before the page is written, this is the write ahead log rule
+    /// so every change that is on disk can be undone after a crash
+    ///
+    /// # Arguments
+    ///
+    /// * `page`: the dirty page that is about to be written
+    ///
+    /// returns: Result<(), DiskError> error if the log could not be written, in which case the page must not be written
+    ///
+    pub(super) fn flush_log_for_page(&self, page: &UnderlyingPage) -> Result<(), DiskError> {
+        let Some(log_manager) = &self.log_manager else {
+            return Ok(());
+        };
+
+        let page_lsn = page.get_lsn();
+
+        // Page LSN from before the log manager was created (e.g. page that was never logged) has nothing to persist
+        if page_lsn <= log_manager.get_persistent_lsn() || page_lsn >= log_manager.get_next_lsn() {
+            return Ok(());
+        }
+
+        log_manager.flush(page_lsn)
     }
 
     /// Find replacement frame from either the free list or the replacer, always use the free list first
@@ -108,21 +202,22 @@ impl BufferPoolManager {
     ///
     /// returns: Result<FrameId, NoAvailableFrameFound> Frame id if available frame found or error if not
     ///
-    fn find_replacement_frame(&self, inner: &mut InnerBufferPoolManager) -> Result<FrameId, errors::NoAvailableFrameFound> {
-        // Pick the replacement frame from the free list first
-        if !inner.free_list.is_empty() {
-            inner.free_list.pop_front().ok_or(errors::NoAvailableFrameFound)
-        } else {
-            // pick replacement from the replacer, can't be empty
-            inner.eviction_policy.evict().ok_or(errors::NoAvailableFrameFound)
+    pub(super) fn find_replacement_frame(&self, inner: &mut InnerBufferPoolManager) -> Result<FrameId, errors::NoAvailableFrameFound> {
+        if let Some(frame_id) = inner.take_free_or_evictable_frame() {
+            return Ok(frame_id);
         }
+
+        // Pages that finished reading ahead are pinned until completed
+        self.reap_finished_read_aheads(inner);
+
+        inner.take_free_or_evictable_frame().ok_or(errors::NoAvailableFrameFound)
     }
 
-    fn wait_for_pending_request_page_to_finish(&self, requests_map: &Mutex<HashMap<PageId, SharedFuture<()>>>, page_id: PageId) -> MutexGuard<InnerBufferPoolManager> {
+    fn wait_for_pending_request_page_to_finish(&self, requests_map: &Mutex<HashMap<PageId, SharedFuture<()>>>, page_id: PageId, access_type: &AccessType) -> MutexGuard<InnerBufferPoolManager> {
         // TODO - wait for condvar to avoid taking cpu time
 
         // 1. Hold replacer guard as all pin and unpin must first hold the replacer to avoid getting replaced in the middle
-        let mut inner = self.inner.lock();
+        let mut inner = self.lock_inner_without_read_ahead(page_id);
 
         // 2. Wait for the fetch from disk to finish
         loop {
@@ -134,12 +229,13 @@ impl BufferPoolManager {
             if let Some(pending_fetch_request) = pending_fetch_request {
                 // 2.2.1. Release locks so we won't block while we wait for the other fetch to finish
                 drop(inner);
+                self.metrics.record_pin_wait(access_type);
 
                 // 2.2.2. Wait for the fetch to finish
                 pending_fetch_request.wait();
 
                 // 2.2.3. Try to acquire again the replacer so we nothing can add to the pending again
-                inner = self.inner.lock();
+                inner = self.lock_inner_without_read_ahead(page_id);
             } else {
                 // 2.2.1 No pending fetch requested page is running
                 return inner;
@@ -147,11 +243,11 @@ impl BufferPoolManager {
         }
     }
 
-    fn wait_for_pending_fetch_page_to_finish(&self, page_id: PageId) -> MutexGuard<InnerBufferPoolManager> {
-        self.wait_for_pending_request_page_to_finish(&self.pending_fetch_requests, page_id)
+    fn wait_for_pending_fetch_page_to_finish(&self, page_id: PageId, access_type: &AccessType) -> MutexGuard<InnerBufferPoolManager> {
+        self.wait_for_pending_request_page_to_finish(&self.pending_fetch_requests, page_id, access_type)
     }
 
-    fn finish_current_pending_fetch_page_request(&self, page_id: PageId, fetch_promise: SharedPromise<()>) {
+    pub(super) fn finish_current_pending_fetch_page_request(&self, page_id: PageId, fetch_promise: SharedPromise<()>) {
         // First removing the pending requests
         self.pending_fetch_requests.lock().remove(&page_id);
 
@@ -159,11 +255,66 @@ impl BufferPoolManager {
         fetch_promise.set_value(());
     }
 
+    /// Undo the registration of a page that could not be read from disk so the frame can be reused
+    ///
+    /// The page in the frame is cleared as its content is no longer valid
+    fn abort_fetch_page(&self, page_id: PageId, frame_id: FrameId, mut page_guard: PageAndWriteGuard, fetch_promise: SharedPromise<()>) {
+        page_guard.clear_page(INVALID_PAGE_ID);
+        page_guard.page().set_is_dirty(false);
+        page_guard.page().unpin();
+        drop(page_guard);
+
+        {
+            let mut inner = self.inner.lock();
+
+            inner.page_table.remove(&page_id);
+
+            // Frame must be evictable in order to be removed from the eviction policy
+            inner.eviction_policy.set_evictable(frame_id, true);
+            inner.eviction_policy.remove(frame_id);
+            inner.free_list.push_front(frame_id);
+        }
+
+        self.finish_current_pending_fetch_page_request(page_id, fetch_promise);
+    }
+
+    /// Register page that is about to be flushed and replaced as pending,
+    /// so it won't be fetched from disk until we know whether the flush succeeded
+    fn register_pending_replaced_page(&self, replaced_page_id: PageId) -> SharedPromise<()> {
+        let flush_promise = SharedPromise::new();
+
+        self.pending_fetch_requests.lock().insert(replaced_page_id, flush_promise.get_future());
+
+        flush_promise
+    }
+
+    /// Put back the page that was about to be replaced after failing to flush it to disk
+    ///
+    /// The page data was not changed and it is still dirty, so it is registered back in its frame instead of the requested page
+    fn restore_replaced_page(&self, requested_page_id: PageId, frame_id: FrameId, page_guard: PageAndWriteGuard, flush_promise: SharedPromise<()>) {
+        let replaced_page_id = page_guard.get_page_id();
+
+        page_guard.page().unpin();
+        drop(page_guard);
+
+        {
+            let mut inner = self.inner.lock();
+
+            inner.page_table.remove(&requested_page_id);
+            inner.page_table.insert(replaced_page_id, frame_id);
+
+            // The page was evictable, otherwise it would not be picked for replacement
+            inner.eviction_policy.set_evictable(frame_id, true);
+        }
+
+        self.finish_current_pending_fetch_page_request(replaced_page_id, flush_promise);
+    }
+
     fn fetch_page<'a, PageAndGuardImpl: PageAndGuard<'a>, R, F: FnOnce(Arc<Self>, PageAndGuardImpl) -> R>(self: &Arc<Self>, page_id: PageId, access_type: AccessType, create_guard: F) -> Result<R, errors::FetchPageError> {
         // Find available frame
 
         // 1. Hold replacer guard as all pin and unpin must first hold the replacer to avoid getting replaced in the middle
-        let mut inner = self.wait_for_pending_fetch_page_to_finish(page_id);
+        let mut inner = self.wait_for_pending_fetch_page_to_finish(page_id, &access_type);
 
         #[cfg(any(feature = "tracing", feature = "statistics"))]
         let holding_inner_latch = (
@@ -179,6 +330,17 @@ impl BufferPoolManager {
         // 3. page does not exist in the buffer pool and we NEED to flush existing page
         // 3. Page exists in the buffer pool
         if let Some(&frame_id) = inner.page_table.get(&page_id) {
+            self.metrics.record_hit(&access_type);
+
+            if matches!(access_type, AccessType::Scan) {
+                if inner.read_ahead_pages.remove(&page_id) {
+                    self.read_ahead_stats.record_hit();
+                }
+            } else {
+                // The page is used by others as well, so the scans should not replace it
+                inner.scan_ring.forget(frame_id);
+            }
+
             // 3.1. Record access so the frame will be inserted and the replacer algorithm will work and avoid eviction in the meantime
             inner.record_access_and_avoid_eviction(frame_id, access_type);
 
@@ -203,9 +365,14 @@ impl BufferPoolManager {
         }
 
         // Option 2, page does not exists in the buffer pool
+        self.metrics.record_miss(&access_type);
+
+        if matches!(access_type, AccessType::Scan) {
+            self.read_ahead_stats.record_miss();
+        }
 
         // 4. Find replacement frame
-        let frame_id = self.find_replacement_frame(&mut inner)?;
+        let frame_id = self.find_replacement_frame_for_access(&mut inner, page_id, &access_type)?;
 
         // 5. Create promise for when the entire fetch is finished
         let current_fetch_promise = SharedPromise::new();
@@ -214,7 +381,7 @@ impl BufferPoolManager {
         self.pending_fetch_requests.lock().insert(page_id, current_fetch_promise.get_future());
 
         // 7. Record access so the frame will be inserted and the replacer algorithm will work and avoid eviction in the meantime
-        inner.record_access_and_avoid_eviction(frame_id, access_type);
+        inner.record_new_page_access_and_avoid_eviction(frame_id, page_id, access_type);
 
         // 8. Register the requested page in the page table
         inner.page_table.insert(page_id, frame_id);
@@ -236,38 +403,61 @@ impl BufferPoolManager {
             page_to_replace_guard.page().pin();
 
             // 3. Remove the old page from the page table so it won't be available
-            inner.page_table.remove(&page_to_replace_guard.get_page_id());
+            if inner.page_table.remove(&page_to_replace_guard.get_page_id()).is_some() {
+                self.metrics.record_eviction(&access_type);
+            }
+            inner.read_ahead_pages.remove(&page_to_replace_guard.get_page_id());
 
             // 4. If page to replace is dirty, need to flush it
             if page_to_replace_guard.page().is_dirty() {
-                // 6. Add flush + read message to the scheduler
-                let (flush_and_fetch_page_result, _) = inner.disk_scheduler.clone().write_and_read_page_from_disk(
-                    page_to_replace_guard.write_guard_mut(),
-                    page_id,
-                    #[inline]
-                    || {
-                        // 8. release all locks as we don't want to hold the entire lock while flushing to disk
+                // 5. Avoid fetching the page to replace until it is flushed, as it will be restored if the flush failed
+                let replaced_page_id = page_to_replace_guard.get_page_id();
+                let flush_promise = self.register_pending_replaced_page(replaced_page_id);
+                let disk_scheduler = inner.disk_scheduler.clone();
 
-                        #[cfg(any(feature = "tracing", feature = "statistics"))]
-                        drop(holding_inner_latch);
-                        drop(inner);
+                // 6. Release all locks as we don't want to hold the entire lock while flushing the log and the page to disk,
+                //    both pages are pending so they are not fetched in the meantime
+                #[cfg(any(feature = "tracing", feature = "statistics"))]
+                drop(holding_inner_latch);
+                drop(inner);
+
+                // 7. The log of the page changes must be persisted before the page, otherwise it is like a failed flush
+                if let Err(err) = self.flush_log_for_page(page_to_replace_guard.deref()) {
+                    self.restore_replaced_page(page_id, frame_id, page_to_replace_guard, flush_promise);
+                    self.finish_current_pending_fetch_page_request(page_id, current_fetch_promise);
 
-                        // 9. Wait for the flush and fetch to finish
-                        // TODO - handle errors in flushing
-                    }
+                    return Err(err.into());
+                }
+
+                // 8. Add flush + read message to the scheduler and wait for the flush and fetch to finish
+                let (flush_and_fetch_page_result, _) = disk_scheduler.write_and_read_page_from_disk(
+                    page_to_replace_guard.write_guard_mut(),
+                    page_id,
+                    || {}
                 );
 
-                if !flush_and_fetch_page_result {
+                // 10. If the flush failed, the page to replace is still in memory, so put it back
+                if let Err(WriteAndReadError::Write(err)) = flush_and_fetch_page_result {
+                    self.restore_replaced_page(page_id, frame_id, page_to_replace_guard, flush_promise);
                     self.finish_current_pending_fetch_page_request(page_id, current_fetch_promise);
 
-                    // TODO - reset page in page table
-                    panic!("Must be able to flush and fetch page");
+                    return Err(err.into());
                 }
 
-                // 10. Mark page as not dirty after read from disk
+                // 11. Mark page as not dirty after read from disk, the old page was already written
                 page_to_replace_guard.page().set_is_dirty(false);
+                self.metrics.record_dirty_write_back(&access_type);
+                self.metrics.record_disk_write(&access_type);
+                self.finish_current_pending_fetch_page_request(replaced_page_id, flush_promise);
+
+                if let Err(WriteAndReadError::Read(err)) = flush_and_fetch_page_result {
+                    self.abort_fetch_page(page_id, frame_id, page_to_replace_guard, current_fetch_promise);
+
+                    return Err(err.into());
+                }
+                self.metrics.record_disk_read(&access_type);
 
-                // 11. Set page id to be the correct page id
+                // 12. Set page id to be the correct page id
                 page_to_replace_guard.set_page_id(page_id);
 
                 // Convert write lock to the desired lock
@@ -293,11 +483,12 @@ impl BufferPoolManager {
 
                 // 8. Wait for the fetch to finish
 
-                if !fetch_page_result {
-                    self.finish_current_pending_fetch_page_request(page_id, current_fetch_promise);
+                if let Err(err) = fetch_page_result {
+                    self.abort_fetch_page(page_id, frame_id, page_to_replace_guard, current_fetch_promise);
 
-                    panic!("Must be able to fetch page");
+                    return Err(err.into());
                 }
+                self.metrics.record_disk_read(&access_type);
 
 
                 // Convert write lock to the requested guard
@@ -315,6 +506,7 @@ impl BufferPoolManager {
 
             // 2. Pin page
             page.pin();
+            inner.pages.insert(frame_id as usize, page.clone());
 
             let mut write_guard = PageAndWriteGuard::from(page);
 
@@ -334,11 +526,15 @@ impl BufferPoolManager {
             );
 
 
-            if !fetch_page_result {
-                self.finish_current_pending_fetch_page_request(page_id, current_fetch_promise);
+            if let Err(err) = fetch_page_result {
+                self.abort_fetch_page(page_id, frame_id, write_guard, current_fetch_promise);
 
-                panic!("Must be able to fetch page");
+                return Err(err.into());
             }
+            self.metrics.record_disk_read(&access_type);
+
+            // New pages are dirty by default, but this page match the disk content
+            write_guard.page().set_is_dirty(false);
 
             let requested_guard = PageAndGuardImpl::from(write_guard);
 
@@ -402,45 +598,28 @@ impl BufferPoolManager {
         }
     }
 
-    #[cfg(feature = "statistics")]
-    pub fn get_stats(&self) -> &BufferPoolManagerStats {
-        &self.stats
-    }
-}
-
-impl InnerBufferPoolManager {
-    #[inline(always)]
-    fn record_access_and_avoid_eviction(&mut self, frame_id: FrameId, access_type: AccessType) {
-
-        // Avoid evicting the frame
-        self.eviction_policy.set_evictable(frame_id, false);
-
-
-        // Record access to the frame so the LRU-K would work
-        // this is done after the set evictable for performance reasons (avoiding updating the evictable heap twice)
-        self.eviction_policy.record_access(frame_id, access_type);
-    }
-}
+    pub(super) fn lock_inner_for_new_page(&self) -> MutexGuard<InnerBufferPoolManager> {
+        let _stat = (
+            #[cfg(feature = "tracing")]
+            span!("[new_page] waiting for root lock"),
+            #[cfg(feature = "statistics")]
+            self.stats.waiting_for_inner_latch.create_single(),
+        );
 
-impl BufferPool for Arc<BufferPoolManager> {
-    fn get_pool_size(&self) -> usize {
-        self.pool_size
+        self.inner.lock()
     }
 
-    fn new_page<'a>(&self, access_type: AccessType) -> Result<PageWriteGuard<'a>, errors::NewPageError> {
-        // Find available frame
-
-        // 1. Hold replacer guard as all pin and unpin must first hold the replacer to avoid getting replaced in the middle
-        let mut inner = {
-            let _stat = (
-                #[cfg(feature = "tracing")]
-                span!("[new_page] waiting for root lock"),
-                #[cfg(feature = "statistics")]
-                self.stats.waiting_for_inner_latch.create_single(),
-            );
-            self.inner.lock()
-        };
-
+    /// Put a new page with an already allocated page id in the buffer pool
+    ///
+    /// The page is deallocated if it could not be put in the buffer pool
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: the inner buffer pool, locked by the caller while allocating the page
+    /// * `page_id`: the allocated page id
+    /// * `access_type`: For leaderboard
+    ///
+    pub(super) fn new_page_with_id<'a>(self: &Arc<Self>, mut inner: MutexGuard<InnerBufferPoolManager>, page_id: PageId, access_type: AccessType) -> Result<PageWriteGuard<'a>, errors::NewPageError> {
         #[cfg(any(feature = "tracing", feature = "statistics"))]
         let holding_root_lock = (
             #[cfg(feature = "tracing")]
@@ -449,14 +628,19 @@ impl BufferPool for Arc<BufferPoolManager> {
             self.stats.holding_inner_latch.create_single(),
         );
 
-        // 2. Find replacement frame
-        let frame_id = self.find_replacement_frame(&mut inner)?;
+        // 3. Find replacement frame
+        let frame_id = match self.find_replacement_frame_for_access(&mut inner, page_id, &access_type) {
+            Ok(frame_id) => frame_id,
+            Err(err) => {
+                // Missing frame is the relevant error, failing to deallocate only leaks the page on disk
+                let _ = self.deallocate_page(&inner.disk_scheduler, page_id);
 
-        // 3. Record access so the frame will be inserted and the replacer algorithm will work and avoid eviction in the meantime
-        inner.record_access_and_avoid_eviction(frame_id, access_type);
+                return Err(err.into());
+            }
+        };
 
-        // 4. Allocate page id
-        let page_id = self.allocate_page();
+        // 4. Record access so the frame will be inserted and the replacer algorithm will work and avoid eviction in the meantime
+        inner.record_new_page_access_and_avoid_eviction(frame_id, page_id, access_type);
 
         // 5. Register the new page in the page table
         inner.page_table.insert(page_id, frame_id);
@@ -478,28 +662,54 @@ impl BufferPool for Arc<BufferPoolManager> {
             page_and_write.page().pin();
 
             // 3. Remove the old page from the page table so it won't be available
-            inner.page_table.remove(&page_and_write.get_page_id());
+            if inner.page_table.remove(&page_and_write.get_page_id()).is_some() {
+                self.metrics.record_eviction(&access_type);
+            }
+            inner.read_ahead_pages.remove(&page_and_write.get_page_id());
 
             // 4. If page to replace is dirty, need to flush it
             if page_and_write.page().is_dirty() {
-                // 6. Add flush message to the scheduler
-                let (flush_page_result, _) = inner.disk_scheduler.clone().write_page_to_disk(
-                    page_and_write.deref(),
-                    || {
-                        // 7. release all locks as we don't want to hold the entire lock while flushing to disk
-                        #[cfg(any(feature = "tracing", feature = "statistics"))]
-                        drop(holding_root_lock);
-                        drop(inner);
+                // 5. Avoid fetching the page to replace until it is flushed, as it will be restored if the flush failed
+                let flush_promise = self.register_pending_replaced_page(page_and_write.get_page_id());
+                let disk_scheduler = inner.disk_scheduler.clone();
+
+                // 6. Release all locks as we don't want to hold the entire lock while flushing the log and the page to disk,
+                //    the replaced page is pending so it is not fetched in the meantime
+                #[cfg(any(feature = "tracing", feature = "statistics"))]
+                drop(holding_root_lock);
+                drop(inner);
 
-                        // 8. Wait for the flush to finish
-                    }
+                // 7. The log of the page changes must be persisted before the page, otherwise it is like a failed flush
+                if let Err(err) = self.flush_log_for_page(page_and_write.deref()) {
+                    self.restore_replaced_page(page_id, frame_id, page_and_write, flush_promise);
+
+                    // The log error is the relevant error, failing to deallocate only leaks the page on disk
+                    let _ = self.deallocate_page(&disk_scheduler, page_id);
+
+                    return Err(err.into());
+                }
+
+                // 8. Add flush message to the scheduler and wait for the flush to finish
+                let (flush_page_result, _) = disk_scheduler.clone().write_page_to_disk(
+                    page_and_write.deref(),
+                    || {}
                 );
 
-                // TODO - handle errors in flushing
-                assert_eq!(flush_page_result, true, "Must be able to flush page");
+                // 9. If the flush failed, put back the page to replace as it is still in memory
+                if let Err(err) = flush_page_result {
+                    self.restore_replaced_page(page_id, frame_id, page_and_write, flush_promise);
+
+                    // The flush error is the relevant error, failing to deallocate only leaks the page on disk
+                    let _ = self.deallocate_page(&disk_scheduler, page_id);
+
+                    return Err(err.into());
+                }
 
-                // 9. Reset dirty
+                // 10. Reset dirty
                 page_and_write.page().set_is_dirty(false);
+                self.metrics.record_dirty_write_back(&access_type);
+                self.metrics.record_disk_write(&access_type);
+                self.finish_current_pending_fetch_page_request(page_and_write.get_page_id(), flush_promise);
             }
 
             // 5. Reset page data + Change page id to be this page id
@@ -524,16 +734,111 @@ impl BufferPool for Arc<BufferPoolManager> {
         }
     }
 
-    fn fetch_page_read(&self, page_id: PageId, access_type: AccessType) -> Result<PageReadGuard, errors::FetchPageError> {
-        BufferPoolManager::fetch_page(self, page_id, access_type, |bpm, guard: PageAndReadGuard| {
+    #[cfg(feature = "statistics")]
+    pub fn get_stats(&self) -> &BufferPoolManagerStats {
+        &self.stats
+    }
+}
+
+impl InnerBufferPoolManager {
+    /// Take frame from the free list first and only then from the replacer
+    fn take_free_or_evictable_frame(&mut self) -> Option<FrameId> {
+        if !self.free_list.is_empty() {
+            self.free_list.pop_front()
+        } else {
+            self.eviction_policy.evict()
+        }
+    }
+
+    #[inline(always)]
+    pub(super) fn record_access_and_avoid_eviction(&mut self, frame_id: FrameId, access_type: AccessType) {
+
+        // Avoid evicting the frame
+        self.eviction_policy.set_evictable(frame_id, false);
+
+
+        // Record access to the frame so the LRU-K would work
+        // this is done after the set evictable for performance reasons (avoiding updating the evictable heap twice)
+        self.eviction_policy.record_access(frame_id, access_type);
+    }
+
+    /// Same as `record_access_and_avoid_eviction` for a frame that is about to hold a page that was not in the buffer pool
+    #[inline(always)]
+    pub(super) fn record_new_page_access_and_avoid_eviction(&mut self, frame_id: FrameId, page_id: PageId, access_type: AccessType) {
+        self.eviction_policy.record_new_page(frame_id, page_id);
+
+        self.record_access_and_avoid_eviction(frame_id, access_type);
+    }
+}
+
+impl BufferPoolManager {
+    /// Fetch page with read guard that is not bound to the borrow of the buffer pool
+    pub(super) fn fetch_page_with_read_guard<'a>(self: &Arc<Self>, page_id: PageId, access_type: AccessType) -> Result<PageReadGuard<'a>, errors::FetchPageError> {
+        let is_scan = matches!(access_type, AccessType::Scan);
+
+        let page_guard = BufferPoolManager::fetch_page(self, page_id, access_type, |bpm, guard: PageAndReadGuard| {
             PageReadGuard::new(bpm, guard)
-        })
+        })?;
+
+        if is_scan {
+            self.read_ahead(page_id);
+        }
+
+        Ok(page_guard)
     }
 
-    fn fetch_page_write(&self, page_id: PageId, access_type: AccessType) -> Result<PageWriteGuard, errors::FetchPageError> {
-        BufferPoolManager::fetch_page(self, page_id, access_type, |bpm, guard: PageAndWriteGuard| {
+    /// Fetch page with write guard that is not bound to the borrow of the buffer pool
+    pub(super) fn fetch_page_with_write_guard<'a>(self: &Arc<Self>, page_id: PageId, access_type: AccessType) -> Result<PageWriteGuard<'a>, errors::FetchPageError> {
+        let is_scan = matches!(access_type, AccessType::Scan);
+
+        let page_guard = BufferPoolManager::fetch_page(self, page_id, access_type, |bpm, guard: PageAndWriteGuard| {
             PageWriteGuard::new(bpm, guard)
-        })
+        })?;
+
+        if is_scan {
+            self.read_ahead(page_id);
+        }
+
+        Ok(page_guard)
+    }
+}
+
+impl BufferPool for Arc<BufferPoolManager> {
+    fn get_pool_size(&self) -> usize {
+        self.pool_size.load(Ordering::Relaxed)
+    }
+
+    fn new_page<'a>(&self, access_type: AccessType) -> Result<PageWriteGuard<'a>, errors::NewPageError> {
+        // Find available frame
+
+        // 1. Hold replacer guard as all pin and unpin must first hold the replacer to avoid getting replaced in the middle
+        let inner = self.lock_inner_for_new_page();
+
+        // 2. Allocate page id, before finding a frame so a failed allocation does not need to give back the frame
+        let page_id = self.allocate_page(&inner).map_err(errors::NewPageError::AllocatePageError)?;
+
+        self.new_page_with_id(inner, page_id, access_type)
+    }
+
+    fn fetch_page_read(&self, page_id: PageId, access_type: AccessType) -> Result<PageReadGuard, errors::FetchPageError> {
+        self.fetch_page_with_read_guard(page_id, access_type)
+    }
+
+    fn fetch_page_write(&self, page_id: PageId, access_type: AccessType) -> Result<PageWriteGuard, errors::FetchPageError> {
+        self.fetch_page_with_write_guard(page_id, access_type)
+    }
+
+    fn new_page_async(&self, access_type: AccessType) -> PageFuture<Result<PageWriteGuard<'static>, errors::NewPageError>> {
+        // New pages are not read from disk
+        PageFuture::ready(self.new_page(access_type))
+    }
+
+    fn fetch_page_read_async(&self, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageReadGuard<'static>, errors::FetchPageError>> {
+        self.fetch_page_with_read_guard_async(page_id, access_type)
+    }
+
+    fn fetch_page_write_async(&self, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageWriteGuard<'static>, errors::FetchPageError>> {
+        self.fetch_page_with_write_guard_async(page_id, access_type)
     }
 
     fn flush_page(&self, page_id: PageId) -> bool {
@@ -546,7 +851,7 @@ impl BufferPool for Arc<BufferPoolManager> {
                 #[cfg(feature = "statistics")]
                 self.stats.waiting_for_inner_latch.create_single(),
             );
-            self.inner.lock()
+            self.lock_inner_without_read_ahead(page_id)
         };
 
         #[cfg(any(feature = "tracing", feature = "statistics"))]
@@ -575,30 +880,30 @@ impl BufferPool for Arc<BufferPoolManager> {
 
         let page_guard = page.read();
 
-        // Add flush message to the scheduler
-        let (flush_page_result, _) = inner.disk_scheduler.clone().write_page_to_disk(
-            page_guard.deref(),
-            #[inline]
-            || {
-                // release all locks as we don't want to hold the entire lock while flushing to disk
-                #[cfg(any(feature = "tracing", feature = "statistics"))]
-                drop(holding_root_lock);
-                drop(inner);
+        // Write ahead log rule, release the inner latch first as waiting for the log can take a while
+        let disk_scheduler = inner.disk_scheduler.clone();
+        #[cfg(any(feature = "tracing", feature = "statistics"))]
+        drop(holding_root_lock);
+        drop(inner);
 
-                // Wait for the flush to finish
-            }
-        );
+        let flush_page_result = self.flush_log_for_page(page_guard.deref()).and_then(|_| {
+            // Add flush message to the scheduler and wait for the flush to finish
+            let (flush_page_result, _) = disk_scheduler.write_page_to_disk(page_guard.deref(), || {});
 
-        // TODO - handle errors in flushing
-        assert_eq!(flush_page_result, true, "Must be able to flush page");
+            flush_page_result
+        });
 
-        page.set_is_dirty(false);
+        // Keep the page dirty if the flush failed so it will be flushed again later
+        if flush_page_result.is_ok() {
+            page.set_is_dirty(false);
+            self.metrics.record_disk_write(&AccessType::Unknown);
+        }
 
         drop(page_guard);
 
         self.unpin_page(page_id, AccessType::Unknown);
 
-        true
+        flush_page_result.is_ok()
     }
 
     fn flush_all_pages(&self) {
@@ -617,7 +922,7 @@ impl BufferPool for Arc<BufferPoolManager> {
                 #[cfg(feature = "statistics")]
                 self.stats.waiting_for_inner_latch.create_single(),
             );
-            self.inner.lock()
+            self.lock_inner_without_read_ahead(page_id)
         };
 
         let _holding_root_lock = (
@@ -629,7 +934,9 @@ impl BufferPool for Arc<BufferPoolManager> {
 
 
         if !inner.page_table.contains_key(&page_id) {
-            // Page is missing
+            // Page is missing from the buffer pool, only need to free it on disk
+            self.deallocate_page(&inner.disk_scheduler, page_id)?;
+
             return Ok(true);
         }
 
@@ -642,6 +949,7 @@ impl BufferPool for Arc<BufferPoolManager> {
 
         // TODO - what about if page is dirty?
         (*inner).page_table.remove(&page_id);
+        (*inner).read_ahead_pages.remove(&page_id);
         (*inner).free_list.push_front(frame_id);
 
         (*inner).eviction_policy.remove(frame_id);
@@ -653,7 +961,7 @@ impl BufferPool for Arc<BufferPoolManager> {
         });
         page.set_is_dirty(false);
 
-        BufferPoolManager::deallocate_page(self, page_id);
+        self.deallocate_page(&inner.disk_scheduler, page_id)?;
 
         Ok(true)
     }
@@ -667,7 +975,7 @@ impl BufferPool for Arc<BufferPoolManager> {
                 #[cfg(feature = "statistics")]
                 self.stats.waiting_for_inner_latch.create_single(),
             );
-            self.inner.lock()
+            self.lock_inner_without_read_ahead(page_id)
         };
 
         let _holding_root_lock = (
@@ -0,0 +1,177 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use buffer_common::AccessType;
+use crate::BufferPoolManager;
+
+/// Counters of a single access type
+#[derive(Default)]
+struct AccessTypeCounters {
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    evictions: AtomicUsize,
+    dirty_write_backs: AtomicUsize,
+    pin_waits: AtomicUsize,
+    disk_reads: AtomicUsize,
+    disk_writes: AtomicUsize,
+}
+
+/// Counters of the buffer pool activity by the access type that caused it
+///
+/// The counters are always collected, unlike the latch timings of the `statistics` feature
+#[derive(Default)]
+pub(super) struct BufferPoolMetrics {
+    // Indexed by `access_type_index`
+    counters: [AccessTypeCounters; AccessType::ALL.len()],
+}
+
+fn access_type_index(access_type: &AccessType) -> usize {
+    match access_type {
+        AccessType::Unknown => 0,
+        AccessType::Lookup => 1,
+        AccessType::Scan => 2,
+        AccessType::Index => 3,
+    }
+}
+
+impl BufferPoolMetrics {
+    fn counters(&self, access_type: &AccessType) -> &AccessTypeCounters {
+        &self.counters[access_type_index(access_type)]
+    }
+
+    /// The requested page was already in the buffer pool
+    pub(super) fn record_hit(&self, access_type: &AccessType) {
+        self.counters(access_type).hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The requested page had to be read from disk
+    pub(super) fn record_miss(&self, access_type: &AccessType) {
+        self.counters(access_type).misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A page was removed from the buffer pool to make room for another page
+    pub(super) fn record_eviction(&self, access_type: &AccessType) {
+        self.counters(access_type).evictions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The evicted page was dirty and had to be written before its frame was reused
+    pub(super) fn record_dirty_write_back(&self, access_type: &AccessType) {
+        self.counters(access_type).dirty_write_backs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The requested page was being read or written by another request, so it had to wait before pinning it
+    pub(super) fn record_pin_wait(&self, access_type: &AccessType) {
+        self.counters(access_type).pin_waits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn record_disk_read(&self, access_type: &AccessType) {
+        self.counters(access_type).disk_reads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn record_disk_write(&self, access_type: &AccessType) {
+        self.counters(access_type).disk_writes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> BufferPoolMetricsSnapshot {
+        BufferPoolMetricsSnapshot {
+            by_access_type: self.counters.each_ref().map(|counters| AccessTypeMetrics {
+                hits: counters.hits.load(Ordering::Relaxed),
+                misses: counters.misses.load(Ordering::Relaxed),
+                evictions: counters.evictions.load(Ordering::Relaxed),
+                dirty_write_backs: counters.dirty_write_backs.load(Ordering::Relaxed),
+                pin_waits: counters.pin_waits.load(Ordering::Relaxed),
+                disk_reads: counters.disk_reads.load(Ordering::Relaxed),
+                disk_writes: counters.disk_writes.load(Ordering::Relaxed),
+            }),
+        }
+    }
+
+    fn reset(&self) {
+        for counters in &self.counters {
+            counters.hits.store(0, Ordering::Relaxed);
+            counters.misses.store(0, Ordering::Relaxed);
+            counters.evictions.store(0, Ordering::Relaxed);
+            counters.dirty_write_backs.store(0, Ordering::Relaxed);
+            counters.pin_waits.store(0, Ordering::Relaxed);
+            counters.disk_reads.store(0, Ordering::Relaxed);
+            counters.disk_writes.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Buffer pool activity of a single access type (or of all of them)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AccessTypeMetrics {
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+    pub dirty_write_backs: usize,
+    pub pin_waits: usize,
+    pub disk_reads: usize,
+    pub disk_writes: usize,
+}
+
+impl AccessTypeMetrics {
+    /// The ratio of requests that found the page in the buffer pool, `None` if there were no requests
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let requests = self.hits + self.misses;
+
+        if requests == 0 {
+            return None;
+        }
+
+        Some(self.hits as f64 / requests as f64)
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.evictions += other.evictions;
+        self.dirty_write_backs += other.dirty_write_backs;
+        self.pin_waits += other.pin_waits;
+        self.disk_reads += other.disk_reads;
+        self.disk_writes += other.disk_writes;
+    }
+}
+
+/// The buffer pool metrics at the time of the snapshot
+///
+/// The counters are not read atomically together, so a snapshot taken while the buffer pool is in use
+/// can be off by the requests that are in progress
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BufferPoolMetricsSnapshot {
+    by_access_type: [AccessTypeMetrics; AccessType::ALL.len()],
+}
+
+impl BufferPoolMetricsSnapshot {
+    pub fn get(&self, access_type: AccessType) -> &AccessTypeMetrics {
+        &self.by_access_type[access_type_index(&access_type)]
+    }
+
+    /// The metrics of all the access types together
+    pub fn total(&self) -> AccessTypeMetrics {
+        let mut total = AccessTypeMetrics::default();
+
+        self.by_access_type.iter().for_each(|metrics| total.add(metrics));
+
+        total
+    }
+
+    /// Add the metrics of another buffer pool, used to combine the shards of a sharded buffer pool
+    pub fn merge(&mut self, other: &Self) {
+        self.by_access_type
+            .iter_mut()
+            .zip(other.by_access_type.iter())
+            .for_each(|(metrics, other)| metrics.add(other));
+    }
+}
+
+impl BufferPoolManager {
+    /// Get the counters of the buffer pool activity since it was created or since the last reset
+    pub fn get_metrics_snapshot(&self) -> BufferPoolMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Reset all the counters of the buffer pool activity
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::{Debug, Formatter};
 use std::mem;
-use super::super::BufferPoolManager;
+use super::super::{BufferPoolManager, PageWriteGuard};
 use pages::{
     PageAndReadGuard,
     PageData,
@@ -8,6 +8,8 @@ use pages::{
 };
 use std::sync::Arc;
 use buffer_common::AccessType;
+#[cfg(feature = "guard_tracking")]
+use crate::PageGuardKind;
 
 /// Read guard on page that will also unpin on drop
 #[clippy::has_significant_drop]
@@ -15,16 +17,25 @@ use buffer_common::AccessType;
 pub struct PageReadGuard<'a> {
     bpm: Arc<BufferPoolManager>,
 
-    page_and_read_guard: Option<PageAndReadGuard<'a>>
+    page_and_read_guard: Option<PageAndReadGuard<'a>>,
+
+    #[cfg(feature = "guard_tracking")]
+    guard_id: u64,
 }
 
 impl<'a> PageReadGuard<'a> {
     pub(in super::super) fn new(bpm: Arc<BufferPoolManager>, page: PageAndReadGuard<'a>) -> Self {
+        #[cfg(feature = "guard_tracking")]
+        let guard_id = bpm.guard_tracker.track(page.get_page_id(), PageGuardKind::Read);
+
         Self {
             bpm,
 
             // The option is done only for the custom drop
             page_and_read_guard: Some(page),
+
+            #[cfg(feature = "guard_tracking")]
+            guard_id,
         }
     }
 
@@ -49,18 +60,48 @@ impl<'a> PageReadGuard<'a> {
             None => unreachable!()
         }
     }
+
+    /// Try to convert to write guard while keeping the page pinned, without another page table lookup
+    ///
+    /// Succeeds only if no other guard holds the page, the page latch is held for the entire conversion
+    /// so the page did not change since it was read.
+    /// On failure the read guard is returned and the page did not change either
+    ///
+    /// returns: Result<PageWriteGuard, PageReadGuard> the write guard or the read guard when failed
+    ///
+    pub fn try_upgrade(mut self) -> Result<PageWriteGuard<'a>, PageReadGuard<'a>> {
+        let page_and_read_guard = mem::take(&mut self.page_and_read_guard).unwrap();
+
+        match page_and_read_guard.try_upgrade() {
+            // The pin moves to the write guard
+            Ok(page_and_write_guard) => Ok(PageWriteGuard::new(self.bpm.clone(), page_and_write_guard)),
+            Err(page_and_read_guard) => {
+                self.page_and_read_guard = Some(page_and_read_guard);
+
+                Err(self)
+            }
+        }
+    }
 }
 
 impl<'a> Drop for PageReadGuard<'a> {
     fn drop(&mut self) {
-        // Must always have page and read guard,
-        let page_and_read_guard = mem::take(&mut self.page_and_read_guard).unwrap();
+        // Must always have page and read guard, unless it was upgraded to a write guard that is now responsible for the pin
+        let Some(page_and_read_guard) = mem::take(&mut self.page_and_read_guard) else {
+            #[cfg(feature = "guard_tracking")]
+            self.bpm.guard_tracker.untrack(self.guard_id);
+
+            return;
+        };
         let page_id = page_and_read_guard.get_page_id().clone();
         let bpm = self.bpm.clone();
 
         // Drop the write guard
         drop(page_and_read_guard);
 
+        #[cfg(feature = "guard_tracking")]
+        bpm.guard_tracker.untrack(self.guard_id);
+
         // Unpin page
         bpm.unpin_page(page_id, AccessType::Unknown);
     }
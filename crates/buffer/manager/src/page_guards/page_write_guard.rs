@@ -1,7 +1,10 @@
 use std::fmt::{Debug, Formatter};
-use super::super::BufferPoolManager;
+use super::super::{BufferPoolManager, PageReadGuard};
 use buffer_common::AccessType;
-use pages::{PageAndGuard, PageAndWriteGuard,PageData, PageId};
+use common::config::LSN;
+#[cfg(feature = "guard_tracking")]
+use crate::PageGuardKind;
+use pages::{PageAndGuard, PageAndReadGuard, PageAndWriteGuard,PageData, PageId};
 use std::mem;
 use std::sync::Arc;
 
@@ -11,16 +14,25 @@ use std::sync::Arc;
 pub struct PageWriteGuard<'a> {
     bpm: Arc<BufferPoolManager>,
 
-    page_and_write_guard: Option<PageAndWriteGuard<'a>>
+    page_and_write_guard: Option<PageAndWriteGuard<'a>>,
+
+    #[cfg(feature = "guard_tracking")]
+    guard_id: u64,
 }
 
 impl<'a> PageWriteGuard<'a> {
     pub(in super::super) fn new(bpm: Arc<BufferPoolManager>, page: PageAndWriteGuard<'a>) -> Self {
+        #[cfg(feature = "guard_tracking")]
+        let guard_id = bpm.guard_tracker.track(page.get_page_id(), PageGuardKind::Write);
+
         Self {
             bpm,
 
             // The option is done only for the custom drop
             page_and_write_guard: Some(page),
+
+            #[cfg(feature = "guard_tracking")]
+            guard_id,
         }
     }
 
@@ -71,18 +83,71 @@ impl<'a> PageWriteGuard<'a> {
             None => unreachable!()
         }
     }
+
+    /// @return the page LSN, the LSN of the last log record that changed the page
+    pub fn get_lsn(&self) -> LSN {
+        match &self.page_and_write_guard {
+            Some(p) => p.get_lsn(),
+            None => unreachable!()
+        }
+    }
+
+    /// Set the page LSN after appending the log record of a change to this page,
+    /// the buffer pool will flush the log up to this LSN before writing the page to disk
+    ///
+    /// The first LSN that is set after the page was written is the recovery LSN of the page in the dirty page table
+    pub fn set_lsn(&mut self, lsn: LSN) {
+        match &mut self.page_and_write_guard {
+            Some(p) => {
+                p.page().set_is_dirty(true);
+                p.page().set_rec_lsn_if_unset(lsn);
+
+                p.set_lsn(lsn)
+            },
+            None => unreachable!()
+        }
+    }
+
+    /// Same as `set_lsn` but keeps the page LSN if it is already greater
+    pub fn set_lsn_if_greater(&mut self, lsn: LSN) {
+        match &mut self.page_and_write_guard {
+            Some(p) => {
+                p.page().set_is_dirty(true);
+                p.page().set_rec_lsn_if_unset(lsn);
+
+                p.set_lsn_if_greater(lsn)
+            },
+            None => unreachable!()
+        }
+    }
+
+    /// Convert to read guard while keeping the page pinned, no other writer can take the page latch in between
+    pub fn downgrade(mut self) -> PageReadGuard<'a> {
+        let page_and_write_guard = mem::take(&mut self.page_and_write_guard).unwrap();
+
+        // The pin moves to the read guard
+        PageReadGuard::new(self.bpm.clone(), PageAndReadGuard::from(page_and_write_guard))
+    }
 }
 
 impl<'a> Drop for PageWriteGuard<'a> {
     fn drop(&mut self) {
-        // Must always have page and write guard,
-        let page_and_write_guard = mem::take(&mut self.page_and_write_guard).unwrap();
+        // Must always have page and write guard, unless it was downgraded to a read guard that is now responsible for the pin
+        let Some(page_and_write_guard) = mem::take(&mut self.page_and_write_guard) else {
+            #[cfg(feature = "guard_tracking")]
+            self.bpm.guard_tracker.untrack(self.guard_id);
+
+            return;
+        };
         let page_id = page_and_write_guard.get_page_id().clone();
         let bpm = self.bpm.clone();
 
         // Drop the write guard
         drop(page_and_write_guard);
 
+        #[cfg(feature = "guard_tracking")]
+        bpm.guard_tracker.untrack(self.guard_id);
+
         // Unpin page
         bpm.unpin_page(page_id, AccessType::Unknown);
     }
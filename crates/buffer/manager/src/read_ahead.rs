@@ -0,0 +1,271 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use parking_lot::MutexGuard;
+use buffer_common::{AccessType, FrameId};
+use common::{Future, Promise, SharedPromise};
+use disk_storage::errors::DiskError;
+use pages::{Page, PageAndGuard, PageAndWriteGuard, PageId, UnderlyingPage, INVALID_PAGE_ID, PAGE_SIZE};
+use crate::BufferPoolManager;
+use crate::manager::InnerBufferPoolManager;
+
+/// Page that is being read ahead or fetched in the background
+///
+/// The page is pinned and registered as pending fetch until the read is completed, which keeps other threads from using
+/// the frame while it is loading. The page is read into a buffer of the request and copied to the frame when completed,
+/// so the frame is not latched while the read is in flight
+pub(super) struct ReadAheadRequest {
+    frame_id: FrameId,
+
+    page: Page,
+
+    // Boxed so the buffer the disk scheduler reads into does not move with the request
+    buffer: Box<UnderlyingPage>,
+
+    read_future: Future<Result<(), DiskError>>,
+
+    fetch_promise: SharedPromise<()>,
+
+    access_type: AccessType,
+
+    // Set for pages that were fetched in the background, the page stays pinned for the fetch when the read succeeded
+    fetch_result: Option<Promise<Result<(), DiskError>>>,
+}
+
+/// Counters of the sequential read-ahead that is done for scan accesses
+#[derive(Default)]
+pub struct ReadAheadStats {
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl ReadAheadStats {
+    /// Number of scan fetches of pages that were read ahead
+    pub fn get_hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of scan fetches that had to read the page from disk
+    pub fn get_misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn record_hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl BufferPoolManager {
+    /// Schedule reading the pages after the scanned page without waiting for them
+    ///
+    /// Table pages are allocated one after the other, so the next pages by id are most likely the next pages of the scan.
+    /// Pages that are already in the buffer pool or that are not allocated are not read,
+    /// and frames are only taken when enough frames are left for the requested pages
+    ///
+    /// # Arguments
+    ///
+    /// * `page_id`: The page that was fetched for scan
+    ///
+    pub(super) fn read_ahead(&self, page_id: PageId) {
+        if self.read_ahead_window == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock();
+
+        self.reap_finished_read_aheads(&mut inner);
+
+        let last_page_id = page_id.saturating_add(self.read_ahead_window as PageId);
+
+        for next_page_id in page_id.saturating_add(1)..=last_page_id {
+            if inner.page_table.contains_key(&next_page_id) || self.pending_fetch_requests.lock().contains_key(&next_page_id) {
+                continue;
+            }
+
+            // Most likely reached the end of the data
+            if !inner.disk_scheduler.is_page_allocated(next_page_id) {
+                break;
+            }
+
+            // Keep frames for the pages that are requested while the read-ahead pages are pinned
+            if inner.free_list.len() + inner.eviction_policy.size() <= self.read_ahead_window {
+                break;
+            }
+
+            if self.schedule_page_read(&mut inner, next_page_id, AccessType::Scan, None).is_none() {
+                break;
+            }
+        }
+    }
+
+    /// Schedule reading the page into a replaced frame without waiting for the disk
+    ///
+    /// # Arguments
+    ///
+    /// * `page_id`: The page to read
+    /// * `access_type`: The access that requested the page
+    /// * `fetch_result`: Set when the read is completed for a page that is fetched in the background, the page is kept pinned when the read succeeded
+    ///
+    /// returns: Option<Page> the page the data is read into, None if no frame that can be replaced without flushing was found
+    ///
+    pub(super) fn schedule_page_read(&self, inner: &mut InnerBufferPoolManager, page_id: PageId, access_type: AccessType, fetch_result: Option<Promise<Result<(), DiskError>>>) -> Option<Page> {
+        let Ok(frame_id) = self.find_replacement_frame_for_access(inner, page_id, &access_type) else {
+            return None;
+        };
+
+        let page = match inner.pages.get(frame_id as usize).cloned() {
+            Some(page) => {
+                // The read is not waited for, so it never waits for a flush either, give the frame back
+                if page.is_dirty() {
+                    inner.eviction_policy.record_access(frame_id, AccessType::Unknown);
+                    inner.eviction_policy.set_evictable(frame_id, true);
+
+                    return None;
+                }
+
+                page
+            }
+            None => {
+                let page = Page::new(page_id);
+                inner.pages.insert(frame_id as usize, page.clone());
+
+                page
+            }
+        };
+
+        {
+            let mut page_guard = PageAndWriteGuard::from(page.clone());
+
+            let replaced_page_id = page_guard.get_page_id();
+            if inner.page_table.remove(&replaced_page_id).is_some() {
+                self.metrics.record_eviction(&access_type);
+            }
+            inner.read_ahead_pages.remove(&replaced_page_id);
+
+            page_guard.page().pin();
+            page_guard.set_page_id(page_id);
+
+            // New pages are dirty by default, the frame must not be written while its content is loading
+            page_guard.page().set_is_dirty(false);
+        }
+
+        inner.record_new_page_access_and_avoid_eviction(frame_id, page_id, access_type);
+        inner.page_table.insert(page_id, frame_id);
+
+        // Fetches of the page wait until the read-ahead is completed
+        let fetch_promise = SharedPromise::new();
+        self.pending_fetch_requests.lock().insert(page_id, fetch_promise.get_future());
+
+        let mut buffer = Box::new(UnderlyingPage::new(page_id, [0u8; PAGE_SIZE]));
+
+        // SAFETY: the buffer is kept with the request until the read is completed
+        let read_future = unsafe { inner.disk_scheduler.schedule_read_page_from_disk(&mut buffer) };
+
+        inner.read_ahead_requests.insert(page_id, ReadAheadRequest {
+            frame_id,
+            page: page.clone(),
+            buffer,
+            read_future,
+            fetch_promise,
+            access_type,
+            fetch_result,
+        });
+
+        Some(page)
+    }
+
+    /// Complete the read-ahead pages that finished reading without waiting for the rest
+    pub(super) fn reap_finished_read_aheads(&self, inner: &mut InnerBufferPoolManager) {
+        let finished: Vec<PageId> = inner.read_ahead_requests
+            .iter()
+            .filter(|(_, request)| request.read_future.wait_for(Duration::ZERO))
+            .map(|(&page_id, _)| page_id)
+            .collect();
+
+        for page_id in finished {
+            let request = inner.read_ahead_requests.remove(&page_id).unwrap();
+            let result = request.read_future.wait();
+
+            self.complete_read_ahead(inner, page_id, request, result);
+        }
+    }
+
+    /// Lock the inner buffer pool after completing the read-ahead of the page if there is one
+    pub(super) fn lock_inner_without_read_ahead(&self, page_id: PageId) -> MutexGuard<'_, InnerBufferPoolManager> {
+        let mut inner = self.inner.lock();
+
+        while let Some(request) = inner.read_ahead_requests.remove(&page_id) {
+            // Release the lock while waiting for the disk
+            drop(inner);
+            let result = request.read_future.wait();
+
+            inner = self.inner.lock();
+            self.complete_read_ahead(&mut inner, page_id, request, result);
+        }
+
+        inner
+    }
+
+    /// Release the read-ahead page once the read finished
+    ///
+    /// A page that failed to be read is removed from the buffer pool, as the error is reported when the page is fetched.
+    /// A page that is fetched in the background is released by its fetch instead
+    pub(super) fn complete_read_ahead(&self, inner: &mut InnerBufferPoolManager, page_id: PageId, request: ReadAheadRequest, result: Result<(), DiskError>) {
+        let ReadAheadRequest { frame_id, page, buffer, fetch_promise, access_type, fetch_result, .. } = request;
+        let mut page_guard = PageAndWriteGuard::from(page);
+
+        if result.is_ok() {
+            self.metrics.record_disk_read(&access_type);
+
+            page_guard.set_data(*buffer.get_data());
+
+            if fetch_result.is_none() {
+                page_guard.page().unpin();
+                drop(page_guard);
+
+                inner.eviction_policy.set_evictable(frame_id, true);
+                inner.read_ahead_pages.insert(page_id);
+            }
+        } else {
+            page_guard.clear_page(INVALID_PAGE_ID);
+            page_guard.page().set_is_dirty(false);
+            page_guard.page().unpin();
+            drop(page_guard);
+
+            inner.page_table.remove(&page_id);
+
+            // Frame must be evictable in order to be removed from the eviction policy
+            inner.eviction_policy.set_evictable(frame_id, true);
+            inner.eviction_policy.remove(frame_id);
+            inner.free_list.push_front(frame_id);
+        }
+
+        self.finish_current_pending_fetch_page_request(page_id, fetch_promise);
+
+        if let Some(fetch_result) = fetch_result {
+            fetch_result.set_value(result);
+        }
+    }
+
+    pub fn get_read_ahead_stats(&self) -> &ReadAheadStats {
+        &self.read_ahead_stats
+    }
+}
+
+impl Drop for BufferPoolManager {
+    fn drop(&mut self) {
+        self.stop_background_writer();
+
+        // The disk scheduler must not read into the pages after they are released
+        let mut inner = self.inner.lock();
+
+        for (page_id, request) in std::mem::take(&mut inner.read_ahead_requests) {
+            let result = request.read_future.wait();
+
+            self.complete_read_ahead(&mut inner, page_id, request, result);
+        }
+    }
+}
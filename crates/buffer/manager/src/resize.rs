@@ -0,0 +1,171 @@
+use std::mem;
+use std::ops::Deref;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use buffer_common::{AccessType, FrameId};
+use disk_storage::DiskScheduler;
+use pages::{Page, PageId};
+use crate::{errors, BufferPoolManager};
+use crate::manager::InnerBufferPoolManager;
+
+impl BufferPoolManager {
+    /// Change the number of frames in the buffer pool while it is in use
+    ///
+    /// Growing adds empty frames to the free list.
+    /// Shrinking removes the frames with the highest ids, their pages are moved to the free frames that are kept,
+    /// and the pages that don't fit are written to disk if dirty and evicted.
+    ///
+    /// Pinned pages are never moved, as the frame of a pinned page may be in the middle of loading or replacing a page.
+    /// So if one of the removed frames is pinned the resize fails even when there are free frames to move it to,
+    /// and the caller can retry after the page is released.
+    ///
+    /// The dirty pages are written without holding the inner latch, after the writes the removed frames are checked again
+    /// as they may have been used in the meantime.
+    /// When a write fails the pool size is not changed, the pages that were already moved stay in their new frames
+    /// and the frames they were moved from are free
+    ///
+    /// # Arguments
+    ///
+    /// * `new_size`: the new number of frames, must be at least 1
+    ///
+    pub fn resize(&self, new_size: usize) -> Result<(), errors::ResizeError> {
+        if new_size == 0 {
+            return Err(errors::ResizeError::InvalidPoolSize);
+        }
+
+        loop {
+            let mut inner = self.inner.lock();
+
+            // Pages that finished reading ahead are pinned until completed
+            self.reap_finished_read_aheads(&mut inner);
+
+            let pool_size = self.pool_size.load(Ordering::Relaxed);
+
+            if new_size >= pool_size {
+                inner.eviction_policy.resize(new_size);
+                inner.free_list.extend(pool_size as FrameId..new_size as FrameId);
+
+                self.pool_size.store(new_size, Ordering::Relaxed);
+
+                return Ok(());
+            }
+
+            // The pages of the removed frames, frames in the free list don't have a page even if they were used
+            let mut removed_pages: Vec<(PageId, FrameId)> = inner.page_table
+                .iter()
+                .filter(|(_, &frame_id)| frame_id as usize >= new_size)
+                .map(|(&page_id, &frame_id)| (page_id, frame_id))
+                .collect();
+
+            // 1. Check all the removed frames first, so a pinned page leaves the buffer pool as is
+            if let Some(&(page_id, _)) = removed_pages.iter().find(|(_, frame_id)| inner.pages[*frame_id as usize].is_pinned()) {
+                return Err(errors::ResizeError::PageIsPinned(page_id));
+            }
+
+            // 2. Move the pages to the free frames that are kept, so they don't need to be written and evicted.
+            //    The frames they were moved from are free, so they are still usable if writing the dirty pages fails
+            let kept_free_frames: Vec<FrameId> = inner.free_list.iter().copied().filter(|&frame_id| (frame_id as usize) < new_size).collect();
+            let moved = removed_pages.len().min(kept_free_frames.len());
+
+            for (&(page_id, frame_id), &free_frame_id) in removed_pages.iter().zip(&kept_free_frames) {
+                Self::move_page_to_frame(&mut inner, page_id, frame_id, free_frame_id);
+            }
+
+            let free_list = mem::take(&mut inner.free_list);
+            inner.free_list = free_list.into_iter().filter(|frame_id| !kept_free_frames[..moved].contains(frame_id)).collect();
+            inner.free_list.extend(removed_pages.drain(..moved).map(|(_, frame_id)| frame_id));
+
+            // 3. Write the dirty pages that don't fit without holding the latch, a failed write keeps the page in the buffer pool
+            let mut dirty_pages: Vec<(PageId, Page)> = vec![];
+
+            for &(page_id, frame_id) in &removed_pages {
+                let page = inner.pages[frame_id as usize].clone();
+
+                if page.is_dirty() {
+                    // Avoid evicting in the middle
+                    inner.eviction_policy.set_evictable(frame_id, false);
+                    page.pin();
+
+                    dirty_pages.push((page_id, page));
+                }
+            }
+
+            if !dirty_pages.is_empty() {
+                let disk_scheduler = inner.disk_scheduler.clone();
+                drop(inner);
+
+                self.write_removed_pages(&disk_scheduler, dirty_pages)?;
+
+                continue;
+            }
+
+            // 4. Evict the pages of the removed frames
+            for (page_id, frame_id) in removed_pages {
+                inner.page_table.remove(&page_id);
+                inner.read_ahead_pages.remove(&page_id);
+
+                // Frame must be evictable in order to be removed from the eviction policy
+                inner.eviction_policy.set_evictable(frame_id, true);
+                inner.eviction_policy.remove(frame_id);
+                inner.scan_ring.forget(frame_id);
+
+                self.metrics.record_eviction(&AccessType::Unknown);
+            }
+
+            inner.pages.truncate(new_size);
+
+            let free_list = mem::take(&mut inner.free_list);
+            inner.free_list = free_list.into_iter().filter(|&frame_id| (frame_id as usize) < new_size).collect();
+
+            inner.eviction_policy.resize(new_size);
+
+            self.pool_size.store(new_size, Ordering::Relaxed);
+
+            return Ok(());
+        }
+    }
+
+    /// Move an unpinned page to a free frame, the page keeps its data and dirty flag
+    ///
+    /// The empty page of the free frame is moved to the frame of the page,
+    /// the free frame must be removed from the free list and the frame of the page added to it by the caller
+    fn move_page_to_frame(inner: &mut InnerBufferPoolManager, page_id: PageId, frame_id: FrameId, free_frame_id: FrameId) {
+        inner.pages.swap(frame_id as usize, free_frame_id as usize);
+        inner.page_table.insert(page_id, free_frame_id);
+
+        // Frame must be evictable in order to be removed from the eviction policy
+        inner.eviction_policy.set_evictable(frame_id, true);
+        inner.eviction_policy.remove(frame_id);
+        inner.scan_ring.forget(frame_id);
+
+        inner.eviction_policy.record_new_page(free_frame_id, page_id);
+        inner.eviction_policy.record_access(free_frame_id, AccessType::Unknown);
+        inner.eviction_policy.set_evictable(free_frame_id, true);
+    }
+
+    /// Write the pinned dirty pages of removed frames and unpin them, even if one of the writes failed
+    fn write_removed_pages(&self, disk_scheduler: &Arc<DiskScheduler>, dirty_pages: Vec<(PageId, Page)>) -> Result<(), errors::ResizeError> {
+        let mut result = Ok(());
+
+        for (page_id, page) in dirty_pages {
+            if result.is_ok() {
+                let page_guard = page.read();
+
+                result = self.flush_log_for_page(page_guard.deref()).and_then(|_| {
+                    let (write_page_result, _) = disk_scheduler.clone().write_page_to_disk(page_guard.deref(), || {});
+
+                    write_page_result
+                });
+
+                if result.is_ok() {
+                    page.set_is_dirty(false);
+                    self.metrics.record_disk_write(&AccessType::Unknown);
+                }
+            }
+
+            self.unpin_page(page_id, AccessType::Unknown);
+        }
+
+        result.map_err(errors::ResizeError::from)
+    }
+}
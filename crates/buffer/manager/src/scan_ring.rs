@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use buffer_common::{AccessType, FrameId};
+use pages::PageId;
+use crate::BufferPoolManager;
+use crate::errors;
+use crate::manager::InnerBufferPoolManager;
+
+/// Ring of frames that scans replace in turn, like Postgres buffer access strategies
+///
+/// A large scan reads each page once, so instead of evicting the hot pages from the entire pool,
+/// a scan that misses reuses the frame of the page it read `size` pages ago.
+/// The ring frames are still part of the pool, other accesses can evict them or keep using their pages
+#[derive(Debug, Default)]
+pub(super) struct ScanRing {
+    /// The frame of each slot and the page the scan loaded into it
+    slots: Vec<(FrameId, PageId)>,
+
+    /// Slot of each frame in the ring
+    frame_slots: HashMap<FrameId, usize>,
+
+    /// Slot that is replaced next once the ring is full
+    next_slot: usize,
+
+    size: usize,
+}
+
+impl ScanRing {
+    pub(super) fn new(size: usize) -> Self {
+        ScanRing {
+            slots: Vec::with_capacity(size),
+            frame_slots: HashMap::with_capacity(size),
+            next_slot: 0,
+            size,
+        }
+    }
+
+    pub(super) fn is_enabled(&self) -> bool {
+        self.size > 0
+    }
+
+    /// The frame and page that the next scan miss replaces, `None` while the ring is not full
+    fn next_victim(&self) -> Option<(FrameId, PageId)> {
+        if self.slots.len() < self.size {
+            return None;
+        }
+
+        Some(self.slots[self.next_slot])
+    }
+
+    /// Put the frame in the ring in place of the next victim
+    fn push(&mut self, frame_id: FrameId, page_id: PageId) {
+        // The frame might still be in another slot if the main pool evicted it and a scan got it again
+        if self.frame_slots.get(&frame_id).is_some_and(|&slot| self.next_victim().is_none() || slot != self.next_slot) {
+            self.forget(frame_id);
+        }
+
+        if self.slots.len() < self.size {
+            self.frame_slots.insert(frame_id, self.slots.len());
+            self.slots.push((frame_id, page_id));
+
+            return;
+        }
+
+        let (replaced_frame_id, _) = self.slots[self.next_slot];
+        self.frame_slots.remove(&replaced_frame_id);
+
+        self.slots[self.next_slot] = (frame_id, page_id);
+        self.frame_slots.insert(frame_id, self.next_slot);
+        self.next_slot = (self.next_slot + 1) % self.size;
+    }
+
+    /// Leave the frame to the main pool, the slot won't be reused until a scan loads another frame into it
+    pub(super) fn forget(&mut self, frame_id: FrameId) {
+        let Some(slot) = self.frame_slots.remove(&frame_id) else {
+            return;
+        };
+
+        let last_slot = self.slots.len() - 1;
+        self.slots.swap_remove(slot);
+
+        if slot != last_slot {
+            let (moved_frame_id, _) = self.slots[slot];
+            self.frame_slots.insert(moved_frame_id, slot);
+        }
+
+        if self.next_slot >= self.slots.len() {
+            self.next_slot = 0;
+        }
+    }
+}
+
+impl BufferPoolManager {
+    /// Find replacement frame for the page, scans reuse the frames of their ring when it is enabled
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: the locked inner buffer pool
+    /// * `page_id`: the page that is about to be loaded into the frame
+    /// * `access_type`: the access type of the page that is about to be loaded
+    ///
+    /// returns: Result<FrameId, NoAvailableFrameFound> Frame id if available frame found or error if not
+    ///
+    pub(super) fn find_replacement_frame_for_access(&self, inner: &mut InnerBufferPoolManager, page_id: PageId, access_type: &AccessType) -> Result<FrameId, errors::NoAvailableFrameFound> {
+        if !matches!(access_type, AccessType::Scan) || !inner.scan_ring.is_enabled() {
+            return self.find_replacement_frame(inner);
+        }
+
+        let frame_id = match inner.take_scan_ring_victim() {
+            Some(frame_id) => frame_id,
+            None => self.find_replacement_frame(inner)?,
+        };
+
+        inner.scan_ring.push(frame_id, page_id);
+
+        Ok(frame_id)
+    }
+}
+
+impl InnerBufferPoolManager {
+    /// Take the next frame of the ring if it still holds the page the scan loaded into it and the page is not used
+    fn take_scan_ring_victim(&mut self) -> Option<FrameId> {
+        let (frame_id, page_id) = self.scan_ring.next_victim()?;
+
+        // The frame was evicted by the main pool, or its page was deleted
+        if self.page_table.get(&page_id) != Some(&frame_id) {
+            return None;
+        }
+
+        if self.pages[frame_id as usize].is_pinned() || self.read_ahead_requests.contains_key(&page_id) {
+            return None;
+        }
+
+        // Unpinned frames are evictable, so this removes the frame like evicting it
+        self.eviction_policy.remove(frame_id);
+
+        Some(frame_id)
+    }
+}
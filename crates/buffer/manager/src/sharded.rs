@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use buffer_common::AccessType;
+use disk_storage::DiskScheduler;
+use pages::PageId;
+use crate::{errors, BufferPool, BufferPoolManager, BufferPoolMetricsSnapshot, PageFuture, PageReadGuard, PageWriteGuard};
+
+///
+/// ShardedBufferPoolManager splits the frames between independent buffer pools,
+/// each page always goes to the same shard by its page id.
+///
+/// Every shard has its own page table, free list, eviction policy and latch,
+/// so accesses to pages of different shards don't wait for each other.
+/// The shards share the disk scheduler, so page ids are allocated from the same file.
+///
+pub struct ShardedBufferPoolManager {
+    shards: Vec<Arc<BufferPoolManager>>,
+
+    disk_scheduler: Arc<DiskScheduler>,
+}
+
+impl ShardedBufferPoolManager {
+    pub(super) fn new(shards: Vec<Arc<BufferPoolManager>>, disk_scheduler: Arc<DiskScheduler>) -> Self {
+        ShardedBufferPoolManager {
+            shards,
+            disk_scheduler,
+        }
+    }
+
+    /// The shard that holds the page when it is in the buffer pool
+    pub fn get_shard(&self, page_id: PageId) -> &Arc<BufferPoolManager> {
+        // Consecutive pages go to different shards, so scans and new pages are spread between the shards
+        &self.shards[page_id.rem_euclid(self.shards.len() as PageId) as usize]
+    }
+
+    /// All the shards, for stats and background writers that are per shard
+    pub fn get_shards(&self) -> &[Arc<BufferPoolManager>] {
+        &self.shards
+    }
+
+    /// The metrics of all the shards together
+    pub fn get_metrics_snapshot(&self) -> BufferPoolMetricsSnapshot {
+        let mut snapshot = BufferPoolMetricsSnapshot::default();
+
+        self.shards.iter().for_each(|shard| snapshot.merge(&shard.get_metrics_snapshot()));
+
+        snapshot
+    }
+
+    /// Reset the metrics of all the shards
+    pub fn reset_metrics(&self) {
+        self.shards.iter().for_each(|shard| shard.reset_metrics());
+    }
+}
+
+impl BufferPool for ShardedBufferPoolManager {
+    fn get_pool_size(&self) -> usize {
+        self.shards.iter().map(|shard| shard.get_pool_size()).sum()
+    }
+
+    fn new_page<'a>(&self, access_type: AccessType) -> Result<PageWriteGuard<'a>, errors::NewPageError> {
+        // The page id decides the shard, so it is allocated before locking the shard
+        let page_id = self.disk_scheduler.allocate_page().map_err(errors::NewPageError::AllocatePageError)?;
+
+        let shard = self.get_shard(page_id);
+
+        shard.new_page_with_id(shard.lock_inner_for_new_page(), page_id, access_type)
+    }
+
+    fn fetch_page_read(&self, page_id: PageId, access_type: AccessType) -> Result<PageReadGuard, errors::FetchPageError> {
+        self.get_shard(page_id).fetch_page_read(page_id, access_type)
+    }
+
+    fn fetch_page_write(&self, page_id: PageId, access_type: AccessType) -> Result<PageWriteGuard, errors::FetchPageError> {
+        self.get_shard(page_id).fetch_page_write(page_id, access_type)
+    }
+
+    fn new_page_async(&self, access_type: AccessType) -> PageFuture<Result<PageWriteGuard<'static>, errors::NewPageError>> {
+        // New pages are not read from disk
+        PageFuture::ready(self.new_page(access_type))
+    }
+
+    fn fetch_page_read_async(&self, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageReadGuard<'static>, errors::FetchPageError>> {
+        self.get_shard(page_id).fetch_page_read_async(page_id, access_type)
+    }
+
+    fn fetch_page_write_async(&self, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageWriteGuard<'static>, errors::FetchPageError>> {
+        self.get_shard(page_id).fetch_page_write_async(page_id, access_type)
+    }
+
+    fn flush_page(&self, page_id: PageId) -> bool {
+        self.get_shard(page_id).flush_page(page_id)
+    }
+
+    fn flush_all_pages(&self) {
+        for shard in &self.shards {
+            shard.flush_all_pages();
+        }
+    }
+
+    fn delete_page(&self, page_id: PageId) -> Result<bool, errors::DeletePageError> {
+        self.get_shard(page_id).delete_page(page_id)
+    }
+
+    fn get_pin_count(&self, page_id: PageId) -> Option<usize> {
+        self.get_shard(page_id).get_pin_count(page_id)
+    }
+}
@@ -1,9 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use crate::errors::{FetchPageError, NewPageError, NoAvailableFrameFound};
+    use crate::errors::{FetchPageError, NewPageError, NoAvailableFrameFound, ResizeError};
     use buffer_common::AccessType;
-    use crate::{BufferPool, BufferPoolManager, PageWriteGuard};
-    use pages::{AlignToPageData, PageData, PageId, PAGE_SIZE};
+    use crate::{AccessTypeMetrics, BackgroundWriterOptions, BufferPool, BufferPoolManager, BufferPoolMetricsSnapshot, PageWriteGuard};
+    use pages::{AlignToPageData, PageData, PageId, PAGE_SIZE, PAGE_USABLE_SIZE};
     use parking_lot::{Condvar, Mutex};
     use rand::Rng;
     use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
@@ -11,7 +11,12 @@ mod tests {
     use std::time::Duration;
     use std::thread;
     use tempdir::TempDir;
-    use disk_storage::DefaultDiskManager;
+    use disk_storage::{DefaultDiskManager, DiskFault, DiskManager, DiskManagerFaultInjection, DiskManagerUnlimitedMemory, FaultRule};
+    use disk_storage::errors::DiskError;
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+    use common::config::INVALID_LSN;
+    use recovery_log_manager::{LogManager, LogRecord, LogRecordBody};
 
     fn setup() -> TempDir {
         TempDir::new("buffer_pool_manager_tests").expect("Should create tmp directory")
@@ -45,14 +50,14 @@ mod tests {
 
         assert_eq!(page0.get_page_id(), 0);
 
-        // Generate random binary data
+        // Generate random binary data, the checksum trailer is reserved for the disk manager
         let mut random_binary_data: PageData = [0; PAGE_SIZE];
 
-        random_binary_data.fill_with(|| rng.gen_range(lower_bound..upper_bound) as u8);
+        random_binary_data[..PAGE_USABLE_SIZE].fill_with(|| rng.gen_range(lower_bound..upper_bound) as u8);
 
         // Insert terminal characters both in the middle and at end
-        random_binary_data[PAGE_SIZE / 2] = 0;
-        random_binary_data[PAGE_SIZE - 1] = 0;
+        random_binary_data[PAGE_USABLE_SIZE / 2] = 0;
+        random_binary_data[PAGE_USABLE_SIZE - 1] = 0;
 
         // Scenario: Once we have a page, we should be able to read and write content.
         page0.get_data_mut().copy_from_slice(&random_binary_data);
@@ -100,6 +105,48 @@ mod tests {
         // delete disk_manager;
     }
 
+    #[test]
+    fn fetch_pages_into_empty_frames() {
+        let tmpdir = setup();
+        let db_name = tmpdir.path().join("test.db");
+
+        {
+            let bpm = BufferPoolManager::builder()
+                .with_pool_size(3)
+                .with_disk_manager(DefaultDiskManager::new(db_name.clone()).expect("should create disk manager"))
+                .build_arc();
+
+            for data in ["page0", "page1"] {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+                page.get_data_mut()[..data.len()].copy_from_slice(data.as_bytes());
+                let page_id = page.get_page_id();
+                drop(page);
+
+                assert_eq!(bpm.flush_page(page_id), true);
+            }
+        }
+
+        // Every frame is empty, so the pages are read into empty frames
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(3)
+            .with_disk_manager(DefaultDiskManager::new(db_name).expect("should create disk manager"))
+            .build_arc();
+
+        for (page_id, data) in [(0, "page0"), (1, "page1")] {
+            let page = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page into empty frame");
+            assert_eq!(page.get_data(), &data.align_to_page_data());
+        }
+
+        // The pages are kept in their frames
+        for (page_id, data) in [(0, "page0"), (1, "page1")] {
+            let page = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page from its frame");
+            assert_eq!(page.get_data(), &data.align_to_page_data());
+        }
+
+        // The last frame is still empty
+        bpm.new_page(AccessType::Unknown).expect("should create a page in the last empty frame");
+    }
+
     #[test]
     fn sample() {
         let tmpdir = setup();
@@ -668,5 +715,1085 @@ mod tests {
             }
         }
     }
-}
 
+    #[test]
+    fn fetch_corrupted_page_should_return_disk_error_and_release_frame() {
+        let tmpdir = setup();
+        let db_name = tmpdir.path().join("test.db");
+
+        {
+            let bpm = BufferPoolManager::builder()
+                .with_pool_size(2)
+                .with_disk_manager(DefaultDiskManager::new(db_name.clone()).expect("should create disk manager"))
+                .build_arc();
+
+            for data in ["page0", "page1"] {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+                page.get_data_mut()[..data.len()].copy_from_slice(data.as_bytes());
+                let page_id = page.get_page_id();
+                drop(page);
+
+                assert_eq!(bpm.flush_page(page_id), true);
+            }
+        }
+
+        // Corrupt page 0 on disk, it comes after the superblock
+        {
+            let mut file = OpenOptions::new().write(true).open(&db_name).expect("should open db file");
+            file.seek(SeekFrom::Start((PAGE_SIZE + 10) as u64)).expect("should seek");
+            file.write_all(&[0xFF]).expect("should write");
+        }
+
+        // Only a single frame, so the frame of the failed fetch must be released for the next fetch to succeed
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(1)
+            .with_disk_manager(DefaultDiskManager::new(db_name).expect("should create disk manager"))
+            .build_arc();
+
+        let err = bpm.fetch_page_read(0, AccessType::Unknown).expect_err("page 0 is corrupted");
+        assert!(matches!(err, FetchPageError::DiskError(DiskError::PageCorrupted { page_id: 0, .. })), "got {:?}", err);
+        assert_eq!(bpm.get_pin_count(0), None);
+
+        let page1 = bpm.fetch_page_read(1, AccessType::Unknown).expect("page 1 is not corrupted");
+        assert_eq!(page1.get_data(), &"page1".align_to_page_data());
+        drop(page1);
+
+        let err = bpm.fetch_page_write(0, AccessType::Unknown).expect_err("page 0 is still corrupted");
+        assert!(matches!(err, FetchPageError::DiskError(DiskError::PageCorrupted { page_id: 0, .. })), "got {:?}", err);
+
+        let page1 = bpm.fetch_page_write(1, AccessType::Unknown).expect("frame should be released again");
+        assert_eq!(page1.get_data(), &"page1".align_to_page_data());
+    }
+
+    #[test]
+    fn failed_flush_of_replaced_page_should_return_error_and_keep_the_page() {
+        let disk_manager = Arc::new(DiskManagerFaultInjection::new(DiskManagerUnlimitedMemory::new()));
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(1)
+            .with_arc_disk_manager(Arc::clone(&disk_manager))
+            .build_arc();
+
+        let page0_id = {
+            let mut page0 = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+            page0.get_data_mut()[..5].copy_from_slice("page0".as_bytes());
+
+            page0.get_page_id()
+        };
+
+        // Write page 1 to disk so it can be fetched later
+        let page1_id = {
+            let mut page1 = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+            page1.get_data_mut()[..5].copy_from_slice("page1".as_bytes());
+
+            page1.get_page_id()
+        };
+
+        // Page 0 was flushed when page 1 was created, make page 0 dirty again
+        bpm.fetch_page_write(page0_id, AccessType::Unknown).expect("should fetch page 0").get_data_mut()[..7].copy_from_slice("page0v2".as_bytes());
+
+        disk_manager.add_fault(FaultRule::new(DiskFault::FailWrite).always());
+
+        let err = bpm.new_page(AccessType::Unknown).expect_err("page 0 cannot be flushed");
+        assert!(matches!(err, NewPageError::DiskError(DiskError::Io { .. })), "got {:?}", err);
+
+        let err = bpm.fetch_page_read(page1_id, AccessType::Unknown).expect_err("page 0 cannot be flushed");
+        assert!(matches!(err, FetchPageError::DiskError(DiskError::Io { .. })), "got {:?}", err);
+
+        assert_eq!(bpm.flush_page(page0_id), false, "flush should fail");
+
+        // Page 0 is still in the buffer pool with the modified data
+        assert_eq!(bpm.get_pin_count(page0_id), Some(0));
+        assert_eq!(bpm.fetch_page_read(page0_id, AccessType::Unknown).expect("page 0 should still be in memory").get_data(), &"page0v2".align_to_page_data());
+
+        disk_manager.clear_faults();
+
+        assert_eq!(bpm.fetch_page_read(page1_id, AccessType::Unknown).expect("should fetch page 1 after writes are working again").get_data(), &"page1".align_to_page_data());
+        assert_eq!(bpm.fetch_page_read(page0_id, AccessType::Unknown).expect("should fetch page 0 from disk").get_data(), &"page0v2".align_to_page_data());
+    }
+
+    #[test]
+    fn deleted_pages_should_be_reused_by_new_page() {
+        let tmpdir = setup();
+        let db_name = tmpdir.path().join("test.db");
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_disk_manager(DefaultDiskManager::new(db_name).expect("should create disk manager"))
+            .build_arc();
+
+        let page_ids = (0..4)
+            .map(|_| {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+                page.get_data_mut()[..4].copy_from_slice("page".as_bytes());
+
+                page.get_page_id()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(page_ids, vec![0, 1, 2, 3]);
+
+        // Page 0 is no longer in the buffer pool, page 3 is
+        assert_eq!(bpm.get_pin_count(0), None);
+        assert_eq!(bpm.get_pin_count(3), Some(0));
+
+        assert_eq!(bpm.delete_page(0), Ok(true));
+        assert_eq!(bpm.delete_page(3), Ok(true));
+
+        let page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+        assert_eq!(page.get_page_id(), 0, "page deleted while not in the buffer pool should be reused");
+        assert_eq!(page.get_data(), &[0u8; PAGE_SIZE]);
+        drop(page);
+
+        let page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+        assert_eq!(page.get_page_id(), 3, "page deleted from the buffer pool should be reused");
+        assert_eq!(page.get_data(), &[0u8; PAGE_SIZE]);
+        drop(page);
+
+        assert_eq!(bpm.new_page(AccessType::Unknown).expect("should be able to create new page").get_page_id(), 4);
+    }
+
+    #[test]
+    fn scan_should_read_ahead_the_next_pages() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        let number_of_pages = 20;
+
+        {
+            let bpm = BufferPoolManager::builder()
+                .with_pool_size(number_of_pages)
+                .with_arc_disk_manager(disk_manager.clone())
+                .build_arc();
+
+            for i in 0..number_of_pages {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+                page.get_data_mut().copy_from_slice(&format!("page {}", i).as_str().align_to_page_data());
+                let page_id = page.get_page_id();
+                drop(page);
+
+                assert!(bpm.flush_page(page_id), "should flush page {}", page_id);
+            }
+        }
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_read_ahead_window(3)
+            .build_arc();
+
+        for page_id in 0..number_of_pages as PageId {
+            let page = bpm.fetch_page_read(page_id, AccessType::Scan).expect("should fetch page");
+
+            assert_eq!(page.get_data(), &format!("page {}", page_id).as_str().align_to_page_data());
+        }
+
+        assert_eq!(bpm.get_read_ahead_stats().get_misses(), 1, "only the first page should be read from disk by the scan");
+        assert_eq!(bpm.get_read_ahead_stats().get_hits(), number_of_pages - 1);
+
+        // Not allocated pages are not read ahead
+        assert_eq!(bpm.get_pin_count(number_of_pages as PageId), None);
+    }
+
+    #[test]
+    fn read_ahead_should_not_be_done_for_other_access_types() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        {
+            let bpm = BufferPoolManager::builder()
+                .with_pool_size(5)
+                .with_arc_disk_manager(disk_manager.clone())
+                .build_arc();
+
+            for _ in 0..5 {
+                let page_id = bpm.new_page(AccessType::Unknown).expect("should be able to create new page").get_page_id();
+
+                assert!(bpm.flush_page(page_id), "should flush page {}", page_id);
+            }
+        }
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_read_ahead_window(3)
+            .build_arc();
+
+        drop(bpm.fetch_page_read(0, AccessType::Lookup).expect("should fetch page"));
+        assert_eq!(bpm.get_pin_count(1), None, "lookup should not read ahead");
+
+        drop(bpm.fetch_page_read(0, AccessType::Scan).expect("should fetch page"));
+
+        // Deleting and flushing the read ahead pages wait for the read to finish
+        assert!(bpm.flush_page(1));
+        assert_eq!(bpm.delete_page(2), Ok(true));
+        assert_eq!(bpm.get_pin_count(3), Some(0));
+
+        assert_eq!(bpm.get_read_ahead_stats().get_misses(), 0, "page 0 was already in the buffer pool");
+        assert_eq!(bpm.get_read_ahead_stats().get_hits(), 0);
+    }
+
+    #[test]
+    fn read_ahead_should_be_completed_by_other_threads() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        {
+            let bpm = BufferPoolManager::builder()
+                .with_pool_size(4)
+                .with_arc_disk_manager(disk_manager.clone())
+                .build_arc();
+
+            for i in 0..4 {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+                page.get_data_mut().copy_from_slice(&format!("page {}", i).as_str().align_to_page_data());
+                drop(page);
+
+                assert!(bpm.flush_page(i), "should flush page {}", i);
+            }
+        }
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_read_ahead_window(3)
+            .build_arc();
+
+        drop(bpm.fetch_page_read(0, AccessType::Scan).expect("should fetch page"));
+
+        // The frames are not latched while loading, so reading them ahead does not tie them to the scanning thread
+        thread::scope(|s| {
+            for page_id in 1..4 {
+                let bpm = bpm.clone();
+
+                s.spawn(move || {
+                    let page = bpm.fetch_page_read(page_id, AccessType::Scan).expect("should fetch page");
+
+                    assert_eq!(page.get_data(), &format!("page {}", page_id).as_str().align_to_page_data());
+                });
+            }
+        });
+
+        assert_eq!(bpm.get_read_ahead_stats().get_hits(), 3);
+        assert_eq!(bpm.get_read_ahead_stats().get_misses(), 1);
+    }
+
+    fn create_pages_on_disk(disk_manager: &Arc<DiskManagerUnlimitedMemory>, number_of_pages: usize) {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(number_of_pages)
+            .with_arc_disk_manager(disk_manager.clone())
+            .build_arc();
+
+        for _ in 0..number_of_pages {
+            let page_id = bpm.new_page(AccessType::Unknown).expect("should be able to create new page").get_page_id();
+
+            assert!(bpm.flush_page(page_id), "should flush page {}", page_id);
+        }
+    }
+
+    #[test]
+    fn scan_should_only_replace_the_frames_of_the_scan_ring() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 20);
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_scan_ring_size(2)
+            .build_arc();
+
+        for page_id in 0..5 {
+            drop(bpm.fetch_page_read(page_id, AccessType::Index).expect("should fetch page"));
+        }
+
+        for page_id in 5..20 {
+            drop(bpm.fetch_page_read(page_id, AccessType::Scan).expect("should fetch page"));
+        }
+
+        for page_id in 0..5 {
+            assert_eq!(bpm.get_pin_count(page_id), Some(0), "hot page {} should not be evicted by the scan", page_id);
+        }
+
+        let scanned_pages_in_pool = (5..20).filter(|&page_id| bpm.get_pin_count(page_id).is_some()).count();
+        assert_eq!(scanned_pages_in_pool, 2, "scan should only use the ring frames");
+
+        // The frames that are left are used by other accesses
+        for page_id in 5..8 {
+            drop(bpm.fetch_page_read(page_id, AccessType::Lookup).expect("should fetch page"));
+        }
+
+        for page_id in 0..5 {
+            assert_eq!(bpm.get_pin_count(page_id), Some(0), "hot page {} should still be in the buffer pool", page_id);
+        }
+    }
+
+    #[test]
+    fn scan_should_not_replace_page_that_was_accessed_by_other_access_type() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 10);
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_scan_ring_size(2)
+            .build_arc();
+
+        drop(bpm.fetch_page_read(0, AccessType::Scan).expect("should fetch page"));
+        drop(bpm.fetch_page_read(1, AccessType::Scan).expect("should fetch page"));
+
+        // Page 1 is now used by the index as well
+        drop(bpm.fetch_page_read(1, AccessType::Index).expect("should fetch page"));
+
+        drop(bpm.fetch_page_read(2, AccessType::Scan).expect("should fetch page"));
+        drop(bpm.fetch_page_read(3, AccessType::Scan).expect("should fetch page"));
+
+        assert_eq!(bpm.get_pin_count(0), None, "page 0 frame should be reused by the scan");
+        assert_eq!(bpm.get_pin_count(1), Some(0), "page 1 should be left to the main pool");
+        assert_eq!(bpm.get_pin_count(2), Some(0));
+        assert_eq!(bpm.get_pin_count(3), Some(0));
+    }
+
+    #[test]
+    fn scan_should_not_replace_pinned_ring_frame() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 10);
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_scan_ring_size(2)
+            .build_arc();
+
+        let page_0 = bpm.fetch_page_read(0, AccessType::Scan).expect("should fetch page");
+        drop(bpm.fetch_page_read(1, AccessType::Scan).expect("should fetch page"));
+
+        // Page 0 is still pinned, so the scan takes another frame
+        drop(bpm.fetch_page_read(2, AccessType::Scan).expect("should fetch page"));
+
+        assert_eq!(bpm.get_pin_count(0), Some(1));
+        assert_eq!(bpm.get_pin_count(1), Some(0));
+        assert_eq!(bpm.get_pin_count(2), Some(0));
+
+        drop(page_0);
+    }
+
+    #[test]
+    fn background_writer_should_only_write_until_enough_frames_are_clean() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .build_arc();
+
+        for i in 0..6 {
+            let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+            page.get_data_mut().copy_from_slice(&format!("page {}", i).as_str().align_to_page_data());
+        }
+
+        // 4 free frames, 1 more is needed to have half of the frames clean
+        assert_eq!(bpm.write_dirty_pages(&BackgroundWriterOptions::new(Duration::ZERO, 100, 50)), 1);
+        assert_eq!(bpm.write_dirty_pages(&BackgroundWriterOptions::new(Duration::ZERO, 100, 50)), 0);
+
+        // Limited by the max pages per round
+        assert_eq!(bpm.write_dirty_pages(&BackgroundWriterOptions::new(Duration::ZERO, 2, 100)), 2);
+        assert_eq!(bpm.write_dirty_pages(&BackgroundWriterOptions::new(Duration::ZERO, 100, 100)), 3);
+        assert_eq!(bpm.write_dirty_pages(&BackgroundWriterOptions::new(Duration::ZERO, 100, 100)), 0);
+
+        assert_eq!(bpm.get_background_writer_stats().get_pages_written(), 6);
+        assert_eq!(bpm.get_background_writer_stats().get_rounds(), 5);
+    }
+
+    #[test]
+    fn background_writer_should_write_dirty_unpinned_pages() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .build_arc();
+
+        let mut pinned_page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+        pinned_page.get_data_mut().copy_from_slice(&"pinned".align_to_page_data());
+
+        let page_ids: Vec<PageId> = (0..4).map(|i| {
+            let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+            page.get_data_mut().copy_from_slice(&format!("page {}", i).as_str().align_to_page_data());
+
+            page.get_page_id()
+        }).collect();
+
+        bpm.start_background_writer(BackgroundWriterOptions::new(Duration::from_millis(1), 100, 100));
+
+        let stats = bpm.get_background_writer_stats();
+        let rounds_to_wait = stats.get_rounds() + 3;
+
+        while stats.get_rounds() < rounds_to_wait {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        bpm.stop_background_writer();
+
+        assert_eq!(stats.get_pages_written(), 4, "pinned page should not be written");
+
+        // No rounds after stopping
+        let rounds = stats.get_rounds();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(stats.get_rounds(), rounds);
+
+        let other_bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .build_arc();
+
+        for (i, page_id) in page_ids.into_iter().enumerate() {
+            let page = other_bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+
+            assert_eq!(page.get_data(), &format!("page {}", i).as_str().align_to_page_data());
+        }
+
+        drop(pinned_page);
+
+        // Dropping the buffer pool stops the background writer
+        bpm.start_background_writer(BackgroundWriterOptions::new(Duration::from_millis(1), 100, 100));
+        drop(bpm);
+    }
+
+    #[test]
+    fn sharded_pool_should_keep_pages_data() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .build_sharded_arc(4);
+
+        assert_eq!(bpm.get_pool_size(), 10);
+        assert_eq!(bpm.get_shards().iter().map(|shard| shard.get_pool_size()).collect::<Vec<_>>(), vec![3, 3, 2, 2]);
+
+        let page_ids: Vec<PageId> = (0..30).map(|i| {
+            let mut page = bpm.new_page(AccessType::Unknown).expect("should be able to create new page");
+            page.get_data_mut().copy_from_slice(&format!("page {}", i).as_str().align_to_page_data());
+
+            page.get_page_id()
+        }).collect();
+
+        for (i, &page_id) in page_ids.iter().enumerate() {
+            let page = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+
+            assert_eq!(page.get_data(), &format!("page {}", i).as_str().align_to_page_data());
+            assert_eq!(bpm.get_shard(page_id).get_pin_count(page_id), Some(1), "page should be in its shard");
+        }
+
+        assert_eq!(bpm.delete_page(page_ids[0]), Ok(true));
+        assert_eq!(bpm.get_pin_count(page_ids[0]), None);
+    }
+
+    #[test]
+    fn sharded_pool_should_not_use_frames_of_other_shards() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 6);
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(4)
+            .with_arc_disk_manager(disk_manager.clone())
+            .build_sharded_arc(2);
+
+        // Fill the shard of the even pages
+        let page_0 = bpm.fetch_page_write(0, AccessType::Unknown).expect("should fetch page");
+        let page_2 = bpm.fetch_page_write(2, AccessType::Unknown).expect("should fetch page");
+
+        assert_eq!(bpm.fetch_page_read(4, AccessType::Unknown).err(), Some(FetchPageError::NoAvailableFrameFound));
+
+        // The other shard is not affected
+        drop(bpm.fetch_page_read(1, AccessType::Unknown).expect("should fetch page"));
+        drop(bpm.fetch_page_read(3, AccessType::Unknown).expect("should fetch page"));
+        drop(bpm.fetch_page_read(5, AccessType::Unknown).expect("should fetch page"));
+
+        drop(page_0);
+        drop(page_2);
+
+        drop(bpm.fetch_page_read(4, AccessType::Unknown).expect("should fetch page"));
+    }
+
+    #[test]
+    fn metrics_should_count_activity_by_access_type() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 3);
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(1)
+            .with_arc_disk_manager(disk_manager.clone())
+            .build_arc();
+
+        // Miss and then hit
+        drop(bpm.fetch_page_read(0, AccessType::Lookup).expect("should fetch page"));
+        drop(bpm.fetch_page_read(0, AccessType::Lookup).expect("should fetch page"));
+
+        // Evict page 0 and make the page dirty
+        {
+            let mut page = bpm.fetch_page_write(1, AccessType::Index).expect("should fetch page");
+            page.get_data_mut()[0] = 1;
+        }
+
+        // Evict dirty page 1
+        drop(bpm.fetch_page_read(2, AccessType::Scan).expect("should fetch page"));
+
+        assert!(bpm.flush_page(2), "should flush page");
+
+        let snapshot = bpm.get_metrics_snapshot();
+
+        assert_eq!(snapshot.get(AccessType::Lookup), &AccessTypeMetrics {
+            hits: 1,
+            misses: 1,
+            disk_reads: 1,
+            ..AccessTypeMetrics::default()
+        });
+        assert_eq!(snapshot.get(AccessType::Lookup).hit_ratio(), Some(0.5));
+
+        assert_eq!(snapshot.get(AccessType::Index), &AccessTypeMetrics {
+            misses: 1,
+            evictions: 1,
+            disk_reads: 1,
+            ..AccessTypeMetrics::default()
+        });
+
+        assert_eq!(snapshot.get(AccessType::Scan), &AccessTypeMetrics {
+            misses: 1,
+            evictions: 1,
+            dirty_write_backs: 1,
+            disk_reads: 1,
+            disk_writes: 1,
+            ..AccessTypeMetrics::default()
+        });
+
+        assert_eq!(snapshot.get(AccessType::Unknown), &AccessTypeMetrics {
+            disk_writes: 1,
+            ..AccessTypeMetrics::default()
+        });
+        assert_eq!(snapshot.get(AccessType::Unknown).hit_ratio(), None);
+
+        assert_eq!(snapshot.total(), AccessTypeMetrics {
+            hits: 1,
+            misses: 3,
+            evictions: 2,
+            dirty_write_backs: 1,
+            pin_waits: 0,
+            disk_reads: 3,
+            disk_writes: 2,
+        });
+
+        bpm.reset_metrics();
+
+        assert_eq!(bpm.get_metrics_snapshot(), BufferPoolMetricsSnapshot::default());
+    }
+
+    #[test]
+    fn resize_should_add_frames_when_growing() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let pages: Vec<PageWriteGuard> = (0..2).map(|_| bpm.new_page(AccessType::Unknown).expect("should create page")).collect();
+        assert_eq!(bpm.new_page(AccessType::Unknown).err(), Some(NewPageError::NoAvailableFrameFound));
+
+        bpm.resize(4).expect("should grow");
+        assert_eq!(bpm.get_pool_size(), 4);
+
+        let new_pages: Vec<PageWriteGuard> = (0..2).map(|_| bpm.new_page(AccessType::Unknown).expect("should use the added frames")).collect();
+        assert_eq!(bpm.new_page(AccessType::Unknown).err(), Some(NewPageError::NoAvailableFrameFound));
+
+        drop(pages);
+        drop(new_pages);
+    }
+
+    #[test]
+    fn resize_should_flush_and_evict_removed_frames_when_shrinking() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(4)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let page_ids: Vec<PageId> = (0..4u8)
+            .map(|i| {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should create page");
+                page.get_data_mut()[0] = i + 1;
+
+                page.get_page_id()
+            })
+            .collect();
+
+        bpm.resize(2).expect("should shrink");
+        assert_eq!(bpm.get_pool_size(), 2);
+
+        let resident_pages = page_ids.iter().filter(|&&page_id| bpm.get_pin_count(page_id).is_some()).count();
+        assert_eq!(resident_pages, 2, "pages of removed frames should be evicted");
+
+        // The dirty pages of the removed frames were written before they were evicted
+        for (i, &page_id) in page_ids.iter().enumerate() {
+            let page = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+            assert_eq!(page.get_data()[0], i as u8 + 1);
+        }
+
+        let pages: Vec<PageWriteGuard> = page_ids[..2].iter().map(|&page_id| bpm.fetch_page_write(page_id, AccessType::Unknown).expect("should fetch page")).collect();
+        assert_eq!(bpm.fetch_page_read(page_ids[2], AccessType::Unknown).err(), Some(FetchPageError::NoAvailableFrameFound));
+
+        drop(pages);
+    }
+
+    #[test]
+    fn resize_should_fail_without_changes_when_removed_frame_is_pinned() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let first_page_id = bpm.new_page(AccessType::Unknown).expect("should create page").get_page_id();
+        let pinned_page = bpm.new_page(AccessType::Unknown).expect("should create page");
+
+        assert_eq!(bpm.resize(1), Err(ResizeError::PageIsPinned(pinned_page.get_page_id())));
+        assert_eq!(bpm.get_pool_size(), 2);
+        assert_eq!(bpm.get_pin_count(first_page_id), Some(0));
+
+        drop(pinned_page);
+
+        bpm.resize(1).expect("should shrink after the page was released");
+        assert_eq!(bpm.get_pool_size(), 1);
+        assert_eq!(bpm.resize(0), Err(ResizeError::InvalidPoolSize));
+    }
+
+    #[test]
+    fn resize_should_move_pages_of_removed_frames_to_free_frames() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(4)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let page_ids: Vec<PageId> = (0..4u8)
+            .map(|i| {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should create page");
+                page.get_data_mut()[0] = i + 1;
+
+                page.get_page_id()
+            })
+            .collect();
+
+        // Free the first frames
+        assert_eq!(bpm.delete_page(page_ids[0]), Ok(true));
+        assert_eq!(bpm.delete_page(page_ids[1]), Ok(true));
+
+        bpm.resize(2).expect("should shrink");
+        assert_eq!(bpm.get_pool_size(), 2);
+
+        let total = bpm.get_metrics_snapshot().total();
+        assert_eq!(total.evictions, 0, "pages should be moved instead of evicted");
+        assert_eq!(total.disk_writes, 0, "moved pages should not be written");
+
+        let pages: Vec<PageWriteGuard> = page_ids[2..].iter().map(|&page_id| bpm.fetch_page_write(page_id, AccessType::Unknown).expect("should fetch page")).collect();
+        assert_eq!(pages[0].get_data()[0], 3);
+        assert_eq!(pages[1].get_data()[0], 4);
+        assert_eq!(bpm.get_metrics_snapshot().total().misses, 0, "moved pages should stay in the buffer pool");
+
+        assert_eq!(bpm.new_page(AccessType::Unknown).err(), Some(NewPageError::NoAvailableFrameFound));
+
+        drop(pages);
+    }
+
+    #[test]
+    fn resize_should_keep_all_frames_usable_when_failed_to_write_removed_pages() {
+        let disk_manager = Arc::new(DiskManagerFaultInjection::new(DiskManagerUnlimitedMemory::new()));
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(4)
+            .with_arc_disk_manager(Arc::clone(&disk_manager))
+            .build_arc();
+
+        let page_ids: Vec<PageId> = (0..4u8)
+            .map(|i| {
+                let mut page = bpm.new_page(AccessType::Unknown).expect("should create page");
+                page.get_data_mut()[0] = i + 1;
+
+                page.get_page_id()
+            })
+            .collect();
+
+        // Free the first frame, so the page of the second frame is moved to it and the rest must be written
+        assert_eq!(bpm.delete_page(page_ids[0]), Ok(true));
+
+        disk_manager.add_fault(FaultRule::new(DiskFault::FailWrite).always());
+
+        let err = bpm.resize(1).expect_err("removed pages cannot be written");
+        assert!(matches!(err, ResizeError::DiskError(DiskError::Io { .. })), "got {:?}", err);
+        assert_eq!(bpm.get_pool_size(), 4);
+
+        // The frame the page was moved from is free, so a page is created without writing the dirty pages
+        let new_page_id = bpm.new_page(AccessType::Unknown).expect("should create page in the frame the page was moved from").get_page_id();
+
+        for (&page_id, expected) in page_ids[1..].iter().zip(2u8..) {
+            assert_eq!(bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page").get_data()[0], expected);
+        }
+
+        disk_manager.clear_faults();
+        assert_eq!(bpm.delete_page(new_page_id), Ok(true));
+
+        bpm.resize(1).expect("should shrink once the pages can be written");
+        assert_eq!(bpm.get_pool_size(), 1);
+    }
+
+    #[test]
+    fn resize_should_fail_when_removed_frame_is_pinned_even_with_free_frames() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let first_page_id = bpm.new_page(AccessType::Unknown).expect("should create page").get_page_id();
+        let pinned_page = bpm.new_page(AccessType::Unknown).expect("should create page");
+
+        assert_eq!(bpm.delete_page(first_page_id), Ok(true));
+
+        // Pinned pages are not moved as their frame may be in the middle of loading a page
+        assert_eq!(bpm.resize(1), Err(ResizeError::PageIsPinned(pinned_page.get_page_id())));
+        assert_eq!(bpm.get_pool_size(), 2);
+
+        let pinned_page_id = pinned_page.get_page_id();
+        drop(pinned_page);
+
+        bpm.resize(1).expect("should shrink after the page was released");
+        assert_eq!(bpm.get_pin_count(pinned_page_id), Some(0), "released page should be moved to the free frame");
+    }
+
+    #[test]
+    fn read_guard_should_upgrade_and_downgrade_while_keeping_the_pin() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let page_id = bpm.new_page(AccessType::Unknown).expect("should create page").get_page_id();
+
+        let read_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+        assert_eq!(read_guard.get_data()[0], 0);
+
+        let mut write_guard = read_guard.try_upgrade().ok().expect("should upgrade when the only guard of the page");
+        assert_eq!(bpm.get_pin_count(page_id), Some(1));
+
+        write_guard.get_data_mut()[0] = 42;
+
+        let read_guard = write_guard.downgrade();
+        assert_eq!(bpm.get_pin_count(page_id), Some(1));
+        assert_eq!(read_guard.get_data()[0], 42);
+
+        drop(read_guard);
+        assert_eq!(bpm.get_pin_count(page_id), Some(0));
+
+        let read_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+        assert_eq!(read_guard.get_data()[0], 42, "page should keep the data written before downgrade");
+    }
+
+    #[test]
+    fn read_guard_upgrade_should_fail_while_other_guard_holds_the_page() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let page_id = bpm.new_page(AccessType::Unknown).expect("should create page").get_page_id();
+
+        let read_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+        let other_read_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+
+        let read_guard = read_guard.try_upgrade().err().expect("should not upgrade while other read guard exists");
+        assert_eq!(bpm.get_pin_count(page_id), Some(2));
+        assert_eq!(read_guard.get_page_id(), page_id);
+
+        drop(other_read_guard);
+
+        let write_guard = read_guard.try_upgrade().ok().expect("should upgrade after the other guard was dropped");
+        assert_eq!(bpm.get_pin_count(page_id), Some(1));
+
+        drop(write_guard);
+        assert_eq!(bpm.get_pin_count(page_id), Some(0));
+    }
+
+    #[test]
+    fn async_fetches_should_resolve_to_pinned_pages() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let page_ids: Vec<PageId> = (0..5u8)
+            .map(|i| {
+                let mut page = bpm.new_page_async(AccessType::Unknown).wait().expect("should create page");
+                page.get_data_mut()[0] = i + 1;
+
+                page.get_page_id()
+            })
+            .collect();
+
+        // Evict the pages so the fetches read from disk
+        bpm.resize(1).expect("should shrink");
+        bpm.resize(10).expect("should grow");
+
+        let futures: Vec<_> = page_ids.iter().map(|&page_id| bpm.fetch_page_read_async(page_id, AccessType::Lookup)).collect();
+
+        for (i, future) in futures.into_iter().enumerate() {
+            let page = future.wait().expect("should fetch page");
+
+            assert_eq!(page.get_page_id(), page_ids[i]);
+            assert_eq!(page.get_data()[0], i as u8 + 1);
+            assert_eq!(bpm.get_pin_count(page_ids[i]), Some(1));
+        }
+
+        let mut page = bpm.fetch_page_write_async(page_ids[0], AccessType::Lookup).wait().expect("should fetch page");
+        page.get_data_mut()[0] = 42;
+        drop(page);
+
+        let future = bpm.fetch_page_read_async(page_ids[0], AccessType::Lookup);
+        let page = future.wait().expect("should fetch page");
+        assert_eq!(page.get_data()[0], 42);
+
+        let missing_frame_bpm = BufferPoolManager::builder()
+            .with_pool_size(1)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let pinned_page = missing_frame_bpm.new_page(AccessType::Unknown).expect("should create page");
+        let future = missing_frame_bpm.new_page_async(AccessType::Unknown);
+        assert_eq!(future.wait().err(), Some(NewPageError::NoAvailableFrameFound));
+
+        drop(pinned_page);
+    }
+
+    #[test]
+    fn async_fetch_should_keep_the_page_pinned_until_waited_or_dropped() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 2);
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_arc_disk_manager(disk_manager.clone())
+            .build_arc();
+
+        let future = bpm.fetch_page_read_async(0, AccessType::Lookup);
+        assert_eq!(bpm.get_pin_count(0), Some(1), "page should be pinned while the read is scheduled");
+
+        while !future.is_ready() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // The read finished, but the page is still pinned for the future
+        assert_eq!(bpm.get_pin_count(0), Some(1));
+
+        let page = future.wait().expect("should fetch page");
+        assert_eq!(bpm.get_pin_count(0), Some(1));
+        drop(page);
+        assert_eq!(bpm.get_pin_count(0), Some(0));
+
+        drop(bpm.fetch_page_write_async(1, AccessType::Lookup));
+        assert_eq!(bpm.get_pin_count(1), Some(0), "dropped future should release the page");
+
+        let page = bpm.fetch_page_read(1, AccessType::Lookup).expect("should fetch page");
+        assert_eq!(page.get_page_id(), 1);
+        assert_eq!(bpm.get_metrics_snapshot().get(AccessType::Lookup).disk_reads, 2, "page should be read once");
+    }
+
+    #[test]
+    fn fetch_pages_should_return_the_pages_in_the_requested_order() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 6);
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(4)
+            .with_arc_disk_manager(disk_manager.clone())
+            .build_arc();
+
+        let page_ids: Vec<PageId> = vec![3, 0, 5, 3];
+
+        let pages = bpm.fetch_pages(&page_ids, AccessType::Index);
+        assert_eq!(pages.len(), page_ids.len());
+
+        for (page, &page_id) in pages.iter().zip(page_ids.iter()) {
+            assert_eq!(page.as_ref().expect("should fetch page").get_page_id(), page_id);
+        }
+
+        assert_eq!(bpm.get_pin_count(3), Some(2));
+        assert_eq!(bpm.get_metrics_snapshot().get(AccessType::Index).disk_reads, 3);
+
+        drop(pages);
+
+        // Only 4 frames for 6 pages
+        let pages = bpm.fetch_pages(&[0, 1, 2, 3, 4, 5], AccessType::Index);
+        assert_eq!(pages.iter().filter(|page| page.is_ok()).count(), 4);
+        assert_eq!(pages.iter().filter(|page| matches!(page, Err(FetchPageError::NoAvailableFrameFound))).count(), 2);
+    }
+
+    #[test]
+    fn sharded_pool_async_requests_should_use_the_shard_of_the_page() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(8)
+            .build_sharded_arc(2);
+
+        let page_ids: Vec<PageId> = (0..4)
+            .map(|_| bpm.new_page_async(AccessType::Unknown).wait().expect("should create page").get_page_id())
+            .collect();
+
+        let pages = bpm.fetch_pages(&page_ids, AccessType::Unknown);
+
+        for (page, &page_id) in pages.iter().zip(page_ids.iter()) {
+            assert_eq!(page.as_ref().expect("should fetch page").get_page_id(), page_id);
+            assert_eq!(bpm.get_shard(page_id).get_pin_count(page_id), Some(1), "page should be in its shard");
+        }
+    }
+
+    #[test]
+    fn warm_up_should_restore_the_hot_pages_in_access_order() {
+        let tmpdir = setup();
+        let warm_up_file = tmpdir.path().join("test.warm");
+
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        create_pages_on_disk(&disk_manager, 4);
+
+        {
+            let bpm = BufferPoolManager::builder()
+                .with_pool_size(3)
+                .with_arc_disk_manager(disk_manager.clone())
+                .with_lru_k_eviction_policy(2)
+                .build_arc();
+
+            // Page 0 is the least recently used
+            for page_id in [0, 1, 2, 1, 2] {
+                drop(bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page"));
+            }
+
+            assert_eq!(bpm.save_hot_pages(&warm_up_file), Ok(3));
+        }
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_lru_k_eviction_policy(2)
+            .build_arc();
+
+        assert_eq!(bpm.warm_up(warm_up_file.clone()).wait(), Ok(3));
+
+        // Smaller pool keeps only the most recently used pages
+        assert_eq!(bpm.get_pin_count(0), None);
+        assert_eq!(bpm.get_pin_count(1), Some(0));
+        assert_eq!(bpm.get_pin_count(2), Some(0));
+
+        assert_eq!(bpm.warm_up(tmpdir.path().join("missing.warm")).wait(), Ok(0), "missing file should load no pages");
+    }
+
+    #[test]
+    fn flush_page_should_flush_the_log_up_to_the_page_lsn_first() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        let log_manager = Arc::new(LogManager::new(disk_manager.clone()));
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(2)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_log_manager(Some(log_manager.clone()))
+            .build_arc();
+
+        let lsn = log_manager.append_log_record(&mut LogRecord::new(0, INVALID_LSN, LogRecordBody::Begin));
+
+        let page_id = {
+            let mut page = bpm.new_page(AccessType::Unknown).expect("should create page");
+            page.get_data_mut()[0] = 1;
+            page.set_lsn(lsn);
+
+            page.get_page_id()
+        };
+
+        assert_eq!(log_manager.get_persistent_lsn(), INVALID_LSN, "should not flush the log before writing the page");
+
+        assert!(bpm.flush_page(page_id));
+        assert_eq!(log_manager.get_persistent_lsn(), lsn, "log must be flushed before the page is written");
+    }
+
+    #[test]
+    fn evicting_dirty_page_should_flush_the_log_up_to_the_page_lsn_first() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        let log_manager = Arc::new(LogManager::new(disk_manager.clone()));
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(1)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_log_manager(Some(log_manager.clone()))
+            .build_arc();
+
+        let lsn = log_manager.append_log_record(&mut LogRecord::new(0, INVALID_LSN, LogRecordBody::Begin));
+
+        let page_id = {
+            let mut page = bpm.new_page(AccessType::Unknown).expect("should create page");
+            page.get_data_mut()[0] = 1;
+            page.set_lsn(lsn);
+
+            page.get_page_id()
+        };
+
+        // Replaces the only frame
+        drop(bpm.new_page(AccessType::Unknown).expect("should create page"));
+        assert_eq!(log_manager.get_persistent_lsn(), lsn, "log must be flushed before the page is written");
+
+        let page = bpm.fetch_page_write(page_id, AccessType::Unknown).expect("should fetch page");
+        assert_eq!(page.get_lsn(), lsn, "page LSN should be written with the page");
+        assert_eq!(page.get_data()[0], 1);
+    }
+
+    #[test]
+    #[cfg(feature = "guard_tracking")]
+    fn should_list_holders_of_page_guards_that_were_not_dropped() {
+        use crate::PageGuardKind;
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(4)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .build_arc();
+
+        let write_guard = bpm.new_page(AccessType::Unknown).expect("should create page");
+        let page_id = write_guard.get_page_id();
+        drop(write_guard);
+
+        let read_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+        let other_read_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch page");
+
+        let holders = bpm.get_page_guard_holders();
+        assert_eq!(holders.len(), 2);
+
+        for holder in holders {
+            assert_eq!(holder.page_id, page_id);
+            assert_eq!(holder.kind, PageGuardKind::Read);
+            assert_eq!(holder.thread_id, thread::current().id());
+        }
+
+        drop(read_guard);
+        assert_eq!(bpm.get_page_guard_holders().len(), 1);
+
+        drop(other_read_guard);
+        assert!(bpm.get_page_guard_holders().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "guard_tracking")]
+    fn should_detect_page_latches_taken_in_inconsistent_order() {
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(4)
+            .with_disk_manager(DiskManagerUnlimitedMemory::new())
+            .with_lock_order_detection(true)
+            .build_arc();
+
+        let first_page_id = bpm.new_page(AccessType::Unknown).expect("should create page").get_page_id();
+        let second_page_id = bpm.new_page(AccessType::Unknown).expect("should create page").get_page_id();
+
+        {
+            let _first = bpm.fetch_page_write(first_page_id, AccessType::Unknown).expect("should fetch page");
+            let _second = bpm.fetch_page_write(second_page_id, AccessType::Unknown).expect("should fetch page");
+        }
+
+        assert!(bpm.get_lock_order_inversions().is_empty(), "same order should not be reported");
+
+        {
+            let _second = bpm.fetch_page_read(second_page_id, AccessType::Unknown).expect("should fetch page");
+            let _first = bpm.fetch_page_read(first_page_id, AccessType::Unknown).expect("should fetch page");
+        }
+
+        let inversions = bpm.get_lock_order_inversions();
+        assert_eq!(inversions.len(), 1);
+        assert_eq!(inversions[0].held_page_id, second_page_id);
+        assert_eq!(inversions[0].acquired_page_id, first_page_id);
+    }
+}
@@ -1,5 +1,6 @@
 use super::errors::{DeletePageError, FetchPageError, NewPageError};
 use super::page_guards::{PageReadGuard, PageWriteGuard};
+use super::async_fetch::PageFuture;
 use buffer_common::AccessType;
 use pages::PageId;
 
@@ -38,6 +39,62 @@ pub trait BufferPool {
     ///
     fn fetch_page_write(&self, page_id: PageId, access_type: AccessType) -> Result<PageWriteGuard, FetchPageError>;
 
+    /// Create new page, new pages are not read from disk so the page is created in the calling thread
+    ///
+    /// # Arguments
+    ///
+    /// * `access_type`: For leaderboard
+    ///
+    /// returns: PageFuture<Result<PageWriteGuard, NewPageError>> future that resolves to the write guard of the new page
+    ///
+    fn new_page_async(&self, access_type: AccessType) -> PageFuture<Result<PageWriteGuard<'static>, NewPageError>>;
+
+    /// Fetch page with read guard without blocking the calling thread, the page is read by the disk scheduler
+    ///
+    /// Pages that are in the buffer pool or that need the replaced page to be flushed are fetched when the future is waited for
+    ///
+    /// # Arguments
+    ///
+    /// * `page_id`: The page id to fetch
+    /// * `access_type`: For leaderboard
+    ///
+    /// returns: PageFuture<Result<PageReadGuard, FetchPageError>> future that resolves to the read guard of the page
+    ///
+    fn fetch_page_read_async(&self, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageReadGuard<'static>, FetchPageError>>;
+
+    /// Fetch page with write guard without blocking the calling thread, the page is read by the disk scheduler
+    ///
+    /// Pages that are in the buffer pool or that need the replaced page to be flushed are fetched when the future is waited for
+    ///
+    /// # Arguments
+    ///
+    /// * `page_id`: The page id to fetch
+    /// * `access_type`: For leaderboard
+    ///
+    /// returns: PageFuture<Result<PageWriteGuard, FetchPageError>> future that resolves to the write guard of the page
+    ///
+    fn fetch_page_write_async(&self, page_id: PageId, access_type: AccessType) -> PageFuture<Result<PageWriteGuard<'static>, FetchPageError>>;
+
+    /// Fetch multiple pages with read guards, the pages are read from disk in parallel
+    ///
+    /// Each page holds a frame until its guard is dropped, so fetching more pages than the buffer pool has frames fails for the rest
+    ///
+    /// # Arguments
+    ///
+    /// * `page_ids`: The page ids to fetch
+    /// * `access_type`: For leaderboard
+    ///
+    /// returns: Vec<Result<PageReadGuard, FetchPageError>> the result of each page in the order of `page_ids`
+    ///
+    fn fetch_pages(&self, page_ids: &[PageId], access_type: AccessType) -> Vec<Result<PageReadGuard<'static>, FetchPageError>> {
+        let futures: Vec<_> = page_ids
+            .iter()
+            .map(|&page_id| self.fetch_page_read_async(page_id, access_type))
+            .collect();
+
+        futures.into_iter().map(PageFuture::wait).collect()
+    }
+
 
     /// Flush page to disk REGARDLESS of the dirty flag.
     ///
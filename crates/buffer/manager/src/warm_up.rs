@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use buffer_common::AccessType;
+use disk_storage::errors::DiskError;
+use pages::PageId;
+use crate::{errors, BufferPool, BufferPoolManager, PageFuture};
+
+impl BufferPoolManager {
+    /// Write the pages that are in the buffer pool to a file, so they can be loaded after restart with `warm_up`
+    ///
+    /// The file is a list of page ids, one for each access in the eviction policy history ordered from the least recent.
+    /// Policies without history write each page once.
+    /// Pages that were read ahead and not used yet are not written
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path`: the file to write, replaced if exists
+    ///
+    /// returns: Result<usize, DiskError> the number of pages that were written
+    ///
+    pub fn save_hot_pages(&self, file_path: &Path) -> Result<usize, DiskError> {
+        let mut accesses: Vec<(u64, PageId)> = vec![];
+        let number_of_pages;
+
+        {
+            let inner = self.inner.lock();
+
+            let hot_pages = inner.page_table
+                .iter()
+                .filter(|(page_id, _)| !inner.read_ahead_pages.contains(page_id) && !inner.read_ahead_requests.contains_key(page_id));
+
+            for (&page_id, &frame_id) in hot_pages {
+                let history = inner.eviction_policy.get_access_history(frame_id);
+
+                if history.is_empty() {
+                    accesses.push((0, page_id));
+                } else {
+                    accesses.extend(history.into_iter().map(|access| (access, page_id)));
+                }
+            }
+
+            number_of_pages = accesses.iter().map(|&(_, page_id)| page_id).collect::<HashSet<_>>().len();
+        }
+
+        accesses.sort();
+
+        let content: Vec<u8> = accesses
+            .into_iter()
+            .flat_map(|(_, page_id)| page_id.to_le_bytes())
+            .collect();
+
+        fs::write(file_path, content)?;
+
+        Ok(number_of_pages)
+    }
+
+    /// Load the pages that were written by `save_hot_pages` in the background
+    ///
+    /// The accesses are replayed in the same order, so the eviction policy keeps the same pages as before.
+    /// Pages that are no longer allocated or that fail to be fetched are skipped,
+    /// and loading stops when all the frames are pinned
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path`: the file written by `save_hot_pages`, a missing file loads no pages
+    ///
+    /// returns: PageFuture<Result<usize, DiskError>> future that resolves to the number of pages that were loaded
+    ///
+    pub fn warm_up(self: &Arc<Self>, file_path: PathBuf) -> PageFuture<Result<usize, DiskError>> {
+        let bpm = self.clone();
+
+        PageFuture::spawn(move || {
+            let content = match fs::read(&file_path) {
+                Ok(content) => content,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+                Err(err) => return Err(err.into()),
+            };
+
+            let disk_scheduler = bpm.inner.lock().disk_scheduler.clone();
+            let mut loaded_pages = HashSet::new();
+
+            for page_id_bytes in content.chunks_exact(size_of::<PageId>()) {
+                let page_id = PageId::from_le_bytes(page_id_bytes.try_into().unwrap());
+
+                // The page might have been deleted after the file was written
+                if !disk_scheduler.is_page_allocated(page_id) {
+                    continue;
+                }
+
+                match bpm.fetch_page_read(page_id, AccessType::Unknown) {
+                    Ok(_) => {
+                        loaded_pages.insert(page_id);
+                    }
+                    Err(errors::FetchPageError::NoAvailableFrameFound) => break,
+                    Err(_) => continue,
+                }
+            }
+
+            Ok(loaded_pages.len())
+        })
+    }
+}
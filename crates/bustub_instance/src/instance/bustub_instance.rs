@@ -1,16 +1,18 @@
 use crate::instance::ddl::StatementHandler;
 use crate::rows::Rows;
 use binder::{Binder, StatementTypeImpl};
-use buffer_pool_manager::BufferPoolManager;
+use buffer_pool_manager::{BufferPoolManager, PageFuture};
 use catalog_schema_mocks::MockTableName;
-use checkpoint_manager::CheckpointManager;
+use checkpoint_manager::{CheckpointManager, CheckpointOptions};
 use db_core::catalog::Catalog;
 use db_core::concurrency::TransactionManager;
 use disk_storage::{DefaultDiskManager, DiskManager, DiskManagerUnlimitedMemory};
-use error_utils::ToAnyhow;
+use disk_storage::errors::DiskError;
+use error_utils::{Context, ToAnyhow, ToAnyhowResult};
 use execution_common::CheckOptions;
 use execution_engine::{ExecutionEngine, ExecutorContext};
 use lock_manager::LockManager;
+use log_recovery::LogRecovery;
 use parking_lot::Mutex;
 use planner::{PlanNode, Planner};
 use recovery_log_manager::LogManager;
@@ -18,13 +20,16 @@ use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
 use std::sync::Arc;
-use transaction::{Transaction, TransactionManager as TransactionManagerTrait};
+use transaction::{Transaction, TransactionManager as TransactionManagerTrait, TransactionState};
 use crate::instance::db_output::{DBOutput, MultipleCommandsOutput, SqlDBOutput, SystemOutput};
 use crate::table_generator::TableGenerator;
 
 const DEFAULT_BPM_SIZE: usize = 128;
 const LRU_K_REPLACER_K: usize = 10;
 
+/// Extension of the file next to the database file that keeps the pages that were in the buffer pool
+const WARM_UP_FILE_EXTENSION: &str = "warm";
+
 
 pub struct BustubInstance {
     pub(super) disk_manager: Arc<dyn DiskManager>,
@@ -41,14 +46,52 @@ pub struct BustubInstance {
     pub(super) session_variables: HashMap<String, String>,
     pub(super) current_txn: Option<Arc<Transaction>>,
     pub(super) managed_txn_mode: bool,
+
+    /// File to save the pages of the buffer pool to when closed, None for in memory instance
+    pub(super) warm_up_file_path: Option<PathBuf>,
+
+    /// Loading of the pages that were in the buffer pool when the database was closed
+    pub(super) warm_up: Option<PageFuture<Result<usize, DiskError>>>,
 }
 
 impl BustubInstance {
     /// Create bustub instance from file
     ///
     /// the default bpm size is `DEFAULT_BPM_SIZE`
-    pub fn from_file(db_file_path: PathBuf, bpm_size: Option<usize>) -> Self {
-        Self::create_from_disk_manager(DefaultDiskManager::new(db_file_path).expect("disk manager failed to initialize"), bpm_size)
+    ///
+    /// The pages that were in the buffer pool when the database was closed are loaded in the background,
+    /// use `wait_for_warm_up` to wait for them
+    ///
+    /// The log is replayed first so the changes of committed transactions are kept and the rest are rolled back,
+    /// from the last checkpoint. Checkpoints are then taken periodically in the background
+    ///
+    /// returns error if the file is not a database file, was created by an incompatible version or failed to replay the log
+    pub fn from_file(db_file_path: PathBuf, bpm_size: Option<usize>) -> error_utils::anyhow::Result<Self> {
+        let disk_manager = DefaultDiskManager::new(db_file_path.clone())
+            .with_context(|| format!("Failed to open database file {}", db_file_path.display()))?;
+
+        let mut instance = Self::create_from_disk_manager(disk_manager, bpm_size);
+
+        // Bring the pages back to the committed state before anything reads them
+        if let Some(log_manager) = &instance.log_manager {
+            LogRecovery::new(instance.disk_manager.clone(), instance.buffer_pool_manager.clone(), log_manager.clone())
+                .recover()
+                .map_err(|err| err.to_anyhow())
+                .context("Failed to recover the database from the log")?;
+
+            instance.rebuild_indexes().context("Failed to rebuild the indexes after recovery")?;
+        }
+
+        // Checkpoint periodically so the next recovery does not read the whole log
+        if let Some(checkpoint_manager) = &instance.checkpoint_manager {
+            checkpoint_manager.start_checkpoint_thread(CheckpointOptions::default());
+        }
+
+        let warm_up_file_path = db_file_path.with_extension(WARM_UP_FILE_EXTENSION);
+        instance.warm_up = Some(instance.buffer_pool_manager.warm_up(warm_up_file_path.clone()));
+        instance.warm_up_file_path = Some(warm_up_file_path);
+
+        Ok(instance)
     }
 
     /// Create bustub instance in memory
@@ -61,7 +104,7 @@ impl BustubInstance {
     /// Create bustub instance from provided disk manager
     ///
     /// the default bpm size is `DEFAULT_BPM_SIZE`
-    fn create_from_disk_manager<DiskManagerImpl: DiskManager>(disk_manager: DiskManagerImpl, bpm_size: Option<usize>) -> Self {
+    pub(crate) fn create_from_disk_manager<DiskManagerImpl: DiskManager>(disk_manager: DiskManagerImpl, bpm_size: Option<usize>) -> Self {
         // TODO - add global enable logging variable should be false
 
         let disk_manager = Arc::new(disk_manager);
@@ -69,16 +112,26 @@ impl BustubInstance {
         let mut log_manager: Option<Arc<LogManager>> = None;
 
         #[cfg(feature = "checkpoint_manager")]
-        let log_manager = Some(Arc::new(LogManager::new(disk_manager.clone())));
+        let log_manager = {
+            let log_manager = Arc::new(LogManager::new(disk_manager.clone()));
+            log_manager.run_flush_thread();
+
+            Some(log_manager)
+        };
 
         // We need more frames for generate_test_table to work. Therefore, we use 128 instead of the default
         // buffer pool size specified in `config.h`.
 
-        let bpm = BufferPoolManager::builder()
+        let bpm_builder = BufferPoolManager::builder()
             .with_pool_size(bpm_size.unwrap_or(DEFAULT_BPM_SIZE))
             .with_arc_disk_manager(disk_manager.clone())
             .with_lru_k_eviction_policy(LRU_K_REPLACER_K)
-            .with_log_manager(log_manager.clone()).build_arc();
+            .with_log_manager(log_manager.clone());
+
+        #[cfg(feature = "guard_tracking")]
+        let bpm_builder = bpm_builder.with_lock_order_detection(true);
+
+        let bpm = bpm_builder.build_arc();
 
         let mut lock_manager: Option<Arc<LockManager>> = None;
 
@@ -106,16 +159,20 @@ impl BustubInstance {
         // }
 
 
+        let catalog = Arc::new(Mutex::new(Catalog::new(Some(bpm.clone()), lock_manager.clone(), log_manager.clone())));
+        let txn_manager = Arc::new(TransactionManager::new(catalog.clone(), log_manager.clone()));
+
         #[cfg(feature = "checkpoint_manager")]
-        let checkpoint_manager = Some(Arc::new(CheckpointManager::new(None, log_manager.clone().unwrap(), bpm.clone())));
+        let checkpoint_manager = Some(Arc::new(CheckpointManager::new(
+            Some(txn_manager.clone()),
+            log_manager.clone().unwrap(),
+            bpm.clone(),
+            disk_manager.clone(),
+        )));
 
         #[cfg(not(feature = "checkpoint_manager"))]
         let mut checkpoint_manager: Option<Arc<CheckpointManager>> = None;
 
-
-        let catalog = Arc::new(Mutex::new(Catalog::new(Some(bpm.clone()), lock_manager.clone(), log_manager.clone())));
-        let txn_manager = Arc::new(TransactionManager::new(catalog.clone()));
-
         let execution_engine = Arc::new(ExecutionEngine::new(
             bpm.clone(),
             txn_manager.clone(),
@@ -135,9 +192,38 @@ impl BustubInstance {
             session_variables: HashMap::new(),
             current_txn: None,
             managed_txn_mode: false,
+
+            warm_up_file_path: None,
+            warm_up: None,
         }
     }
 
+    /// Wait until the pages that were in the buffer pool when the database was closed are loaded
+    ///
+    /// returns: Option<Result<usize, DiskError>> the number of loaded pages, None if there is no loading in progress
+    pub fn wait_for_warm_up(&mut self) -> Option<Result<usize, DiskError>> {
+        self.warm_up.take().map(PageFuture::wait)
+    }
+
+    /// Save the pages that are in the buffer pool, so they are loaded when the database file is opened again
+    ///
+    /// This is done when the instance is dropped, in memory instance has nothing to save
+    ///
+    /// returns: the number of saved pages
+    pub fn save_hot_pages(&mut self) -> error_utils::anyhow::Result<usize> {
+        // Saving while still loading would lose the pages that were not loaded yet
+        let _ = self.wait_for_warm_up();
+
+        let Some(warm_up_file_path) = &self.warm_up_file_path else {
+            return Ok(0);
+        };
+
+        self.buffer_pool_manager
+            .save_hot_pages(warm_up_file_path)
+            .map_err(|err| err.to_anyhow())
+            .with_context(|| format!("Failed to save hot pages to {}", warm_up_file_path.display()))
+    }
+
     /// FOR TEST ONLY. Generate test tables in this BusTub instance.
     /// It's used in the shell to predefine some tables, as we don't support
     /// create / drop table and insert for now. Should remove it in the future.
@@ -153,18 +239,43 @@ impl BustubInstance {
 
         drop(catalog_guard);
 
-        self.txn_manager.commit(txn);
+        self.txn_manager.commit(txn).expect("should persist the commit of the mock tables");
     }
 
-    pub fn generate_test_table(&self) {
+    pub fn generate_test_table(&self) -> error_utils::anyhow::Result<()> {
         let txn = self.txn_manager.begin(None);
 
-        {
+        let generated = {
             let mut catalog = self.catalog.lock();
-            TableGenerator::generate_test_tables(txn.clone(), catalog.deref_mut());
+            TableGenerator::generate_test_tables(txn.clone(), catalog.deref_mut()).to_anyhow()
+        };
+
+        if let Err(err) = generated {
+            self.txn_manager.abort(txn);
+
+            return Err(err);
+        }
+
+        self.txn_manager.commit(txn).map_err(|err| err.to_anyhow())?;
+
+        Ok(())
+    }
+
+    /// Rebuild the indexes from the recovered table heaps as index changes are not logged
+    pub(crate) fn rebuild_indexes(&self) -> error_utils::anyhow::Result<()> {
+        let txn = self.txn_manager.begin(None);
+
+        let rebuilt = self.catalog.lock().rebuild_indexes(txn.deref());
+
+        if let Err(err) = rebuilt {
+            self.txn_manager.abort(txn);
+
+            return Err(err);
         }
 
-        self.txn_manager.commit(txn);
+        self.txn_manager.commit(txn).map_err(|err| err.to_anyhow())?;
+
+        Ok(())
     }
 
     /// Enable managed txn mode on this BusTub instance, allowing statements like `BEGIN`
@@ -197,7 +308,7 @@ impl BustubInstance {
         )
     }
 
-    fn wrap_with_txn<R, F: FnOnce(&mut Self, Arc<Transaction>) -> R>(&mut self, f: F) -> R {
+    fn wrap_with_txn<R, F: FnOnce(&mut Self, Arc<Transaction>) -> error_utils::anyhow::Result<R>>(&mut self, f: F) -> error_utils::anyhow::Result<R> {
         let is_local_txn = self.current_txn.is_some();
 
         let txn = self.current_txn.clone().unwrap_or_else(|| self.txn_manager.begin(None));
@@ -205,7 +316,14 @@ impl BustubInstance {
         let result = f(self, txn.clone());
 
         if !is_local_txn {
-            let res = self.txn_manager.commit(txn);
+            // The statement failed after changing the tables
+            if txn.get_transaction_state() == TransactionState::Tainted {
+                self.txn_manager.abort(txn);
+
+                return result;
+            }
+
+            let res = self.txn_manager.commit(txn).map_err(|err| err.to_anyhow())?;
 
             // TODO - change this to return result instead
             assert!(res, "Failed to commit txn");
@@ -335,6 +453,10 @@ impl BustubInstance {
             "\\dt" => self.cmd_display_tables(),
             "\\di" => self.cmd_display_indices(),
             "\\help" => Self::cmd_display_help(),
+            "\\bpm" => self.cmd_display_buffer_pool_metrics(),
+            "\\bpm reset" => self.cmd_reset_buffer_pool_metrics(),
+            "\\pins" => self.cmd_display_page_guard_holders(),
+            "\\pins order" => self.cmd_display_lock_order_inversions(),
             _ => {
                 if cmd.starts_with("\\dbgmvcc") {
                     self.cmd_dbg_mvcc(cmd.split("").collect())
@@ -380,6 +502,9 @@ impl BustubInstance {
 
 impl Drop for BustubInstance {
     fn drop(&mut self) {
+        // Only makes the next start slower if failed
+        let _ = self.save_hot_pages();
+
         if let Some(log_manager) = &self.log_manager {
             log_manager.stop_flush_thread();
         }
@@ -1,3 +1,5 @@
+use buffer_common::AccessType;
+use buffer_pool_manager::AccessTypeMetrics;
 use common::config::{TxnId, TXN_START_ID};
 use transaction::TransactionManager;
 use crate::BustubInstance;
@@ -77,12 +79,107 @@ impl BustubInstance {
         ))
     }
 
+    pub fn cmd_display_buffer_pool_metrics(&self) -> SystemOutputResult {
+        let snapshot = self.buffer_pool_manager.get_metrics_snapshot();
+
+        let metrics_row = |name: String, metrics: &AccessTypeMetrics| vec![
+            name,
+            metrics.hits.to_string(),
+            metrics.misses.to_string(),
+            metrics.hit_ratio().map_or("-".to_string(), |ratio| format!("{:.2}", ratio)),
+            metrics.evictions.to_string(),
+            metrics.dirty_write_backs.to_string(),
+            metrics.pin_waits.to_string(),
+            metrics.disk_reads.to_string(),
+            metrics.disk_writes.to_string(),
+        ];
+
+        let mut rows: Vec<Vec<String>> = AccessType::ALL
+            .iter()
+            .map(|&access_type| metrics_row(format!("{:?}", access_type), snapshot.get(access_type)))
+            .collect();
+
+        rows.push(metrics_row("Total".to_string(), &snapshot.total()));
+
+        Ok(SystemOutput::new(
+            vec![
+                "access_type".to_string(),
+                "hits".to_string(),
+                "misses".to_string(),
+                "hit_ratio".to_string(),
+                "evictions".to_string(),
+                "dirty_write_backs".to_string(),
+                "pin_waits".to_string(),
+                "disk_reads".to_string(),
+                "disk_writes".to_string(),
+            ],
+            rows,
+            false
+        ))
+    }
+
+    pub fn cmd_reset_buffer_pool_metrics(&self) -> SystemOutputResult {
+        self.buffer_pool_manager.reset_metrics();
+
+        Ok(SystemOutput::single_cell("buffer pool metrics reset".to_string()))
+    }
+
+    #[cfg(feature = "guard_tracking")]
+    pub fn cmd_display_page_guard_holders(&self) -> SystemOutputResult {
+        Ok(SystemOutput::new(
+            vec!["page_id".to_string(), "guard".to_string(), "thread".to_string(), "backtrace".to_string()],
+            self.buffer_pool_manager
+                .get_page_guard_holders()
+                .iter()
+                .map(|holder| vec![
+                    holder.page_id.to_string(),
+                    format!("{:?}", holder.kind),
+                    holder.thread_name.clone().unwrap_or_else(|| format!("{:?}", holder.thread_id)),
+                    holder.backtrace.to_string(),
+                ])
+                .collect(),
+            false
+        ))
+    }
+
+    #[cfg(not(feature = "guard_tracking"))]
+    pub fn cmd_display_page_guard_holders(&self) -> SystemOutputResult {
+        Err(error_utils::anyhow!("pinned pages are only tracked when compiled with the guard_tracking feature"))
+    }
+
+    #[cfg(feature = "guard_tracking")]
+    pub fn cmd_display_lock_order_inversions(&self) -> SystemOutputResult {
+        Ok(SystemOutput::new(
+            vec!["held_page_id".to_string(), "acquired_page_id".to_string(), "thread".to_string(), "backtrace".to_string()],
+            self.buffer_pool_manager
+                .get_lock_order_inversions()
+                .iter()
+                .map(|inversion| vec![
+                    inversion.held_page_id.to_string(),
+                    inversion.acquired_page_id.to_string(),
+                    format!("{:?}", inversion.thread_id),
+                    inversion.backtrace.to_string(),
+                ])
+                .collect(),
+            false
+        ))
+    }
+
+    #[cfg(not(feature = "guard_tracking"))]
+    pub fn cmd_display_lock_order_inversions(&self) -> SystemOutputResult {
+        Err(error_utils::anyhow!("page latches order is only tracked when compiled with the guard_tracking feature"))
+    }
+
     pub fn cmd_display_help() -> SystemOutputResult {
         Ok(SystemOutput::single_cell(r"(Welcome to the BusTub shell!
 
 \dt: show all tables
 \di: show all indices
 \dbgmvcc <table>: show version chain of a table
+\bpm: show buffer pool metrics by access type
+\bpm reset: reset buffer pool metrics
+\pins: show pinned pages and the threads holding them (requires the guard_tracking feature)
+\pins order: show page latches taken in inconsistent order (requires the guard_tracking feature)
 \help: show this message again
 \txn: show current txn information
 \txn <txn_id>: switch to txn
@@ -1,5 +1,6 @@
 use crate::table_generator::column_insert_meta::{ColumnInsertMeta, GenerateMeta, GenerateType};
 use crate::table_generator::table_insert_meta::TableInsertMeta;
+use buffer_pool_manager::errors::BufferPoolError;
 use data_types::{DBTypeId, IntUnderlyingType, Value};
 use db_core::catalog::{Catalog, TableInfo};
 use execution_engine::ExecutorContext;
@@ -20,7 +21,7 @@ use crate::table_generator::generate_values::GenerateValues;
 pub(crate) struct TableGenerator;
 
 impl TableGenerator {
-    pub fn generate_test_tables(txn: Arc<Transaction>, catalog: &mut Catalog) {
+    pub fn generate_test_tables(txn: Arc<Transaction>, catalog: &mut Catalog) -> Result<(), BufferPoolError> {
         for mut table_meta in Self::get_insert_meta() {
             // Create Schema
             let schema: Schema = table_meta.col_meta
@@ -43,11 +44,13 @@ impl TableGenerator {
                 None,
             ).expect("Should be able to create table");
             
-            Self::fill_table(info, &mut table_meta);
+            Self::fill_table(info, &mut table_meta)?;
         }
+
+        Ok(())
     }
 
-    fn fill_table(info: &TableInfo, table_meta: &mut TableInsertMeta) {
+    fn fill_table(info: &TableInfo, table_meta: &mut TableInsertMeta) -> Result<(), BufferPoolError> {
         let mut rng = thread_rng();
         let mut num_inserted = 0;
         let batch_size = 128;
@@ -61,7 +64,7 @@ impl TableGenerator {
                 .map(|item| Self::make_values(item, num_values, &mut rng))
                 .collect::<Vec<_>>();
 
-            (0..num_values).for_each(|i| {
+            for i in 0..num_values {
                 let entry = values
                     .iter()
                     // TODO - remove clone
@@ -74,11 +77,14 @@ impl TableGenerator {
                     &None,
                     &dummy_txn,
                     None,
-                ).expect("Sequential insertion cannot fail");
+                )?
+                    .expect("Sequential insertion cannot fail");
 
                 num_inserted += 1;
-            });
+            }
         }
+
+        Ok(())
     }
 
     fn make_values(col_meta: &mut ColumnInsertMeta, count: usize, rng: &mut ThreadRng) -> Vec<Value> {
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use crate::BustubInstance;
+    use execution_common::CheckOptions;
+
+    #[test]
+    fn should_display_buffer_pool_metrics() {
+        let mut instance = BustubInstance::in_memory(None);
+
+        instance.execute_user_input("CREATE TABLE books (id int);", CheckOptions::default()).expect("Should execute");
+
+        let output = instance.execute_user_input("\\bpm", CheckOptions::default()).expect("Should execute");
+        let output = format!("{:?}", output);
+
+        for access_type in ["Unknown", "Lookup", "Scan", "Index", "Total"] {
+            assert!(output.contains(access_type), "should have row for {}, got {}", access_type, output);
+        }
+    }
+
+    #[test]
+    fn should_reset_buffer_pool_metrics() {
+        let mut instance = BustubInstance::in_memory(None);
+
+        instance.execute_user_input("\\bpm reset", CheckOptions::default()).expect("Should execute");
+    }
+}
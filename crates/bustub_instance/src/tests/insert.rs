@@ -3,6 +3,7 @@ mod tests {
     use crate::result_writer::NoopWriter;
     use crate::BustubInstance;
     use data_types::{DBTypeId, Value};
+    use disk_storage::{DiskFault, DiskManagerFaultInjection, DiskManagerUnlimitedMemory, FaultRule};
     use execution_common::CheckOptions;
 
     fn successful_create_table(instance: &mut BustubInstance, sql: &str) {
@@ -566,6 +567,38 @@ mod tests {
         instance.verify_integrity();
     }
 
+    #[test]
+    fn should_return_error_when_failed_to_write_table_pages() {
+        let disk_manager = DiskManagerFaultInjection::new(DiskManagerUnlimitedMemory::new());
+        disk_manager.add_fault(FaultRule::new(DiskFault::FailWrite).always());
+
+        // Too few frames for the table, so inserting must write pages back
+        let mut instance = BustubInstance::create_from_disk_manager(disk_manager, Some(3));
+
+        successful_create_table(&mut instance, "CREATE TABLE books (id int);");
+
+        let values = (0..5000).map(|i| format!("({})", i)).collect::<Vec<_>>().join(", ");
+        let sql = format!("INSERT INTO books (id) VALUES {};", values);
+
+        instance.execute_single_insert_sql(sql.as_str(), CheckOptions::default())
+            .expect_err("Should fail to insert when the pages can not be written");
+    }
+
+    #[test]
+    fn should_keep_all_inserted_rows_in_index_after_rebuilding_indexes() {
+        let mut instance = BustubInstance::in_memory(None);
+
+        successful_create_table(&mut instance, "CREATE TABLE books (id int PRIMARY KEY);");
+        successful_execute_insert_without_returning(&mut instance, "INSERT INTO books (id) VALUES (1), (15), (24);", 3);
+
+        instance.rebuild_indexes().expect("Should rebuild indexes");
+
+        instance.verify_integrity();
+
+        // The rebuilt index should keep being updated by new inserts
+        successful_execute_insert_without_returning(&mut instance, "INSERT INTO books (id) VALUES (30);", 1);
+    }
+
     #[ignore]
     #[test]
     fn fail_to_insert_null_to_non_nullable_column() {
@@ -3,3 +3,7 @@ mod create_table;
 mod insert;
 mod delete;
 mod drop_table;
+mod open_file;
+mod buffer_pool_metrics;
+mod page_guards;
+mod warm_up;
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use crate::BustubInstance;
+    use disk_storage::errors::SuperblockError;
+    use execution_common::CheckOptions;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_reopen_database_file() {
+        let tmp_dir = TempDir::new("open_file_tests").expect("Should create tmp directory");
+        let db_file = tmp_dir.path().join("test.db");
+
+        {
+            let mut instance = BustubInstance::from_file(db_file.clone(), None).expect("Should create database file");
+            instance.execute_user_input("CREATE TABLE books (id int);", CheckOptions::default()).expect("Should execute");
+        }
+
+        BustubInstance::from_file(db_file, None).expect("Should reopen database file");
+    }
+
+    #[test]
+    fn should_refuse_file_that_is_not_a_database() {
+        let tmp_dir = TempDir::new("open_file_tests").expect("Should create tmp directory");
+        let db_file = tmp_dir.path().join("notes.txt");
+        std::fs::write(&db_file, "some notes that are not a database").expect("Should write file");
+
+        let err = BustubInstance::from_file(db_file, None).err().expect("Should refuse to open the file");
+
+        assert_eq!(err.downcast_ref::<SuperblockError>(), Some(&SuperblockError::InvalidMagic));
+        assert!(format!("{:?}", err).contains("not a database file"), "got {:?}", err);
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod tests {
+    use crate::BustubInstance;
+    use execution_common::CheckOptions;
+
+    #[test]
+    #[cfg(feature = "guard_tracking")]
+    fn should_display_pinned_pages_and_lock_order_inversions() {
+        let mut instance = BustubInstance::in_memory(None);
+
+        instance.execute_user_input("CREATE TABLE books (id int);", CheckOptions::default()).expect("Should execute");
+
+        instance.execute_user_input("\\pins", CheckOptions::default()).expect("Should execute");
+        instance.execute_user_input("\\pins order", CheckOptions::default()).expect("Should execute");
+    }
+
+    #[test]
+    #[cfg(not(feature = "guard_tracking"))]
+    fn should_fail_to_display_pinned_pages_without_guard_tracking() {
+        let mut instance = BustubInstance::in_memory(None);
+
+        instance.execute_user_input("\\pins", CheckOptions::default()).expect_err("Should fail");
+        instance.execute_user_input("\\pins order", CheckOptions::default()).expect_err("Should fail");
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::BustubInstance;
+    use execution_common::CheckOptions;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_load_hot_pages_after_reopen() {
+        let tmp_dir = TempDir::new("warm_up_tests").expect("Should create tmp directory");
+        let db_file = tmp_dir.path().join("test.db");
+
+        {
+            let mut instance = BustubInstance::from_file(db_file.clone(), None).expect("Should create database file");
+            assert_eq!(instance.wait_for_warm_up().map(|result| result.expect("Should warm up")), Some(0), "new database has no hot pages");
+
+            instance.execute_user_input("CREATE TABLE books (id int);", CheckOptions::default()).expect("Should execute");
+            instance.execute_user_input("INSERT INTO books VALUES (1), (2), (3);", CheckOptions::default()).expect("Should execute");
+        }
+
+        assert!(db_file.with_extension("warm").exists(), "should save hot pages on drop");
+
+        let mut instance = BustubInstance::from_file(db_file, None).expect("Should reopen database file");
+
+        let loaded_pages = instance.wait_for_warm_up().expect("Should have warm up").expect("Should warm up");
+        assert!(loaded_pages > 0, "should load the hot pages");
+        assert_eq!(instance.wait_for_warm_up(), None, "warm up should be done");
+    }
+
+    #[test]
+    fn in_memory_instance_should_not_save_hot_pages() {
+        let mut instance = BustubInstance::in_memory(None);
+
+        assert_eq!(instance.wait_for_warm_up(), None);
+        assert_eq!(instance.save_hot_pages().expect("Should not fail"), 0);
+    }
+}
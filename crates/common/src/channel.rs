@@ -35,4 +35,9 @@ impl<T> Channel<T> {
 
         guard.pop_front().unwrap()
     }
+
+    /// Get the next element without waiting, None if the channel is empty
+    pub fn try_get(&self) -> Option<T> {
+        self.mutex.lock().pop_front()
+    }
 }
@@ -1,4 +1,5 @@
 use std::sync::atomic::{AtomicI32, AtomicI64, AtomicU32};
+use std::time::Duration;
 
 /** Cycle detection is performed every CYCLE_DETECTION_INTERVAL milliseconds. */
 // extern std::chrono::milliseconds cycle_detection_interval;
@@ -8,6 +9,7 @@ use std::sync::atomic::{AtomicI32, AtomicI64, AtomicU32};
 
 /** If ENABLE_LOGGING is true, the log should be flushed to disk every LOG_TIMEOUT. */
 // extern std::chrono::duration<int64_t> log_timeout;
+pub const LOG_TIMEOUT: Duration = Duration::from_secs(1);
 
 pub const INVALID_TXN_ID: TxnId = -1;                                            // invalid transaction id
 pub const INVALID_LSN: LSN = -1;                                               // invalid log sequence number
@@ -0,0 +1,8 @@
+use disk_storage::errors::DiskError;
+
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum CommitError {
+    /// The commit log record could not be flushed, so the transaction was aborted
+    #[error("failed to persist the commit, the transaction was aborted")]
+    PersistCommit(#[from] DiskError),
+}
@@ -5,6 +5,7 @@ mod transaction;
 mod version_undo_link;
 mod watermark;
 mod manager_trait;
+pub mod errors;
 
 pub use types::{IsolationLevel, TransactionState};
 pub use undo_link::UndoLink;
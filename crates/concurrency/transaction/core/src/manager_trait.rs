@@ -1,4 +1,5 @@
 use crate::{IsolationLevel, Transaction, UndoLink, UndoLog, VersionUndoLink};
+use crate::errors::CommitError;
 use common::config::Timestamp;
 use rid::RID;
 use std::sync::Arc;
@@ -35,8 +36,8 @@ pub trait TransactionManager: Sync + Send {
     ///
     /// * `txn`: txn the transaction to commit, the txn will be managed by the txn manager so no need to delete it by yourself
     ///
-    /// returns: bool
-    fn commit(&self, txn: Arc<Transaction>) -> bool;
+    /// returns: Result<bool, CommitError> false if the transaction was aborted instead, error if the commit could not be persisted
+    fn commit(&self, txn: Arc<Transaction>) -> Result<bool, CommitError>;
 
     /// Aborts a transaction
     ///
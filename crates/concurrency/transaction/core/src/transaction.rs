@@ -3,7 +3,7 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use atomic::Atomic;
 use parking_lot::Mutex;
-use common::config::{AtomicTimestamp, TableOID, Timestamp, TxnId, INVALID_TIMESTAMP, TXN_START_ID};
+use common::config::{AtomicLSN, AtomicTimestamp, TableOID, Timestamp, TxnId, INVALID_LSN, INVALID_TIMESTAMP, LSN, TXN_START_ID};
 use expression::ExpressionType;
 use rid::RID;
 use crate::{IsolationLevel, TransactionState, UndoLink, UndoLog};
@@ -24,6 +24,10 @@ pub struct Transaction {
     /// Default: `INVALID_TIMESTAMP`
     commit_ts: AtomicTimestamp,
 
+    /// The LSN of the last log record of this transaction, the next log record points to it
+    /// Default: `INVALID_LSN`
+    prev_lsn: AtomicLSN,
+
     /// The latch for this transaction for accessing txn-level undo logs
     latch: Mutex<TransactionData>,
 
@@ -57,6 +61,7 @@ impl Transaction {
             state: Atomic::new(TransactionState::Running),
             read_ts: AtomicTimestamp::new(0),
             commit_ts: AtomicTimestamp::new(INVALID_TIMESTAMP),
+            prev_lsn: AtomicLSN::new(INVALID_LSN),
 
             latch: Mutex::new(TransactionData {
                 undo_logs: vec![],
@@ -103,6 +108,12 @@ impl Transaction {
     /// Return the commit ts
     pub fn get_commit_ts(&self) -> Timestamp { self.commit_ts.load(Ordering::SeqCst) }
 
+    /** @return the LSN of the last log record of this transaction */
+    pub fn get_prev_lsn(&self) -> LSN { self.prev_lsn.load(Ordering::SeqCst) }
+
+    /** Set the LSN of the last log record of this transaction, after appending a log record */
+    pub fn set_prev_lsn(&self, prev_lsn: LSN) { self.prev_lsn.store(prev_lsn, Ordering::SeqCst) }
+
     /// Modify an existing undo log.
     pub fn modify_undo_log(&self, log_idx: i32, new_log: UndoLog) {
         let mut guard = self.latch.lock();
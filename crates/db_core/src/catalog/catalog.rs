@@ -313,6 +313,41 @@ impl Catalog {
         Ok(self.indexes.get(&index_oid).unwrap())
     }
 
+    /// Rebuild all the indexes from their table heaps
+    ///
+    /// Index changes are not logged, so after recovery the index pages can hold entries of rolled back transactions
+    /// or miss entries of committed ones. The table heaps are recovered from the log, so the indexes are built again from them
+    pub fn rebuild_indexes(&mut self, txn: &Transaction) -> error_utils::anyhow::Result<()> {
+        let bpm = self.bpm.as_ref().expect("Must have bpm");
+
+        for index_info in self.indexes.values_mut() {
+            let table_info = self.table_names.get(index_info.get_table_name())
+                .and_then(|table_oid| self.tables.get(table_oid))
+                .expect("Broken Invariant");
+
+            let metadata = index_info.get_index().get_metadata();
+            let key_schema = index_info.get_key_schema();
+
+            let index = match index_info.get_index_type() {
+                IndexType::HashTableIndex => create_extendible_hashing_index(index_info.get_key_size(), metadata.clone(), bpm.clone()).to_anyhow()?,
+            };
+            let index = IndexWithMetadata::new(index, metadata.clone());
+
+            for (_, tuple) in table_info.get_table_heap().iter() {
+                index.insert_entry(
+                    &tuple.key_from_tuple(&table_info.get_schema(), &key_schema, metadata.get_key_attrs()),
+                    *tuple.get_rid(),
+                    txn,
+                )?;
+            }
+
+            // The old index pages are not freed, they may point to pages that were never written, so traversing them is not safe
+            index_info.replace_index(index);
+        }
+
+        Ok(())
+    }
+
     pub fn verify_integrity(&self, txn: &Transaction) {
         self.indexes.iter().for_each(|(_, index)| {
             let index_name = index.get_name().as_str();
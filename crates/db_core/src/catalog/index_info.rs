@@ -26,7 +26,6 @@ pub struct IndexInfo {
     table_name: String,
 
     /// The size of the index key, in bytes
-    #[allow(dead_code)]
     key_size: usize,
 
     /// Is primary key index?
@@ -34,7 +33,6 @@ pub struct IndexInfo {
     is_primary_key: bool,
 
     /// The index type
-    #[allow(dead_code)]
     index_type: IndexType,
 }
 
@@ -90,6 +88,19 @@ impl IndexInfo {
         &self.index
     }
 
+    pub fn get_key_size(&self) -> usize {
+        self.key_size
+    }
+
+    pub fn get_index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    /// Replace the index with a new one that has the same metadata, returns the previous index
+    pub fn replace_index(&mut self, index: IndexWithMetadata) -> IndexWithMetadata {
+        std::mem::replace(&mut self.index, index)
+    }
+
     pub fn delete_completely(self, transaction: &Transaction) -> error_utils::anyhow::Result<()> {
         self.index.delete_completely(transaction)
     }
@@ -1,12 +1,14 @@
 use crate::catalog::{Catalog, TableInfo};
-use common::config::{AtomicTimestamp, AtomicTxnId, SlotOffset, Timestamp, TxnId, TXN_START_ID};
+use common::config::{AtomicTimestamp, AtomicTxnId, SlotOffset, Timestamp, TxnId, INVALID_LSN, LSN, TXN_START_ID};
 use pages::PageId;
 use parking_lot::Mutex;
+use recovery_log_manager::{LogManager, LogRecord, LogRecordBody};
 use rid::RID;
 use std::collections::HashMap;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use table::TableHeap;
+use transaction::errors::CommitError;
 use transaction::{CheckUndoLink, CheckVersionUndoLink, IsolationLevel, Transaction, TransactionManager as TransactionManagerTrait, TransactionState, UndoLink, UndoLog, VersionUndoLink, Watermark};
 
 pub struct TransactionManager {
@@ -42,6 +44,9 @@ pub struct TransactionManager {
     // Default: TXN_START_ID
     #[allow(unused)]
     next_txn_id: AtomicTxnId,
+
+    /// Begin, commit and abort are logged to it, `None` if logging is disabled
+    log_manager: Option<Arc<LogManager>>,
 }
 
 unsafe impl Sync for TransactionManager {
@@ -57,7 +62,7 @@ struct PageVersionInfo {
 }
 
 impl TransactionManager {
-    pub fn new(catalog: Arc<Mutex<Catalog>>) -> Self {
+    pub fn new(catalog: Arc<Mutex<Catalog>>, log_manager: Option<Arc<LogManager>>) -> Self {
         Self {
             txn_map: Mutex::new(HashMap::new()),
             version_info: Mutex::new(HashMap::new()),
@@ -66,9 +71,21 @@ impl TransactionManager {
             last_commit_ts: AtomicTimestamp::new(0),
             catalog,
             next_txn_id: AtomicTxnId::new(TXN_START_ID),
+            log_manager,
         }
     }
 
+    /// Append the log record as the last log record of the transaction
+    ///
+    /// returns: Option<LSN> the LSN of the log record, `None` if logging is disabled
+    fn append_log_record(&self, txn: &Transaction, body: LogRecordBody) -> Option<LSN> {
+        let log_manager = self.log_manager.as_ref()?;
+
+        let mut log_record = LogRecord::new(txn.get_transaction_id(), txn.get_prev_lsn(), body);
+
+        Some(log_manager.append_log_record_with(&mut log_record, |lsn, _| txn.set_prev_lsn(lsn)))
+    }
+
     #[allow(unused_variables)]
     pub fn verify_txn(&self, txn: Arc<Transaction>) -> bool {
         // TODO - implement
@@ -79,6 +96,18 @@ impl TransactionManager {
         self.txn_map.lock().get(&txn_id).cloned()
     }
 
+    /// The transactions that did not commit or abort with the LSN of their last log record, this is the active transaction table of a checkpoint
+    ///
+    /// Transactions that did not log anything are skipped as there is nothing to undo for them
+    pub fn get_active_transaction_table(&self) -> Vec<(TxnId, LSN)> {
+        self.txn_map.lock()
+            .iter()
+            .filter(|(_, txn)| matches!(txn.get_transaction_state(), TransactionState::Running | TransactionState::Tainted))
+            .map(|(&txn_id, txn)| (txn_id, txn.get_prev_lsn()))
+            .filter(|&(_, last_lsn)| last_lsn != INVALID_LSN)
+            .collect()
+    }
+
     pub fn debug(&self, info: String, _table_info: Option<&TableInfo>, _table_heap: Option<&TableHeap>) {
         // always use stderr for printing logs...
         eprintln!("debug_hook: {}", info);
@@ -116,6 +145,8 @@ impl TransactionManagerTrait for TransactionManager {
         let txn = Arc::new(Transaction::new(txn_id, isolation_level));
         txn_map_guard.insert(txn_id, txn.clone());
 
+        self.append_log_record(&txn, LogRecordBody::Begin);
+
         // TODO(fall2023): set the timestamps here. Watermark updated below.
 
         self.running_txns.add_txn(txn.get_read_ts());
@@ -123,7 +154,7 @@ impl TransactionManagerTrait for TransactionManager {
         txn
     }
 
-    fn commit(&self, txn: Arc<Transaction>) -> bool {
+    fn commit(&self, txn: Arc<Transaction>) -> Result<bool, CommitError> {
         #[allow(unused_variables)]
         let commit_lock = self.commit_mutex.lock();
 
@@ -137,12 +168,25 @@ impl TransactionManagerTrait for TransactionManager {
                 drop(commit_lock);
                 self.abort(txn);
 
-                return false;
+                return Ok(false);
             }
         }
 
         // TODO(fall2023): Implement the commit logic!
 
+        let commit_lsn = self.append_log_record(&txn, LogRecordBody::Commit);
+
+        // Other transactions can append their commit while waiting, so the commits share the same log flush
+        drop(commit_lock);
+
+        if let (Some(log_manager), Some(commit_lsn)) = (&self.log_manager, commit_lsn) {
+            if let Err(err) = log_manager.flush(commit_lsn) {
+                self.abort(txn);
+
+                return Err(err.into());
+            }
+        }
+
         #[allow(unused_variables)]
         let txn_map_guard = self.txn_map.lock();
 
@@ -152,7 +196,7 @@ impl TransactionManagerTrait for TransactionManager {
         self.running_txns.update_commit_ts(txn.get_commit_ts());
         self.running_txns.remove_txn(txn.get_read_ts());
 
-        true
+        Ok(true)
     }
 
     fn abort(&self, txn: Arc<Transaction>) {
@@ -161,6 +205,8 @@ impl TransactionManagerTrait for TransactionManager {
 
         // TODO(fall2023): Implement the abort logic!
 
+        self.append_log_record(&txn, LogRecordBody::Abort);
+
         #[allow(unused_variables)]
         let txn_map_guard = self.txn_map.lock();
 
@@ -9,7 +9,7 @@ use recovery_log_manager::LogManager;
 use std::collections::VecDeque;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
-use transaction::Transaction;
+use transaction::{Transaction, TransactionState};
 
 /// ExecutorContext stores all the context necessary to run an executor.
 pub struct ExecutorContext<'a> {
@@ -37,6 +37,9 @@ pub struct ExecutorContext<'a> {
     check_options: Arc<CheckOptions>,
 
     is_delete: bool,
+
+    /// The error that stopped an executor, the executors are iterators so it is returned after the query finished
+    execution_error: Mutex<Option<error_utils::anyhow::Error>>,
 }
 
 impl Debug for ExecutorContext<'_> {
@@ -69,6 +72,7 @@ impl<'a> ExecutorContext<'a> {
             nested_loop_join_check_exec_set: VecDeque::new(),
             check_options: Arc::new(CheckOptions::default()),
             is_delete,
+            execution_error: Mutex::new(None),
         }
     }
 
@@ -111,6 +115,20 @@ impl<'a> ExecutorContext<'a> {
         self.check_options = check_options
     }
 
+    /// Fail the query, the transaction is tainted as the tables might be changed partially
+    pub(crate) fn set_execution_error(&self, err: error_utils::anyhow::Error) {
+        if self.transaction.get_transaction_state() == TransactionState::Running {
+            self.transaction.set_tainted();
+        }
+
+        self.execution_error.lock().get_or_insert(err);
+    }
+
+    /** @return the error that stopped an executor */
+    pub(crate) fn take_execution_error(&self) -> Option<error_utils::anyhow::Error> {
+        self.execution_error.lock().take()
+    }
+
     /** As of Fall 2023, this function should not be used. */
     pub(crate) fn is_delete(&self) -> bool {
         self.is_delete
@@ -52,6 +52,10 @@ impl ExecutionEngine {
             .map(|item| item.0)
             .collect::<Vec<Tuple>>();
 
+        if let Some(err) = exec_ctx.take_execution_error() {
+            return Err(err);
+        }
+
         // TODO - perform checks
 
         Ok(res)
@@ -70,7 +70,8 @@ impl Iterator for DeleteExecutor<'_>
                     get_timestamp(),
                     true,
                 ),
-                &rid
+                &rid,
+                self.ctx.get_transaction(),
             );
             
             // If not deleted just try the next one
@@ -3,6 +3,7 @@ use crate::context::ExecutorContext;
 use crate::executors::{Executor, ExecutorImpl, ExecutorItem, ExecutorMetadata, ExecutorRef};
 use catalog_schema::{ColumnDefault, Schema};
 use common::get_timestamp;
+use error_utils::ToAnyhowResult;
 use db_core::catalog::{IndexInfo, TableInfo};
 use planner::{InsertPlan, PlanNode};
 use std::fmt;
@@ -104,7 +105,7 @@ impl Iterator for InsertExecutor<'_>
                 .expect("Must be able to cast tuple to the table schema (this should be blocked in the parsing state)");
         }
 
-        let rid = self.dest_table_info.get_table_heap().insert_tuple(
+        let inserted = self.dest_table_info.get_table_heap().insert_tuple(
             &TupleMeta::new(
                 get_timestamp(),
                 false,
@@ -113,7 +114,17 @@ impl Iterator for InsertExecutor<'_>
             self.ctx.get_lock_manager(),
             self.ctx.get_transaction(),
             Some(self.plan.get_table_oid()),
-        ).expect("Tuple is too big to fit in a page (this should be blocked in the planner)");
+        );
+
+        let rid = match inserted.to_anyhow() {
+            Ok(rid) => rid.expect("Tuple is too big to fit in a page (this should be blocked in the planner)"),
+            Err(err) => {
+                // Stop inserting, the error is returned after the query finished
+                self.ctx.set_execution_error(err);
+
+                return None;
+            }
+        };
 
         tuple.set_rid(rid);
         
@@ -1,7 +1,7 @@
 use super::errors::InsertionErrors;
 use comfy_table::Table;
 use common::{Comparator, OrdComparator, PageKey, PageValue};
-use pages::PAGE_SIZE;
+use pages::PAGE_USABLE_SIZE;
 use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
@@ -32,7 +32,7 @@ const _: () = {
 const PAGE_METADATA_SIZE: usize = size_of::<u32>() * 2;
 
 pub const fn bucket_array_size<Key, Value>() -> usize {
-    (PAGE_SIZE - PAGE_METADATA_SIZE) / size_of::<MappingType<Key, Value>>()
+    (PAGE_USABLE_SIZE - PAGE_METADATA_SIZE) / size_of::<MappingType<Key, Value>>()
 }
 
 #[macro_export]
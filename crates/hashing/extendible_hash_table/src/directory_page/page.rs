@@ -1,5 +1,5 @@
 use binary_utils::GetNBits;
-use pages::{PageId, PAGE_SIZE, INVALID_PAGE_ID};
+use pages::{PageId, PAGE_USABLE_SIZE, INVALID_PAGE_ID};
 use generics::GetOr;
 use comfy_table::{Table};
 use std::collections::HashMap;
@@ -16,7 +16,7 @@ const PAGE_METADATA_SIZE: usize = size_of::<u32>() * 2;
 const _: () = assert!(size_of::<PageId>() == 4);
 //noinspection RsAssertEqual
 const _: () = assert!(size_of::<DirectoryPage>() == PAGE_METADATA_SIZE + DirectoryPage::ARRAY_SIZE + size_of::<PageId>() * DirectoryPage::ARRAY_SIZE);
-const _: () = assert!(size_of::<DirectoryPage>() <= PAGE_SIZE);
+const _: () = assert!(size_of::<DirectoryPage>() <= PAGE_USABLE_SIZE);
 
 ///
 /// Directory pages sit at the second level of our disk-based extendible hash table.
@@ -10,7 +10,7 @@ use tracy_client::span;
 use crate::header_page::HeaderPage;
 use buffer_common::AccessType;
 use buffer_pool_manager::errors::MapErrorToBufferPoolError;
-use buffer_pool_manager::{BufferPool, BufferPoolManager};
+use buffer_pool_manager::{BufferPool, BufferPoolManager, PageWriteGuard};
 use common::{Comparator, PageKey, PageValue};
 use hashing_common::KeyHasher;
 use pages::{PageId, INVALID_PAGE_ID};
@@ -18,6 +18,7 @@ use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 use std::sync::Arc;
 use crate::HashTableIterator;
+use transaction::Transaction;
 
 
 /// Thread safe implementation of extendible hash table that is backed by a buffer pool
@@ -187,6 +188,16 @@ where
         KeyHasherImpl::hash_key(key) as u32
     }
 
+    /// Set the page LSN of a page that the transaction changed
+    ///
+    /// Index changes are not logged, so this does not make the index recoverable, the indexes are rebuilt from
+    /// the table heaps after recovery instead. It only keeps the index page from reaching the disk before the
+    /// table change the transaction logged last
+    pub(super) fn set_page_lsn(page_guard: &mut PageWriteGuard, transaction: &Transaction) {
+        // Nothing was logged when the transaction prev LSN is invalid, which is never greater than the page LSN
+        page_guard.set_lsn_if_greater(transaction.get_prev_lsn());
+    }
+
     fn init_new_header(bpm: Arc<BufferPoolManager>, header_max_depth: u32) -> Result<PageId, errors::InitError> {
         // TODO - this should be removed, we should not create on each instance and instead it should depend if the hash table exists or not
         let mut page_guard = bpm.new_page(AccessType::Unknown).map_err_to_buffer_pool_err()?;
@@ -54,7 +54,7 @@ where
     ///
     /// TODO - return custom result if inserted or not - NotInsertedError
     ///
-    pub fn insert(&self, key: &Key, value: &Value, _transaction: &Transaction) -> Result<(), InsertionError> {
+    pub fn insert(&self, key: &Key, value: &Value, transaction: &Transaction) -> Result<(), InsertionError> {
         #[cfg(feature = "tracing")]
         let _insert = span!("[extendible hash table] insert");
 
@@ -91,6 +91,8 @@ where
 
             // 5. Register the directory in the header page
             header_page.set_directory_page_id(directory_index, directory_page_id);
+            Self::set_page_lsn(&mut header, transaction);
+            Self::set_page_lsn(&mut directory, transaction);
         } else {
             // 6. Get the directory page
             directory = self.bpm.fetch_page_write(directory_page_id, AccessType::Unknown).map_err_to_buffer_pool_err().context("Directory page should exists")?;
@@ -116,6 +118,7 @@ where
 
             // 10. Register the bucket in the directory page
             directory_page.set_bucket_page_id(bucket_index, bucket_page_id);
+            Self::set_page_lsn(&mut directory, transaction);
         } else {
             // 11. Get the bucket page
             bucket = self.bpm.fetch_page_write(bucket_page_id, AccessType::Unknown).map_err_to_buffer_pool_err().context("Failed to fetch bucket page")?;
@@ -130,7 +133,7 @@ where
 
         // 13. if bucket page is full, need to split
         if bucket_page.is_full() {
-            bucket = self.trigger_split(&mut directory, bucket, bucket_index, key_hash)?;
+            bucket = self.trigger_split(&mut directory, bucket, bucket_index, key_hash, transaction)?;
         }
 
         let bucket_page = bucket.cast_mut::<<Self as TypeAliases>::BucketPage>();
@@ -139,16 +142,17 @@ where
         // Safety: doing unwrap as it should not happen since we split
         //         and we have a lock - TODO - add a lock
         bucket_page.insert(key, value, &self.cmp).unwrap();
+        Self::set_page_lsn(&mut bucket, transaction);
 
         Ok(())
     }
 
-    fn trigger_split<'a>(&'a self, directory_page_guard: &mut PageWriteGuard, bucket_page_guard: PageWriteGuard<'a>, bucket_index: u32, key_hash: u32) -> Result<PageWriteGuard<'a>, InsertionError> {
+    fn trigger_split<'a>(&'a self, directory_page_guard: &mut PageWriteGuard, bucket_page_guard: PageWriteGuard<'a>, bucket_index: u32, key_hash: u32, transaction: &Transaction) -> Result<PageWriteGuard<'a>, InsertionError> {
         // Try to split the bucket with 3 iteration (after that it seems like the hash function is not good, or we have a bug)
-        self.try_split(directory_page_guard, bucket_page_guard, bucket_index, key_hash, NUMBER_OF_SPLIT_RETRIES)
+        self.try_split(directory_page_guard, bucket_page_guard, bucket_index, key_hash, NUMBER_OF_SPLIT_RETRIES, transaction)
     }
 
-    fn try_split<'a>(&'a self, directory_page_guard: &mut PageWriteGuard, mut bucket_page_guard: PageWriteGuard<'a>, bucket_index: u32, key_hash: u32, tries_left: usize) -> Result<PageWriteGuard<'a>, InsertionError> {
+    fn try_split<'a>(&'a self, directory_page_guard: &mut PageWriteGuard, mut bucket_page_guard: PageWriteGuard<'a>, bucket_index: u32, key_hash: u32, tries_left: usize, transaction: &Transaction) -> Result<PageWriteGuard<'a>, InsertionError> {
         // 1. Check if reached max tries
         if tries_left == 0 {
             eprintln!("Trying to insert key but after split the page is still full, the hash might not evenly distribute the keys");
@@ -184,12 +188,18 @@ where
         let bucket_to_insert_page_id = directory_page.get_bucket_page_id(bucket_index_to_insert);
         let bucket_to_insert = if bucket_to_insert_page_id == new_bucket_page_id { new_bucket_page } else { bucket_page };
 
+        let still_full = bucket_to_insert.is_full();
+
+        Self::set_page_lsn(directory_page_guard, transaction);
+        Self::set_page_lsn(&mut bucket_page_guard, transaction);
+        Self::set_page_lsn(&mut new_bucket, transaction);
+
         // 7. Check if still after the split we can't insert
-        if bucket_to_insert.is_full() {
+        if still_full {
             let bucket_guard_to_insert = if bucket_to_insert_page_id == new_bucket_page_id { new_bucket } else { bucket_page_guard };
 
             // 7.1 Split again with the current bucket that is full (The bucket index is always the one that about to overflow)
-            return self.try_split(directory_page_guard, bucket_guard_to_insert, bucket_index_to_insert, key_hash, tries_left - 1);
+            return self.try_split(directory_page_guard, bucket_guard_to_insert, bucket_index_to_insert, key_hash, tries_left - 1, transaction);
         }
 
         let bucket_guard_to_insert = if bucket_to_insert_page_id == new_bucket_page_id { new_bucket } else { bucket_page_guard };
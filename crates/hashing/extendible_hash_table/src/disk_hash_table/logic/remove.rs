@@ -50,7 +50,7 @@ where
     pub fn remove(
         &self,
         key: &Key,
-        _transaction: &Transaction,
+        transaction: &Transaction,
     ) -> Result<bool, RemoveError> {
         // TODO - performance improvement release write latch as soon as can
 
@@ -121,6 +121,9 @@ where
             // 11. If bucket is empty, need to merge
             if bucket_page.is_empty() {
                 self.trigger_merge(&mut directory, bucket, bucket_index)?;
+                Self::set_page_lsn(&mut directory, transaction);
+            } else {
+                Self::set_page_lsn(&mut bucket, transaction);
             }
         }
 
@@ -131,6 +134,7 @@ where
             // 13. Remove directory from header
             // TODO - remove the page as well
             // TODO - Do not remove the directory page and keep it, and when doing some compaction or GC claim that page
+            Self::set_page_lsn(&mut header, transaction);
             self.remove_directory(&mut header, directory, directory_index)
                 .context("Failed to remove directory")?;
         }
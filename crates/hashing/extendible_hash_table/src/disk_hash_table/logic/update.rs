@@ -38,7 +38,7 @@ where
     ///
     /// Returns: `()` the value(s) associated with the given key
     ///
-    pub fn update(&self, key: &Key, value: &Value, _transaction: &Transaction) -> Result<(), UpdateError> {
+    pub fn update(&self, key: &Key, value: &Value, transaction: &Transaction) -> Result<(), UpdateError> {
         let directory_page_id: PageId;
         let bucket_page_id: PageId;
         let key_hash = self.hash(key);
@@ -90,6 +90,8 @@ where
             return Err(UpdateError::KeyIsMissing);
         }
 
+        Self::set_page_lsn(&mut bucket, transaction);
+
         Ok(())
     }
 }
@@ -1,7 +1,7 @@
 use crate::header_page::HeaderIter;
 use binary_utils::GetNBits;
 use comfy_table::Table;
-use pages::{PageId, INVALID_PAGE_ID, PAGE_SIZE};
+use pages::{PageId, INVALID_PAGE_ID, PAGE_USABLE_SIZE};
 use std::fmt::{Debug, Formatter};
 use std::mem::size_of;
 use crate::header_page::iterator::HeaderIterState;
@@ -13,13 +13,13 @@ const PAGE_METADATA_SIZE: usize = size_of::<u32>();
 const _: () = assert!(size_of::<PageId>() == 4);
 //noinspection RsAssertEqual
 const _: () = assert!(size_of::<HeaderPage>() == size_of::<PageId>() * HeaderPage::ARRAY_SIZE + PAGE_METADATA_SIZE);
-const _: () = assert!(size_of::<HeaderPage>() <= PAGE_SIZE);
+const _: () = assert!(size_of::<HeaderPage>() <= PAGE_USABLE_SIZE);
 
 ///
 /// Header page format:
-///  ---------------------------------------------------
-/// | DirectoryPageIds(2048) | MaxDepth (4) | Free(2044)
-///  ---------------------------------------------------
+///  ----------------------------------------------------------------
+/// | DirectoryPageIds(2048) | MaxDepth (4) | Free(2040) | Checksum(4)
+///  ----------------------------------------------------------------
 ///
 ///
 ///
@@ -0,0 +1,15 @@
+use disk_storage::errors::DiskError;
+
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum CheckpointError {
+    #[error("failed to persist the checkpoint")]
+    DiskError(#[from] DiskError),
+
+    /// `end_checkpoint` was called without `begin_checkpoint`, or the checkpoint already ended
+    #[error("checkpoint was not started")]
+    NotStarted,
+
+    /// Too many active transactions or dirty pages to fit the end checkpoint record in the log buffer
+    #[error("end checkpoint record of size {0} does not fit in the log buffer")]
+    RecordTooLarge(usize),
+}
@@ -1,3 +1,4 @@
 mod manager;
+pub mod errors;
 
-pub use manager::CheckpointManager;
+pub use manager::{CheckpointManager, CheckpointOptions, CHECKPOINT_INTERVAL, CHECKPOINT_LOG_SIZE};
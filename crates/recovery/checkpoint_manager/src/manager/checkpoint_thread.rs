@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use crate::errors::CheckpointError;
+use crate::CheckpointManager;
+
+/// Time between periodic checkpoints, like Postgres `checkpoint_timeout`
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Size of the log that is appended before checkpointing without waiting for the interval, like Postgres `max_wal_size`
+pub const CHECKPOINT_LOG_SIZE: usize = 64 * 1024 * 1024;
+
+pub struct CheckpointOptions {
+    interval: Duration,
+    log_size: usize,
+}
+
+impl CheckpointOptions {
+    /// # Arguments
+    ///
+    /// * `interval`: the maximum time between checkpoints
+    /// * `log_size`: the size of the log appended since the last checkpoint that triggers a checkpoint
+    ///
+    pub fn new(interval: Duration, log_size: usize) -> Self {
+        Self {
+            interval,
+            log_size,
+        }
+    }
+}
+
+impl Default for CheckpointOptions {
+    fn default() -> Self {
+        Self {
+            interval: CHECKPOINT_INTERVAL,
+            log_size: CHECKPOINT_LOG_SIZE,
+        }
+    }
+}
+
+/// Running checkpoint thread
+pub(super) struct CheckpointThread {
+    stop: Arc<AtomicBool>,
+
+    /// Finishes with the error of the checkpoint that failed
+    thread: JoinHandle<Result<(), CheckpointError>>,
+}
+
+impl CheckpointManager {
+    /// Start a thread that checkpoints when the interval passed or the log grew past the size since the last checkpoint
+    ///
+    /// The thread is parked between checkpoints, it is unparked by the log manager when the log grew past the size.
+    /// The thread does not keep the checkpoint manager alive, it is stopped when the checkpoint manager is dropped.
+    /// A failed checkpoint stops the thread, its error is returned by `stop_checkpoint_thread`
+    /// and recovery starts from the previous checkpoint meanwhile.
+    /// If the checkpoint thread is already running it is restarted with the new options
+    ///
+    /// # Arguments
+    ///
+    /// * `options`: when to checkpoint
+    ///
+    pub fn start_checkpoint_thread(self: &Arc<Self>, options: CheckpointOptions) {
+        // The error of the previous thread is dropped with it
+        let _ = self.stop_checkpoint_thread();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let checkpoint_manager: Weak<Self> = Arc::downgrade(self);
+
+        let thread = thread::Builder::new()
+            .name("Checkpoint".to_string())
+            .spawn(move || {
+                let mut last_checkpoint = Instant::now();
+
+                // Stop when requested or when the checkpoint manager is dropped
+                while !thread_stop.load(Ordering::SeqCst) {
+                    let Some(checkpoint_manager) = checkpoint_manager.upgrade() else {
+                        break;
+                    };
+
+                    let next_checkpoint = last_checkpoint + options.interval;
+
+                    if Instant::now() >= next_checkpoint || checkpoint_manager.get_log_size_since_checkpoint() >= options.log_size {
+                        checkpoint_manager.checkpoint()?;
+                        last_checkpoint = Instant::now();
+
+                        continue;
+                    }
+
+                    if let Some(log_size) = checkpoint_manager.last_checkpoint_log_size.load(Ordering::Relaxed).checked_add(options.log_size) {
+                        checkpoint_manager.log_manager.unpark_on_log_size(log_size, thread::current());
+                    }
+
+                    drop(checkpoint_manager);
+
+                    // Unparked when stopped, and might be unparked spuriously
+                    thread::park_timeout(next_checkpoint.saturating_duration_since(Instant::now()));
+                }
+
+                Ok(())
+            })
+            .expect("should create checkpoint thread");
+
+        *self.checkpoint_thread.lock() = Some(CheckpointThread {
+            stop,
+            thread,
+        });
+    }
+
+    /// Stop the checkpoint thread and wait for the current checkpoint to finish, nothing is done if it is not running
+    ///
+    /// returns: Result<(), CheckpointError> the error of the checkpoint that stopped the thread
+    ///
+    pub fn stop_checkpoint_thread(&self) -> Result<(), CheckpointError> {
+        let Some(CheckpointThread { stop, thread }) = self.checkpoint_thread.lock().take() else {
+            return Ok(());
+        };
+
+        stop.store(true, Ordering::SeqCst);
+        thread.thread().unpark();
+
+        // The checkpoint manager can be dropped by the checkpoint thread itself when it holds the last reference
+        if thread.thread().id() == thread::current().id() {
+            return Ok(());
+        }
+
+        thread.join().expect("checkpoint thread should not panic")
+    }
+
+    /// @return whether the checkpoint thread was started and did not stop because a checkpoint failed
+    pub fn is_checkpoint_thread_running(&self) -> bool {
+        self.checkpoint_thread.lock().as_ref().is_some_and(|checkpoint_thread| !checkpoint_thread.thread.is_finished())
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use buffer_pool_manager::BufferPoolManager;
+use common::config::{INVALID_LSN, INVALID_TXN_ID, LSN};
+use db_core::concurrency::TransactionManager;
+use disk_storage::DiskManager;
+use parking_lot::Mutex;
+use recovery_log_manager::{LogManager, LogRecord, LogRecordBody, LOG_BUFFER_SIZE};
+use crate::errors::CheckpointError;
+use crate::CheckpointManager;
+
+impl CheckpointManager {
+    pub fn new(transaction_manager: Option<Arc<TransactionManager>>,
+               log_manager: Arc<LogManager>,
+               buffer_pool_manager: Arc<BufferPoolManager>,
+               disk_manager: Arc<dyn DiskManager>) -> Self {
+        Self {
+            transaction_manager,
+            buffer_pool_manager,
+            disk_manager,
+            in_progress: Mutex::new(None),
+            last_checkpoint_log_size: AtomicUsize::new(log_manager.get_log_size()),
+            checkpoint_thread: Mutex::new(None),
+            log_manager,
+        }
+    }
+
+    /// Begin a checkpoint by appending the begin checkpoint record, nothing is blocked
+    ///
+    /// A checkpoint that was begun and did not end is replaced
+    ///
+    /// returns: LSN the LSN of the begin checkpoint record
+    ///
+    pub fn begin_checkpoint(&self) -> LSN {
+        let mut in_progress = self.in_progress.lock();
+
+        let mut log_record = LogRecord::new(INVALID_TXN_ID, INVALID_LSN, LogRecordBody::BeginCheckpoint);
+
+        self.log_manager.append_log_record_with(&mut log_record, |lsn, offset| *in_progress = Some((lsn, offset)))
+    }
+
+    /// End the checkpoint that was begun, by appending the active transaction table and the dirty page table
+    /// and making it the checkpoint that recovery starts from once it is persisted
+    ///
+    /// The tables are collected while transactions keep running, so they include every change before the begin checkpoint record
+    /// and maybe some changes after it, which recovery reads anyway
+    ///
+    /// returns: Result<LSN, CheckpointError> the LSN of the end checkpoint record,
+    /// error if the checkpoint was not begun or failed to persist, in which case recovery starts from the previous checkpoint
+    ///
+    pub fn end_checkpoint(&self) -> Result<LSN, CheckpointError> {
+        let (begin_lsn, begin_offset) = self.in_progress.lock().take().ok_or(CheckpointError::NotStarted)?;
+
+        let active_txn = self.transaction_manager
+            .as_ref()
+            .map(|transaction_manager| transaction_manager.get_active_transaction_table())
+            .unwrap_or_default();
+
+        let dirty_pages = self.buffer_pool_manager.get_dirty_page_table();
+
+        let mut log_record = LogRecord::new(INVALID_TXN_ID, begin_lsn, LogRecordBody::EndCheckpoint { active_txn, dirty_pages });
+
+        let size = log_record.get_size() as usize;
+        if size > LOG_BUFFER_SIZE {
+            return Err(CheckpointError::RecordTooLarge(size));
+        }
+
+        let end_lsn = self.log_manager.append_log_record(&mut log_record);
+        self.log_manager.flush(end_lsn)?;
+
+        // Recovery can only start from the checkpoint once the end checkpoint record is persisted
+        self.disk_manager.set_checkpoint_offset(begin_offset as u64)?;
+        self.last_checkpoint_log_size.store(begin_offset, Ordering::Relaxed);
+
+        Ok(end_lsn)
+    }
+
+    /// Begin and end a checkpoint
+    ///
+    /// returns: Result<LSN, CheckpointError> the LSN of the end checkpoint record
+    ///
+    pub fn checkpoint(&self) -> Result<LSN, CheckpointError> {
+        self.begin_checkpoint();
+
+        self.end_checkpoint()
+    }
+
+    /// The size of the log that was appended since the last completed checkpoint began
+    pub fn get_log_size_since_checkpoint(&self) -> usize {
+        self.log_manager.get_log_size().saturating_sub(self.last_checkpoint_log_size.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for CheckpointManager {
+    fn drop(&mut self) {
+        // Nobody is left to handle the error, the next checkpoint is done when the database is opened again
+        let _ = self.stop_checkpoint_thread();
+    }
+}
@@ -0,0 +1,44 @@
+mod manager_impl;
+mod checkpoint_thread;
+mod tests;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use buffer_pool_manager::BufferPoolManager;
+use common::config::LSN;
+use db_core::concurrency::TransactionManager;
+use disk_storage::DiskManager;
+use parking_lot::Mutex;
+use recovery_log_manager::LogManager;
+
+pub use checkpoint_thread::{CheckpointOptions, CHECKPOINT_INTERVAL, CHECKPOINT_LOG_SIZE};
+use checkpoint_thread::CheckpointThread;
+
+///
+/// Fuzzy checkpoints of the write ahead log.
+///
+/// A checkpoint appends a begin checkpoint record, then collects the active transaction table and the dirty page table
+/// while transactions keep running and appends them in an end checkpoint record.
+/// Once the end checkpoint record is persisted, the offset of the begin checkpoint record is stored as the checkpoint offset of the disk manager
+/// so recovery starts from it instead of the start of the log
+///
+pub struct CheckpointManager {
+    /// Without a transaction manager no transaction is running, the active transaction table is empty
+    transaction_manager: Option<Arc<TransactionManager>>,
+
+    log_manager: Arc<LogManager>,
+
+    buffer_pool_manager: Arc<BufferPoolManager>,
+
+    /// Stores the offset of the last completed checkpoint
+    disk_manager: Arc<dyn DiskManager>,
+
+    /// The LSN and the offset in the log of the begin checkpoint record of the checkpoint in progress
+    in_progress: Mutex<Option<(LSN, usize)>>,
+
+    /// The log size when the last completed checkpoint began
+    last_checkpoint_log_size: AtomicUsize,
+
+    /// Thread that checkpoints periodically, when started
+    checkpoint_thread: Mutex<Option<CheckpointThread>>,
+}
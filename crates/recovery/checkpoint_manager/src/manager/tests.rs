@@ -0,0 +1,310 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use buffer_common::AccessType;
+    use buffer_pool_manager::{BufferPool, BufferPoolManager};
+    use db_core::catalog::Catalog;
+    use db_core::concurrency::TransactionManager;
+    use disk_storage::{DiskManager, DiskManagerUnlimitedMemory};
+    use log_recovery::LogRecovery;
+    use pages::PageId;
+    use parking_lot::Mutex;
+    use recovery_log_manager::{LogManager, LogRecord, LogRecordBody};
+    use rid::RID;
+    use table::{TableHeap, TablePage};
+    use transaction::{Transaction, TransactionManager as TransactionManagerTrait};
+    use tuple::{Tuple, TupleMeta};
+    use crate::errors::CheckpointError;
+    use crate::{CheckpointManager, CheckpointOptions};
+
+    struct Setup {
+        disk_manager: Arc<DiskManagerUnlimitedMemory>,
+        log_manager: Arc<LogManager>,
+        bpm: Arc<BufferPoolManager>,
+        transaction_manager: Arc<TransactionManager>,
+        checkpoint_manager: Arc<CheckpointManager>,
+    }
+
+    fn setup() -> Setup {
+        setup_on_disk(Arc::new(DiskManagerUnlimitedMemory::new()))
+    }
+
+    /// Open the database on the disk, like opening it after crashing
+    fn setup_on_disk(disk_manager: Arc<DiskManagerUnlimitedMemory>) -> Setup {
+        let log_manager = Arc::new(LogManager::new(disk_manager.clone()));
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_log_manager(Some(log_manager.clone()))
+            .build_arc();
+
+        let catalog = Arc::new(Mutex::new(Catalog::new(Some(bpm.clone()), None, Some(log_manager.clone()))));
+        let transaction_manager = Arc::new(TransactionManager::new(catalog, Some(log_manager.clone())));
+
+        let checkpoint_manager = Arc::new(CheckpointManager::new(
+            Some(transaction_manager.clone()),
+            log_manager.clone(),
+            bpm.clone(),
+            disk_manager.clone(),
+        ));
+
+        Setup { disk_manager, log_manager, bpm, transaction_manager, checkpoint_manager }
+    }
+
+    /// Read the log records from the offset until the end of the log
+    fn read_log_from(disk_manager: &DiskManagerUnlimitedMemory, offset: usize) -> Vec<LogRecord> {
+        let mut buffer = [0u8; 4096];
+        assert!(disk_manager.read_log(&mut buffer, 4096, offset as i32).expect("should read the log"));
+
+        let mut log_records = vec![];
+        let mut buffer_offset = 0;
+
+        while let Ok(log_record) = LogRecord::deserialize_from(&buffer[buffer_offset..]) {
+            buffer_offset += log_record.get_size() as usize;
+            log_records.push(log_record);
+        }
+
+        log_records
+    }
+
+    fn insert(table_heap: &TableHeap, txn: &Arc<Transaction>, value: u8) -> RID {
+        table_heap.insert_tuple(&TupleMeta::new(0, false), &Tuple::from([value; 8].as_slice()), &None, txn, None)
+            .expect("should fetch the table page")
+            .expect("should insert the tuple")
+    }
+
+    /// The data and whether the tuple is deleted, for every tuple in the page
+    fn read_tuples(bpm: &Arc<BufferPoolManager>, page_id: PageId) -> Vec<(Vec<u8>, bool)> {
+        let page_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch the page");
+        let page = page_guard.cast::<TablePage>();
+
+        (0..page.get_num_tuples())
+            .map(|slot_num| {
+                let (meta, tuple) = page.get_tuple(&RID::new(page_id, slot_num));
+
+                (tuple.get_data().to_vec(), meta.is_deleted)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_record_active_transactions_and_dirty_pages() {
+        let Setup { disk_manager, bpm, transaction_manager, checkpoint_manager, .. } = setup();
+
+        let table_heap = TableHeap::new(bpm.clone());
+        let page_id = table_heap.get_first_page_id();
+
+        let committed = transaction_manager.begin(None);
+        let tuple = Tuple::from([1u8; 8].as_slice());
+        table_heap.insert_tuple(&TupleMeta::new(0, false), &tuple, &None, &committed, None).unwrap().unwrap();
+        let insert_lsn = committed.get_prev_lsn();
+        assert_eq!(transaction_manager.commit(committed), Ok(true));
+
+        let running = transaction_manager.begin(None);
+
+        assert_eq!(disk_manager.get_checkpoint_offset(), None);
+
+        checkpoint_manager.checkpoint().expect("should checkpoint");
+
+        let offset = disk_manager.get_checkpoint_offset().expect("should store the offset of the checkpoint");
+
+        let log_records = read_log_from(&disk_manager, offset as usize);
+        let bodies = log_records.iter().map(|log_record| log_record.get_body().clone()).collect::<Vec<_>>();
+
+        assert_eq!(bodies.len(), 2);
+        assert!(matches!(bodies[0], LogRecordBody::BeginCheckpoint));
+
+        let LogRecordBody::EndCheckpoint { active_txn, dirty_pages } = &bodies[1] else {
+            panic!("expected end checkpoint log record but got {:?}", bodies[1]);
+        };
+
+        assert_eq!(active_txn, &vec![(running.get_transaction_id(), running.get_prev_lsn())]);
+        assert_eq!(dirty_pages, &vec![(page_id, insert_lsn)], "the recovery LSN is the first change that is not on disk");
+    }
+
+    #[test]
+    fn written_pages_should_not_be_in_the_dirty_page_table() {
+        let Setup { disk_manager, bpm, transaction_manager, checkpoint_manager, .. } = setup();
+
+        let table_heap = TableHeap::new(bpm.clone());
+
+        let txn = transaction_manager.begin(None);
+        table_heap.insert_tuple(&TupleMeta::new(0, false), &Tuple::from([1u8; 8].as_slice()), &None, &txn, None).unwrap().unwrap();
+        assert_eq!(transaction_manager.commit(txn), Ok(true));
+
+        assert!(bpm.flush_page(table_heap.get_first_page_id()));
+
+        checkpoint_manager.checkpoint().expect("should checkpoint");
+
+        let log_records = read_log_from(&disk_manager, disk_manager.get_checkpoint_offset().unwrap() as usize);
+
+        let LogRecordBody::EndCheckpoint { active_txn, dirty_pages } = log_records[1].get_body() else {
+            panic!("expected end checkpoint log record but got {:?}", log_records[1].get_body());
+        };
+
+        assert_eq!(active_txn, &vec![]);
+        assert_eq!(dirty_pages, &vec![]);
+    }
+
+    #[test]
+    fn end_checkpoint_should_fail_when_not_started() {
+        let Setup { disk_manager, log_manager, checkpoint_manager, .. } = setup();
+
+        assert_eq!(checkpoint_manager.end_checkpoint(), Err(CheckpointError::NotStarted));
+
+        let begin_lsn = checkpoint_manager.begin_checkpoint();
+        let end_lsn = checkpoint_manager.end_checkpoint().expect("should end the checkpoint");
+
+        assert_eq!(end_lsn, begin_lsn + 1);
+        assert_eq!(log_manager.get_persistent_lsn(), end_lsn, "the end checkpoint record should be persisted");
+        assert_eq!(disk_manager.get_checkpoint_offset(), Some(0));
+
+        assert_eq!(checkpoint_manager.end_checkpoint(), Err(CheckpointError::NotStarted), "the checkpoint already ended");
+    }
+
+    #[test]
+    fn should_checkpoint_when_the_log_grows() {
+        let Setup { disk_manager, log_manager, transaction_manager, checkpoint_manager, .. } = setup();
+
+        checkpoint_manager.start_checkpoint_thread(CheckpointOptions::new(Duration::from_secs(3600), 100));
+
+        for _ in 0..10 {
+            let txn = transaction_manager.begin(None);
+            assert_eq!(transaction_manager.commit(txn), Ok(true));
+        }
+
+        assert!(log_manager.get_log_size() >= 100);
+
+        let start = Instant::now();
+        while disk_manager.get_checkpoint_offset().is_none() {
+            assert!(start.elapsed() < Duration::from_secs(10), "should checkpoint after the log grew past the size");
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(checkpoint_manager.stop_checkpoint_thread(), Ok(()));
+
+        assert!(checkpoint_manager.get_log_size_since_checkpoint() < 100);
+    }
+
+    #[test]
+    fn checkpoint_thread_should_stop_with_the_error_of_the_failed_checkpoint() {
+        let Setup { disk_manager, transaction_manager, checkpoint_manager, .. } = setup();
+
+        // Too many active transactions to fit the end checkpoint record in the log buffer
+        let _running = (0..6000).map(|_| transaction_manager.begin(None)).collect::<Vec<_>>();
+
+        checkpoint_manager.start_checkpoint_thread(CheckpointOptions::new(Duration::from_secs(3600), 100));
+
+        let start = Instant::now();
+        while checkpoint_manager.is_checkpoint_thread_running() {
+            assert!(start.elapsed() < Duration::from_secs(10), "should stop after the checkpoint failed");
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(matches!(checkpoint_manager.stop_checkpoint_thread(), Err(CheckpointError::RecordTooLarge(_))));
+        assert_eq!(disk_manager.get_checkpoint_offset(), None, "recovery should start from the previous checkpoint");
+
+        assert_eq!(checkpoint_manager.stop_checkpoint_thread(), Ok(()), "the error is only returned once");
+    }
+
+    #[test]
+    fn should_checkpoint_on_interval() {
+        let Setup { disk_manager, checkpoint_manager, .. } = setup();
+
+        checkpoint_manager.start_checkpoint_thread(CheckpointOptions::new(Duration::from_millis(20), usize::MAX));
+
+        let start = Instant::now();
+        while disk_manager.get_checkpoint_offset().is_none() {
+            assert!(start.elapsed() < Duration::from_secs(10), "should checkpoint after the interval");
+
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn recovery_should_start_from_the_last_checkpoint() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let page_id = {
+            let Setup { bpm, transaction_manager, checkpoint_manager, .. } = setup_on_disk(disk_manager.clone());
+
+            // Creating the table is not logged, so its first page is written right away
+            let table_heap = TableHeap::new(bpm.clone());
+            assert!(bpm.flush_page(table_heap.get_first_page_id()));
+
+            let committed = transaction_manager.begin(None);
+            insert(&table_heap, &committed, 1);
+            assert_eq!(transaction_manager.commit(committed), Ok(true));
+
+            // Began before the checkpoint and did not end
+            let loser = transaction_manager.begin(None);
+            insert(&table_heap, &loser, 2);
+
+            checkpoint_manager.checkpoint().expect("should checkpoint");
+
+            insert(&table_heap, &loser, 3);
+
+            let committed = transaction_manager.begin(None);
+            insert(&table_heap, &committed, 4);
+            assert_eq!(transaction_manager.commit(committed), Ok(true));
+
+            // Crash without writing the pages
+            table_heap.get_first_page_id()
+        };
+
+        let expected = vec![(vec![1; 8], false), (vec![2; 8], true), (vec![3; 8], true), (vec![4; 8], false)];
+
+        for _ in 0..2 {
+            let Setup { disk_manager, log_manager, bpm, .. } = setup_on_disk(disk_manager.clone());
+
+            LogRecovery::new(disk_manager, bpm.clone(), log_manager)
+                .recover()
+                .expect("should recover");
+
+            assert_eq!(read_tuples(&bpm, page_id), expected, "should redo the dirty pages from before the checkpoint and undo the loser");
+        }
+    }
+
+    #[test]
+    fn recovery_should_continue_the_log_after_the_checkpoint() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        {
+            let Setup { transaction_manager, checkpoint_manager, .. } = setup_on_disk(disk_manager.clone());
+
+            let txn = transaction_manager.begin(None);
+            assert_eq!(transaction_manager.commit(txn), Ok(true));
+
+            checkpoint_manager.checkpoint().expect("should checkpoint");
+        }
+
+        let first_checkpoint = disk_manager.get_checkpoint_offset().expect("should store the offset of the checkpoint");
+
+        {
+            let Setup { disk_manager, log_manager, bpm, checkpoint_manager, .. } = setup_on_disk(disk_manager.clone());
+
+            LogRecovery::new(disk_manager.clone(), bpm, log_manager.clone())
+                .recover()
+                .expect("should recover");
+
+            assert_eq!(log_manager.get_next_lsn(), 4);
+
+            // The checkpoint record is appended after the recovered log
+            checkpoint_manager.checkpoint().expect("should checkpoint");
+            assert!(disk_manager.get_checkpoint_offset().unwrap() > first_checkpoint);
+        }
+
+        let Setup { disk_manager, log_manager, bpm, .. } = setup_on_disk(disk_manager.clone());
+
+        LogRecovery::new(disk_manager, bpm, log_manager.clone())
+            .recover()
+            .expect("should recover from the second checkpoint");
+
+        assert_eq!(log_manager.get_next_lsn(), 6);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::LogRecordType;
+
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum DeserializeLogRecordError {
+    /// The input ends in the middle of the record, happens for the last record when crashed while writing the log
+    #[error("log record is incomplete, needs {needed} bytes but only {available} are available")]
+    Incomplete {
+        needed: usize,
+        available: usize,
+    },
+
+    #[error("log record type {0} is unknown")]
+    UnknownType(i32),
+
+    #[error("log record of type {log_record_type:?} has invalid size {size}")]
+    InvalidSize {
+        log_record_type: LogRecordType,
+        size: i32,
+    },
+
+    /// The log record content does not match its checksum, happens for the last record when crashed while writing the log
+    #[error("log record checksum mismatch, expected {expected} but calculated {actual}")]
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+
+    /// Compensation log record can only redo a change to a tuple
+    #[error("compensation log record can't redo log record of type {0:?}")]
+    InvalidCompensationRedoType(LogRecordType),
+}
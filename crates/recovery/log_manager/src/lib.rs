@@ -1,7 +1,8 @@
 mod log_manager;
 mod log_record;
 mod types;
+pub mod errors;
 
 pub use log_manager::*;
-pub use log_record::LogRecord;
-pub(crate) use types::*;
+pub use log_record::{LogRecord, LogRecordBody, LogRecordType, LOG_RECORD_HEADER_SIZE};
+pub use types::LOG_BUFFER_SIZE;
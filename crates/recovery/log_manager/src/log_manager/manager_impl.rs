@@ -1,51 +1,299 @@
+use std::mem;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
+use std::thread;
+use std::thread::Thread;
 use parking_lot::Mutex;
-use common::config::{AtomicLSN, INVALID_LSN, LSN};
+use common::config::{AtomicLSN, INVALID_LSN, LOG_TIMEOUT, LSN};
+use disk_storage::errors::DiskError;
 use disk_storage::DiskManager;
 use crate::{LogManager, LogRecord, LOG_BUFFER_SIZE};
-use crate::types::LogBuffer;
+use crate::log_manager::LogBufferState;
 
 impl LogManager {
 
-    pub fn new<D: DiskManager>(_disk_manager: Arc<D>) -> Self {
+    pub fn new<D: DiskManager>(disk_manager: Arc<D>) -> Self {
         LogManager {
             next_lsn: AtomicLSN::new(0),
             persistent_lsn: AtomicLSN::new(INVALID_LSN),
-            log_buffer: [0u8; LOG_BUFFER_SIZE],
-            flush_buffer: [0u8; LOG_BUFFER_SIZE],
-            latch: Mutex::new(()),
-            // flush_thread: (),
+            latch: Mutex::new(LogBufferState {
+                log_buffer: Box::new([0u8; LOG_BUFFER_SIZE]),
+                offset: 0,
+                last_lsn: INVALID_LSN,
+                log_size: 0,
+                flush_requested: false,
+                flush_thread_running: false,
+                stop_flush_thread: false,
+                flush_error: None,
+                log_size_trigger: None,
+            }),
+            flush_buffer: Mutex::new(Box::new([0u8; LOG_BUFFER_SIZE])),
             cv: Default::default(),
-            // disk_manager,
+            flushed_cv: Default::default(),
+            flush_thread: Mutex::new(None),
+            disk_manager,
         }
     }
 
-    pub fn run_flush_thread(&self) {
-        unimplemented!()
+    /// Start the background thread that flushes the log buffer, does nothing if already running
+    ///
+    /// The thread holds the log manager until `stop_flush_thread` is called
+    pub fn run_flush_thread(self: &Arc<Self>) {
+        let mut flush_thread = self.flush_thread.lock();
+
+        if flush_thread.is_some() {
+            return;
+        }
+
+        self.latch.lock().flush_thread_running = true;
+
+        let log_manager = self.clone();
+
+        *flush_thread = Some(
+            thread::Builder::new()
+                .name("Log Flush".to_string())
+                .spawn(move || log_manager.flush_thread_loop())
+                .expect("should spawn log flush thread")
+        );
     }
 
+    /// Stop the flush thread after flushing the log buffer, does nothing if not running
     pub fn stop_flush_thread(&self) {
-        // unimplemented!()
+        let mut flush_thread = self.flush_thread.lock();
+
+        let Some(handle) = flush_thread.take() else {
+            return;
+        };
+
+        {
+            let mut state = self.latch.lock();
+            state.stop_flush_thread = true;
+            self.cv.notify_one();
+        }
+
+        handle.join().expect("log flush thread should not panic");
+
+        self.latch.lock().stop_flush_thread = false;
+    }
+
+    fn flush_thread_loop(&self) {
+        let mut state = self.latch.lock();
+
+        loop {
+            if !state.flush_requested && !state.stop_flush_thread {
+                self.cv.wait_for(&mut state, LOG_TIMEOUT);
+            }
+
+            let stop = state.stop_flush_thread;
+
+            // Waits that come after this point flush by themselves
+            if stop {
+                state.flush_thread_running = false;
+            }
+
+            drop(state);
+            self.flush_log_buffer();
+
+            if stop {
+                return;
+            }
+
+            state = self.latch.lock();
+        }
+    }
+
+    /// Swap the log buffer with the flush buffer and write the flush buffer to disk
+    fn flush_log_buffer(&self) {
+        let mut flush_buffer = self.flush_buffer.lock();
+
+        let (size, last_lsn) = {
+            let mut state = self.latch.lock();
+            state.flush_requested = false;
+
+            if state.offset == 0 {
+                return;
+            }
+
+            mem::swap(&mut state.log_buffer, &mut *flush_buffer);
+
+            let size = mem::take(&mut state.offset);
+
+            // Appends that waited for space can continue while writing
+            self.flushed_cv.notify_all();
+
+            (size, state.last_lsn)
+        };
+
+        let write_result = self.disk_manager.write_log(&flush_buffer[..size], size as i32);
+
+        let mut state = self.latch.lock();
+
+        match write_result {
+            Ok(()) if state.flush_error.is_none() => self.persistent_lsn.store(last_lsn, Ordering::SeqCst),
+            Ok(()) => {}
+            Err(err) => state.flush_error = Some(err),
+        }
+
+        self.flushed_cv.notify_all();
+    }
+
+    /// Append log record to the log buffer and assign its LSN
+    ///
+    /// If the log buffer is full, waits for it to be flushed.
+    /// The log record is not persisted when returning, use `flush` to wait until it is
+    ///
+    /// # Arguments
+    ///
+    /// * `log_record`: the log record to append, its LSN is set to the assigned LSN
+    ///
+    /// returns: LSN the LSN of the log record
+    ///
+    pub fn append_log_record(&self, log_record: &mut LogRecord) -> LSN {
+        self.append_log_record_with(log_record, |_, _| {})
     }
 
-    pub fn append_log_record(&mut self, _log_record: &LogRecord) -> LSN {
-        unimplemented!()
+    /// Same as `append_log_record`, and run `on_append` with the LSN and the offset in the log of the log record
+    /// before the next log record can be appended
+    ///
+    /// Use it to keep state that must match the log, a checkpoint that begins after the log record sees the state
+    ///
+    /// # Arguments
+    ///
+    /// * `log_record`: the log record to append, its LSN is set to the assigned LSN
+    /// * `on_append`: called with the LSN and the offset of the log record while holding the log buffer latch
+    ///
+    /// returns: LSN the LSN of the log record
+    ///
+    pub fn append_log_record_with<F: FnOnce(LSN, usize)>(&self, log_record: &mut LogRecord, on_append: F) -> LSN {
+        let size = log_record.get_size() as usize;
+        assert!(size <= LOG_BUFFER_SIZE, "log record of size {} does not fit in the log buffer", size);
+
+        let mut state = self.latch.lock();
+
+        while state.offset + size > LOG_BUFFER_SIZE {
+            if state.flush_thread_running {
+                state.flush_requested = true;
+                self.cv.notify_one();
+                self.flushed_cv.wait(&mut state);
+            } else {
+                drop(state);
+                self.flush_log_buffer();
+                state = self.latch.lock();
+            }
+        }
+
+        // LSN is assigned under the latch so the log records are in LSN order in the log
+        let lsn = self.next_lsn.fetch_add(1, Ordering::SeqCst);
+        log_record.set_lsn(lsn);
+
+        let offset = state.offset;
+        log_record.serialize_to(&mut state.log_buffer[offset..offset + size]);
+
+        state.offset += size;
+        state.last_lsn = lsn;
+
+        on_append(lsn, state.log_size);
+        state.log_size += size;
+
+        if state.log_size_trigger.as_ref().is_some_and(|(log_size, _)| state.log_size >= *log_size) {
+            let (_, thread) = state.log_size_trigger.take().unwrap();
+            thread.unpark();
+        }
+
+        lsn
+    }
+
+    /// Unpark the thread once the log size reaches `log_size`, right away if it already did
+    ///
+    /// Only one thread is unparked, a later call replaces the thread and the size
+    ///
+    /// # Arguments
+    ///
+    /// * `log_size`: the size of the log, as returned by `get_log_size`
+    /// * `thread`: the thread to unpark
+    ///
+    pub fn unpark_on_log_size(&self, log_size: usize, thread: Thread) {
+        let mut state = self.latch.lock();
+
+        if state.log_size >= log_size {
+            state.log_size_trigger = None;
+            thread.unpark();
+        } else {
+            state.log_size_trigger = Some((log_size, thread));
+        }
+    }
+
+    /// Wait until the log record with `lsn` and all the log records before it are persisted
+    ///
+    /// Concurrent waits share the same write, this is how commits are grouped
+    ///
+    /// # Arguments
+    ///
+    /// * `lsn`: the LSN to wait for, must be already appended
+    ///
+    /// returns: Result<(), DiskError> error if writing the log failed, in which case the log will never be persisted
+    ///
+    pub fn flush(&self, lsn: LSN) -> Result<(), DiskError> {
+        assert!(lsn < self.get_next_lsn(), "LSN {} was not appended yet", lsn);
+
+        let mut state = self.latch.lock();
+
+        loop {
+            if let Some(err) = &state.flush_error {
+                return Err(err.clone());
+            }
+
+            if self.get_persistent_lsn() >= lsn {
+                return Ok(());
+            }
+
+            if state.flush_thread_running {
+                state.flush_requested = true;
+                self.cv.notify_one();
+                self.flushed_cv.wait(&mut state);
+            } else {
+                drop(state);
+                self.flush_log_buffer();
+                state = self.latch.lock();
+            }
+        }
+    }
+
+    /// Wait until all the appended log records are persisted
+    pub fn flush_all(&self) -> Result<(), DiskError> {
+        let lsn = self.get_next_lsn() - 1;
+
+        if lsn == INVALID_LSN {
+            return Ok(());
+        }
+
+        self.flush(lsn)
     }
 
     pub fn get_next_lsn(&self) -> LSN {
         self.next_lsn.load(Ordering::SeqCst)
     }
 
+    /// Continue the LSNs after the log records that are already in the log, must be called before appending
+    pub fn set_next_lsn(&self, lsn: LSN) {
+        self.next_lsn.store(lsn, Ordering::SeqCst)
+    }
+
+    /// Number of bytes in the log including the log records that are not persisted yet
+    pub fn get_log_size(&self) -> usize {
+        self.latch.lock().log_size
+    }
+
+    /// Set the size of the log that is already on disk so the offsets continue after it, must be called before appending
+    pub fn set_log_size(&self, log_size: usize) {
+        self.latch.lock().log_size = log_size;
+    }
+
     pub fn get_persistent_lsn(&self) -> LSN {
         self.persistent_lsn.load(Ordering::SeqCst)
     }
 
-    pub fn set_persistent_lsn(&mut self, lsn: LSN) {
+    pub fn set_persistent_lsn(&self, lsn: LSN) {
         self.persistent_lsn.store(lsn, Ordering::SeqCst)
     }
-
-    pub fn get_log_buffer(&self) -> LogBuffer {
-        self.log_buffer
-    }
 }
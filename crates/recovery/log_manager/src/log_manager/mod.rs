@@ -1,34 +1,70 @@
 mod manager_impl;
+mod tests;
 
+use std::sync::Arc;
+use std::thread::{JoinHandle, Thread};
 use crate::types::{FlushBuffer, LogBuffer};
-use common::config::AtomicLSN;
+use common::config::{AtomicLSN, LSN};
+use disk_storage::errors::DiskError;
+use disk_storage::DiskManager;
 use parking_lot::{Condvar, Mutex};
 
+///
+/// Write ahead log with group commit.
+///
+/// Log records are appended to the log buffer, and the flush thread writes them to disk in the background.
+/// When flushing, the log buffer is swapped with the flush buffer so appends can continue while the flush buffer is written,
+/// and all the transactions that wait for their commit to be persisted share the same write.
+///
+/// The log is flushed every `LOG_TIMEOUT`, when the log buffer is full or when someone waits for an LSN to be persisted
+///
 pub struct LogManager {
 
     /** The atomic counter which records the next log sequence number. */
-    #[allow(unused)]
     next_lsn: AtomicLSN,
 
     /** The log records before and including the persistent lsn have been written to disk. */
-    #[allow(unused)]
     persistent_lsn: AtomicLSN,
 
-    #[allow(unused)]
-    log_buffer: LogBuffer,
-    #[allow(unused)]
-    flush_buffer: FlushBuffer,
+    /** Protects the log buffer and the flush requests */
+    latch: Mutex<LogBufferState>,
 
-    #[allow(unused)]
-    latch: Mutex<()>,
+    /** Held for the entire flush, only one flush can write to disk at a time */
+    flush_buffer: Mutex<Box<FlushBuffer>>,
 
-    // TODO - add
-    // flush_thread: JoinHandle<()>,
-    // std::thread *flush_thread_ __attribute__((__unused__));
-
-    #[allow(unused)]
+    /** Wakes the flush thread */
     cv: Condvar,
-    //
-    // #[allow(unused)]
-    // disk_manager: Arc<Box<dyn DiskManager>>,
+
+    /** Wakes the appends that wait for space and the waits for an LSN to be persisted, after each flush */
+    flushed_cv: Condvar,
+
+    flush_thread: Mutex<Option<JoinHandle<()>>>,
+
+    disk_manager: Arc<dyn DiskManager>,
+}
+
+struct LogBufferState {
+    log_buffer: Box<LogBuffer>,
+
+    /** Number of bytes used in the log buffer */
+    offset: usize,
+
+    /** The LSN of the last log record in the log buffer */
+    last_lsn: LSN,
+
+    /** Number of bytes in the log including the log buffer, the offset of the next log record in the log */
+    log_size: usize,
+
+    /** Someone waits for the log buffer to be flushed */
+    flush_requested: bool,
+
+    flush_thread_running: bool,
+
+    stop_flush_thread: bool,
+
+    /** A failed write loses the log records that were in the flush buffer, so the log can't be persisted after it */
+    flush_error: Option<DiskError>,
+
+    /** The thread to unpark once the log size reaches the size, set by `unpark_on_log_size` */
+    log_size_trigger: Option<(usize, Thread)>,
 }
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use common::config::{INVALID_LSN, LOG_TIMEOUT};
+    use disk_storage::{DefaultDiskManager, DiskManager};
+    use rid::RID;
+    use tempdir::TempDir;
+    use tuple::Tuple;
+    use crate::{LogManager, LogRecord, LogRecordBody, LogRecordType, LOG_BUFFER_SIZE};
+
+    fn create_disk_manager(tmp_dir: &TempDir) -> Arc<DefaultDiskManager> {
+        Arc::new(DefaultDiskManager::new(tmp_dir.path().join("test.db")).expect("should create disk manager"))
+    }
+
+    fn insert_record(txn_id: i64, data: &[u8]) -> LogRecord {
+        LogRecord::new(txn_id, INVALID_LSN, LogRecordBody::Insert { rid: RID::new(0, 0), tuple: Tuple::from(data) })
+    }
+
+    /// Read all the log records that were written to the log file
+    fn read_log(disk_manager: &DefaultDiskManager) -> Vec<LogRecord> {
+        let log_size = LOG_BUFFER_SIZE * 4;
+        let mut log = vec![0u8; log_size];
+        assert!(disk_manager.read_log(&mut log, log_size as i32, 0).expect("should read log"), "log should not be empty");
+
+        let mut records = vec![];
+        let mut offset = 0;
+
+        while let Ok(record) = LogRecord::deserialize_from(&log[offset..]) {
+            offset += record.get_size() as usize;
+            records.push(record);
+        }
+
+        records
+    }
+
+    #[test]
+    fn should_assign_lsn_in_append_order_and_persist_on_flush() {
+        let tmp_dir = TempDir::new("log_manager_tests").expect("should create tmp directory");
+        let disk_manager = create_disk_manager(&tmp_dir);
+        let log_manager = LogManager::new(disk_manager.clone());
+
+        let mut begin = LogRecord::new(1, INVALID_LSN, LogRecordBody::Begin);
+        let mut insert = insert_record(1, b"row");
+        let mut commit = LogRecord::new(1, 1, LogRecordBody::Commit);
+
+        assert_eq!(log_manager.append_log_record(&mut begin), 0);
+        assert_eq!(log_manager.append_log_record(&mut insert), 1);
+        assert_eq!(log_manager.append_log_record(&mut commit), 2);
+        assert_eq!(commit.get_lsn(), 2);
+
+        assert_eq!(log_manager.get_persistent_lsn(), INVALID_LSN, "should not persist before flush");
+
+        log_manager.flush(commit.get_lsn()).expect("should flush");
+        assert_eq!(log_manager.get_persistent_lsn(), 2);
+
+        let records = read_log(&disk_manager);
+        let types: Vec<LogRecordType> = records.iter().map(|record| record.get_log_record_type()).collect();
+        let lsns: Vec<i32> = records.iter().map(|record| record.get_lsn()).collect();
+
+        assert_eq!(types, vec![LogRecordType::Begin, LogRecordType::Insert, LogRecordType::Commit]);
+        assert_eq!(lsns, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn should_flush_when_log_buffer_is_full() {
+        let tmp_dir = TempDir::new("log_manager_tests").expect("should create tmp directory");
+        let disk_manager = create_disk_manager(&tmp_dir);
+        let log_manager = LogManager::new(disk_manager.clone());
+
+        let data = vec![1u8; 1000];
+        let record_size = insert_record(1, &data).get_size() as usize;
+        let records_in_buffer = LOG_BUFFER_SIZE / record_size;
+
+        for _ in 0..records_in_buffer {
+            log_manager.append_log_record(&mut insert_record(1, &data));
+        }
+
+        assert_eq!(log_manager.get_persistent_lsn(), INVALID_LSN, "buffer is not full yet");
+
+        let lsn = log_manager.append_log_record(&mut insert_record(1, &data));
+
+        assert_eq!(log_manager.get_persistent_lsn(), lsn - 1, "should flush the full buffer before appending");
+        assert_eq!(disk_manager.get_num_flushes(), 1);
+    }
+
+    #[test]
+    fn concurrent_commits_should_share_one_flush() {
+        let tmp_dir = TempDir::new("log_manager_tests").expect("should create tmp directory");
+        let disk_manager = create_disk_manager(&tmp_dir);
+        let log_manager = Arc::new(LogManager::new(disk_manager.clone()));
+
+        let number_of_transactions = 8;
+
+        let commit_lsns: Vec<i32> = (0..number_of_transactions)
+            .map(|txn_id| log_manager.append_log_record(&mut LogRecord::new(txn_id, INVALID_LSN, LogRecordBody::Commit)))
+            .collect();
+
+        log_manager.run_flush_thread();
+
+        let barrier = Arc::new(Barrier::new(number_of_transactions as usize));
+
+        let handles: Vec<_> = commit_lsns
+            .into_iter()
+            .map(|lsn| {
+                let log_manager = log_manager.clone();
+                let barrier = barrier.clone();
+
+                thread::spawn(move || {
+                    barrier.wait();
+                    log_manager.flush(lsn).expect("should flush");
+
+                    assert!(log_manager.get_persistent_lsn() >= lsn);
+                })
+            })
+            .collect();
+
+        handles.into_iter().for_each(|handle| handle.join().expect("commit thread should not panic"));
+
+        log_manager.stop_flush_thread();
+
+        assert_eq!(disk_manager.get_num_flushes(), 1, "all the commits should be written together");
+        assert_eq!(log_manager.get_persistent_lsn(), number_of_transactions as i32 - 1);
+    }
+
+    #[test]
+    fn flush_thread_should_flush_on_timeout_and_on_stop() {
+        let tmp_dir = TempDir::new("log_manager_tests").expect("should create tmp directory");
+        let disk_manager = create_disk_manager(&tmp_dir);
+        let log_manager = Arc::new(LogManager::new(disk_manager.clone()));
+
+        log_manager.run_flush_thread();
+
+        let lsn = log_manager.append_log_record(&mut LogRecord::new(1, INVALID_LSN, LogRecordBody::Begin));
+
+        thread::sleep(LOG_TIMEOUT * 2);
+        assert_eq!(log_manager.get_persistent_lsn(), lsn, "should flush on timeout");
+
+        let lsn = log_manager.append_log_record(&mut LogRecord::new(1, lsn, LogRecordBody::Abort));
+
+        log_manager.stop_flush_thread();
+        assert_eq!(log_manager.get_persistent_lsn(), lsn, "should flush when stopped");
+
+        // Flushes by itself when the flush thread is not running
+        let lsn = log_manager.append_log_record(&mut LogRecord::new(2, INVALID_LSN, LogRecordBody::Begin));
+        log_manager.flush_all().expect("should flush");
+        assert_eq!(log_manager.get_persistent_lsn(), lsn);
+
+        assert_eq!(read_log(&disk_manager).len(), 3);
+    }
+
+    #[test]
+    fn should_report_the_offset_of_each_log_record_in_the_log() {
+        let tmp_dir = TempDir::new("log_manager_tests").expect("should create tmp directory");
+        let disk_manager = create_disk_manager(&tmp_dir);
+        let log_manager = LogManager::new(disk_manager.clone());
+
+        let mut begin = LogRecord::new(1, INVALID_LSN, LogRecordBody::Begin);
+        let mut insert = insert_record(1, b"row");
+
+        let mut offsets = vec![];
+        log_manager.append_log_record_with(&mut begin, |lsn, offset| offsets.push((lsn, offset)));
+        log_manager.append_log_record_with(&mut insert, |lsn, offset| offsets.push((lsn, offset)));
+
+        assert_eq!(offsets, vec![(0, 0), (1, begin.get_size() as usize)]);
+        assert_eq!(log_manager.get_log_size(), (begin.get_size() + insert.get_size()) as usize);
+
+        // Flushing does not change the offsets
+        log_manager.flush_all().expect("should flush");
+
+        let mut log = vec![0u8; insert.get_size() as usize];
+        disk_manager.read_log(&mut log, insert.get_size(), offsets[1].1 as i32).expect("should read log");
+        assert_eq!(LogRecord::deserialize_from(&log).expect("should read the log record at the offset").get_lsn(), 1);
+    }
+}
@@ -0,0 +1,492 @@
+use common::config::{TxnId, INVALID_LSN, LSN};
+use pages::PageId;
+use rid::RID;
+use tuple::Tuple;
+use crate::errors::DeserializeLogRecordError;
+use crate::LogRecordType;
+
+/// The content of the log record that is specific to its type
+#[derive(Debug, Clone)]
+pub enum LogRecordBody {
+    Begin,
+    Commit,
+    Abort,
+
+    Insert {
+        rid: RID,
+        tuple: Tuple,
+    },
+
+    /// Mark the tuple as deleted, the tuple is removed by `ApplyDelete` when the transaction commits
+    MarkDelete {
+        rid: RID,
+        tuple: Tuple,
+    },
+
+    ApplyDelete {
+        rid: RID,
+        tuple: Tuple,
+    },
+
+    /// Undo `MarkDelete` when the transaction aborts
+    RollbackDelete {
+        rid: RID,
+        tuple: Tuple,
+    },
+
+    Update {
+        rid: RID,
+        old_tuple: Tuple,
+        new_tuple: Tuple,
+    },
+
+    NewPage {
+        prev_page_id: PageId,
+        page_id: PageId,
+    },
+
+    /// Compensation log record (CLR), written when undoing a change of a transaction that did not commit.
+    /// It is only redone and never undone, undo continues from `undo_next_lsn`
+    Compensation {
+        /// The LSN of the next log record of the transaction to undo, the prev LSN of the undone log record
+        undo_next_lsn: LSN,
+
+        /// The change that undoes the log record, must be a change to a tuple
+        redo: Box<LogRecordBody>,
+    },
+
+    /// Start of a fuzzy checkpoint, the tables in the end checkpoint were collected after it while the database kept running
+    BeginCheckpoint,
+
+    /// End of a fuzzy checkpoint, recovery starts reading the log from the begin checkpoint of the last completed checkpoint
+    EndCheckpoint {
+        /// The transactions that did not end when the checkpoint began, with their last LSN
+        active_txn: Vec<(TxnId, LSN)>,
+
+        /// The dirty pages with their recovery LSN, the LSN of the first change that is not on disk
+        dirty_pages: Vec<(PageId, LSN)>,
+    },
+}
+
+impl LogRecordBody {
+    pub fn get_log_record_type(&self) -> LogRecordType {
+        match self {
+            LogRecordBody::Begin => LogRecordType::Begin,
+            LogRecordBody::Commit => LogRecordType::Commit,
+            LogRecordBody::Abort => LogRecordType::Abort,
+            LogRecordBody::Insert { .. } => LogRecordType::Insert,
+            LogRecordBody::MarkDelete { .. } => LogRecordType::MarkDelete,
+            LogRecordBody::ApplyDelete { .. } => LogRecordType::ApplyDelete,
+            LogRecordBody::RollbackDelete { .. } => LogRecordType::RollbackDelete,
+            LogRecordBody::Update { .. } => LogRecordType::Update,
+            LogRecordBody::NewPage { .. } => LogRecordType::NewPage,
+            LogRecordBody::Compensation { .. } => LogRecordType::Compensation,
+            LogRecordBody::BeginCheckpoint => LogRecordType::BeginCheckpoint,
+            LogRecordBody::EndCheckpoint { .. } => LogRecordType::EndCheckpoint,
+        }
+    }
+
+    /// Whether the log record changes a tuple, these are the changes that a compensation log record can redo
+    pub fn is_tuple_change(&self) -> bool {
+        matches!(
+            self,
+            LogRecordBody::Insert { .. } |
+            LogRecordBody::MarkDelete { .. } |
+            LogRecordBody::ApplyDelete { .. } |
+            LogRecordBody::RollbackDelete { .. } |
+            LogRecordBody::Update { .. }
+        )
+    }
+
+    fn serialized_size(&self) -> usize {
+        match self {
+            LogRecordBody::Begin | LogRecordBody::Commit | LogRecordBody::Abort | LogRecordBody::BeginCheckpoint => 0,
+            LogRecordBody::Insert { tuple, .. } |
+            LogRecordBody::MarkDelete { tuple, .. } |
+            LogRecordBody::ApplyDelete { tuple, .. } |
+            LogRecordBody::RollbackDelete { tuple, .. } => RID_SIZE + serialized_tuple_size(tuple),
+            LogRecordBody::Update { old_tuple, new_tuple, .. } => RID_SIZE + serialized_tuple_size(old_tuple) + serialized_tuple_size(new_tuple),
+            LogRecordBody::NewPage { .. } => size_of::<PageId>() * 2,
+            LogRecordBody::Compensation { redo, .. } => size_of::<LSN>() + size_of::<i32>() + redo.serialized_size(),
+            LogRecordBody::EndCheckpoint { active_txn, dirty_pages } => {
+                size_of::<u32>() + active_txn.len() * (size_of::<TxnId>() + size_of::<LSN>()) +
+                    size_of::<u32>() + dirty_pages.len() * (size_of::<PageId>() + size_of::<LSN>())
+            }
+        }
+    }
+}
+
+/// Size of the header that every log record starts with
+pub const LOG_RECORD_HEADER_SIZE: usize = CHECKSUM_OFFSET + size_of::<u32>();
+
+/// The checksum is the last field of the header
+const CHECKSUM_OFFSET: usize = size_of::<i32>() + size_of::<LSN>() + size_of::<TxnId>() + size_of::<LSN>() + size_of::<i32>();
+
+/// Calculate the checksum of the serialized log record, which is every byte of the record except the checksum itself
+fn calculate_checksum(record: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+
+    hasher.update(&record[..CHECKSUM_OFFSET]);
+    hasher.update(&record[LOG_RECORD_HEADER_SIZE..]);
+
+    hasher.finalize()
+}
+
+/// Write the checksum of the serialized log record into its header
+pub(crate) fn stamp_checksum(record: &mut [u8]) {
+    let checksum = calculate_checksum(record);
+
+    record[CHECKSUM_OFFSET..LOG_RECORD_HEADER_SIZE].copy_from_slice(&checksum.to_le_bytes());
+}
+
+const RID_SIZE: usize = size_of::<PageId>() + size_of::<u32>();
+
+fn serialized_tuple_size(tuple: &Tuple) -> usize {
+    size_of::<u32>() + tuple.get_data().len()
+}
+
+///
+/// Log record of a single change, written to the log before the change is written to the page.
+///
+/// All the fields are little endian, so the log can be read on any machine.
+///
+/// Header (28 bytes), same for every log record:
+/// --------------------------------------------------------------
+/// | size | LSN | txn id | prev LSN | log record type | checksum |
+/// --------------------------------------------------------------
+///
+/// The checksum is CRC32 of the whole log record except the checksum itself,
+/// a mismatch means the log record was not written completely and the log ends before it.
+///
+/// Insert, mark delete, apply delete and rollback delete:
+/// ----------------------------------------------------
+/// | HEADER | RID | tuple size | tuple data            |
+/// ----------------------------------------------------
+///
+/// Update:
+/// -----------------------------------------------------------------------------------
+/// | HEADER | RID | old tuple size | old tuple data | new tuple size | new tuple data |
+/// -----------------------------------------------------------------------------------
+///
+/// New page:
+/// -------------------------------------
+/// | HEADER | prev page id | page id    |
+/// -------------------------------------
+///
+/// Compensation, the redo body is the body of the change that undoes the log record:
+/// -------------------------------------------------------------------
+/// | HEADER | undo next LSN | redo log record type | redo body        |
+/// -------------------------------------------------------------------
+///
+/// End checkpoint:
+/// ------------------------------------------------------------------------------------------
+/// | HEADER | txn count | (txn id, last LSN) ... | page count | (page id, recovery LSN) ... |
+/// ------------------------------------------------------------------------------------------
+///
+/// Begin, commit, abort and begin checkpoint only have the header.
+/// RID is page id followed by slot number
+///
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    /// Size of the entire log record in bytes including the header
+    size: i32,
+
+    lsn: LSN,
+
+    txn_id: TxnId,
+
+    /// The LSN of the previous log record of the same transaction, `INVALID_LSN` for the first one
+    prev_lsn: LSN,
+
+    body: LogRecordBody,
+}
+
+impl LogRecord {
+    /// Create log record, the LSN is assigned when the record is appended to the log
+    ///
+    /// # Arguments
+    ///
+    /// * `txn_id`: the transaction that made the change
+    /// * `prev_lsn`: the LSN of the previous log record of the transaction
+    /// * `body`: the change
+    ///
+    pub fn new(txn_id: TxnId, prev_lsn: LSN, body: LogRecordBody) -> Self {
+        if let LogRecordBody::Compensation { redo, .. } = &body {
+            assert!(redo.is_tuple_change(), "compensation log record can't redo {:?}", redo.get_log_record_type());
+        }
+
+        LogRecord {
+            size: (LOG_RECORD_HEADER_SIZE + body.serialized_size()) as i32,
+            lsn: INVALID_LSN,
+            txn_id,
+            prev_lsn,
+            body,
+        }
+    }
+
+    pub fn get_size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn get_lsn(&self) -> LSN {
+        self.lsn
+    }
+
+    pub fn set_lsn(&mut self, lsn: LSN) {
+        self.lsn = lsn;
+    }
+
+    pub fn get_txn_id(&self) -> TxnId {
+        self.txn_id
+    }
+
+    pub fn get_prev_lsn(&self) -> LSN {
+        self.prev_lsn
+    }
+
+    pub fn get_log_record_type(&self) -> LogRecordType {
+        self.body.get_log_record_type()
+    }
+
+    pub fn get_body(&self) -> &LogRecordBody {
+        &self.body
+    }
+
+    /// Write the log record to the start of `dest`
+    ///
+    /// # Arguments
+    ///
+    /// * `dest`: the destination, must have at least `get_size` bytes
+    ///
+    pub fn serialize_to(&self, dest: &mut [u8]) {
+        assert!(dest.len() >= self.size as usize, "destination must fit the log record");
+
+        let mut writer = Writer { dest, offset: 0 };
+
+        writer.write(&self.size.to_le_bytes());
+        writer.write(&self.lsn.to_le_bytes());
+        writer.write(&self.txn_id.to_le_bytes());
+        writer.write(&self.prev_lsn.to_le_bytes());
+        writer.write(&(self.get_log_record_type() as i32).to_le_bytes());
+        // Placeholder for the checksum, which is calculated once the whole record is written
+        writer.write(&0u32.to_le_bytes());
+        writer.write_body(&self.body);
+
+        debug_assert_eq!(writer.offset, self.size as usize, "size must match the serialized log record");
+
+        stamp_checksum(&mut dest[..self.size as usize]);
+    }
+
+    /// Read the log record from the start of `input`
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: the log starting at the log record, can have more log records after it
+    ///
+    /// returns: Result<LogRecord, DeserializeLogRecordError> the log record, its size is the number of bytes that were read
+    ///
+    pub fn deserialize_from(input: &[u8]) -> Result<Self, DeserializeLogRecordError> {
+        let mut reader = Reader { input, offset: 0 };
+
+        let size = i32::from_le_bytes(reader.read()?);
+        let lsn = LSN::from_le_bytes(reader.read()?);
+        let txn_id = TxnId::from_le_bytes(reader.read()?);
+        let prev_lsn = LSN::from_le_bytes(reader.read()?);
+        let log_record_type = i32::from_le_bytes(reader.read()?);
+        let checksum = u32::from_le_bytes(reader.read()?);
+
+        // Invalid type is where the log ends, the rest of the log buffer is zeroed
+        let log_record_type = match LogRecordType::try_from(log_record_type) {
+            Ok(LogRecordType::Invalid) | Err(_) => return Err(DeserializeLogRecordError::UnknownType(log_record_type)),
+            Ok(log_record_type) => log_record_type,
+        };
+
+        if size < LOG_RECORD_HEADER_SIZE as i32 {
+            return Err(DeserializeLogRecordError::InvalidSize { log_record_type, size });
+        }
+
+        // Don't read past the record when the size is corrupted
+        if input.len() < size as usize {
+            return Err(DeserializeLogRecordError::Incomplete { needed: size as usize, available: input.len() });
+        }
+        let mut reader = Reader { input: &input[..size as usize], offset: reader.offset };
+
+        // A record that was torn while written can look valid up to here, the tail of the log after a crash
+        let calculated = calculate_checksum(reader.input);
+        if checksum != calculated {
+            return Err(DeserializeLogRecordError::ChecksumMismatch { expected: checksum, actual: calculated });
+        }
+
+        let body = reader.read_body(log_record_type)?;
+
+        if reader.offset != size as usize {
+            return Err(DeserializeLogRecordError::InvalidSize { log_record_type, size });
+        }
+
+        Ok(LogRecord {
+            size,
+            lsn,
+            txn_id,
+            prev_lsn,
+            body,
+        })
+    }
+}
+
+struct Writer<'a> {
+    dest: &'a mut [u8],
+    offset: usize,
+}
+
+impl Writer<'_> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.dest[self.offset..self.offset + bytes.len()].copy_from_slice(bytes);
+        self.offset += bytes.len();
+    }
+
+    fn write_rid(&mut self, rid: &RID) {
+        self.write(&rid.get_page_id().to_le_bytes());
+        self.write(&rid.get_slot_num().to_le_bytes());
+    }
+
+    fn write_tuple(&mut self, tuple: &Tuple) {
+        self.write(&(tuple.get_data().len() as u32).to_le_bytes());
+        self.write(tuple.get_data());
+    }
+
+    fn write_body(&mut self, body: &LogRecordBody) {
+        match body {
+            LogRecordBody::Begin | LogRecordBody::Commit | LogRecordBody::Abort | LogRecordBody::BeginCheckpoint => {}
+            LogRecordBody::Insert { rid, tuple } |
+            LogRecordBody::MarkDelete { rid, tuple } |
+            LogRecordBody::ApplyDelete { rid, tuple } |
+            LogRecordBody::RollbackDelete { rid, tuple } => {
+                self.write_rid(rid);
+                self.write_tuple(tuple);
+            }
+            LogRecordBody::Update { rid, old_tuple, new_tuple } => {
+                self.write_rid(rid);
+                self.write_tuple(old_tuple);
+                self.write_tuple(new_tuple);
+            }
+            LogRecordBody::NewPage { prev_page_id, page_id } => {
+                self.write(&prev_page_id.to_le_bytes());
+                self.write(&page_id.to_le_bytes());
+            }
+            LogRecordBody::Compensation { undo_next_lsn, redo } => {
+                self.write(&undo_next_lsn.to_le_bytes());
+                self.write(&(redo.get_log_record_type() as i32).to_le_bytes());
+                self.write_body(redo);
+            }
+            LogRecordBody::EndCheckpoint { active_txn, dirty_pages } => {
+                self.write(&(active_txn.len() as u32).to_le_bytes());
+                for (txn_id, last_lsn) in active_txn {
+                    self.write(&txn_id.to_le_bytes());
+                    self.write(&last_lsn.to_le_bytes());
+                }
+
+                self.write(&(dirty_pages.len() as u32).to_le_bytes());
+                for (page_id, rec_lsn) in dirty_pages {
+                    self.write(&page_id.to_le_bytes());
+                    self.write(&rec_lsn.to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn read_slice(&mut self, length: usize) -> Result<&[u8], DeserializeLogRecordError> {
+        if self.input.len() < self.offset + length {
+            return Err(DeserializeLogRecordError::Incomplete { needed: self.offset + length, available: self.input.len() });
+        }
+
+        let slice = &self.input[self.offset..self.offset + length];
+        self.offset += length;
+
+        Ok(slice)
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], DeserializeLogRecordError> {
+        Ok(self.read_slice(N)?.try_into().unwrap())
+    }
+
+    fn read_rid(&mut self) -> Result<RID, DeserializeLogRecordError> {
+        let page_id = PageId::from_le_bytes(self.read()?);
+        let slot_num = u32::from_le_bytes(self.read()?);
+
+        Ok(RID::new(page_id, slot_num))
+    }
+
+    fn read_tuple(&mut self) -> Result<Tuple, DeserializeLogRecordError> {
+        let size = u32::from_le_bytes(self.read()?);
+
+        Ok(Tuple::from(self.read_slice(size as usize)?))
+    }
+
+    fn read_body(&mut self, log_record_type: LogRecordType) -> Result<LogRecordBody, DeserializeLogRecordError> {
+        Ok(match log_record_type {
+            LogRecordType::Invalid => unreachable!("invalid log record type is rejected before reading the body"),
+            LogRecordType::Begin => LogRecordBody::Begin,
+            LogRecordType::Commit => LogRecordBody::Commit,
+            LogRecordType::Abort => LogRecordBody::Abort,
+            LogRecordType::Insert => LogRecordBody::Insert { rid: self.read_rid()?, tuple: self.read_tuple()? },
+            LogRecordType::MarkDelete => LogRecordBody::MarkDelete { rid: self.read_rid()?, tuple: self.read_tuple()? },
+            LogRecordType::ApplyDelete => LogRecordBody::ApplyDelete { rid: self.read_rid()?, tuple: self.read_tuple()? },
+            LogRecordType::RollbackDelete => LogRecordBody::RollbackDelete { rid: self.read_rid()?, tuple: self.read_tuple()? },
+            LogRecordType::Update => LogRecordBody::Update {
+                rid: self.read_rid()?,
+                old_tuple: self.read_tuple()?,
+                new_tuple: self.read_tuple()?,
+            },
+            LogRecordType::NewPage => LogRecordBody::NewPage {
+                prev_page_id: PageId::from_le_bytes(self.read()?),
+                page_id: PageId::from_le_bytes(self.read()?),
+            },
+            LogRecordType::Compensation => {
+                let undo_next_lsn = LSN::from_le_bytes(self.read()?);
+                let redo_type = i32::from_le_bytes(self.read()?);
+
+                let redo_type = match LogRecordType::try_from(redo_type) {
+                    Ok(redo_type) => redo_type,
+                    Err(redo_type) => return Err(DeserializeLogRecordError::UnknownType(redo_type)),
+                };
+
+                let redo = self.read_body_of_tuple_change(redo_type)?;
+
+                LogRecordBody::Compensation { undo_next_lsn, redo: Box::new(redo) }
+            }
+            LogRecordType::BeginCheckpoint => LogRecordBody::BeginCheckpoint,
+            LogRecordType::EndCheckpoint => {
+                // Not preallocated by the count, a corrupted count fails on the record size instead
+                let mut active_txn = vec![];
+                for _ in 0..u32::from_le_bytes(self.read()?) {
+                    active_txn.push((TxnId::from_le_bytes(self.read()?), LSN::from_le_bytes(self.read()?)));
+                }
+
+                let mut dirty_pages = vec![];
+                for _ in 0..u32::from_le_bytes(self.read()?) {
+                    dirty_pages.push((PageId::from_le_bytes(self.read()?), LSN::from_le_bytes(self.read()?)));
+                }
+
+                LogRecordBody::EndCheckpoint { active_txn, dirty_pages }
+            }
+        })
+    }
+
+    fn read_body_of_tuple_change(&mut self, log_record_type: LogRecordType) -> Result<LogRecordBody, DeserializeLogRecordError> {
+        match log_record_type {
+            LogRecordType::Insert |
+            LogRecordType::MarkDelete |
+            LogRecordType::ApplyDelete |
+            LogRecordType::RollbackDelete |
+            LogRecordType::Update => self.read_body(log_record_type),
+            _ => Err(DeserializeLogRecordError::InvalidCompensationRedoType(log_record_type)),
+        }
+    }
+}
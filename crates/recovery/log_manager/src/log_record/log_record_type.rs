@@ -0,0 +1,45 @@
+/// The type of log record, stored in the log record header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum LogRecordType {
+    Invalid = 0,
+    Insert = 1,
+    MarkDelete = 2,
+    ApplyDelete = 3,
+    RollbackDelete = 4,
+    Update = 5,
+    Begin = 6,
+    Commit = 7,
+    Abort = 8,
+    /// Create a new page in the table heap
+    NewPage = 9,
+    /// Compensation log record (CLR) of an undone change
+    Compensation = 10,
+    /// Start of a fuzzy checkpoint
+    BeginCheckpoint = 11,
+    /// End of a fuzzy checkpoint with the active transactions and the dirty pages
+    EndCheckpoint = 12,
+}
+
+impl TryFrom<i32> for LogRecordType {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => LogRecordType::Invalid,
+            1 => LogRecordType::Insert,
+            2 => LogRecordType::MarkDelete,
+            3 => LogRecordType::ApplyDelete,
+            4 => LogRecordType::RollbackDelete,
+            5 => LogRecordType::Update,
+            6 => LogRecordType::Begin,
+            7 => LogRecordType::Commit,
+            8 => LogRecordType::Abort,
+            9 => LogRecordType::NewPage,
+            10 => LogRecordType::Compensation,
+            11 => LogRecordType::BeginCheckpoint,
+            12 => LogRecordType::EndCheckpoint,
+            _ => return Err(value),
+        })
+    }
+}
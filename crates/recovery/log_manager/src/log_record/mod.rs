@@ -1,3 +1,6 @@
-pub struct LogRecord {
-// TODO - implement
-}
+mod log_record;
+mod log_record_type;
+mod tests;
+
+pub use log_record::*;
+pub use log_record_type::LogRecordType;
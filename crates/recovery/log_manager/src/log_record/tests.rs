@@ -0,0 +1,256 @@
+#[cfg(test)]
+mod tests {
+    use common::config::{INVALID_LSN, INVALID_TXN_ID};
+    use rid::RID;
+    use tuple::Tuple;
+    use crate::errors::DeserializeLogRecordError;
+    use crate::{LogRecord, LogRecordBody, LogRecordType, LOG_RECORD_HEADER_SIZE};
+    use crate::log_record::log_record::stamp_checksum;
+
+    fn tuple(data: &[u8]) -> Tuple {
+        Tuple::from(data)
+    }
+
+    fn round_trip(log_record: &LogRecord) -> LogRecord {
+        let mut buffer = vec![0u8; log_record.get_size() as usize];
+        log_record.serialize_to(&mut buffer);
+
+        LogRecord::deserialize_from(&buffer).expect("should deserialize log record")
+    }
+
+    fn assert_same_header(actual: &LogRecord, expected: &LogRecord) {
+        assert_eq!(actual.get_size(), expected.get_size());
+        assert_eq!(actual.get_lsn(), expected.get_lsn());
+        assert_eq!(actual.get_txn_id(), expected.get_txn_id());
+        assert_eq!(actual.get_prev_lsn(), expected.get_prev_lsn());
+        assert_eq!(actual.get_log_record_type(), expected.get_log_record_type());
+    }
+
+    #[test]
+    fn transaction_records_should_only_have_header() {
+        for body in [LogRecordBody::Begin, LogRecordBody::Commit, LogRecordBody::Abort] {
+            let mut log_record = LogRecord::new(3, INVALID_LSN, body);
+            log_record.set_lsn(7);
+
+            assert_eq!(log_record.get_size() as usize, LOG_RECORD_HEADER_SIZE);
+
+            let deserialized = round_trip(&log_record);
+            assert_same_header(&deserialized, &log_record);
+        }
+    }
+
+    #[test]
+    fn tuple_records_should_round_trip() {
+        let rid = RID::new(4, 2);
+
+        let bodies = [
+            LogRecordBody::Insert { rid, tuple: tuple(b"inserted") },
+            LogRecordBody::MarkDelete { rid, tuple: tuple(b"marked") },
+            LogRecordBody::ApplyDelete { rid, tuple: tuple(b"applied") },
+            LogRecordBody::RollbackDelete { rid, tuple: tuple(b"") },
+        ];
+
+        for body in bodies {
+            let mut log_record = LogRecord::new(1, 5, body);
+            log_record.set_lsn(6);
+
+            let deserialized = round_trip(&log_record);
+            assert_same_header(&deserialized, &log_record);
+
+            match (deserialized.get_body(), log_record.get_body()) {
+                (LogRecordBody::Insert { rid: actual_rid, tuple: actual }, LogRecordBody::Insert { rid: expected_rid, tuple: expected }) |
+                (LogRecordBody::MarkDelete { rid: actual_rid, tuple: actual }, LogRecordBody::MarkDelete { rid: expected_rid, tuple: expected }) |
+                (LogRecordBody::ApplyDelete { rid: actual_rid, tuple: actual }, LogRecordBody::ApplyDelete { rid: expected_rid, tuple: expected }) |
+                (LogRecordBody::RollbackDelete { rid: actual_rid, tuple: actual }, LogRecordBody::RollbackDelete { rid: expected_rid, tuple: expected }) => {
+                    assert_eq!(actual_rid, expected_rid);
+                    assert!(Tuple::is_tuple_content_equal(actual, expected));
+                }
+                (actual, expected) => panic!("expected {:?} but got {:?}", expected, actual),
+            }
+        }
+    }
+
+    #[test]
+    fn update_record_should_keep_old_and_new_tuples() {
+        let mut log_record = LogRecord::new(2, 10, LogRecordBody::Update {
+            rid: RID::new(1, 9),
+            old_tuple: tuple(b"old value"),
+            new_tuple: tuple(b"new"),
+        });
+        log_record.set_lsn(11);
+
+        let deserialized = round_trip(&log_record);
+        assert_same_header(&deserialized, &log_record);
+
+        let LogRecordBody::Update { rid, old_tuple, new_tuple } = deserialized.get_body() else {
+            panic!("expected update log record but got {:?}", deserialized.get_body());
+        };
+
+        assert_eq!(*rid, RID::new(1, 9));
+        assert_eq!(old_tuple.get_data(), b"old value");
+        assert_eq!(new_tuple.get_data(), b"new");
+    }
+
+    #[test]
+    fn new_page_record_should_round_trip() {
+        let log_record = LogRecord::new(8, INVALID_LSN, LogRecordBody::NewPage { prev_page_id: 3, page_id: 4 });
+
+        let deserialized = round_trip(&log_record);
+        assert_same_header(&deserialized, &log_record);
+
+        let LogRecordBody::NewPage { prev_page_id, page_id } = deserialized.get_body() else {
+            panic!("expected new page log record but got {:?}", deserialized.get_body());
+        };
+
+        assert_eq!(*prev_page_id, 3);
+        assert_eq!(*page_id, 4);
+    }
+
+    #[test]
+    fn compensation_record_should_keep_the_redo_change_and_undo_next_lsn() {
+        let log_record = LogRecord::new(5, 20, LogRecordBody::Compensation {
+            undo_next_lsn: 12,
+            redo: Box::new(LogRecordBody::RollbackDelete { rid: RID::new(2, 3), tuple: tuple(b"row") }),
+        });
+
+        let deserialized = round_trip(&log_record);
+        assert_same_header(&deserialized, &log_record);
+
+        let LogRecordBody::Compensation { undo_next_lsn, redo } = deserialized.get_body() else {
+            panic!("expected compensation log record but got {:?}", deserialized.get_body());
+        };
+
+        assert_eq!(*undo_next_lsn, 12);
+
+        let LogRecordBody::RollbackDelete { rid, tuple } = redo.as_ref() else {
+            panic!("expected rollback delete redo but got {:?}", redo);
+        };
+
+        assert_eq!(*rid, RID::new(2, 3));
+        assert_eq!(tuple.get_data(), b"row");
+    }
+
+    #[test]
+    fn compensation_record_should_only_redo_tuple_changes() {
+        let log_record = LogRecord::new(5, 20, LogRecordBody::Compensation {
+            undo_next_lsn: 12,
+            redo: Box::new(LogRecordBody::Insert { rid: RID::new(2, 3), tuple: tuple(b"row") }),
+        });
+
+        let mut buffer = vec![0u8; log_record.get_size() as usize];
+        log_record.serialize_to(&mut buffer);
+
+        // Redo type offset after the header and the undo next LSN
+        buffer[32..36].copy_from_slice(&(LogRecordType::Commit as i32).to_le_bytes());
+        stamp_checksum(&mut buffer);
+
+        assert_eq!(
+            LogRecord::deserialize_from(&buffer).unwrap_err(),
+            DeserializeLogRecordError::InvalidCompensationRedoType(LogRecordType::Commit)
+        );
+    }
+
+    #[test]
+    fn checkpoint_records_should_keep_active_transactions_and_dirty_pages() {
+        let begin_checkpoint = LogRecord::new(INVALID_TXN_ID, INVALID_LSN, LogRecordBody::BeginCheckpoint);
+        assert_eq!(begin_checkpoint.get_size() as usize, LOG_RECORD_HEADER_SIZE);
+        assert_same_header(&round_trip(&begin_checkpoint), &begin_checkpoint);
+
+        let log_record = LogRecord::new(INVALID_TXN_ID, INVALID_LSN, LogRecordBody::EndCheckpoint {
+            active_txn: vec![(3, 10), (4, 12)],
+            dirty_pages: vec![(7, 2), (8, 11), (9, 5)],
+        });
+
+        let deserialized = round_trip(&log_record);
+        assert_same_header(&deserialized, &log_record);
+
+        let LogRecordBody::EndCheckpoint { active_txn, dirty_pages } = deserialized.get_body() else {
+            panic!("expected end checkpoint log record but got {:?}", deserialized.get_body());
+        };
+
+        assert_eq!(active_txn, &vec![(3, 10), (4, 12)]);
+        assert_eq!(dirty_pages, &vec![(7, 2), (8, 11), (9, 5)]);
+    }
+
+    #[test]
+    fn should_read_consecutive_records_from_buffer() {
+        let records = [
+            LogRecord::new(1, INVALID_LSN, LogRecordBody::Begin),
+            LogRecord::new(1, 0, LogRecordBody::Insert { rid: RID::new(0, 0), tuple: tuple(b"row") }),
+            LogRecord::new(1, 1, LogRecordBody::Commit),
+        ];
+
+        let mut buffer = vec![0u8; 1024];
+        let mut offset = 0;
+
+        for record in &records {
+            record.serialize_to(&mut buffer[offset..]);
+            offset += record.get_size() as usize;
+        }
+
+        let mut offset = 0;
+        let mut types = vec![];
+
+        for _ in 0..records.len() {
+            let record = LogRecord::deserialize_from(&buffer[offset..]).expect("should deserialize log record");
+            offset += record.get_size() as usize;
+            types.push(record.get_log_record_type());
+        }
+
+        assert_eq!(types, vec![LogRecordType::Begin, LogRecordType::Insert, LogRecordType::Commit]);
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_partial_or_corrupted_record() {
+        let log_record = LogRecord::new(1, INVALID_LSN, LogRecordBody::Insert { rid: RID::new(0, 0), tuple: tuple(b"row") });
+
+        let mut buffer = vec![0u8; log_record.get_size() as usize];
+        log_record.serialize_to(&mut buffer);
+
+        assert_eq!(
+            LogRecord::deserialize_from(&buffer[..buffer.len() - 1]).unwrap_err(),
+            DeserializeLogRecordError::Incomplete { needed: buffer.len(), available: buffer.len() - 1 }
+        );
+
+        assert!(matches!(
+            LogRecord::deserialize_from(&buffer[..10]).unwrap_err(),
+            DeserializeLogRecordError::Incomplete { .. }
+        ));
+
+        // Empty log after the last record
+        assert_eq!(
+            LogRecord::deserialize_from(&[0u8; LOG_RECORD_HEADER_SIZE]).unwrap_err(),
+            DeserializeLogRecordError::UnknownType(LogRecordType::Invalid as i32)
+        );
+
+        // Type offset in the header
+        buffer[20..24].copy_from_slice(&42i32.to_le_bytes());
+        assert_eq!(LogRecord::deserialize_from(&buffer).unwrap_err(), DeserializeLogRecordError::UnknownType(42));
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_record_that_does_not_match_its_checksum() {
+        let log_record = LogRecord::new(1, INVALID_LSN, LogRecordBody::Insert { rid: RID::new(0, 0), tuple: tuple(b"row") });
+
+        let mut buffer = vec![0u8; log_record.get_size() as usize];
+        log_record.serialize_to(&mut buffer);
+
+        // Torn tail of the tuple data, the header and the size are still valid
+        let last = buffer.len() - 1;
+        buffer[last] ^= 0xFF;
+
+        assert!(matches!(
+            LogRecord::deserialize_from(&buffer).unwrap_err(),
+            DeserializeLogRecordError::ChecksumMismatch { .. }
+        ));
+
+        // Corrupted header field that is not validated otherwise
+        log_record.serialize_to(&mut buffer);
+        buffer[8..16].copy_from_slice(&99i64.to_le_bytes());
+
+        assert!(matches!(
+            LogRecord::deserialize_from(&buffer).unwrap_err(),
+            DeserializeLogRecordError::ChecksumMismatch { .. }
+        ));
+    }
+}
@@ -2,7 +2,8 @@ use pages::PAGE_SIZE;
 
 /// size of buffer pool
 pub(crate) const BUFFER_POOL_SIZE: usize = 10;
-pub(crate) const LOG_BUFFER_SIZE: usize = (BUFFER_POOL_SIZE + 1) * PAGE_SIZE;  // size of a log buffer in byte
+/// Size of a log buffer in bytes, no log record is bigger than that
+pub const LOG_BUFFER_SIZE: usize = (BUFFER_POOL_SIZE + 1) * PAGE_SIZE;
 
 pub(crate) type LogBuffer = [u8; LOG_BUFFER_SIZE];
 pub(crate) type FlushBuffer = [u8; LOG_BUFFER_SIZE];
@@ -0,0 +1,43 @@
+use buffer_pool_manager::errors::BufferPoolError;
+use common::config::LSN;
+use disk_storage::errors::DiskError;
+use pages::PageId;
+use recovery_log_manager::errors::DeserializeLogRecordError;
+
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum RecoveryError {
+    #[error("failed to read the log")]
+    DiskError(#[from] DiskError),
+
+    #[error("buffer pool error")]
+    BufferPoolError(#[from] BufferPoolError),
+
+    /// The prev LSN chain of a transaction points to a log record that is not in the log
+    #[error("log record with LSN {0} is missing from the log")]
+    MissingLogRecord(LSN),
+
+    #[error("log record with LSN {lsn} is corrupted")]
+    InvalidLogRecord {
+        lsn: LSN,
+
+        #[source]
+        error: DeserializeLogRecordError,
+    },
+
+    /// The change can't be applied to the page, the page was changed without logging
+    #[error("log record with LSN {lsn} does not match page {page_id}")]
+    PageMismatch {
+        lsn: LSN,
+        page_id: PageId,
+    },
+
+    /// Redo of an insert would put the tuple in another slot than the logged one,
+    /// the page on disk has tuples that are not in the log (or lost tuples that are) so it can't be recovered
+    #[error("page {page_id} is corrupted, redo of the insert with LSN {lsn} is for slot {slot_num} but the page has {num_tuples} tuples")]
+    PageCorrupted {
+        lsn: LSN,
+        page_id: PageId,
+        slot_num: u32,
+        num_tuples: u32,
+    },
+}
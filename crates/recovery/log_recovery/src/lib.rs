@@ -0,0 +1,4 @@
+mod log_recovery;
+pub mod errors;
+
+pub use log_recovery::LogRecovery;
@@ -0,0 +1,53 @@
+mod recovery_impl;
+mod tests;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use buffer_pool_manager::BufferPoolManager;
+use common::config::{TxnId, LSN};
+use disk_storage::DiskManager;
+use pages::PageId;
+use recovery_log_manager::LogManager;
+
+///
+/// ARIES crash recovery, brings the table pages back to the state of the committed transactions when the database is opened.
+///
+/// 1. Analysis - scan the log to find the transactions that did not commit or abort (the losers) and where each log record is
+/// 2. Redo - repeat history by redoing every change that is not on its page yet, a page has every change up to its page LSN
+/// 3. Undo - roll back the losers from their last log record backwards using the prev LSN chain,
+///    every undone change is logged with a compensation log record so a crash during recovery won't undo it again
+///
+/// Analysis starts from the last completed checkpoint, its active transaction table and dirty page table replace the log before it.
+/// Redo starts from the oldest change of the dirty pages, and the log before the checkpoint is only read when redo or undo needs it
+///
+/// Index changes are not logged, so only the table pages are recovered
+///
+pub struct LogRecovery {
+    disk_manager: Arc<dyn DiskManager>,
+
+    bpm: Arc<BufferPoolManager>,
+
+    /** The compensation log records and the aborts of the losers are appended to it */
+    log_manager: Arc<LogManager>,
+
+    /** The transactions that did not commit or abort, mapped to their last log record */
+    active_txn: HashMap<TxnId, LSN>,
+
+    /** The offset in the log of every log record, used to read the log records of the losers while undoing */
+    lsn_mapping: HashMap<LSN, usize>,
+
+    /** The LSN of the last log record in the log */
+    last_lsn: LSN,
+
+    /** The size of the log that was read, new log records are appended after it */
+    log_size: usize,
+
+    /** The LSN of the begin checkpoint record that analysis started from, INVALID_LSN when the whole log is read */
+    checkpoint_lsn: LSN,
+
+    /** The pages that were dirty at the checkpoint, mapped to the LSN of their first change that may not be on disk */
+    dirty_pages: HashMap<PageId, LSN>,
+
+    /** The offset of the first log record in lsn_mapping, the log before it was not read yet */
+    mapped_offset: usize,
+}
@@ -0,0 +1,416 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+use buffer_common::AccessType;
+use buffer_pool_manager::errors::MapErrorToBufferPoolError;
+use buffer_pool_manager::{BufferPool, BufferPoolManager};
+use common::config::{TxnId, INVALID_LSN, LSN};
+use disk_storage::DiskManager;
+use pages::PageId;
+use recovery_log_manager::errors::DeserializeLogRecordError;
+use recovery_log_manager::{LogManager, LogRecord, LogRecordBody, LOG_BUFFER_SIZE};
+use rid::RID;
+use table::TablePage;
+use tuple::TupleMeta;
+use crate::errors::RecoveryError;
+use crate::LogRecovery;
+
+impl LogRecovery {
+
+    pub fn new(disk_manager: Arc<dyn DiskManager>, bpm: Arc<BufferPoolManager>, log_manager: Arc<LogManager>) -> Self {
+        LogRecovery {
+            disk_manager,
+            bpm,
+            log_manager,
+            active_txn: HashMap::new(),
+            lsn_mapping: HashMap::new(),
+            last_lsn: INVALID_LSN,
+            log_size: 0,
+            checkpoint_lsn: INVALID_LSN,
+            dirty_pages: HashMap::new(),
+            mapped_offset: 0,
+        }
+    }
+
+    /// Recover the table pages from the log, must run before anything is appended to the log
+    ///
+    /// When done, the log manager continues the LSNs of the log and the losers are rolled back and aborted in the log
+    ///
+    /// returns: Result<(), RecoveryError> error if failed to read the log or the pages, in which case the database must not be used
+    ///
+    pub fn recover(mut self) -> Result<(), RecoveryError> {
+        assert_eq!(self.log_manager.get_next_lsn(), 0, "recovery must run before appending to the log");
+
+        self.analysis()?;
+
+        // The log after the last complete log record was torn by the crash, it must be removed before the
+        // compensation log records are appended, otherwise they would be written after it and never read again
+        self.disk_manager.truncate_log(self.log_size as u64)?;
+
+        self.redo()?;
+
+        // The log that was read is already persisted, new log records continue after it
+        self.log_manager.set_next_lsn(self.last_lsn + 1);
+        self.log_manager.set_persistent_lsn(self.last_lsn);
+        self.log_manager.set_log_size(self.log_size);
+
+        self.undo()?;
+
+        self.log_manager.flush_all()?;
+
+        Ok(())
+    }
+
+    /// Find the transactions that did not end and the offset of every log record, from the last completed checkpoint
+    fn analysis(&mut self) -> Result<(), RecoveryError> {
+        // The offset of the begin checkpoint record, the log is read from the start when there was no checkpoint
+        let checkpoint_offset = self.disk_manager.get_checkpoint_offset().map_or(0, |offset| offset as usize);
+
+        let mut active_txn = HashMap::new();
+        let mut dirty_pages: HashMap<PageId, LSN> = HashMap::new();
+        let mut lsn_mapping = HashMap::new();
+        let mut last_lsn = INVALID_LSN;
+        let mut log_size = checkpoint_offset;
+        let mut first_lsn = INVALID_LSN;
+
+        // The transactions that ended after the checkpoint began, they may still be in its active transaction table
+        let mut ended_txn = HashSet::new();
+
+        self.scan_log(checkpoint_offset, usize::MAX, |offset, log_record| {
+            lsn_mapping.insert(log_record.get_lsn(), offset);
+            last_lsn = log_record.get_lsn();
+            log_size = offset + log_record.get_size() as usize;
+
+            if first_lsn == INVALID_LSN {
+                first_lsn = log_record.get_lsn();
+            }
+
+            match log_record.get_body() {
+                LogRecordBody::BeginCheckpoint => {}
+                LogRecordBody::EndCheckpoint { active_txn: checkpoint_txn, dirty_pages: checkpoint_pages } => {
+                    // The tables were collected after the checkpoint began, merge them with the log records that were read since
+                    for &(txn_id, lsn) in checkpoint_txn {
+                        if !ended_txn.contains(&txn_id) {
+                            active_txn.entry(txn_id)
+                                .and_modify(|last_lsn: &mut LSN| *last_lsn = (*last_lsn).max(lsn))
+                                .or_insert(lsn);
+                        }
+                    }
+
+                    for &(page_id, rec_lsn) in checkpoint_pages {
+                        dirty_pages.entry(page_id)
+                            .and_modify(|first_lsn| *first_lsn = (*first_lsn).min(rec_lsn))
+                            .or_insert(rec_lsn);
+                    }
+                }
+                LogRecordBody::Commit | LogRecordBody::Abort => {
+                    active_txn.remove(&log_record.get_txn_id());
+                    ended_txn.insert(log_record.get_txn_id());
+                }
+                _ => {
+                    active_txn.insert(log_record.get_txn_id(), log_record.get_lsn());
+                }
+            }
+
+            Ok(())
+        })?;
+
+        self.active_txn = active_txn;
+        self.lsn_mapping = lsn_mapping;
+        self.last_lsn = last_lsn;
+        self.log_size = log_size;
+        self.mapped_offset = checkpoint_offset;
+
+        // Without a checkpoint every log record is redone
+        if checkpoint_offset > 0 {
+            self.checkpoint_lsn = first_lsn;
+            self.dirty_pages = dirty_pages;
+        }
+
+        Ok(())
+    }
+
+    /// Redo every change in the log that is not on its page, including the changes of the losers
+    ///
+    /// Redo starts from the oldest change of the pages that were dirty at the checkpoint, the changes before it are on disk
+    fn redo(&mut self) -> Result<(), RecoveryError> {
+        let redo_lsn = self.dirty_pages.values().copied().fold(self.checkpoint_lsn, LSN::min);
+
+        if redo_lsn < self.checkpoint_lsn {
+            self.map_log_before_checkpoint()?;
+        }
+
+        let redo_offset = match redo_lsn {
+            INVALID_LSN => 0,
+            lsn => *self.lsn_mapping.get(&lsn).ok_or(RecoveryError::MissingLogRecord(lsn))?,
+        };
+
+        self.scan_log(redo_offset, usize::MAX, |_, log_record| self.redo_log_record(&log_record))
+    }
+
+    fn redo_log_record(&self, log_record: &LogRecord) -> Result<(), RecoveryError> {
+        let lsn = log_record.get_lsn();
+
+        match log_record.get_body() {
+            LogRecordBody::Begin | LogRecordBody::Commit | LogRecordBody::Abort => Ok(()),
+            LogRecordBody::BeginCheckpoint | LogRecordBody::EndCheckpoint { .. } => Ok(()),
+            LogRecordBody::NewPage { prev_page_id, page_id } => {
+                self.redo_on_page(*prev_page_id, lsn, |page| {
+                    page.set_next_page_id(*page_id);
+
+                    Ok(())
+                })?;
+
+                self.redo_on_page(*page_id, lsn, |page| {
+                    page.init();
+
+                    Ok(())
+                })
+            }
+            LogRecordBody::Compensation { redo, .. } => self.redo_tuple_change(lsn, redo),
+            body => self.redo_tuple_change(lsn, body),
+        }
+    }
+
+    fn redo_tuple_change(&self, lsn: LSN, body: &LogRecordBody) -> Result<(), RecoveryError> {
+        let rid = get_rid(body);
+
+        self.redo_on_page(rid.get_page_id(), lsn, |page| apply_tuple_change(lsn, body, page))
+    }
+
+    /// Apply the change of the log record to the table page, unless the page was written after the change
+    fn redo_on_page<F: FnOnce(&mut TablePage) -> Result<(), RecoveryError>>(&self, page_id: PageId, lsn: LSN, f: F) -> Result<(), RecoveryError> {
+        // Before the checkpoint, only the pages that were dirty at the checkpoint can miss the change, the rest are not fetched
+        if lsn < self.checkpoint_lsn && self.dirty_pages.get(&page_id).is_none_or(|&rec_lsn| rec_lsn > lsn) {
+            return Ok(());
+        }
+
+        let mut page_guard = self.bpm.fetch_page_write(page_id, AccessType::Unknown).map_err_to_buffer_pool_err()?;
+
+        if page_guard.get_lsn() >= lsn {
+            return Ok(());
+        }
+
+        f(page_guard.cast_mut::<TablePage>())?;
+        page_guard.set_lsn(lsn);
+
+        Ok(())
+    }
+
+    /// Roll back the losers together, from the latest log record of all of them backwards
+    fn undo(&mut self) -> Result<(), RecoveryError> {
+        // The last log record of each loser, it is the prev LSN of the next log record that is appended
+        let mut last_lsn = self.active_txn.clone();
+
+        let mut to_undo: BinaryHeap<(LSN, TxnId)> = self.active_txn
+            .iter()
+            .map(|(&txn_id, &lsn)| (lsn, txn_id))
+            .collect();
+
+        while let Some((lsn, txn_id)) = to_undo.pop() {
+            if lsn < self.checkpoint_lsn {
+                self.map_log_before_checkpoint()?;
+            }
+
+            let log_record = self.read_log_record(lsn)?;
+
+            let undo_next_lsn = match log_record.get_body() {
+                // Undone before crashing during recovery
+                LogRecordBody::Compensation { undo_next_lsn, .. } => *undo_next_lsn,
+                LogRecordBody::Begin => INVALID_LSN,
+
+                // The new page stays in the table, it is empty once the inserts into it are undone
+                LogRecordBody::NewPage { .. } => log_record.get_prev_lsn(),
+
+                // Ended before the checkpoint began but was still active in its active transaction table
+                LogRecordBody::Commit | LogRecordBody::Abort => {
+                    last_lsn.remove(&txn_id);
+
+                    continue;
+                }
+                _ => {
+                    let clr_lsn = self.undo_tuple_change(&log_record, last_lsn[&txn_id])?;
+                    last_lsn.insert(txn_id, clr_lsn);
+
+                    log_record.get_prev_lsn()
+                }
+            };
+
+            if undo_next_lsn == INVALID_LSN {
+                // Nothing left to undo
+                self.log_manager.append_log_record(&mut LogRecord::new(txn_id, last_lsn[&txn_id], LogRecordBody::Abort));
+            } else {
+                to_undo.push((undo_next_lsn, txn_id));
+            }
+        }
+
+        self.active_txn.clear();
+
+        Ok(())
+    }
+
+    /// Undo the change to the tuple and log it with a compensation log record
+    ///
+    /// returns: Result<LSN, RecoveryError> the LSN of the compensation log record
+    fn undo_tuple_change(&self, log_record: &LogRecord, prev_lsn: LSN) -> Result<LSN, RecoveryError> {
+        let redo = undo_of_tuple_change(log_record.get_body());
+        let rid = get_rid(&redo);
+
+        let mut page_guard = self.bpm.fetch_page_write(rid.get_page_id(), AccessType::Unknown).map_err_to_buffer_pool_err()?;
+
+        let mut clr = LogRecord::new(log_record.get_txn_id(), prev_lsn, LogRecordBody::Compensation {
+            undo_next_lsn: log_record.get_prev_lsn(),
+            redo: Box::new(redo.clone()),
+        });
+        let clr_lsn = self.log_manager.append_log_record(&mut clr);
+
+        // The page has every change after redo, so the undo always applies
+        apply_tuple_change(clr_lsn, &redo, page_guard.cast_mut::<TablePage>())?;
+        page_guard.set_lsn(clr_lsn);
+
+        Ok(clr_lsn)
+    }
+
+    /// Find the offset of the log records before the checkpoint, when redo or undo needs them
+    fn map_log_before_checkpoint(&mut self) -> Result<(), RecoveryError> {
+        if self.mapped_offset == 0 {
+            return Ok(());
+        }
+
+        let mut lsn_mapping = HashMap::new();
+
+        self.scan_log(0, self.mapped_offset, |offset, log_record| {
+            lsn_mapping.insert(log_record.get_lsn(), offset);
+
+            Ok(())
+        })?;
+
+        self.lsn_mapping.extend(lsn_mapping);
+        self.mapped_offset = 0;
+
+        Ok(())
+    }
+
+    /// Read the log records from `start_offset` until the log ends or a log record starts at `end_offset`
+    ///
+    /// The log ends at the first log record that can't be read or does not match its checksum,
+    /// the rest of the log was not written completely when crashed
+    fn scan_log<F: FnMut(usize, LogRecord) -> Result<(), RecoveryError>>(&self, start_offset: usize, end_offset: usize, mut f: F) -> Result<(), RecoveryError> {
+        let mut buffer = vec![0u8; LOG_BUFFER_SIZE];
+        let mut offset = start_offset;
+
+        while offset < end_offset && self.disk_manager.read_log(&mut buffer, LOG_BUFFER_SIZE as i32, offset as i32)? {
+            let mut buffer_offset = 0;
+
+            while offset + buffer_offset < end_offset {
+                match LogRecord::deserialize_from(&buffer[buffer_offset..]) {
+                    Ok(log_record) => {
+                        let size = log_record.get_size() as usize;
+
+                        f(offset + buffer_offset, log_record)?;
+
+                        buffer_offset += size;
+                    }
+
+                    // The log record continues after the buffer, read again from its start
+                    Err(DeserializeLogRecordError::Incomplete { .. }) if buffer_offset > 0 => break,
+
+                    Err(_) => return Ok(()),
+                }
+            }
+
+            offset += buffer_offset;
+        }
+
+        Ok(())
+    }
+
+    fn read_log_record(&self, lsn: LSN) -> Result<LogRecord, RecoveryError> {
+        let offset = *self.lsn_mapping.get(&lsn).ok_or(RecoveryError::MissingLogRecord(lsn))?;
+
+        let mut size = [0u8; size_of::<i32>()];
+        self.disk_manager.read_log(&mut size, size_of::<i32>() as i32, offset as i32)?;
+        let size = i32::from_le_bytes(size);
+
+        let mut buffer = vec![0u8; size as usize];
+        self.disk_manager.read_log(&mut buffer, size, offset as i32)?;
+
+        LogRecord::deserialize_from(&buffer).map_err(|error| RecoveryError::InvalidLogRecord { lsn, error })
+    }
+}
+
+fn get_rid(body: &LogRecordBody) -> RID {
+    match body {
+        LogRecordBody::Insert { rid, .. } |
+        LogRecordBody::MarkDelete { rid, .. } |
+        LogRecordBody::ApplyDelete { rid, .. } |
+        LogRecordBody::RollbackDelete { rid, .. } |
+        LogRecordBody::Update { rid, .. } => *rid,
+        _ => unreachable!("{:?} is not a change to a tuple", body.get_log_record_type()),
+    }
+}
+
+/// The change that undoes the change to the tuple
+fn undo_of_tuple_change(body: &LogRecordBody) -> LogRecordBody {
+    match body.clone() {
+        LogRecordBody::Insert { rid, tuple } => LogRecordBody::ApplyDelete { rid, tuple },
+        LogRecordBody::MarkDelete { rid, tuple } |
+        LogRecordBody::ApplyDelete { rid, tuple } => LogRecordBody::RollbackDelete { rid, tuple },
+        LogRecordBody::RollbackDelete { rid, tuple } => LogRecordBody::MarkDelete { rid, tuple },
+        LogRecordBody::Update { rid, old_tuple, new_tuple } => LogRecordBody::Update { rid, old_tuple: new_tuple, new_tuple: old_tuple },
+        body => unreachable!("{:?} is not a change to a tuple", body.get_log_record_type()),
+    }
+}
+
+/// Apply the change to the tuple on its table page
+fn apply_tuple_change(lsn: LSN, body: &LogRecordBody, page: &mut TablePage) -> Result<(), RecoveryError> {
+    let rid = get_rid(body);
+    let mismatch = RecoveryError::PageMismatch { lsn, page_id: rid.get_page_id() };
+
+    if let LogRecordBody::Insert { tuple, .. } = body {
+        // Tuples are appended, so the tuples before the logged slot are the only tuples on the page
+        if rid.get_slot_num() != page.get_num_tuples() {
+            return Err(RecoveryError::PageCorrupted {
+                lsn,
+                page_id: rid.get_page_id(),
+                slot_num: rid.get_slot_num(),
+                num_tuples: page.get_num_tuples(),
+            });
+        }
+
+        // The tuple meta is not logged, the recovered tuple is visible to everyone
+        return match page.insert_tuple(&TupleMeta::new(0, false), tuple) {
+            Some(_) => Ok(()),
+            None => Err(mismatch),
+        };
+    }
+
+    if rid.get_slot_num() >= page.get_num_tuples() {
+        return Err(mismatch);
+    }
+
+    let mut meta = page.get_tuple_meta(&rid);
+
+    match body {
+        LogRecordBody::MarkDelete { .. } | LogRecordBody::ApplyDelete { .. } => {
+            meta.is_deleted = true;
+            page.update_tuple_meta(&meta, &rid);
+        }
+        LogRecordBody::RollbackDelete { .. } => {
+            meta.is_deleted = false;
+            page.update_tuple_meta(&meta, &rid);
+        }
+        LogRecordBody::Update { new_tuple, .. } => {
+            let (_, old_tuple) = page.get_tuple(&rid);
+
+            if old_tuple.get_length() != new_tuple.get_length() {
+                return Err(mismatch);
+            }
+
+            // Safety: the tuple has the same size as the tuple it replaces
+            unsafe { page.update_tuple_in_place(&meta, new_tuple, &rid) };
+        }
+        _ => unreachable!("{:?} is not a change to a tuple", body.get_log_record_type()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,345 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use buffer_common::AccessType;
+    use buffer_pool_manager::{BufferPool, BufferPoolManager};
+    use common::config::{TxnId, INVALID_LSN, LSN};
+    use disk_storage::{DiskManager, DiskManagerUnlimitedMemory};
+    use pages::PageId;
+    use recovery_log_manager::{LogManager, LogRecord, LogRecordBody};
+    use rid::RID;
+    use table::{TableHeap, TablePage};
+    use transaction::Transaction;
+    use tuple::{Tuple, TupleMeta};
+    use crate::errors::RecoveryError;
+    use crate::LogRecovery;
+
+    /// Open the database on the disk, like opening it after crashing
+    fn open(disk_manager: &Arc<DiskManagerUnlimitedMemory>) -> (Arc<BufferPoolManager>, Arc<LogManager>) {
+        let log_manager = Arc::new(LogManager::new(disk_manager.clone()));
+
+        let bpm = BufferPoolManager::builder()
+            .with_pool_size(10)
+            .with_arc_disk_manager(disk_manager.clone())
+            .with_log_manager(Some(log_manager.clone()))
+            .build_arc();
+
+        (bpm, log_manager)
+    }
+
+    fn recover(disk_manager: &Arc<DiskManagerUnlimitedMemory>) -> (Arc<BufferPoolManager>, Arc<LogManager>) {
+        let (bpm, log_manager) = open(disk_manager);
+
+        LogRecovery::new(disk_manager.clone(), bpm.clone(), log_manager.clone())
+            .recover()
+            .expect("should recover");
+
+        (bpm, log_manager)
+    }
+
+    fn begin(log_manager: &LogManager, txn_id: TxnId) -> Arc<Transaction> {
+        let txn = Arc::new(Transaction::new(txn_id, None));
+
+        let lsn = log_manager.append_log_record(&mut LogRecord::new(txn_id, INVALID_LSN, LogRecordBody::Begin));
+        txn.set_prev_lsn(lsn);
+
+        txn
+    }
+
+    fn commit(log_manager: &LogManager, txn: &Transaction) -> LSN {
+        let lsn = log_manager.append_log_record(&mut LogRecord::new(txn.get_transaction_id(), txn.get_prev_lsn(), LogRecordBody::Commit));
+        log_manager.flush(lsn).expect("should flush the log");
+
+        lsn
+    }
+
+    /// Create a table, creating the table is not logged so its first page is written right away
+    fn create_table(bpm: &Arc<BufferPoolManager>) -> TableHeap {
+        let table_heap = TableHeap::new(bpm.clone());
+        assert!(bpm.flush_page(table_heap.get_first_page_id()));
+
+        table_heap
+    }
+
+    fn insert(table_heap: &TableHeap, txn: &Arc<Transaction>, value: u8) -> RID {
+        table_heap.insert_tuple(&TupleMeta::new(0, false), &Tuple::from([value; 8].as_slice()), &None, txn, None)
+            .expect("should fetch the table page")
+            .expect("should insert the tuple")
+    }
+
+    /// The data and whether the tuple is deleted, for every tuple in the page
+    fn read_tuples(bpm: &Arc<BufferPoolManager>, page_id: PageId) -> Vec<(Vec<u8>, bool)> {
+        let page_guard = bpm.fetch_page_read(page_id, AccessType::Unknown).expect("should fetch the page");
+        let page = page_guard.cast::<TablePage>();
+
+        (0..page.get_num_tuples())
+            .map(|slot_num| {
+                let (meta, tuple) = page.get_tuple(&RID::new(page_id, slot_num));
+
+                (tuple.get_data().to_vec(), meta.is_deleted)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_redo_committed_changes_that_were_not_written() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let page_id = {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let txn = begin(&log_manager, 0);
+            insert(&table_heap, &txn, 1);
+            let rid = insert(&table_heap, &txn, 2);
+            table_heap.mark_tuple_as_deleted(&TupleMeta::new(0, true), &rid, &txn);
+            commit(&log_manager, &txn);
+
+            // Crash without writing the pages
+            table_heap.get_first_page_id()
+        };
+
+        let (bpm, _) = recover(&disk_manager);
+
+        assert_eq!(read_tuples(&bpm, page_id), vec![(vec![1; 8], false), (vec![2; 8], true)]);
+    }
+
+    #[test]
+    fn should_undo_changes_of_transactions_that_did_not_commit_even_if_written() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let page_id = {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let committed = begin(&log_manager, 0);
+            let rid = insert(&table_heap, &committed, 1);
+            commit(&log_manager, &committed);
+
+            let loser = begin(&log_manager, 1);
+            insert(&table_heap, &loser, 2);
+            table_heap.mark_tuple_as_deleted(&TupleMeta::new(0, true), &rid, &loser);
+
+            // The pages of the loser are written before crashing
+            assert!(bpm.flush_page(table_heap.get_first_page_id()));
+
+            table_heap.get_first_page_id()
+        };
+
+        let (bpm, _) = recover(&disk_manager);
+
+        assert_eq!(read_tuples(&bpm, page_id), vec![(vec![1; 8], false), (vec![2; 8], true)]);
+    }
+
+    #[test]
+    fn should_not_undo_delete_of_tuple_that_was_already_deleted() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let page_id = {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let committed = begin(&log_manager, 0);
+            let rid = insert(&table_heap, &committed, 1);
+            assert!(table_heap.mark_tuple_as_deleted(&TupleMeta::new(0, true), &rid, &committed));
+            commit(&log_manager, &committed);
+
+            // The tuple was deleted by the committed transaction, so the loser did not change it
+            let loser = begin(&log_manager, 1);
+            assert!(!table_heap.mark_tuple_as_deleted(&TupleMeta::new(0, true), &rid, &loser));
+            log_manager.flush(loser.get_prev_lsn()).expect("should flush the log");
+
+            table_heap.get_first_page_id()
+        };
+
+        let (bpm, _) = recover(&disk_manager);
+
+        assert_eq!(read_tuples(&bpm, page_id), vec![(vec![1; 8], true)], "the tuple deleted by the committed transaction should stay deleted");
+    }
+
+    #[test]
+    fn should_abort_the_losers_in_the_log() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let loser = begin(&log_manager, 0);
+            insert(&table_heap, &loser, 1);
+            insert(&table_heap, &loser, 2);
+            log_manager.flush_all().expect("should flush the log");
+        }
+
+        let (_, log_manager) = recover(&disk_manager);
+
+        // Begin, 2 inserts, 2 compensation log records and the abort
+        assert_eq!(log_manager.get_next_lsn(), 6);
+        assert_eq!(log_manager.get_persistent_lsn(), 5, "the log records of the undo should be persisted");
+
+        // The loser ended, so recovering again has nothing to undo
+        let (_, log_manager) = recover(&disk_manager);
+        assert_eq!(log_manager.get_next_lsn(), 6);
+    }
+
+    #[test]
+    fn should_remove_the_torn_tail_of_the_log_before_appending_to_it() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let loser = begin(&log_manager, 0);
+            insert(&table_heap, &loser, 1);
+            log_manager.flush_all().expect("should flush the log");
+
+            // Crash in the middle of writing the next log record
+            let mut log_record = LogRecord::new(0, loser.get_prev_lsn(), LogRecordBody::Insert { rid: RID::new(0, 1), tuple: Tuple::from([2u8; 8].as_slice()) });
+            log_record.set_lsn(2);
+
+            let mut buffer = vec![0u8; log_record.get_size() as usize];
+            log_record.serialize_to(&mut buffer);
+            disk_manager.write_log(&buffer, buffer.len() as i32 / 2).expect("should write the log");
+        }
+
+        {
+            // Begin, insert, compensation log record and the abort
+            let (_, log_manager) = recover(&disk_manager);
+            assert_eq!(log_manager.get_next_lsn(), 4);
+
+            let txn = begin(&log_manager, 1);
+            commit(&log_manager, &txn);
+        }
+
+        // The log records appended after recovering must not be hidden behind the torn log record
+        let (_, log_manager) = recover(&disk_manager);
+        assert_eq!(log_manager.get_next_lsn(), 6);
+    }
+
+    #[test]
+    fn recovering_again_after_crashing_should_have_the_same_result() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let page_id = {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let committed = begin(&log_manager, 0);
+            insert(&table_heap, &committed, 1);
+            commit(&log_manager, &committed);
+
+            let loser = begin(&log_manager, 1);
+            insert(&table_heap, &loser, 2);
+            log_manager.flush_all().expect("should flush the log");
+
+            table_heap.get_first_page_id()
+        };
+
+        let expected = vec![(vec![1; 8], false), (vec![2; 8], true)];
+
+        {
+            // Crash again right after recovering, without writing the pages
+            let (bpm, _) = recover(&disk_manager);
+            assert_eq!(read_tuples(&bpm, page_id), expected);
+        }
+
+        let (bpm, _) = recover(&disk_manager);
+        assert_eq!(read_tuples(&bpm, page_id), expected);
+    }
+
+    #[test]
+    fn should_redo_new_pages_of_the_table() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let rids = {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let txn = begin(&log_manager, 0);
+            let rids = (0..1000).map(|i| insert(&table_heap, &txn, i as u8)).collect::<Vec<_>>();
+            commit(&log_manager, &txn);
+
+            rids
+        };
+
+        let mut page_ids = rids.iter().map(|rid| rid.get_page_id()).collect::<Vec<_>>();
+        page_ids.dedup();
+
+        assert!(page_ids.len() > 1, "should insert to multiple pages");
+
+        let (bpm, _) = recover(&disk_manager);
+
+        for pair in page_ids.windows(2) {
+            let page_guard = bpm.fetch_page_read(pair[0], AccessType::Unknown).expect("should fetch the page");
+
+            assert_eq!(page_guard.cast::<TablePage>().get_next_page_id(), pair[1], "should link the new page to the previous page");
+        }
+
+        let tuples = page_ids.iter().flat_map(|&page_id| read_tuples(&bpm, page_id)).collect::<Vec<_>>();
+
+        assert_eq!(tuples, (0..1000).map(|i| (vec![i as u8; 8], false)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_report_page_with_tuples_that_are_not_in_the_log_as_corrupted() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let (page_id, insert_lsn) = {
+            let (bpm, log_manager) = open(&disk_manager);
+            let table_heap = create_table(&bpm);
+
+            let txn = begin(&log_manager, 0);
+            insert(&table_heap, &txn, 1);
+            let insert_lsn = txn.get_prev_lsn();
+            commit(&log_manager, &txn);
+
+            // Crash without writing the pages
+            (table_heap.get_first_page_id(), insert_lsn)
+        };
+
+        {
+            // Change the page without logging, so the slot of the logged insert is already taken
+            let (bpm, _) = open(&disk_manager);
+
+            let mut page_guard = bpm.fetch_page_write(page_id, AccessType::Unknown).expect("should fetch the page");
+            page_guard.cast_mut::<TablePage>().insert_tuple(&TupleMeta::new(0, false), &Tuple::from([2u8; 8].as_slice()))
+                .expect("should insert the tuple");
+            drop(page_guard);
+
+            assert!(bpm.flush_page(page_id));
+        }
+
+        let (bpm, log_manager) = open(&disk_manager);
+        let result = LogRecovery::new(disk_manager.clone(), bpm, log_manager).recover();
+
+        assert_eq!(result, Err(RecoveryError::PageCorrupted { lsn: insert_lsn, page_id, slot_num: 0, num_tuples: 1 }));
+    }
+
+    #[test]
+    fn should_continue_lsn_after_the_log() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        {
+            let (_, log_manager) = open(&disk_manager);
+
+            let txn = begin(&log_manager, 0);
+            commit(&log_manager, &txn);
+        }
+
+        let (_, log_manager) = recover(&disk_manager);
+
+        assert_eq!(log_manager.get_next_lsn(), 2);
+        assert_eq!(log_manager.get_persistent_lsn(), 1);
+    }
+
+    #[test]
+    fn should_do_nothing_when_the_log_is_empty() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+
+        let (_, log_manager) = recover(&disk_manager);
+
+        assert_eq!(log_manager.get_next_lsn(), 0);
+        assert_eq!(log_manager.get_persistent_lsn(), INVALID_LSN);
+    }
+}
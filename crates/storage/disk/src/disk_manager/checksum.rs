@@ -0,0 +1,49 @@
+use crate::errors::DiskError;
+use pages::{PageId, PAGE_CHECKSUM_OFFSET, PAGE_SIZE};
+
+/// Calculate the checksum of the page data, the page id is part of the checksum so a page that was written
+/// to the wrong location will be detected as corrupted as well
+fn calculate_checksum(page_id: PageId, page_data: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+
+    hasher.update(&page_id.to_le_bytes());
+    hasher.update(&page_data[..PAGE_CHECKSUM_OFFSET]);
+
+    hasher.finalize()
+}
+
+fn get_stored_checksum(page_data: &[u8]) -> u32 {
+    u32::from_le_bytes(page_data[PAGE_CHECKSUM_OFFSET..PAGE_SIZE].try_into().unwrap())
+}
+
+/// Write the checksum of the page data into the page trailer
+pub(super) fn stamp_checksum(page_id: PageId, page_data: &mut [u8]) {
+    let checksum = calculate_checksum(page_id, page_data);
+
+    page_data[PAGE_CHECKSUM_OFFSET..PAGE_SIZE].copy_from_slice(&checksum.to_le_bytes());
+}
+
+/// Verify the checksum stored in the page trailer and clear the trailer so it won't leak to the page layouts
+///
+/// Page that is all zeros (including the trailer) is considered valid as this is a page that was never written
+pub(super) fn verify_and_clear_checksum(page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+    let stored = get_stored_checksum(page_data);
+
+    if stored == 0 && page_data[..PAGE_CHECKSUM_OFFSET].iter().all(|&b| b == 0) {
+        return Ok(());
+    }
+
+    let calculated = calculate_checksum(page_id, page_data);
+
+    if stored != calculated {
+        return Err(DiskError::PageCorrupted {
+            page_id,
+            expected: stored,
+            actual: calculated,
+        });
+    }
+
+    page_data[PAGE_CHECKSUM_OFFSET..PAGE_SIZE].fill(0);
+
+    Ok(())
+}
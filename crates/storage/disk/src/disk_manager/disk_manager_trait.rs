@@ -1,5 +1,7 @@
 use common::Future;
 use pages::PageId;
+use crate::errors::DiskError;
+use crate::SystemRoot;
 
 /**
  * DiskManager takes care of the allocation and deallocation of pages within a database. It performs the reading and
@@ -19,38 +21,129 @@ pub trait DiskManager: Sync + Send where Self: 'static {
      * Write a page to the database file.
      * @param page_id id of the page
      * @param page_data raw page data
+     * @return error if the page could not be written
      */
     // TODO - setting back to mut?
 
-    fn write_page(& self, page_id: PageId, page_data: &[u8]);
+    fn write_page(& self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError>;
 
     /**
      * Read a page from the database file.
      * @param page_id id of the page
      * @param[out] page_data output buffer
+     * @return error if the page could not be read or is corrupted
      */
     // TODO - setting back to mut?
 
-    fn read_page(&self, page_id: PageId, page_data: &mut [u8]);
+    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError>;
 
     /**
-     * Flush the entire log buffer into disk.
+     * Write the page bytes exactly as they should be stored, the page trailer is not replaced with the page checksum.
+     * Used to simulate a write that was cut short after the page was checksummed.
+     * @param page_id id of the page
+     * @param page_data page data including the stored trailer
+     * @return error if the page could not be written, same as `write_page` for disk managers without checksums
+     */
+    fn write_raw_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
+        self.write_page(page_id, page_data)
+    }
+
+    /**
+     * Read the page bytes exactly as they are stored, the page checksum is not verified or cleared.
+     * @param page_id id of the page
+     * @param[out] page_data output buffer including the stored trailer
+     * @return error if the page could not be read, same as `read_page` for disk managers without checksums
+     */
+    fn read_raw_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+        self.read_page(page_id, page_data)
+    }
+
+    /**
+     * Flush the entire log buffer into disk, the log is durable when this returns.
      * @param log_data raw log data
      * @param size size of log entry
+     * @return error if the log could not be written or synced
      */
     // TODO - setting back to mut?
-    fn write_log(&self, log_data: &[u8], size: i32);
+    fn write_log(&self, log_data: &[u8], size: i32) -> Result<(), DiskError>;
 
     /**
      * Read a log entry from the log file.
      * @param[out] log_data output buffer
      * @param size size of the log entry
      * @param offset offset of the log entry in the file
-     * @return true if the read was successful, false otherwise
+     * @return true if the read was successful, false if reached the end of the log, error if the log could not be read
      */
     // TODO - setting back to mut?
 
-    fn read_log(& self, log_data: &mut [u8], size: i32, offset: i32) -> bool;
+    fn read_log(& self, log_data: &mut [u8], size: i32, offset: i32) -> Result<bool, DiskError>;
+
+    /**
+     * Cut the log file at `size`, used to remove a log tail that was not written completely before new log records are appended.
+     * The log is durable when this returns.
+     * @param size the new size of the log, must not be greater than the current size
+     * @return error if the log could not be truncated or synced
+     */
+    fn truncate_log(&self, size: u64) -> Result<(), DiskError>;
+
+    /**
+     * Allocate a page, reusing a deallocated page if available.
+     * @return the id of the allocated page
+     */
+    fn allocate_page(&self) -> Result<PageId, DiskError>;
+
+    /**
+     * Deallocate a page so it can be reused by a later allocation.
+     * @param page_id id of the page to deallocate
+     * @return error if the page could not be deallocated
+     */
+    fn deallocate_page(&self, page_id: PageId) -> Result<(), DiskError>;
+
+    /**
+     * Check whether a page is allocated, used before reading a page that was not requested (e.g. read-ahead).
+     * @param page_id id of the page
+     * @return true if the page is allocated, false by default for disk managers that do not track it
+     */
+    fn is_page_allocated(&self, _page_id: PageId) -> bool {
+        false
+    }
+
+    /**
+     * Get the root page of a system structure.
+     * @param root the system structure
+     * @return the root page id, INVALID_PAGE_ID if it was never set
+     */
+    fn get_system_root(&self, root: SystemRoot) -> PageId;
+
+    /**
+     * Set the root page of a system structure so it can be found when the database is opened.
+     * @param root the system structure
+     * @param page_id the root page id
+     * @return error if the root could not be persisted
+     */
+    fn set_system_root(&self, root: SystemRoot, page_id: PageId) -> Result<(), DiskError>;
+
+    /**
+     * Get the offset in the log of the last completed checkpoint, recovery starts reading the log from it.
+     * @return the offset of the begin checkpoint record, None if no checkpoint was completed
+     */
+    fn get_checkpoint_offset(&self) -> Option<u64>;
+
+    /**
+     * Store the offset in the log of the last completed checkpoint, it is durable when this returns.
+     * Must only be set after the end checkpoint record is flushed, otherwise recovery could start from a checkpoint that is not in the log.
+     * @param offset the offset of the begin checkpoint record
+     * @return error if the offset could not be persisted, the previous offset is kept
+     */
+    fn set_checkpoint_offset(&self, offset: u64) -> Result<(), DiskError>;
+
+    /**
+     * Make sure all written pages are durable, this is the equivalent of fsync.
+     * @return error if the pages could not be synced
+     */
+    fn sync(&self) -> Result<(), DiskError> {
+        Ok(())
+    }
 
     /** @return the number of disk flushes */
     fn get_num_flushes(&self) -> i32;
@@ -0,0 +1,139 @@
+use crate::errors::DiskError;
+use pages::PageId;
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/**
+ * Persistent map of the free pages in the database file.
+ *
+ * The file is a bitmap with 1 bit per page, set when the page is free.
+ * Pages past the end of the bitmap are allocated, so growing the database file does not touch the map.
+ * The number of pages is stored in the superblock.
+ */
+pub(super) struct FreeSpaceMap {
+    file: File,
+
+    // Free pages, allocation takes the lowest one first to keep the file compact
+    free_pages: BTreeSet<PageId>,
+}
+
+impl FreeSpaceMap {
+    /**
+     * Create an empty free space map, replacing the existing file if any.
+     * @param file_name the free space map file name
+     */
+    pub(super) fn create(file_name: &Path) -> Result<Self, DiskError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(file_name)?;
+
+        Ok(FreeSpaceMap {
+            file,
+            free_pages: BTreeSet::new(),
+        })
+    }
+
+    /**
+     * Open the free space map file, an empty map is created if missing.
+     * @param file_name the free space map file name
+     * @param next_page_id the number of pages in the database file, bits of pages after it are cleared
+     */
+    pub(super) fn open(file_name: &Path, next_page_id: PageId) -> Result<Self, DiskError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(file_name)?;
+
+        let mut content = vec![];
+        file.read_to_end(&mut content)?;
+
+        let free_pages: BTreeSet<PageId> = content
+            .iter()
+            .enumerate()
+            .flat_map(|(byte_index, &byte)| {
+                (0..8)
+                    .filter(move |bit| byte & (1 << bit) != 0)
+                    .map(move |bit| (byte_index * 8 + bit) as PageId)
+            })
+            .collect();
+
+        let mut fsm = FreeSpaceMap {
+            file,
+            free_pages,
+        };
+
+        // Crashed while the database file was truncated, the removed pages must not be free when the file grows again
+        if fsm.free_pages.last().is_some_and(|&page_id| page_id >= next_page_id) {
+            fsm.remove_from(next_page_id)?;
+        }
+
+        Ok(fsm)
+    }
+
+    pub(super) fn is_free(&self, page_id: PageId) -> bool {
+        self.free_pages.contains(&page_id)
+    }
+
+    /// @return the lowest free page which is no longer free, None if there are no free pages
+    pub(super) fn take_free_page(&mut self) -> Result<Option<PageId>, DiskError> {
+        let Some(page_id) = self.free_pages.pop_first() else {
+            return Ok(None);
+        };
+
+        self.write_bitmap_byte(page_id)?;
+
+        Ok(Some(page_id))
+    }
+
+    pub(super) fn mark_free(&mut self, page_id: PageId) -> Result<(), DiskError> {
+        self.free_pages.insert(page_id);
+
+        self.write_bitmap_byte(page_id)
+    }
+
+    /// Make the free space map durable
+    pub(super) fn sync(&self) -> Result<(), DiskError> {
+        self.file.sync_data()?;
+
+        Ok(())
+    }
+
+    fn bitmap_byte(&self, byte_index: usize) -> u8 {
+        (0..8)
+            .filter(|bit| self.free_pages.contains(&((byte_index * 8 + bit) as PageId)))
+            .fold(0u8, |byte, bit| byte | (1 << bit))
+    }
+
+    fn write_bitmap_byte(&mut self, page_id: PageId) -> Result<(), DiskError> {
+        let byte_index = page_id as usize / 8;
+        let byte = self.bitmap_byte(byte_index);
+
+        self.file.seek(SeekFrom::Start(byte_index as u64))?;
+        self.file.write_all(&[byte])?;
+        self.file.flush()?;
+
+        Ok(())
+    }
+
+    /// Remove the pages starting from `page_id` and cut the bitmap after it
+    pub(super) fn remove_from(&mut self, page_id: PageId) -> Result<(), DiskError> {
+        self.free_pages.split_off(&page_id);
+
+        let byte_index = page_id as usize / 8;
+        let byte = self.bitmap_byte(byte_index);
+
+        self.file.set_len(byte_index as u64)?;
+        self.file.seek(SeekFrom::Start(byte_index as u64))?;
+        self.file.write_all(&[byte])?;
+        self.file.flush()?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use pages::{PageId, PAGE_SIZE};
+use pages::{PageData, PageId, PAGE_SIZE};
 use common::Future;
 use parking_lot::Mutex;
 use std::fs::{File, OpenOptions};
@@ -9,7 +9,11 @@ use std::time::Duration;
 use error_utils::anyhow::anyhow;
 use error_utils::ToAnyhowResult;
 use super::utils::get_file_size;
+use super::checksum::{stamp_checksum, verify_and_clear_checksum};
+use super::free_space_map::FreeSpaceMap;
+use super::superblock::{Superblock, SystemRoot};
 use crate::DiskManager;
+use crate::errors::DiskError;
 
 static BUFFER_USED: Mutex<Option<Vec<u8>>> = Mutex::new(None);
 
@@ -34,11 +38,18 @@ struct InnerDefaultDiskManager {
     // stream to write db file
     // std::fstream db_io_;
     // db_io: File,
+    #[allow(unused)]
     file_name: PathBuf,
 
     // With multiple buffer pool instances, need to protect file access
     db_io: File,
 
+    // The first page of the db file, cached so it is only read when opening the file
+    superblock: Superblock,
+
+    // Free pages of the db file, persisted next to it
+    free_space_map: FreeSpaceMap,
+
     // std::future<void> *flush_log_f_{nullptr};
     flush_log_f: Option<Future<()>>,
 }
@@ -82,7 +93,7 @@ impl DefaultDiskManager {
 
 
 
-        let db_io = OpenOptions::new()
+        let mut db_io = OpenOptions::new()
             .read(true) // std::ios::in
             .write(true) // std::ios::out
             // no std::ios::binary in rust
@@ -90,6 +101,22 @@ impl DefaultDiskManager {
             .open(&file_name)
             .to_anyhow()?;
 
+        let mut free_space_map_name = file_name.clone();
+        free_space_map_name.set_extension("fsm");
+
+        // New db file gets a superblock, existing one must have a superblock this build can read
+        let (superblock, free_space_map) = if db_io.metadata().to_anyhow()?.len() == 0 {
+            let superblock = Superblock::new();
+            write_superblock(&mut db_io, &superblock).to_anyhow()?;
+
+            (superblock, FreeSpaceMap::create(&free_space_map_name).to_anyhow()?)
+        } else {
+            let superblock = read_superblock(&mut db_io)?;
+            let free_space_map = FreeSpaceMap::open(&free_space_map_name, superblock.next_page_id).to_anyhow()?;
+
+            (superblock, free_space_map)
+        };
+
 
         // db_io_.open(db_file, std::ios::binary | std::ios::in | std::ios::out);
         // // directory or file does not exist
@@ -119,6 +146,8 @@ impl DefaultDiskManager {
                     log_name,
                     log_io,
                     db_io,
+                    superblock,
+                    free_space_map,
                     // db_io,
                     // TODO - implement flush_log
                     flush_log_f: None,
@@ -128,6 +157,73 @@ impl DefaultDiskManager {
     }
 }
 
+/// Direct access to the db file for disk scheduler workers that issue the file I/O themselves
+#[cfg(target_os = "linux")]
+impl DefaultDiskManager {
+    pub(crate) fn get_db_raw_fd(&self) -> std::os::fd::RawFd {
+        use std::os::fd::AsRawFd;
+
+        self.inner.lock().db_io.as_raw_fd()
+    }
+
+    /// Hold the db file latch while the worker accesses the file, so the I/O is not interleaved with the disk manager
+    /// own file access (e.g. clearing or truncating deallocated pages)
+    pub(crate) fn with_db_file_latch<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _inner = self.inner.lock();
+
+        f()
+    }
+
+    pub(crate) fn get_page_offset(page_id: PageId) -> u64 {
+        page_offset(page_id)
+    }
+
+    /// Stamp the checksum of a page that is about to be written, the same as `write_page` does
+    pub(crate) fn prepare_page_for_write(&self, page_id: PageId, page_data: &mut PageData) {
+        self.num_writes.fetch_add(1, Ordering::Relaxed);
+
+        stamp_checksum(page_id, page_data);
+    }
+
+    /// Verify the checksum of a page that was read, the same as `read_page` does
+    pub(crate) fn verify_read_page(page_id: PageId, page_data: &mut PageData) -> Result<(), DiskError> {
+        verify_and_clear_checksum(page_id, page_data)
+    }
+}
+
+/// Offset of the page in the db file, the first page of the file is the superblock
+pub(super) fn page_offset(page_id: PageId) -> u64 {
+    (page_id as i64 + 1) as u64 * PAGE_SIZE as u64
+}
+
+pub(super) fn write_superblock(db_io: &mut File, superblock: &Superblock) -> Result<(), DiskError> {
+    db_io.seek(SeekFrom::Start(0))?;
+    db_io.write_all(&superblock.to_page())?;
+    db_io.flush()?;
+
+    Ok(())
+}
+
+pub(super) fn read_superblock(db_io: &mut File) -> error_utils::anyhow::Result<Superblock> {
+    let mut page: PageData = [0u8; PAGE_SIZE];
+
+    db_io.seek(SeekFrom::Start(0)).to_anyhow()?;
+
+    // A file shorter than a page is left zero filled and fails the magic check
+    let read_count = db_io.read(&mut page).to_anyhow()?;
+    if read_count < PAGE_SIZE {
+        page[read_count..].fill(0);
+    }
+
+    Superblock::from_page(&page).to_anyhow()
+}
+
+impl InnerDefaultDiskManager {
+    fn write_superblock(&mut self) -> Result<(), DiskError> {
+        write_superblock(&mut self.db_io, &self.superblock)
+    }
+}
+
 impl DiskManager for DefaultDiskManager {
 
 
@@ -154,33 +250,34 @@ impl DiskManager for DefaultDiskManager {
      * @param page_id id of the page
      * @param page_data raw page data
      *
-     * Write the contents of the specified page into disk file
+     * Write the contents of the specified page into disk file, the page trailer is replaced with the page checksum
      */
-    fn write_page(&self, page_id: PageId, page_data: &[u8]) {
+    fn write_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
+        // Copy the page so the checksum trailer can be stamped without modifying the caller data
+        let mut page_with_checksum: PageData = [0u8; PAGE_SIZE];
+        page_with_checksum.copy_from_slice(&page_data[0..PAGE_SIZE]);
+        stamp_checksum(page_id, &mut page_with_checksum);
+
+        self.write_raw_page(page_id, &page_with_checksum)
+    }
+
+    fn write_raw_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
         let mut inner = self.inner.lock();
         // std::scoped_lock scoped_db_io_latch(db_io_latch_);
 
-        let offset = page_id as u64 * PAGE_SIZE as u64;
+        let offset = page_offset(page_id);
         // set write cursor to offset
         self.num_writes.fetch_add(1, Ordering::Relaxed);
 
         // TODO - cpp seekp is the same as seek from start?
-        let seek_res = inner.db_io.seek(SeekFrom::Start(offset));
-
-        if seek_res.is_err() {
-            println!("I/O error while writing (in seek)");
-            return;
-        }
-        let write_res = inner.db_io.write(&page_data[0..PAGE_SIZE]);
+        inner.db_io.seek(SeekFrom::Start(offset))?;
 
-        // check for I/O error
-        if write_res.is_err() {
-            println!("I/O error while writing (in write)");
-            return;
-        }
+        inner.db_io.write_all(&page_data[0..PAGE_SIZE])?;
 
         // needs to flush to keep disk file in sync
-        inner.db_io.flush().expect("Flush should work");
+        inner.db_io.flush()?;
+
+        Ok(())
     }
 
     /**
@@ -188,45 +285,48 @@ impl DiskManager for DefaultDiskManager {
      * @param page_id id of the page
      * @param[out] page_data output buffer
      *
-     * Read the contents of the specified page into the given memory area
+     * Read the contents of the specified page into the given memory area and verify the page checksum,
+     * the checksum trailer is cleared after verification
      */
-    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) {
+    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+        self.read_raw_page(page_id, page_data)?;
+
+        verify_and_clear_checksum(page_id, &mut page_data[0..PAGE_SIZE])
+    }
+
+    fn read_raw_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
         let mut inner = self.inner.lock();
 
         // std::scoped_lock scoped_db_io_latch(db_io_latch_);
 
 
-        let offset = page_id * PAGE_SIZE as i32;
+        let offset = page_offset(page_id);
         // check if read beyond file length
-        if offset > get_file_size(inner.file_name.as_path()) {
-            println!("I/O error reading past end of file");
-            // std::cerr << "I/O error while reading" << std::endl;
+        if offset >= inner.db_io.metadata()?.len() {
+            // Page was never written, so it is empty
+            page_data[0..PAGE_SIZE].fill(0);
 
-            return;
+            return Ok(());
         }
         // set read cursor to offset
         // db_io.seekp(offset);
-        let seek_result = inner.db_io.seek(SeekFrom::Start(offset as u64));
-        if seek_result.is_err() {
-            eprintln!("I/O error while reading (seek)");
-            return;
-        }
-        let read_res = inner.db_io.read(&mut page_data[0..PAGE_SIZE]);
-
-        if read_res.is_err() {
-            eprintln!("I/O error while reading (read)");
-            return;
+        inner.db_io.seek(SeekFrom::Start(offset))?;
+
+        // A single read can return less than asked, keep reading until the page is full or the file ends
+        let mut read_count = 0;
+        while read_count < PAGE_SIZE {
+            match inner.db_io.read(&mut page_data[read_count..PAGE_SIZE])? {
+                0 => break,
+                n => read_count += n,
+            }
         }
 
-        // if file ends before reading BUSTUB_PAGE_SIZE
-        // let read_count = db_io_.gcount();
-        let read_count = read_res.unwrap();
+        // The file ends in the middle of the page, the write of the page was cut short
         if read_count < PAGE_SIZE {
-            eprintln!("Read less than a page");
-
-            // Set the rest of the to be 0
-            page_data[read_count..(PAGE_SIZE) - read_count].fill(0);
+            return Err(DiskError::PageTruncated { page_id, read: read_count });
         }
+
+        Ok(())
     }
 
     /**
@@ -237,7 +337,7 @@ impl DiskManager for DefaultDiskManager {
     * Write the contents of the log into disk file
     * Only return when sync is done, and only perform sequence write
      */
-    fn write_log(&self, log_data: &[u8], size: i32) {
+    fn write_log(&self, log_data: &[u8], size: i32) -> Result<(), DiskError> {
         let mut inner = self.inner.lock();
         let mut buffer_used = BUFFER_USED.lock();
         // enforce swap log buffer
@@ -249,7 +349,7 @@ impl DiskManager for DefaultDiskManager {
         *buffer_used = Some(log_data.to_vec());
 
         if size == 0 {  // no effect on num_flushes_ if log buffer is empty
-            return;
+            return Ok(());
         }
 
         self.flush_log.store(true, Ordering::SeqCst);
@@ -262,18 +362,15 @@ impl DiskManager for DefaultDiskManager {
         self.num_flushes.fetch_add(1, Ordering::Relaxed);
 
         // sequence write
-        let res = inner.log_io.write(&log_data[0..size as usize]);
-
-        // check for I/O error
-        if res.is_err() {
-            println!("I/O error while writing log");
-            return;
-        }
-
-        // needs to flush to keep disk file in sync
-        inner.log_io.flush().expect("Flush should work");
+        let res = inner.log_io.write_all(&log_data[0..size as usize])
+            // needs to flush to keep disk file in sync
+            .and_then(|_| inner.log_io.flush())
+            // the log records are only durable once synced, all the records of the flush share the same sync
+            .and_then(|_| inner.log_io.sync_data());
 
         self.flush_log.store(false, Ordering::SeqCst);
+
+        Ok(res?)
     }
 
     /**
@@ -287,39 +384,169 @@ impl DiskManager for DefaultDiskManager {
      * Always read from the beginning and perform sequence read
      * @return: false means already reach the end
      */
-    fn read_log(&self, log_data: &mut [u8], size: i32, offset: i32) -> bool {
+    fn read_log(&self, log_data: &mut [u8], size: i32, offset: i32) -> Result<bool, DiskError> {
         let mut inner = self.inner.lock();
 
-        if offset > get_file_size(inner.log_name.as_path()) {
+        if offset >= get_file_size(inner.log_name.as_path()) {
             // LOG_DEBUG("end of log file");
             // LOG_DEBUG("file size is %d", GetFileSize(log_name_));
-            return false;
+            return Ok(false);
         }
 
         // set read cursor to offset
         // log_io.seekp(offset);
-        let seek_result = inner.log_io.seek(SeekFrom::Start(offset as u64));
-        if seek_result.is_err() {
-            println!("I/O error while reading log (seek)");
-            return false;
-        }
-        let read_res = inner.log_io.read(&mut log_data[0..size as usize]);
+        inner.log_io.seek(SeekFrom::Start(offset as u64))?;
 
-        if read_res.is_err() {
-            println!("I/O error while reading log (read)");
-            return false;
-        }
+        let read_count = inner.log_io.read(&mut log_data[0..size as usize])?;
 
         // if log file ends before reading "size"
-        let read_count = read_res.unwrap();
         if read_count < size as usize {
-            println!("Read less than a page");
-
             // Set the rest of the to be 0
-            log_data[read_count..(size as usize) - read_count].fill(0);
+            log_data[read_count..size as usize].fill(0);
+        }
+
+        Ok(true)
+    }
+
+    fn truncate_log(&self, size: u64) -> Result<(), DiskError> {
+        let inner = self.inner.lock();
+
+        // The size change is metadata, so the whole file is synced and not only the data
+        inner.log_io.set_len(size)
+            .and_then(|_| inner.log_io.sync_all())?;
+
+        Ok(())
+    }
+
+    /**
+     * Allocate a page, reusing a deallocated page if available.
+     * @return the id of the allocated page
+     *
+     * Take the lowest free page from the free space map or grow the db file by a page
+     */
+    fn allocate_page(&self) -> Result<PageId, DiskError> {
+        let mut inner = self.inner.lock();
+
+        if let Some(page_id) = inner.free_space_map.take_free_page()? {
+            return Ok(page_id);
+        }
+
+        let page_id = inner.superblock.next_page_id;
+        inner.superblock.next_page_id += 1;
+
+        if let Err(err) = inner.write_superblock() {
+            inner.superblock.next_page_id -= 1;
+
+            return Err(err);
+        }
+
+        Ok(page_id)
+    }
+
+    /**
+     * Deallocate a page so it can be reused by a later allocation.
+     * @param page_id id of the page to deallocate
+     *
+     * Mark the page as free in the free space map and clear it on disk so it reads back empty when reused,
+     * when the end of the db file is free the file is truncated instead.
+     * Deallocating a page that is already free or was never allocated does nothing
+     */
+    fn deallocate_page(&self, page_id: PageId) -> Result<(), DiskError> {
+        let mut inner = self.inner.lock();
+
+        if page_id < 0 || page_id >= inner.superblock.next_page_id || inner.free_space_map.is_free(page_id) {
+            return Ok(());
+        }
+
+        inner.free_space_map.mark_free(page_id)?;
+
+        if page_id == inner.superblock.next_page_id - 1 {
+            let mut next_page_id = page_id;
+            while next_page_id > 0 && inner.free_space_map.is_free(next_page_id - 1) {
+                next_page_id -= 1;
+            }
+
+            // Superblock first, free pages after the next page id are ignored when the file is opened
+            inner.superblock.next_page_id = next_page_id;
+            inner.write_superblock()?;
+            inner.free_space_map.remove_from(next_page_id)?;
+
+            let file_size = page_offset(next_page_id);
+            if inner.db_io.metadata()?.len() > file_size {
+                inner.db_io.set_len(file_size)?;
+            }
+
+            return Ok(());
+        }
+
+        let offset = page_offset(page_id);
+
+        // Page was never written, nothing to clear
+        if offset < inner.db_io.metadata()?.len() {
+            inner.db_io.seek(SeekFrom::Start(offset))?;
+            inner.db_io.write_all(&[0u8; PAGE_SIZE])?;
+            inner.db_io.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn is_page_allocated(&self, page_id: PageId) -> bool {
+        let inner = self.inner.lock();
+
+        page_id >= 0 && page_id < inner.superblock.next_page_id && !inner.free_space_map.is_free(page_id)
+    }
+
+    fn get_system_root(&self, root: SystemRoot) -> PageId {
+        self.inner.lock().superblock.get_root(root)
+    }
+
+    fn set_system_root(&self, root: SystemRoot, page_id: PageId) -> Result<(), DiskError> {
+        let mut inner = self.inner.lock();
+
+        let previous_page_id = inner.superblock.get_root(root);
+        inner.superblock.set_root(root, page_id);
+
+        if let Err(err) = inner.write_superblock() {
+            inner.superblock.set_root(root, previous_page_id);
+
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn get_checkpoint_offset(&self) -> Option<u64> {
+        self.inner.lock().superblock.checkpoint_offset
+    }
+
+    /**
+     * Store the checkpoint offset in the superblock and sync the database file
+     */
+    fn set_checkpoint_offset(&self, offset: u64) -> Result<(), DiskError> {
+        let mut inner = self.inner.lock();
+
+        let previous_offset = inner.superblock.checkpoint_offset.replace(offset);
+
+        if let Err(err) = inner.write_superblock().and_then(|_| Ok(inner.db_io.sync_data()?)) {
+            inner.superblock.checkpoint_offset = previous_offset;
+
+            return Err(err);
         }
 
-        true
+        Ok(())
+    }
+
+    /**
+     * Make sure all written pages are durable
+     *
+     * Sync the database file data and the free space map to the disk
+     */
+    fn sync(&self) -> Result<(), DiskError> {
+        let inner = self.inner.lock();
+
+        inner.db_io.sync_data()?;
+        inner.free_space_map.sync()
     }
 
     /**
@@ -0,0 +1,517 @@
+use crate::errors::DiskError;
+use crate::{DiskManager, SystemRoot};
+use common::Future;
+use pages::{PageData, PageId, INVALID_PAGE_ID, PAGE_CHECKSUM_OFFSET, PAGE_SIZE};
+use parking_lot::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+const MAP_ENTRY_SIZE: usize = size_of::<PageId>() + size_of::<u16>() + size_of::<u16>();
+const ENTRIES_PER_MAP_PAGE: usize = PAGE_CHECKSUM_OFFSET / MAP_ENTRY_SIZE;
+
+const DIRECTORY_HEADER_SIZE: usize = size_of::<u32>();
+const MAX_MAP_PAGES: usize = (PAGE_CHECKSUM_OFFSET - DIRECTORY_HEADER_SIZE) / size_of::<PageId>();
+
+/// Where the data of a logical page is stored
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PageLocation {
+    /// The page is not allocated
+    Free,
+
+    /// The page is allocated but was never written, it reads as zeros
+    Empty,
+
+    /// The page data is stored in `length` bytes from `offset` of the physical page,
+    /// a page that does not compress is stored as is in a physical page of its own
+    Stored {
+        physical_page_id: PageId,
+        offset: u16,
+        length: u16,
+    },
+}
+
+impl PageLocation {
+    fn is_uncompressed(length: u16) -> bool {
+        length as usize == PAGE_CHECKSUM_OFFSET
+    }
+
+    /// Zero filled entry is a free page, so a new map page has all of its pages free
+    fn write_to(&self, entry: &mut [u8]) {
+        let (physical_page_id, offset, length) = match *self {
+            PageLocation::Free => (0, 0, 0),
+            PageLocation::Empty => (INVALID_PAGE_ID, 0, 0),
+            PageLocation::Stored { physical_page_id, offset, length } => (physical_page_id, offset, length),
+        };
+
+        entry[0..4].copy_from_slice(&physical_page_id.to_le_bytes());
+        entry[4..6].copy_from_slice(&offset.to_le_bytes());
+        entry[6..8].copy_from_slice(&length.to_le_bytes());
+    }
+
+    fn read_from(entry: &[u8]) -> Self {
+        let physical_page_id = PageId::from_le_bytes(entry[0..4].try_into().unwrap());
+        let offset = u16::from_le_bytes(entry[4..6].try_into().unwrap());
+        let length = u16::from_le_bytes(entry[6..8].try_into().unwrap());
+
+        match (physical_page_id, length) {
+            (INVALID_PAGE_ID, 0) => PageLocation::Empty,
+            (_, 0) => PageLocation::Free,
+            _ => PageLocation::Stored { physical_page_id, offset, length },
+        }
+    }
+}
+
+/// Physical page that compressed pages are appended to
+struct OpenPage {
+    page_id: PageId,
+
+    // Kept in memory so appending does not need to read the page
+    data: PageData,
+
+    used: usize,
+}
+
+struct CompressionData {
+    // Location of each logical page by its page id
+    locations: Vec<PageLocation>,
+
+    // Free logical pages before the end of the locations
+    free_page_ids: BTreeSet<PageId>,
+
+    // Number of bytes of each physical data page that are still referenced by a logical page
+    live_bytes: HashMap<PageId, usize>,
+
+    open_page: Option<OpenPage>,
+
+    // The physical pages of the map, each one holds the locations of `ENTRIES_PER_MAP_PAGE` logical pages
+    map_page_ids: Vec<PageId>,
+
+    // Physical page listing the map pages, stored as a system root
+    directory_page_id: PageId,
+}
+
+impl CompressionData {
+    fn get_location(&self, page_id: PageId) -> PageLocation {
+        if page_id < 0 {
+            return PageLocation::Free;
+        }
+
+        self.locations.get(page_id as usize).copied().unwrap_or(PageLocation::Free)
+    }
+
+    fn add_live_bytes(&mut self, location: PageLocation) {
+        if let PageLocation::Stored { physical_page_id, length, .. } = location {
+            *self.live_bytes.entry(physical_page_id).or_default() += length as usize;
+        }
+    }
+}
+
+/**
+ * DiskManager wrapper that compresses the pages before writing them and decompresses them on read.
+ *
+ * Logical pages (the page ids the buffer pool sees) are mapped to variable length extents in the physical pages
+ * of the inner disk manager, compressed pages are appended to the current open physical page so a physical page
+ * holds multiple logical pages. Rewriting a page appends it again, the physical page is deallocated once none of its
+ * extents is referenced.
+ *
+ * The map is persisted in physical pages as well, it is written on every change so it is up to date with the data pages,
+ * the map directory is stored as the `PageCompressionMap` system root.
+ */
+pub struct DiskManagerCompression<D: DiskManager> {
+    inner: Arc<D>,
+
+    data: Mutex<CompressionData>,
+}
+
+impl<D: DiskManager> DiskManagerCompression<D> {
+    pub fn new(inner: D) -> Result<Self, DiskError> {
+        Self::with_arc(Arc::new(inner))
+    }
+
+    /// Load the map from the inner disk manager, a disk manager without a map has no pages
+    pub fn with_arc(inner: Arc<D>) -> Result<Self, DiskError> {
+        let mut data = CompressionData {
+            locations: vec![],
+            free_page_ids: BTreeSet::new(),
+            live_bytes: HashMap::new(),
+            open_page: None,
+            map_page_ids: vec![],
+            directory_page_id: inner.get_system_root(SystemRoot::PageCompressionMap),
+        };
+
+        if data.directory_page_id != INVALID_PAGE_ID {
+            let mut page: PageData = [0u8; PAGE_SIZE];
+            inner.read_page(data.directory_page_id, &mut page)?;
+
+            let num_map_pages = u32::from_le_bytes(page[0..DIRECTORY_HEADER_SIZE].try_into().unwrap()) as usize;
+            data.map_page_ids = page[DIRECTORY_HEADER_SIZE..]
+                .chunks_exact(size_of::<PageId>())
+                .take(num_map_pages)
+                .map(|bytes| PageId::from_le_bytes(bytes.try_into().unwrap()))
+                .collect();
+
+            for &map_page_id in &data.map_page_ids {
+                inner.read_page(map_page_id, &mut page)?;
+
+                data.locations.extend(page.chunks_exact(MAP_ENTRY_SIZE).take(ENTRIES_PER_MAP_PAGE).map(PageLocation::read_from));
+            }
+
+            while data.locations.last() == Some(&PageLocation::Free) {
+                data.locations.pop();
+            }
+
+            for (page_id, location) in data.locations.clone().into_iter().enumerate() {
+                match location {
+                    PageLocation::Free => {
+                        data.free_page_ids.insert(page_id as PageId);
+                    }
+                    _ => data.add_live_bytes(location),
+                }
+            }
+        }
+
+        Ok(DiskManagerCompression {
+            inner,
+            data: Mutex::new(data),
+        })
+    }
+
+    pub fn get_inner(&self) -> &Arc<D> {
+        &self.inner
+    }
+
+    /// @return the number of physical pages that hold the data of the logical pages
+    pub fn get_num_data_pages(&self) -> usize {
+        self.data.lock().live_bytes.len()
+    }
+
+    /// Write the data to a physical page
+    ///
+    /// @return the location of the data
+    fn store(&self, data: &mut CompressionData, page_data: &[u8]) -> Result<PageLocation, DiskError> {
+        let mut compressed = vec![0u8; lz4_flex::block::get_maximum_output_size(PAGE_CHECKSUM_OFFSET)];
+
+        let length = match lz4_flex::block::compress_into(&page_data[..PAGE_CHECKSUM_OFFSET], &mut compressed) {
+            Ok(length) if length < PAGE_CHECKSUM_OFFSET => length,
+
+            // Does not fit in the usable size, so it is not worth compressing
+            _ => {
+                let physical_page_id = self.inner.allocate_page()?;
+
+                let mut page: PageData = [0u8; PAGE_SIZE];
+                page[..PAGE_CHECKSUM_OFFSET].copy_from_slice(&page_data[..PAGE_CHECKSUM_OFFSET]);
+
+                if let Err(err) = self.inner.write_page(physical_page_id, &page) {
+                    self.inner.deallocate_page(physical_page_id)?;
+
+                    return Err(err);
+                }
+
+                let location = PageLocation::Stored {
+                    physical_page_id,
+                    offset: 0,
+                    length: PAGE_CHECKSUM_OFFSET as u16,
+                };
+                data.add_live_bytes(location);
+
+                return Ok(location);
+            }
+        };
+
+        if data.open_page.as_ref().is_none_or(|open_page| open_page.used + length > PAGE_CHECKSUM_OFFSET) {
+            let page_id = self.inner.allocate_page()?;
+
+            if let Some(previous) = data.open_page.replace(OpenPage { page_id, data: [0u8; PAGE_SIZE], used: 0 }) {
+                self.release_if_unused(data, previous.page_id)?;
+            }
+        }
+
+        let open_page = data.open_page.as_mut().expect("open page must exist");
+        let offset = open_page.used;
+
+        open_page.data[offset..offset + length].copy_from_slice(&compressed[..length]);
+
+        // The page is only used after a successful write, so a failed write is overridden by the next append
+        self.inner.write_page(open_page.page_id, &open_page.data)?;
+        open_page.used += length;
+
+        let location = PageLocation::Stored {
+            physical_page_id: open_page.page_id,
+            offset: offset as u16,
+            length: length as u16,
+        };
+        data.add_live_bytes(location);
+
+        Ok(location)
+    }
+
+    /// Release the extent of the location, deallocating the physical page when it has no more referenced extents
+    fn release(&self, data: &mut CompressionData, location: PageLocation) -> Result<(), DiskError> {
+        let PageLocation::Stored { physical_page_id, length, .. } = location else {
+            return Ok(());
+        };
+
+        let live_bytes = data.live_bytes.get_mut(&physical_page_id).expect("stored page must have live bytes");
+        *live_bytes -= length as usize;
+
+        self.release_if_unused(data, physical_page_id)
+    }
+
+    fn release_if_unused(&self, data: &mut CompressionData, physical_page_id: PageId) -> Result<(), DiskError> {
+        // The open page will be filled with more pages
+        if data.open_page.as_ref().is_some_and(|open_page| open_page.page_id == physical_page_id) {
+            return Ok(());
+        }
+
+        if data.live_bytes.get(&physical_page_id).is_some_and(|&live_bytes| live_bytes > 0) {
+            return Ok(());
+        }
+
+        data.live_bytes.remove(&physical_page_id);
+
+        self.inner.deallocate_page(physical_page_id)
+    }
+
+    /// Update the location of the logical page and persist the map page that holds it
+    fn set_location(&self, data: &mut CompressionData, page_id: PageId, location: PageLocation) -> Result<(), DiskError> {
+        let index = page_id as usize;
+        let map_page_index = index / ENTRIES_PER_MAP_PAGE;
+
+        if map_page_index >= MAX_MAP_PAGES {
+            return Err(DiskError::CompressionMapFull { page_id });
+        }
+
+        while data.map_page_ids.len() <= map_page_index {
+            self.add_map_page(data)?;
+        }
+
+        let previous = data.get_location(page_id);
+
+        if index >= data.locations.len() {
+            data.free_page_ids.extend(data.locations.len() as PageId..page_id);
+            data.locations.resize(index + 1, PageLocation::Free);
+        }
+
+        data.locations[index] = location;
+
+        if let Err(err) = self.write_map_page(data, map_page_index) {
+            data.locations[index] = previous;
+
+            return Err(err);
+        }
+
+        if location == PageLocation::Free {
+            data.free_page_ids.insert(page_id);
+        } else {
+            data.free_page_ids.remove(&page_id);
+        }
+
+        Ok(())
+    }
+
+    fn write_map_page(&self, data: &CompressionData, map_page_index: usize) -> Result<(), DiskError> {
+        let mut page: PageData = [0u8; PAGE_SIZE];
+
+        let start = map_page_index * ENTRIES_PER_MAP_PAGE;
+        let locations = data.locations.iter().skip(start).take(ENTRIES_PER_MAP_PAGE);
+
+        for (location, entry) in locations.zip(page.chunks_exact_mut(MAP_ENTRY_SIZE)) {
+            location.write_to(entry);
+        }
+
+        self.inner.write_page(data.map_page_ids[map_page_index], &page)
+    }
+
+    fn add_map_page(&self, data: &mut CompressionData) -> Result<(), DiskError> {
+        if data.directory_page_id == INVALID_PAGE_ID {
+            data.directory_page_id = self.inner.allocate_page()?;
+        }
+
+        let map_page_id = self.inner.allocate_page()?;
+
+        // All the pages of a new map page are free
+        self.inner.write_page(map_page_id, &[0u8; PAGE_SIZE])?;
+
+        data.map_page_ids.push(map_page_id);
+
+        let mut page: PageData = [0u8; PAGE_SIZE];
+        page[0..DIRECTORY_HEADER_SIZE].copy_from_slice(&(data.map_page_ids.len() as u32).to_le_bytes());
+
+        for (map_page_id, bytes) in data.map_page_ids.iter().zip(page[DIRECTORY_HEADER_SIZE..].chunks_exact_mut(size_of::<PageId>())) {
+            bytes.copy_from_slice(&map_page_id.to_le_bytes());
+        }
+
+        let result = self.inner.write_page(data.directory_page_id, &page)
+            .and_then(|_| self.inner.set_system_root(SystemRoot::PageCompressionMap, data.directory_page_id));
+
+        if let Err(err) = result {
+            data.map_page_ids.pop();
+            self.inner.deallocate_page(map_page_id)?;
+
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
+
+impl<D: DiskManager> DiskManager for DiskManagerCompression<D> {
+    fn shut_down(&mut self) {}
+
+    /**
+     * Write a page to the database file.
+     * @param page_id id of the page
+     * @param page_data raw page data
+     *
+     * The data is written before the map, so on failure the page keeps its previous data
+     */
+    fn write_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
+        let mut data = self.data.lock();
+
+        let previous = data.get_location(page_id);
+        let location = self.store(&mut data, page_data)?;
+
+        if let Err(err) = self.set_location(&mut data, page_id, location) {
+            self.release(&mut data, location)?;
+
+            return Err(err);
+        }
+
+        self.release(&mut data, previous)
+    }
+
+    /**
+     * Read a page from the database file.
+     * @param page_id id of the page
+     * @param[out] page_data output buffer
+     *
+     * Page that was never written reads as zeros, like in the inner disk manager
+     */
+    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+        let data = self.data.lock();
+
+        let PageLocation::Stored { physical_page_id, offset, length } = data.get_location(page_id) else {
+            page_data[..PAGE_SIZE].fill(0);
+
+            return Ok(());
+        };
+
+        let mut physical_page: PageData = [0u8; PAGE_SIZE];
+
+        match &data.open_page {
+            Some(open_page) if open_page.page_id == physical_page_id => physical_page.copy_from_slice(&open_page.data),
+            _ => self.inner.read_page(physical_page_id, &mut physical_page)?,
+        }
+
+        let extent = &physical_page[offset as usize..offset as usize + length as usize];
+
+        if PageLocation::is_uncompressed(length) {
+            page_data[..PAGE_CHECKSUM_OFFSET].copy_from_slice(extent);
+        } else {
+            let decompressed = lz4_flex::block::decompress_into(extent, &mut page_data[..PAGE_CHECKSUM_OFFSET]);
+
+            if !matches!(decompressed, Ok(PAGE_CHECKSUM_OFFSET)) {
+                return Err(DiskError::PageDecompressionFailed { page_id });
+            }
+        }
+
+        page_data[PAGE_CHECKSUM_OFFSET..PAGE_SIZE].fill(0);
+
+        Ok(())
+    }
+
+    /**
+     * Allocate a page, reusing a deallocated page if available.
+     * @return the id of the allocated page
+     *
+     * Take the lowest free logical page or add a page after the last one, the physical pages are allocated on write
+     */
+    fn allocate_page(&self) -> Result<PageId, DiskError> {
+        let mut data = self.data.lock();
+
+        let page_id = data.free_page_ids.first().copied().unwrap_or(data.locations.len() as PageId);
+
+        self.set_location(&mut data, page_id, PageLocation::Empty)?;
+
+        Ok(page_id)
+    }
+
+    /**
+     * Deallocate a page so it can be reused by a later allocation.
+     * @param page_id id of the page to deallocate
+     *
+     * Deallocating a page that is already free or was never allocated does nothing
+     */
+    fn deallocate_page(&self, page_id: PageId) -> Result<(), DiskError> {
+        let mut data = self.data.lock();
+
+        let previous = data.get_location(page_id);
+
+        if previous == PageLocation::Free {
+            return Ok(());
+        }
+
+        self.set_location(&mut data, page_id, PageLocation::Free)?;
+
+        self.release(&mut data, previous)
+    }
+
+    fn is_page_allocated(&self, page_id: PageId) -> bool {
+        self.data.lock().get_location(page_id) != PageLocation::Free
+    }
+
+    fn get_system_root(&self, root: SystemRoot) -> PageId {
+        self.inner.get_system_root(root)
+    }
+
+    fn set_system_root(&self, root: SystemRoot, page_id: PageId) -> Result<(), DiskError> {
+        self.inner.set_system_root(root, page_id)
+    }
+
+    fn get_checkpoint_offset(&self) -> Option<u64> {
+        self.inner.get_checkpoint_offset()
+    }
+
+    fn set_checkpoint_offset(&self, offset: u64) -> Result<(), DiskError> {
+        self.inner.set_checkpoint_offset(offset)
+    }
+
+    fn write_log(&self, log_data: &[u8], size: i32) -> Result<(), DiskError> {
+        self.inner.write_log(log_data, size)
+    }
+
+    fn read_log(&self, log_data: &mut [u8], size: i32, offset: i32) -> Result<bool, DiskError> {
+        self.inner.read_log(log_data, size, offset)
+    }
+
+    fn truncate_log(&self, size: u64) -> Result<(), DiskError> {
+        self.inner.truncate_log(size)
+    }
+
+    fn sync(&self) -> Result<(), DiskError> {
+        self.inner.sync()
+    }
+
+    fn get_num_flushes(&self) -> i32 {
+        self.inner.get_num_flushes()
+    }
+
+    fn get_flush_state(&self) -> bool {
+        self.inner.get_flush_state()
+    }
+
+    fn get_num_writes(&self) -> i32 {
+        self.inner.get_num_writes()
+    }
+
+    /// The inner disk manager must not be shared, as it is the one checking the future
+    fn set_flush_log_future(&mut self, f: Option<Future<()>>) {
+        Arc::get_mut(&mut self.inner)
+            .expect("inner disk manager should not be shared to set the flush log future")
+            .set_flush_log_future(f)
+    }
+
+    fn has_flush_log_future(&self) -> bool {
+        self.inner.has_flush_log_future()
+    }
+}
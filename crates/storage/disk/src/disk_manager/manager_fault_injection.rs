@@ -0,0 +1,334 @@
+use crate::errors::DiskError;
+use crate::{DiskManager, SystemRoot};
+use common::Future;
+use pages::{PageData, PageId, PAGE_SIZE};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fault to inject into a disk operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiskFault {
+    /// Fail the read with an I/O error, the output buffer is not modified
+    FailRead,
+
+    /// Fail the write with an I/O error, nothing is written
+    FailWrite,
+
+    /// Only the first `bytes` of the stored page (after the inner disk manager checksummed it) are written over the
+    /// previous stored page, the write reports success and the torn page is durable
+    TornWrite { bytes: usize },
+
+    /// Report the write as successful without writing anything
+    DropWrite,
+}
+
+impl DiskFault {
+    fn is_read_fault(&self) -> bool {
+        matches!(self, DiskFault::FailRead)
+    }
+}
+
+/// When and how many times a fault is injected
+///
+/// By default the fault is injected once, on the next matching operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultRule {
+    fault: DiskFault,
+    page_id: Option<PageId>,
+    skip: usize,
+    times: usize,
+}
+
+impl FaultRule {
+    pub fn new(fault: DiskFault) -> Self {
+        FaultRule {
+            fault,
+            page_id: None,
+            skip: 0,
+            times: 1,
+        }
+    }
+
+    /// Only match operations on that page
+    pub fn for_page(mut self, page_id: PageId) -> Self {
+        self.page_id = Some(page_id);
+        self
+    }
+
+    /// Let the first `skip` matching operations succeed before injecting the fault
+    pub fn after(mut self, skip: usize) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    /// Inject the fault on `times` matching operations
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = times;
+        self
+    }
+
+    /// Inject the fault on every matching operation until the faults are cleared
+    pub fn always(self) -> Self {
+        self.times(usize::MAX)
+    }
+
+    fn matches(&self, page_id: PageId, is_read: bool) -> bool {
+        self.fault.is_read_fault() == is_read && self.page_id.is_none_or(|id| id == page_id)
+    }
+}
+
+struct FaultInjectionData {
+    rules: Vec<FaultRule>,
+
+    // Pages that were written but not synced yet, those are lost on crash
+    unsynced_pages: HashMap<PageId, PageData>,
+
+    num_injected_faults: usize,
+}
+
+impl FaultInjectionData {
+    /// Find the fault to inject for the operation and consume it
+    fn next_fault(&mut self, page_id: PageId, is_read: bool) -> Option<DiskFault> {
+        let mut fault = None;
+
+        for rule in self.rules.iter_mut().filter(|rule| rule.matches(page_id, is_read)) {
+            if rule.skip > 0 {
+                rule.skip -= 1;
+                continue;
+            }
+
+            // Only a single fault per operation, but the other rules still see the operation for their skip count
+            if fault.is_none() {
+                fault = Some(rule.fault.clone());
+                rule.times -= 1;
+            }
+        }
+
+        self.rules.retain(|rule| rule.times > 0);
+
+        if fault.is_some() {
+            self.num_injected_faults += 1;
+        }
+
+        fault
+    }
+}
+
+fn injected_error(message: &str) -> DiskError {
+    io::Error::other(message.to_string()).into()
+}
+
+/**
+ * DiskManager wrapper that injects faults on a schedule for deterministic failure tests.
+ *
+ * Written pages are kept in memory (like the OS page cache) until `sync` is called,
+ * which writes them to the inner disk manager, `crash` discards everything that was not synced.
+ * Torn writes are written to the inner disk manager right away as a crash would leave them.
+ * Log writes are passed directly to the inner disk manager, which syncs them before returning, so `crash` does not discard them.
+ */
+pub struct DiskManagerFaultInjection<D: DiskManager> {
+    inner: Arc<D>,
+
+    data: Mutex<FaultInjectionData>,
+
+    // Checked by this wrapper as the inner disk manager can be shared
+    flush_log_f: Option<Future<()>>,
+}
+
+impl<D: DiskManager> DiskManagerFaultInjection<D> {
+    pub fn new(inner: D) -> Self {
+        Self::with_arc(Arc::new(inner))
+    }
+
+    pub fn with_arc(inner: Arc<D>) -> Self {
+        DiskManagerFaultInjection {
+            inner,
+            data: Mutex::new(FaultInjectionData {
+                rules: vec![],
+                unsynced_pages: HashMap::new(),
+                num_injected_faults: 0,
+            }),
+            flush_log_f: None,
+        }
+    }
+
+    pub fn get_inner(&self) -> &Arc<D> {
+        &self.inner
+    }
+
+    pub fn add_fault(&self, rule: FaultRule) {
+        self.data.lock().rules.push(rule);
+    }
+
+    pub fn clear_faults(&self) {
+        self.data.lock().rules.clear();
+    }
+
+    /// @return the number of faults injected so far
+    pub fn get_num_injected_faults(&self) -> usize {
+        self.data.lock().num_injected_faults
+    }
+
+    /// @return the number of pages that would be lost on crash
+    pub fn get_num_unsynced_pages(&self) -> usize {
+        self.data.lock().unsynced_pages.len()
+    }
+
+    /// Simulate a crash, discard all the pages that were written since the last sync
+    pub fn crash(&self) {
+        self.data.lock().unsynced_pages.clear();
+    }
+}
+
+impl<D: DiskManager> DiskManagerFaultInjection<D> {
+    /// Write only the first `bytes` of the page as it is stored by the inner disk manager (including the checksum trailer)
+    /// over the previous stored page, like a crash in the middle of writing back the page.
+    ///
+    /// The torn page is written to the inner disk manager right away, so it is not discarded on crash
+    fn tear_page(&self, data: &mut FaultInjectionData, page_id: PageId, page_data: &[u8], bytes: usize) -> Result<(), DiskError> {
+        // The previous write of the page must be on disk for the tear to land over it
+        if let Some(page) = data.unsynced_pages.remove(&page_id) {
+            self.inner.write_page(page_id, &page)?;
+        }
+
+        let mut torn_page = [0u8; PAGE_SIZE];
+        self.inner.read_raw_page(page_id, &mut torn_page)?;
+
+        // Let the inner disk manager checksum the complete page and tear what it stored
+        let mut written_page = [0u8; PAGE_SIZE];
+        self.inner.write_page(page_id, page_data)?;
+        self.inner.read_raw_page(page_id, &mut written_page)?;
+
+        torn_page[..bytes].copy_from_slice(&written_page[..bytes]);
+
+        self.inner.write_raw_page(page_id, &torn_page)
+    }
+}
+
+impl<D: DiskManager> DiskManager for DiskManagerFaultInjection<D> {
+    /// No-op, the inner disk manager can be shared so it is shut down by its owner
+    fn shut_down(&mut self) {}
+
+    fn write_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
+        let mut data = self.data.lock();
+
+        match data.next_fault(page_id, false) {
+            None => {}
+            Some(DiskFault::FailWrite) => return Err(injected_error("injected write failure")),
+            Some(DiskFault::DropWrite) => return Ok(()),
+            Some(DiskFault::TornWrite { bytes }) => return self.tear_page(&mut data, page_id, page_data, bytes.min(PAGE_SIZE)),
+            Some(DiskFault::FailRead) => unreachable!("read fault should not match a write"),
+        }
+
+        let mut page = [0u8; PAGE_SIZE];
+        page.copy_from_slice(&page_data[..PAGE_SIZE]);
+        data.unsynced_pages.insert(page_id, page);
+
+        Ok(())
+    }
+
+    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+        let mut data = self.data.lock();
+
+        if let Some(fault) = data.next_fault(page_id, true) {
+            assert_eq!(fault, DiskFault::FailRead);
+            return Err(injected_error("injected read failure"));
+        }
+
+        match data.unsynced_pages.get(&page_id) {
+            Some(page) => {
+                page_data[..PAGE_SIZE].copy_from_slice(page);
+                Ok(())
+            }
+            None => self.inner.read_page(page_id, page_data),
+        }
+    }
+
+    fn allocate_page(&self) -> Result<PageId, DiskError> {
+        self.inner.allocate_page()
+    }
+
+    fn deallocate_page(&self, page_id: PageId) -> Result<(), DiskError> {
+        let mut data = self.data.lock();
+
+        // Deallocation is durable so the page must not be resurrected by the next sync
+        data.unsynced_pages.remove(&page_id);
+
+        self.inner.deallocate_page(page_id)
+    }
+
+    fn is_page_allocated(&self, page_id: PageId) -> bool {
+        self.inner.is_page_allocated(page_id)
+    }
+
+    fn get_system_root(&self, root: SystemRoot) -> PageId {
+        self.inner.get_system_root(root)
+    }
+
+    fn set_system_root(&self, root: SystemRoot, page_id: PageId) -> Result<(), DiskError> {
+        self.inner.set_system_root(root, page_id)
+    }
+
+    fn get_checkpoint_offset(&self) -> Option<u64> {
+        self.inner.get_checkpoint_offset()
+    }
+
+    fn set_checkpoint_offset(&self, offset: u64) -> Result<(), DiskError> {
+        self.inner.set_checkpoint_offset(offset)
+    }
+
+    fn write_log(&self, log_data: &[u8], size: i32) -> Result<(), DiskError> {
+        if let Some(flush_log_f) = &self.flush_log_f {
+            // used for checking non-blocking flushing
+            assert!(flush_log_f.wait_for(Duration::from_secs(10)), "log write should be issued after the flush log future is ready");
+        }
+
+        self.inner.write_log(log_data, size)
+    }
+
+    fn read_log(&self, log_data: &mut [u8], size: i32, offset: i32) -> Result<bool, DiskError> {
+        self.inner.read_log(log_data, size, offset)
+    }
+
+    fn truncate_log(&self, size: u64) -> Result<(), DiskError> {
+        self.inner.truncate_log(size)
+    }
+
+    fn sync(&self) -> Result<(), DiskError> {
+        let mut data = self.data.lock();
+
+        // Pages are removed only once they were written so a failed sync does not lose them
+        let mut page_ids = data.unsynced_pages.keys().cloned().collect::<Vec<_>>();
+        page_ids.sort();
+
+        for page_id in page_ids {
+            self.inner.write_page(page_id, &data.unsynced_pages[&page_id])?;
+            data.unsynced_pages.remove(&page_id);
+        }
+
+        self.inner.sync()
+    }
+
+    fn get_num_flushes(&self) -> i32 {
+        self.inner.get_num_flushes()
+    }
+
+    fn get_flush_state(&self) -> bool {
+        self.inner.get_flush_state()
+    }
+
+    fn get_num_writes(&self) -> i32 {
+        self.inner.get_num_writes()
+    }
+
+    fn set_flush_log_future(&mut self, f: Option<Future<()>>) {
+        self.flush_log_f = f;
+    }
+
+    fn has_flush_log_future(&self) -> bool {
+        self.flush_log_f.is_some()
+    }
+}
@@ -0,0 +1,415 @@
+use pages::{PageId, PAGE_SIZE};
+use common::Future;
+use memmap2::MmapMut;
+use parking_lot::RwLock;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::time::Duration;
+use error_utils::anyhow::anyhow;
+use error_utils::ToAnyhowResult;
+use super::utils::get_file_size;
+use super::checksum::{stamp_checksum, verify_and_clear_checksum};
+use super::free_space_map::FreeSpaceMap;
+use super::manager::{page_offset, read_superblock, write_superblock};
+use super::superblock::{Superblock, SystemRoot};
+use crate::DiskManager;
+use crate::errors::DiskError;
+
+/// The mapping grows by at least this number of pages so allocating pages one by one does not remap every time
+const MIN_GROWTH_PAGES: u64 = 64;
+
+/**
+ * DiskManager that maps the database file to memory and serves the page reads and writes by copying from and to the mapping.
+ *
+ * The file layout is the same as `DefaultDiskManager` (superblock, page checksums and free space map),
+ * so a database file can be opened by either of them.
+ * Writes are in the OS page cache once copied, `sync` flushes the mapping to the disk with msync
+ */
+pub struct DiskManagerMmap {
+    inner: RwLock<InnerDiskManagerMmap>,
+
+    num_flushes: AtomicI32,
+    num_writes: AtomicI32,
+    flush_log: AtomicBool,
+}
+
+struct InnerDiskManagerMmap {
+    log_io: File,
+    log_name: PathBuf,
+
+    db_io: File,
+
+    // Mapping of the whole db file, the file is grown before remapping so the mapping never exceeds the file
+    mmap: MmapMut,
+
+    superblock: Superblock,
+
+    free_space_map: FreeSpaceMap,
+
+    flush_log_f: Option<Future<()>>,
+}
+
+impl DiskManagerMmap {
+    /**
+     * Creates a new disk manager that maps the specified database file.
+     * @param db_file the file name of the database file to map
+     */
+    pub fn new(db_file: PathBuf) -> error_utils::anyhow::Result<DiskManagerMmap> {
+        if db_file.extension().is_none() {
+            return Err(anyhow!("wrong file format"));
+        }
+
+        let mut log_name = db_file.clone();
+        log_name.set_extension("log");
+
+        let log_io = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .append(true)
+            .create(true)
+            .open(&log_name)
+            .to_anyhow()?;
+
+        let mut db_io = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&db_file)
+            .to_anyhow()?;
+
+        let mut free_space_map_name = db_file.clone();
+        free_space_map_name.set_extension("fsm");
+
+        // The superblock is written before mapping, so the mapping is never empty
+        let (superblock, free_space_map) = if db_io.metadata().to_anyhow()?.len() == 0 {
+            let superblock = Superblock::new();
+            write_superblock(&mut db_io, &superblock).to_anyhow()?;
+
+            (superblock, FreeSpaceMap::create(&free_space_map_name).to_anyhow()?)
+        } else {
+            let superblock = read_superblock(&mut db_io)?;
+            let free_space_map = FreeSpaceMap::open(&free_space_map_name, superblock.next_page_id).to_anyhow()?;
+
+            (superblock, free_space_map)
+        };
+
+        // SAFETY: the file is only modified through this mapping while the disk manager is alive
+        let mmap = unsafe { MmapMut::map_mut(&db_io) }.to_anyhow()?;
+
+        Ok(
+            DiskManagerMmap {
+                flush_log: AtomicBool::new(false),
+                num_flushes: AtomicI32::new(0),
+                num_writes: AtomicI32::new(0),
+                inner: RwLock::new(InnerDiskManagerMmap {
+                    log_io,
+                    log_name,
+                    db_io,
+                    mmap,
+                    superblock,
+                    free_space_map,
+                    flush_log_f: None,
+                })
+            }
+        )
+    }
+}
+
+impl InnerDiskManagerMmap {
+    fn page_range(page_id: PageId) -> std::ops::Range<usize> {
+        let offset = page_offset(page_id) as usize;
+
+        offset..offset + PAGE_SIZE
+    }
+
+    /// Grow the file and the mapping so it contains the page
+    fn ensure_mapped(&mut self, page_id: PageId) -> Result<(), DiskError> {
+        let required_len = page_offset(page_id) + PAGE_SIZE as u64;
+        let mapped_len = self.mmap.len() as u64;
+
+        if required_len <= mapped_len {
+            return Ok(());
+        }
+
+        let new_len = required_len.max(mapped_len * 2).max(mapped_len + MIN_GROWTH_PAGES * PAGE_SIZE as u64);
+
+        self.db_io.set_len(new_len)?;
+
+        // SAFETY: the file is only modified through this mapping while the disk manager is alive
+        self.mmap = unsafe { MmapMut::map_mut(&self.db_io) }?;
+
+        Ok(())
+    }
+
+    fn write_superblock(&mut self) {
+        self.mmap[0..PAGE_SIZE].copy_from_slice(&self.superblock.to_page());
+    }
+}
+
+impl DiskManager for DiskManagerMmap {
+    fn shut_down(&mut self) {}
+
+    /**
+     * Write a page to the database file.
+     * @param page_id id of the page
+     * @param page_data raw page data
+     *
+     * Copy the page into the mapping, growing it if the page is after the end of the file,
+     * the page trailer is replaced with the page checksum
+     */
+    fn write_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
+        let mut inner = self.inner.write();
+
+        inner.ensure_mapped(page_id)?;
+
+        self.num_writes.fetch_add(1, Ordering::Relaxed);
+
+        let page = &mut inner.mmap[InnerDiskManagerMmap::page_range(page_id)];
+        page.copy_from_slice(&page_data[0..PAGE_SIZE]);
+        stamp_checksum(page_id, page);
+
+        Ok(())
+    }
+
+    fn write_raw_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
+        let mut inner = self.inner.write();
+
+        inner.ensure_mapped(page_id)?;
+
+        self.num_writes.fetch_add(1, Ordering::Relaxed);
+
+        inner.mmap[InnerDiskManagerMmap::page_range(page_id)].copy_from_slice(&page_data[0..PAGE_SIZE]);
+
+        Ok(())
+    }
+
+    /**
+     * Read a page from the database file.
+     * @param page_id id of the page
+     * @param[out] page_data output buffer
+     *
+     * Copy the page from the mapping and verify the page checksum,
+     * page after the end of the file was never written so it is empty
+     */
+    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+        self.read_raw_page(page_id, page_data)?;
+
+        verify_and_clear_checksum(page_id, &mut page_data[0..PAGE_SIZE])
+    }
+
+    fn read_raw_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+        let inner = self.inner.read();
+
+        let range = InnerDiskManagerMmap::page_range(page_id);
+
+        if range.end > inner.mmap.len() {
+            page_data[0..PAGE_SIZE].fill(0);
+
+            return Ok(());
+        }
+
+        page_data[0..PAGE_SIZE].copy_from_slice(&inner.mmap[range]);
+
+        Ok(())
+    }
+
+    /**
+    * Flush the entire log buffer into disk.
+    * @param log_data raw log data
+    * @param size size of log entry
+    *
+    * The log is not mapped, it is appended to the log file
+     */
+    fn write_log(&self, log_data: &[u8], size: i32) -> Result<(), DiskError> {
+        let mut inner = self.inner.write();
+
+        if size == 0 {  // no effect on num_flushes_ if log buffer is empty
+            return Ok(());
+        }
+
+        self.flush_log.store(true, Ordering::SeqCst);
+
+        if let Some(flush_log_f) = &inner.flush_log_f {
+            // used for checking non-blocking flushing
+            assert!(flush_log_f.wait_for(Duration::from_secs(10)));
+        }
+
+        self.num_flushes.fetch_add(1, Ordering::Relaxed);
+
+        // sequence write
+        let res = inner.log_io.write_all(&log_data[0..size as usize])
+            // needs to flush to keep disk file in sync
+            .and_then(|_| inner.log_io.flush())
+            // the log records are only durable once synced, all the records of the flush share the same sync
+            .and_then(|_| inner.log_io.sync_data());
+
+        self.flush_log.store(false, Ordering::SeqCst);
+
+        Ok(res?)
+    }
+
+    /**
+     * Read a log entry from the log file.
+     * @param[out] log_data output buffer
+     * @param size size of the log entry
+     * @param offset offset of the log entry in the file
+     * @return false means already reach the end
+     */
+    fn read_log(&self, log_data: &mut [u8], size: i32, offset: i32) -> Result<bool, DiskError> {
+        let mut inner = self.inner.write();
+
+        if offset >= get_file_size(inner.log_name.as_path()) {
+            return Ok(false);
+        }
+
+        inner.log_io.seek(SeekFrom::Start(offset as u64))?;
+
+        let read_count = inner.log_io.read(&mut log_data[0..size as usize])?;
+
+        // if log file ends before reading "size"
+        if read_count < size as usize {
+            log_data[read_count..size as usize].fill(0);
+        }
+
+        Ok(true)
+    }
+
+    fn truncate_log(&self, size: u64) -> Result<(), DiskError> {
+        let inner = self.inner.write();
+
+        // The size change is metadata, so the whole file is synced and not only the data
+        inner.log_io.set_len(size)
+            .and_then(|_| inner.log_io.sync_all())?;
+
+        Ok(())
+    }
+
+    /**
+     * Allocate a page, reusing a deallocated page if available.
+     * @return the id of the allocated page
+     *
+     * Take the lowest free page from the free space map or add a page after the last one, growing the mapping if needed
+     */
+    fn allocate_page(&self) -> Result<PageId, DiskError> {
+        let mut inner = self.inner.write();
+
+        if let Some(page_id) = inner.free_space_map.take_free_page()? {
+            return Ok(page_id);
+        }
+
+        let page_id = inner.superblock.next_page_id;
+
+        inner.ensure_mapped(page_id)?;
+
+        inner.superblock.next_page_id += 1;
+        inner.write_superblock();
+
+        Ok(page_id)
+    }
+
+    /**
+     * Deallocate a page so it can be reused by a later allocation.
+     * @param page_id id of the page to deallocate
+     *
+     * Mark the page as free in the free space map and clear it so it reads back empty when reused.
+     * Unlike `DefaultDiskManager` the file is not truncated when the end of the file is free, as the mapping is kept
+     */
+    fn deallocate_page(&self, page_id: PageId) -> Result<(), DiskError> {
+        let mut inner = self.inner.write();
+
+        if page_id < 0 || page_id >= inner.superblock.next_page_id || inner.free_space_map.is_free(page_id) {
+            return Ok(());
+        }
+
+        inner.free_space_map.mark_free(page_id)?;
+
+        let range = InnerDiskManagerMmap::page_range(page_id);
+        if range.end <= inner.mmap.len() {
+            inner.mmap[range].fill(0);
+        }
+
+        if page_id == inner.superblock.next_page_id - 1 {
+            let mut next_page_id = page_id;
+            while next_page_id > 0 && inner.free_space_map.is_free(next_page_id - 1) {
+                next_page_id -= 1;
+            }
+
+            // Superblock first, free pages after the next page id are ignored when the file is opened
+            inner.superblock.next_page_id = next_page_id;
+            inner.write_superblock();
+            inner.free_space_map.remove_from(next_page_id)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_page_allocated(&self, page_id: PageId) -> bool {
+        let inner = self.inner.read();
+
+        page_id >= 0 && page_id < inner.superblock.next_page_id && !inner.free_space_map.is_free(page_id)
+    }
+
+    fn get_system_root(&self, root: SystemRoot) -> PageId {
+        self.inner.read().superblock.get_root(root)
+    }
+
+    fn set_system_root(&self, root: SystemRoot, page_id: PageId) -> Result<(), DiskError> {
+        let mut inner = self.inner.write();
+
+        inner.superblock.set_root(root, page_id);
+        inner.write_superblock();
+
+        Ok(())
+    }
+
+    fn get_checkpoint_offset(&self) -> Option<u64> {
+        self.inner.read().superblock.checkpoint_offset
+    }
+
+    /**
+     * Store the checkpoint offset in the superblock and flush the superblock page of the mapping
+     */
+    fn set_checkpoint_offset(&self, offset: u64) -> Result<(), DiskError> {
+        let mut inner = self.inner.write();
+
+        inner.superblock.checkpoint_offset = Some(offset);
+        inner.write_superblock();
+
+        Ok(inner.mmap.flush_range(0, PAGE_SIZE)?)
+    }
+
+    /**
+     * Make sure all written pages are durable
+     *
+     * Flush the mapping with msync and sync the free space map
+     */
+    fn sync(&self) -> Result<(), DiskError> {
+        let inner = self.inner.read();
+
+        inner.mmap.flush()?;
+        inner.free_space_map.sync()
+    }
+
+    fn get_num_flushes(&self) -> i32 {
+        self.num_flushes.load(Ordering::Relaxed)
+    }
+
+    fn get_flush_state(&self) -> bool {
+        self.flush_log.load(Ordering::Relaxed)
+    }
+
+    fn get_num_writes(&self) -> i32 {
+        self.num_writes.load(Ordering::Relaxed)
+    }
+
+    fn set_flush_log_future(&mut self, f: Option<Future<()>>) {
+        self.inner.write().flush_log_f = f;
+    }
+
+    fn has_flush_log_future(&self) -> bool {
+        self.inner.read().flush_log_f.is_some()
+    }
+}
@@ -1,8 +1,10 @@
-use crate::DiskManager;
+use crate::{DiskManager, SystemRoot};
+use crate::errors::DiskError;
 use common::Future;
-use pages::{PageData, PageId, PAGE_SIZE};
+use pages::{PageData, PageId, INVALID_PAGE_ID, PAGE_SIZE};
 use parking_lot::{Mutex, MutexGuard};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 use std::thread;
 use std::thread::{sleep, ThreadId};
@@ -27,6 +29,19 @@ struct DiskManagerUnlimitedMemoryData {
     // Vector of protected pages
     // each protected page has a lock to the underlying value
     pages: Vec<ProtectedPage>,
+
+    // Number of allocated pages, including the free pages
+    num_pages: PageId,
+
+    // Deallocated pages to reuse, lowest first
+    free_pages: BTreeSet<PageId>,
+
+    system_roots: HashMap<SystemRoot, PageId>,
+
+    checkpoint_offset: Option<u64>,
+
+    // The log that was written, kept in memory like the pages
+    log: Vec<u8>,
 }
 
 
@@ -65,6 +80,11 @@ impl DiskManagerUnlimitedMemory {
                 DiskManagerUnlimitedMemoryData {
                     pages: vec![],
                     thread_id: None,
+                    num_pages: 0,
+                    free_pages: BTreeSet::new(),
+                    system_roots: HashMap::new(),
+                    checkpoint_offset: None,
+                    log: vec![],
                 }
             ),
         }
@@ -129,7 +149,7 @@ impl DiskManager for DiskManagerUnlimitedMemory {
      * @param page_id id of the page
      * @param page_data raw page data
      */
-    fn write_page(&self, page_id: PageId, page_data: &[u8]) {
+    fn write_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
             self.process_latency(page_id);
 
             let page_ref: Arc<Mutex<Option<Page>>>;
@@ -170,6 +190,8 @@ impl DiskManager for DiskManagerUnlimitedMemory {
             self.post_process_latency(page_id);
 
             // Unlock the single page lock
+
+            Ok(())
     }
 
     /**
@@ -177,11 +199,11 @@ impl DiskManager for DiskManagerUnlimitedMemory {
      * @param page_id id of the page
      * @param[out] page_data output buffer
      */
-    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) {
+    fn read_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
         self.process_latency(page_id);
 
-        let page_ref: Arc<Mutex<Option<Page>>>;
-        let page_lock: MutexGuard<Option<Page>>;
+        let page_ref: Option<Arc<Mutex<Option<Page>>>>;
+        let is_allocated: bool;
 
         {
             let mut data = self.data.lock();
@@ -190,33 +212,128 @@ impl DiskManager for DiskManagerUnlimitedMemory {
                 data.thread_id = Some(thread::current().id());
             }
 
-            if page_id >= data.pages.len() as i32 {
+            is_allocated = page_id >= 0 && page_id < data.num_pages && !data.free_pages.contains(&page_id);
+
+            if page_id >= data.pages.len() as i32 && !is_allocated {
                 panic!("page {} not in range", page_id);
             }
 
-            page_ref = Arc::clone(&data.pages[page_id as usize]);
-            page_lock = page_ref.lock()
+            page_ref = data.pages.get(page_id as usize).map(Arc::clone);
 
             // Page table lock dropped
         }
 
-        if page_lock.is_none() {
-            panic!("page {} not exists", page_id);
-        }
+        let page_lock: Option<MutexGuard<Option<Page>>> = page_ref.as_ref().map(|page_ref| page_ref.lock());
+
+        match page_lock.as_deref() {
+            Some(Some(page)) => page_data[0..PAGE_SIZE].copy_from_slice(page),
+
+            // Page was allocated but never written (e.g. crashed before writing a new page), so it is empty
+            _ if is_allocated => page_data[0..PAGE_SIZE].fill(0),
 
-        page_data[0..PAGE_SIZE].copy_from_slice(&page_lock.unwrap());
+            _ => panic!("page {} not exists", page_id),
+        }
 
         self.post_process_latency(page_id);
 
         // Single page lock dropped
+
+        Ok(())
     }
 
-    fn write_log(&self, _log_data: &[u8], _size: i32) {
-        unimplemented!();
+    fn allocate_page(&self) -> Result<PageId, DiskError> {
+        let mut data = self.data.lock();
+
+        if let Some(page_id) = data.free_pages.pop_first() {
+            return Ok(page_id);
+        }
+
+        let page_id = data.num_pages;
+        data.num_pages += 1;
+
+        Ok(page_id)
     }
 
-    fn read_log(&self, _log_data: &mut [u8], _size: i32, _offset: i32) -> bool {
-        unimplemented!();
+    fn deallocate_page(&self, page_id: PageId) -> Result<(), DiskError> {
+        let mut data = self.data.lock();
+
+        if page_id < 0 || page_id >= data.num_pages || !data.free_pages.insert(page_id) {
+            return Ok(());
+        }
+
+        // Release the page memory, the page is like a newly allocated page when reused
+        if let Some(page) = data.pages.get(page_id as usize) {
+            *page.lock() = None;
+        }
+
+        // Remove the trailing free pages
+        while data.free_pages.last() == Some(&(data.num_pages - 1)) {
+            data.free_pages.pop_last();
+            data.num_pages -= 1;
+        }
+
+        let num_pages = data.num_pages as usize;
+        data.pages.truncate(num_pages);
+
+        Ok(())
+    }
+
+    fn is_page_allocated(&self, page_id: PageId) -> bool {
+        let data = self.data.lock();
+
+        // Reading a page that was never written panics, so it is not considered allocated until written
+        page_id >= 0 && page_id < data.num_pages && !data.free_pages.contains(&page_id) &&
+            data.pages.get(page_id as usize).is_some_and(|page| page.lock().is_some())
+    }
+
+    fn get_system_root(&self, root: SystemRoot) -> PageId {
+        self.data.lock().system_roots.get(&root).cloned().unwrap_or(INVALID_PAGE_ID)
+    }
+
+    fn set_system_root(&self, root: SystemRoot, page_id: PageId) -> Result<(), DiskError> {
+        self.data.lock().system_roots.insert(root, page_id);
+
+        Ok(())
+    }
+
+    fn get_checkpoint_offset(&self) -> Option<u64> {
+        self.data.lock().checkpoint_offset
+    }
+
+    fn set_checkpoint_offset(&self, offset: u64) -> Result<(), DiskError> {
+        self.data.lock().checkpoint_offset = Some(offset);
+
+        Ok(())
+    }
+
+    fn write_log(&self, log_data: &[u8], size: i32) -> Result<(), DiskError> {
+        self.data.lock().log.extend_from_slice(&log_data[..size as usize]);
+
+        Ok(())
+    }
+
+    fn read_log(&self, log_data: &mut [u8], size: i32, offset: i32) -> Result<bool, DiskError> {
+        let data = self.data.lock();
+        let offset = offset as usize;
+        let size = size as usize;
+
+        if offset >= data.log.len() {
+            return Ok(false);
+        }
+
+        let read_count = size.min(data.log.len() - offset);
+        log_data[..read_count].copy_from_slice(&data.log[offset..offset + read_count]);
+
+        // Same as the file log, the rest is zeroed if the log ends before reading `size`
+        log_data[read_count..size].fill(0);
+
+        Ok(true)
+    }
+
+    fn truncate_log(&self, size: u64) -> Result<(), DiskError> {
+        self.data.lock().log.truncate(size as usize);
+
+        Ok(())
     }
 
     fn get_num_flushes(&self) -> i32 {
@@ -2,8 +2,20 @@ mod disk_manager_trait;
 mod tests;
 mod manager;
 mod utils;
+mod checksum;
+mod free_space_map;
+mod superblock;
 mod manager_unlimited_memory;
+mod manager_fault_injection;
+mod manager_compression;
+#[cfg(not(target_arch = "wasm32"))]
+mod manager_mmap;
 
 pub use manager::DefaultDiskManager;
 pub use manager_unlimited_memory::DiskManagerUnlimitedMemory;
+pub use manager_fault_injection::{DiskFault, DiskManagerFaultInjection, FaultRule};
+pub use manager_compression::DiskManagerCompression;
+#[cfg(not(target_arch = "wasm32"))]
+pub use manager_mmap::DiskManagerMmap;
 pub use disk_manager_trait::DiskManager;
+pub use superblock::SystemRoot;
@@ -0,0 +1,134 @@
+use super::checksum::{stamp_checksum, verify_and_clear_checksum};
+use crate::errors::SuperblockError;
+use pages::{PageData, PageId, INVALID_PAGE_ID, PAGE_SIZE};
+
+const MAGIC: &[u8; 8] = b"BUSTUBRS";
+
+/// Bump when the on-disk format changes in a way older builds can't read
+const FORMAT_VERSION: u32 = 3;
+
+/// The superblock has no page id of its own, using an id no data page has so a data page written there fails the checksum
+const SUPERBLOCK_CHECKSUM_PAGE_ID: PageId = INVALID_PAGE_ID;
+
+const NUM_SYSTEM_ROOTS: usize = 8;
+
+/// Stored when no checkpoint was completed, as offset 0 is a valid checkpoint at the start of the log
+const NO_CHECKPOINT_OFFSET: u64 = u64::MAX;
+
+const MAGIC_OFFSET: usize = 0;
+const FORMAT_VERSION_OFFSET: usize = MAGIC_OFFSET + MAGIC.len();
+const PAGE_SIZE_OFFSET: usize = FORMAT_VERSION_OFFSET + size_of::<u32>();
+const NEXT_PAGE_ID_OFFSET: usize = PAGE_SIZE_OFFSET + size_of::<u32>();
+const ROOTS_OFFSET: usize = NEXT_PAGE_ID_OFFSET + size_of::<PageId>();
+const CHECKPOINT_OFFSET_OFFSET: usize = ROOTS_OFFSET + NUM_SYSTEM_ROOTS * size_of::<PageId>();
+
+/// Root pages of system structures that must be found when the database is opened
+///
+/// Each root has a fixed slot in the superblock, so adding a root does not change the format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SystemRoot {
+    Catalog = 0,
+    PageCompressionMap = 1,
+}
+
+/**
+ * Superblock stored in the first page of the database file, before all the data pages.
+ *
+ * The page layout is:
+ * ---------------------------------------------------------------------------------------------
+ * | Magic (8) | FormatVersion (4) | PageSize (4) | NextPageId (4) | Roots (32) | CheckpointOffset (8) | ... | Checksum (4) |
+ * ---------------------------------------------------------------------------------------------
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Superblock {
+    /// The next page id to allocate when there are no free pages, all the pages before it are allocated or free
+    pub(super) next_page_id: PageId,
+
+    roots: [PageId; NUM_SYSTEM_ROOTS],
+
+    /// The offset in the log of the begin checkpoint record of the last completed checkpoint
+    pub(super) checkpoint_offset: Option<u64>,
+}
+
+impl Superblock {
+    pub(super) fn new() -> Self {
+        Superblock {
+            next_page_id: 0,
+            roots: [INVALID_PAGE_ID; NUM_SYSTEM_ROOTS],
+            checkpoint_offset: None,
+        }
+    }
+
+    pub(super) fn get_root(&self, root: SystemRoot) -> PageId {
+        self.roots[root as usize]
+    }
+
+    pub(super) fn set_root(&mut self, root: SystemRoot, page_id: PageId) {
+        self.roots[root as usize] = page_id;
+    }
+
+    pub(super) fn to_page(&self) -> PageData {
+        let mut page = [0u8; PAGE_SIZE];
+
+        page[MAGIC_OFFSET..FORMAT_VERSION_OFFSET].copy_from_slice(MAGIC);
+        page[FORMAT_VERSION_OFFSET..PAGE_SIZE_OFFSET].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        page[PAGE_SIZE_OFFSET..NEXT_PAGE_ID_OFFSET].copy_from_slice(&(PAGE_SIZE as u32).to_le_bytes());
+        page[NEXT_PAGE_ID_OFFSET..ROOTS_OFFSET].copy_from_slice(&self.next_page_id.to_le_bytes());
+
+        for (index, root) in self.roots.iter().enumerate() {
+            let offset = ROOTS_OFFSET + index * size_of::<PageId>();
+            page[offset..offset + size_of::<PageId>()].copy_from_slice(&root.to_le_bytes());
+        }
+
+        let checkpoint_offset = self.checkpoint_offset.unwrap_or(NO_CHECKPOINT_OFFSET);
+        page[CHECKPOINT_OFFSET_OFFSET..CHECKPOINT_OFFSET_OFFSET + size_of::<u64>()].copy_from_slice(&checkpoint_offset.to_le_bytes());
+
+        stamp_checksum(SUPERBLOCK_CHECKSUM_PAGE_ID, &mut page);
+
+        page
+    }
+
+    /// Parse and validate the superblock, the header fields are checked before the checksum
+    /// so a file from another build reports why it is incompatible instead of being corrupted
+    pub(super) fn from_page(page: &PageData) -> Result<Self, SuperblockError> {
+        if &page[MAGIC_OFFSET..FORMAT_VERSION_OFFSET] != MAGIC {
+            return Err(SuperblockError::InvalidMagic);
+        }
+
+        let format_version = read_u32(page, FORMAT_VERSION_OFFSET);
+        if format_version != FORMAT_VERSION {
+            return Err(SuperblockError::UnsupportedFormatVersion {
+                found: format_version,
+                supported: FORMAT_VERSION,
+            });
+        }
+
+        let page_size = read_u32(page, PAGE_SIZE_OFFSET);
+        if page_size != PAGE_SIZE as u32 {
+            return Err(SuperblockError::PageSizeMismatch {
+                found: page_size,
+                expected: PAGE_SIZE as u32,
+            });
+        }
+
+        let mut page = *page;
+        verify_and_clear_checksum(SUPERBLOCK_CHECKSUM_PAGE_ID, &mut page).map_err(SuperblockError::Corrupted)?;
+
+        let mut roots = [INVALID_PAGE_ID; NUM_SYSTEM_ROOTS];
+        for (index, root) in roots.iter_mut().enumerate() {
+            *root = read_u32(&page, ROOTS_OFFSET + index * size_of::<PageId>()) as PageId;
+        }
+
+        let checkpoint_offset = u64::from_le_bytes(page[CHECKPOINT_OFFSET_OFFSET..CHECKPOINT_OFFSET_OFFSET + size_of::<u64>()].try_into().unwrap());
+
+        Ok(Superblock {
+            next_page_id: read_u32(&page, NEXT_PAGE_ID_OFFSET) as PageId,
+            roots,
+            checkpoint_offset: (checkpoint_offset != NO_CHECKPOINT_OFFSET).then_some(checkpoint_offset),
+        })
+    }
+}
+
+fn read_u32(page: &PageData, offset: usize) -> u32 {
+    u32::from_le_bytes(page[offset..offset + size_of::<u32>()].try_into().unwrap())
+}
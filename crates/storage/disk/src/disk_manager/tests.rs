@@ -1,9 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use pages::PAGE_SIZE;
+    use pages::{PageId, INVALID_PAGE_ID, PAGE_CHECKSUM_OFFSET, PAGE_SIZE};
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
     use std::path::PathBuf;
+    use std::sync::Arc;
     use tempdir::TempDir;
     use crate::*;
+    use crate::errors::{DiskError, SuperblockError};
 
     fn setup() -> TempDir {
         TempDir::new("disk_manager_tests").expect("Should create tmp directory")
@@ -20,10 +24,10 @@ mod tests {
         let val = "A test string.";
         data[0..val.len()].copy_from_slice(val.as_bytes());
 
-        dm.read_page(0, &mut buf);  // tolerate empty read
+        dm.read_page(0, &mut buf).expect("tolerate empty read");
 
-        dm.write_page(0, &data);
-        dm.read_page(0, &mut buf);
+        dm.write_page(0, &data).expect("should write page");
+        dm.read_page(0, &mut buf).expect("should read page");
 
         // EXPECT_EQ(std::memcmp(buf, data, sizeof(buf)), 0);
         assert_eq!(buf, data);
@@ -31,8 +35,8 @@ mod tests {
         // std::memset(buf, 0, sizeof(buf));
         buf.fill(0);
 
-        dm.write_page(5, &data);
-        dm.read_page(5, &mut buf);
+        dm.write_page(5, &data).expect("should write page");
+        dm.read_page(5, &mut buf).expect("should read page");
 
         // EXPECT_EQ(std::memcmp(buf, data, sizeof(buf)), 0);
         assert_eq!(buf, data);
@@ -41,6 +45,114 @@ mod tests {
     }
 
 
+    #[test]
+    fn read_page_with_flipped_bit_should_return_corruption_error() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let mut data = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let mut dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+        let val = "A test string.";
+        data[0..val.len()].copy_from_slice(val.as_bytes());
+
+        dm.write_page(0, &data).expect("should write page");
+        dm.write_page(1, &data).expect("should write page");
+
+        // Flip a single bit in the middle of page 1, the first page in the file is the superblock
+        {
+            let mut file = OpenOptions::new().write(true).open(&db_file).expect("Should open db file");
+            file.seek(SeekFrom::Start((2 * PAGE_SIZE + 100) as u64)).expect("Should seek");
+            file.write_all(&[1u8]).expect("Should write");
+        }
+
+        dm.read_page(0, &mut buf).expect("page 0 was not modified");
+        assert_eq!(buf, data);
+
+        let err = dm.read_page(1, &mut buf).expect_err("page 1 is corrupted");
+        assert!(matches!(err, DiskError::PageCorrupted { page_id: 1, .. }), "got {:?}", err);
+
+        dm.shut_down();
+    }
+
+    #[test]
+    fn read_page_written_to_another_location_should_return_corruption_error() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let mut data = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let mut dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+        let val = "A test string.";
+        data[0..val.len()].copy_from_slice(val.as_bytes());
+
+        dm.write_page(0, &data).expect("should write page");
+        dm.read_page(0, &mut buf).expect("should read page");
+
+        // Copy the raw page 0 (including its checksum) to the location of page 1
+        {
+            let raw = std::fs::read(&db_file).expect("Should read db file");
+            let mut file = OpenOptions::new().write(true).open(&db_file).expect("Should open db file");
+            file.seek(SeekFrom::Start(2 * PAGE_SIZE as u64)).expect("Should seek");
+            file.write_all(&raw[PAGE_SIZE..2 * PAGE_SIZE]).expect("Should write");
+        }
+
+        let err = dm.read_page(1, &mut buf).expect_err("page 1 contains page 0 data");
+        assert!(matches!(err, DiskError::PageCorrupted { page_id: 1, .. }), "got {:?}", err);
+
+        dm.shut_down();
+    }
+
+    #[test]
+    fn read_page_cut_by_the_end_of_the_file_should_return_truncated_error() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let mut data = [0u8; PAGE_SIZE];
+        data[0..PAGE_CHECKSUM_OFFSET].fill(3);
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let mut dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+
+        dm.write_page(0, &data).expect("should write page");
+        dm.write_page(1, &data).expect("should write page");
+
+        // Cut page 1 in the middle, the first page in the file is the superblock
+        {
+            let file = OpenOptions::new().write(true).open(&db_file).expect("Should open db file");
+            file.set_len((2 * PAGE_SIZE + 100) as u64).expect("Should truncate");
+        }
+
+        dm.read_page(0, &mut buf).expect("page 0 is complete");
+        assert_eq!(buf, data);
+
+        let err = dm.read_page(1, &mut buf).expect_err("page 1 is truncated");
+        assert_eq!(err, DiskError::PageTruncated { page_id: 1, read: 100 });
+
+        // Pages after the end of the file were never written
+        dm.read_page(2, &mut buf).expect("page 2 was never written");
+        assert_eq!(buf, [0u8; PAGE_SIZE]);
+
+        dm.shut_down();
+    }
+
+    #[test]
+    fn read_page_should_not_expose_checksum_trailer() {
+        let mut buf = [1u8; PAGE_SIZE];
+        let mut data = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let mut dm = DefaultDiskManager::new(db_file).expect("Should create disk manager");
+        data[0..PAGE_CHECKSUM_OFFSET].fill(7);
+
+        dm.write_page(0, &data).expect("should write page");
+        dm.read_page(0, &mut buf).expect("should read page");
+
+        assert_eq!(buf, data);
+
+        dm.shut_down();
+    }
+
     #[test]
     fn read_write_log() {
         const BUF_SIZE: usize = 16;
@@ -54,10 +166,10 @@ mod tests {
         let val = "A test string.";
         data[0..val.len()].copy_from_slice(val.as_bytes());
 
-        dm.read_log(&mut buf, BUF_SIZE as i32, 0);  // tolerate empty read
+        assert_eq!(dm.read_log(&mut buf, BUF_SIZE as i32, 0), Ok(false), "tolerate empty read");
 
-        dm.write_log(&data, BUF_SIZE as i32);
-        dm.read_log(&mut buf, BUF_SIZE as i32, 0);
+        dm.write_log(&data, BUF_SIZE as i32).expect("should write log");
+        assert_eq!(dm.read_log(&mut buf, BUF_SIZE as i32, 0), Ok(true));
 
         // EXPECT_EQ(std::memcmp(buf, data, sizeof(buf)), 0);
         assert_eq!(buf, data);
@@ -65,6 +177,44 @@ mod tests {
         dm.shut_down();
     }
 
+    #[test]
+    fn should_append_log_after_the_truncated_size() {
+        const BUF_SIZE: usize = 16;
+        let mut buf = [1u8; BUF_SIZE];
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let dm = DefaultDiskManager::new(db_file).expect("Should create disk manager");
+
+        dm.write_log(b"first torn", 10).expect("should write log");
+        dm.truncate_log(6).expect("should truncate log");
+        dm.write_log(b"second", 6).expect("should write log");
+
+        assert_eq!(dm.read_log(&mut buf, BUF_SIZE as i32, 0), Ok(true));
+        assert_eq!(&buf[..12], b"first second");
+        assert_eq!(&buf[12..], &[0u8; 4], "should not keep the truncated log");
+    }
+
+    #[test]
+    fn read_write_log_unlimited_memory() {
+        const BUF_SIZE: usize = 16;
+        let mut buf = [1u8; BUF_SIZE];
+
+        let dm = DiskManagerUnlimitedMemory::new();
+
+        assert_eq!(dm.read_log(&mut buf, BUF_SIZE as i32, 0), Ok(false), "tolerate empty read");
+
+        dm.write_log(b"first ", 6).expect("should write log");
+        dm.write_log(b"second", 6).expect("should write log");
+
+        assert_eq!(dm.read_log(&mut buf, BUF_SIZE as i32, 0), Ok(true));
+        assert_eq!(&buf[..12], b"first second");
+        assert_eq!(&buf[12..], &[0u8; 4], "should zero the rest when the log ends");
+
+        assert_eq!(dm.read_log(&mut buf, 6, 6), Ok(true));
+        assert_eq!(&buf[..6], b"second");
+    }
+
     #[test]
     fn bad_file() {
         let p = PathBuf::from("dev/null\\/foo/bar/baz/test.db");
@@ -83,8 +233,8 @@ mod tests {
         let val = "A test string.";
         data[0..val.len()].copy_from_slice(val.as_bytes());
 
-        dm.write_page(0, &data);
-        dm.read_page(0, &mut buf);
+        dm.write_page(0, &data).expect("should write page");
+        dm.read_page(0, &mut buf).expect("should read page");
 
         // EXPECT_EQ(std::memcmp(buf, data, sizeof(buf)), 0);
         assert_eq!(buf, data);
@@ -92,12 +242,493 @@ mod tests {
         // std::memset(buf, 0, sizeof(buf));
         buf.fill(0);
 
-        dm.write_page(5, &data);
-        dm.read_page(5, &mut buf);
+        dm.write_page(5, &data).expect("should write page");
+        dm.read_page(5, &mut buf).expect("should read page");
 
         // EXPECT_EQ(std::memcmp(buf, data, sizeof(buf)), 0);
         assert_eq!(buf, data);
 
         dm.shut_down();
     }
+
+    #[test]
+    fn deallocated_pages_should_be_reused_and_persisted() {
+        let mut buf = [1u8; PAGE_SIZE];
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+
+        {
+            let dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+
+            for expected_page_id in 0..4 {
+                assert_eq!(dm.allocate_page(), Ok(expected_page_id));
+                dm.write_page(expected_page_id, &page_with(expected_page_id as u8 + 1)).expect("should write page");
+            }
+
+            dm.deallocate_page(2).expect("should deallocate page");
+            dm.deallocate_page(1).expect("should deallocate page");
+
+            dm.read_page(1, &mut buf).expect("should read page");
+            assert_eq!(buf, [0u8; PAGE_SIZE], "deallocated page should be cleared");
+
+            assert_eq!(dm.allocate_page(), Ok(1), "lowest free page should be reused first");
+        }
+
+        let dm = DefaultDiskManager::new(db_file).expect("Should reopen disk manager");
+
+        assert_eq!(dm.allocate_page(), Ok(2), "free page should be persisted");
+        assert_eq!(dm.allocate_page(), Ok(4), "allocated pages should be persisted");
+    }
+
+    #[test]
+    fn deallocating_the_last_pages_should_truncate_the_file() {
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+
+        for page_id in 0..4 {
+            assert_eq!(dm.allocate_page(), Ok(page_id));
+            dm.write_page(page_id, &page_with(1)).expect("should write page");
+        }
+
+        dm.deallocate_page(2).expect("should deallocate page");
+        // The superblock is the first page of the file
+        assert_eq!(db_file.metadata().unwrap().len(), 5 * PAGE_SIZE as u64, "page in the middle should not truncate");
+
+        dm.deallocate_page(3).expect("should deallocate page");
+        assert_eq!(db_file.metadata().unwrap().len(), 3 * PAGE_SIZE as u64, "all the trailing free pages should be truncated");
+
+        assert_eq!(dm.allocate_page(), Ok(2));
+        assert_eq!(dm.allocate_page(), Ok(3));
+    }
+
+    #[test]
+    fn missing_free_space_map_should_keep_pages_allocated() {
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+
+        {
+            let dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+
+            for page_id in 0..3 {
+                assert_eq!(dm.allocate_page(), Ok(page_id));
+            }
+
+            dm.deallocate_page(1).expect("should deallocate page");
+        }
+
+        std::fs::remove_file(db_file.with_extension("fsm")).expect("should remove free space map");
+
+        // The free page is leaked, but no allocated page is reused
+        let dm = DefaultDiskManager::new(db_file).expect("Should reopen disk manager");
+        assert_eq!(dm.allocate_page(), Ok(3));
+    }
+
+    #[test]
+    fn superblock_should_persist_system_roots() {
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+
+        {
+            let dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+            assert_eq!(dm.get_system_root(SystemRoot::Catalog), INVALID_PAGE_ID);
+
+            dm.set_system_root(SystemRoot::Catalog, 5).expect("should set system root");
+        }
+
+        let dm = DefaultDiskManager::new(db_file).expect("Should reopen disk manager");
+        assert_eq!(dm.get_system_root(SystemRoot::Catalog), 5);
+    }
+
+    #[test]
+    fn superblock_should_persist_checkpoint_offset() {
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+
+        {
+            let dm = DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+            assert_eq!(dm.get_checkpoint_offset(), None);
+
+            // Offset 0 is a checkpoint at the start of the log
+            dm.set_checkpoint_offset(0).expect("should set checkpoint offset");
+            assert_eq!(dm.get_checkpoint_offset(), Some(0));
+
+            // Larger than a page id
+            dm.set_checkpoint_offset(u32::MAX as u64 + 1).expect("should set checkpoint offset");
+        }
+
+        let dm = DefaultDiskManager::new(db_file).expect("Should reopen disk manager");
+        assert_eq!(dm.get_checkpoint_offset(), Some(u32::MAX as u64 + 1));
+    }
+
+    fn open_error(db_file: PathBuf) -> SuperblockError {
+        let err = DefaultDiskManager::new(db_file).err().expect("Should refuse to open the file");
+
+        err.downcast_ref::<SuperblockError>().cloned().expect("Should be superblock error")
+    }
+
+    #[test]
+    fn should_refuse_file_without_superblock() {
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        std::fs::write(&db_file, "not a database file").expect("Should write file");
+
+        assert_eq!(open_error(db_file), SuperblockError::InvalidMagic);
+    }
+
+    fn create_db_and_patch_superblock(offset: usize, bytes: &[u8]) -> (TempDir, PathBuf) {
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager");
+
+        let mut file = OpenOptions::new().write(true).open(&db_file).expect("Should open db file");
+        file.seek(SeekFrom::Start(offset as u64)).expect("Should seek");
+        file.write_all(bytes).expect("Should write");
+
+        (tmp_dir, db_file)
+    }
+
+    #[test]
+    fn should_refuse_file_with_different_format_version() {
+        let (_tmp_dir, db_file) = create_db_and_patch_superblock(8, &2u32.to_le_bytes());
+
+        assert_eq!(open_error(db_file), SuperblockError::UnsupportedFormatVersion { found: 2, supported: 3 });
+    }
+
+    #[test]
+    fn should_refuse_file_with_different_page_size() {
+        let (_tmp_dir, db_file) = create_db_and_patch_superblock(12, &8192u32.to_le_bytes());
+
+        assert_eq!(open_error(db_file), SuperblockError::PageSizeMismatch { found: 8192, expected: PAGE_SIZE as u32 });
+    }
+
+    #[test]
+    fn should_refuse_file_with_corrupted_superblock() {
+        let (_tmp_dir, db_file) = create_db_and_patch_superblock(16, &[0xFF]);
+
+        assert!(matches!(open_error(db_file), SuperblockError::Corrupted(DiskError::PageCorrupted { .. })));
+    }
+
+    #[test]
+    fn deallocated_pages_should_be_reused_unlimited_memory() {
+        let dm = DiskManagerUnlimitedMemory::new();
+
+        for page_id in 0..3 {
+            assert_eq!(dm.allocate_page(), Ok(page_id));
+        }
+
+        dm.deallocate_page(0).expect("should deallocate page");
+        dm.deallocate_page(2).expect("should deallocate page");
+
+        assert_eq!(dm.allocate_page(), Ok(0));
+        assert_eq!(dm.allocate_page(), Ok(2));
+        assert_eq!(dm.allocate_page(), Ok(3));
+    }
+
+    fn page_with(value: u8) -> [u8; PAGE_SIZE] {
+        [value; PAGE_SIZE]
+    }
+
+    #[test]
+    fn fault_injection_should_fail_scheduled_operations() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let dm = DiskManagerFaultInjection::new(DiskManagerUnlimitedMemory::new());
+
+        dm.add_fault(FaultRule::new(DiskFault::FailWrite).after(1));
+        dm.add_fault(FaultRule::new(DiskFault::FailRead).for_page(1).times(2));
+
+        dm.write_page(0, &page_with(1)).expect("first write should not fail");
+        assert!(matches!(dm.write_page(1, &page_with(2)), Err(DiskError::Io { .. })));
+        dm.write_page(1, &page_with(3)).expect("fault should only be injected once");
+
+        dm.read_page(0, &mut buf).expect("read of other page should not fail");
+        assert_eq!(buf, page_with(1));
+
+        assert!(matches!(dm.read_page(1, &mut buf), Err(DiskError::Io { .. })));
+        assert!(matches!(dm.read_page(1, &mut buf), Err(DiskError::Io { .. })));
+        assert_eq!(buf, page_with(1), "failed read should not modify the buffer");
+
+        dm.read_page(1, &mut buf).expect("faults should be exhausted");
+        assert_eq!(buf, page_with(3));
+
+        assert_eq!(dm.get_num_injected_faults(), 3);
+    }
+
+    #[test]
+    fn fault_injection_should_tear_and_drop_writes() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let dm = DiskManagerFaultInjection::new(DiskManagerUnlimitedMemory::new());
+
+        dm.write_page(0, &page_with(1)).expect("should write page");
+        dm.write_page(1, &page_with(1)).expect("should write page");
+
+        dm.add_fault(FaultRule::new(DiskFault::TornWrite { bytes: 100 }).for_page(0));
+        dm.add_fault(FaultRule::new(DiskFault::DropWrite).for_page(1));
+
+        dm.write_page(0, &page_with(2)).expect("torn write should report success");
+        dm.write_page(1, &page_with(2)).expect("dropped write should report success");
+
+        dm.read_page(0, &mut buf).expect("should read page");
+        assert_eq!(buf[..100], page_with(2)[..100]);
+        assert_eq!(buf[100..], page_with(1)[100..]);
+
+        dm.read_page(1, &mut buf).expect("should read page");
+        assert_eq!(buf, page_with(1));
+    }
+
+    #[test]
+    fn torn_write_should_fail_the_page_checksum() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let dm = DiskManagerFaultInjection::new(DefaultDiskManager::new(db_file).expect("Should create disk manager"));
+
+        dm.write_page(0, &page_with(1)).expect("should write page");
+        dm.write_page(1, &page_with(1)).expect("should write page");
+        dm.sync().expect("should sync");
+
+        dm.add_fault(FaultRule::new(DiskFault::TornWrite { bytes: 100 }).for_page(0));
+
+        dm.write_page(0, &page_with(2)).expect("torn write should report success");
+        dm.write_page(1, &page_with(2)).expect("should write page");
+        dm.sync().expect("should sync");
+
+        assert!(matches!(dm.read_page(0, &mut buf), Err(DiskError::PageCorrupted { page_id: 0, .. })));
+
+        dm.crash();
+        assert!(matches!(dm.read_page(0, &mut buf), Err(DiskError::PageCorrupted { page_id: 0, .. })), "torn page should be durable");
+
+        dm.read_page(1, &mut buf).expect("page that was not torn should be valid");
+        assert_eq!(buf[..PAGE_CHECKSUM_OFFSET], page_with(2)[..PAGE_CHECKSUM_OFFSET]);
+    }
+
+    #[test]
+    fn fault_injection_crash_should_discard_unsynced_writes() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+
+        let db_file = tmp_dir.path().join("test.db");
+        let dm = DiskManagerFaultInjection::new(DefaultDiskManager::new(db_file).expect("Should create disk manager"));
+
+        dm.write_page(0, &page_with(1)).expect("should write page");
+        dm.sync().expect("should sync");
+        assert_eq!(dm.get_num_unsynced_pages(), 0);
+
+        dm.write_page(0, &page_with(2)).expect("should write page");
+        dm.write_page(1, &page_with(2)).expect("should write page");
+        assert_eq!(dm.get_num_unsynced_pages(), 2);
+
+        dm.read_page(0, &mut buf).expect("should read page");
+        assert_eq!(buf[..PAGE_CHECKSUM_OFFSET], page_with(2)[..PAGE_CHECKSUM_OFFSET], "unsynced writes should be visible before crash");
+
+        dm.crash();
+
+        dm.read_page(0, &mut buf).expect("should read page");
+        assert_eq!(buf[..PAGE_CHECKSUM_OFFSET], page_with(1)[..PAGE_CHECKSUM_OFFSET]);
+
+        dm.read_page(1, &mut buf).expect("should read page");
+        assert_eq!(buf, [0u8; PAGE_SIZE], "page never synced should be lost");
+    }
+
+    #[test]
+    fn fault_injection_should_check_the_flush_log_future_with_a_shared_inner_disk_manager() {
+        let tmp_dir = setup();
+        let db_file = tmp_dir.path().join("test.db");
+
+        let inner = Arc::new(DefaultDiskManager::new(db_file).expect("Should create disk manager"));
+        let mut dm = DiskManagerFaultInjection::with_arc(inner.clone());
+        assert!(!dm.has_flush_log_future());
+
+        let promise = common::Promise::new();
+        dm.set_flush_log_future(Some(promise.get_future()));
+        assert!(dm.has_flush_log_future());
+        assert!(!inner.has_flush_log_future(), "the shared inner disk manager should not be changed");
+
+        promise.set_value(());
+        dm.write_log(b"log record", 10).expect("should write log after the flush log future is ready");
+        assert_eq!(inner.get_num_flushes(), 1);
+    }
+
+    /// Page with repetitive rows and zero filled free space, like a table page
+    fn repetitive_page(page_id: PageId) -> [u8; PAGE_SIZE] {
+        let mut page = [0u8; PAGE_SIZE];
+        let rows = format!("row of page {} with some repeated varchar value;", page_id).repeat(30);
+        page[..rows.len()].copy_from_slice(rows.as_bytes());
+
+        page
+    }
+
+    /// Page that does not compress
+    fn random_page(seed: u32) -> [u8; PAGE_SIZE] {
+        let mut page = [0u8; PAGE_SIZE];
+        let mut state = seed;
+
+        for byte in page[..PAGE_CHECKSUM_OFFSET].iter_mut() {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            *byte = (state >> 24) as u8;
+        }
+
+        page
+    }
+
+    #[test]
+    fn compression_should_pack_pages_and_persist_the_map() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+        let db_file = tmp_dir.path().join("test.db");
+        let number_of_pages = 100;
+
+        {
+            let dm = DiskManagerCompression::new(DefaultDiskManager::new(db_file.clone()).expect("Should create disk manager"))
+                .expect("should create compression disk manager");
+
+            for page_id in 0..number_of_pages {
+                assert_eq!(dm.allocate_page(), Ok(page_id));
+                dm.write_page(page_id, &repetitive_page(page_id)).expect("should write page");
+            }
+
+            assert!(dm.get_num_data_pages() < number_of_pages as usize / 10, "got {} data pages", dm.get_num_data_pages());
+        }
+
+        let file_size = std::fs::metadata(&db_file).expect("should get file size").len();
+        assert!(file_size < (number_of_pages as u64 / 5) * PAGE_SIZE as u64, "file size is {}", file_size);
+
+        let dm = DiskManagerCompression::new(DefaultDiskManager::new(db_file).expect("Should reopen disk manager"))
+            .expect("should load compression map");
+
+        for page_id in 0..number_of_pages {
+            assert!(dm.is_page_allocated(page_id));
+
+            dm.read_page(page_id, &mut buf).expect("should read page");
+            assert_eq!(buf, repetitive_page(page_id));
+        }
+
+        assert!(!dm.is_page_allocated(number_of_pages));
+        assert_eq!(dm.allocate_page(), Ok(number_of_pages));
+    }
+
+    #[test]
+    fn compression_should_release_overwritten_and_deallocated_pages() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let dm = DiskManagerCompression::new(DiskManagerUnlimitedMemory::new()).expect("should create compression disk manager");
+
+        for page_id in 0..3 {
+            assert_eq!(dm.allocate_page(), Ok(page_id));
+
+            dm.read_page(page_id, &mut buf).expect("should read page that was never written");
+            assert_eq!(buf, [0u8; PAGE_SIZE]);
+        }
+
+        // Each incompressible page is stored in a physical page of its own
+        for page_id in 0..3 {
+            dm.write_page(page_id, &random_page(page_id as u32)).expect("should write page");
+        }
+        assert_eq!(dm.get_num_data_pages(), 3);
+
+        for page_id in 0..3 {
+            dm.read_page(page_id, &mut buf).expect("should read page");
+            assert_eq!(buf, random_page(page_id as u32));
+        }
+
+        for page_id in 0..3 {
+            dm.write_page(page_id, &repetitive_page(page_id)).expect("should write page");
+        }
+        assert_eq!(dm.get_num_data_pages(), 1, "overwritten pages should be released");
+
+        dm.deallocate_page(1).expect("should deallocate page");
+        assert!(!dm.is_page_allocated(1));
+
+        dm.read_page(1, &mut buf).expect("should read deallocated page");
+        assert_eq!(buf, [0u8; PAGE_SIZE]);
+
+        assert_eq!(dm.allocate_page(), Ok(1));
+        assert_eq!(dm.allocate_page(), Ok(3));
+
+        for page_id in [0, 2] {
+            dm.read_page(page_id, &mut buf).expect("should read page");
+            assert_eq!(buf, repetitive_page(page_id));
+        }
+    }
+
+    #[test]
+    fn compression_should_forward_the_flush_log_future() {
+        let tmp_dir = setup();
+        let db_file = tmp_dir.path().join("test.db");
+
+        let mut dm = DiskManagerCompression::new(DefaultDiskManager::new(db_file).expect("Should create disk manager"))
+            .expect("should create compression disk manager");
+        assert!(!dm.has_flush_log_future());
+
+        dm.set_flush_log_future(Some(common::Promise::new().get_future()));
+        assert!(dm.get_inner().has_flush_log_future());
+    }
+
+    #[test]
+    fn mmap_should_read_write_and_grow_the_mapping() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+        let db_file = tmp_dir.path().join("test.db");
+
+        {
+            let dm = DiskManagerMmap::new(db_file.clone()).expect("Should create disk manager");
+
+            dm.read_page(0, &mut buf).expect("tolerate empty read");
+            assert_eq!(buf, [0u8; PAGE_SIZE]);
+
+            // More pages than the initial growth of the mapping
+            for page_id in 0..200 {
+                assert_eq!(dm.allocate_page(), Ok(page_id));
+                dm.write_page(page_id, &repetitive_page(page_id)).expect("should write page");
+            }
+
+            for page_id in 0..200 {
+                dm.read_page(page_id, &mut buf).expect("should read page");
+                assert_eq!(buf, repetitive_page(page_id));
+            }
+
+            dm.deallocate_page(3).expect("should deallocate page");
+            dm.set_system_root(SystemRoot::Catalog, 7).expect("should set system root");
+
+            dm.sync().expect("should sync");
+        }
+
+        // Same file format as the default disk manager
+        let dm = DefaultDiskManager::new(db_file).expect("Should reopen disk manager");
+
+        for page_id in (0..200).filter(|&page_id| page_id != 3) {
+            dm.read_page(page_id, &mut buf).expect("should read page");
+            assert_eq!(buf, repetitive_page(page_id));
+        }
+
+        assert_eq!(dm.get_system_root(SystemRoot::Catalog), 7);
+        assert_eq!(dm.allocate_page(), Ok(3));
+        assert_eq!(dm.allocate_page(), Ok(200));
+    }
+
+    #[test]
+    fn mmap_read_page_with_flipped_bit_should_return_corruption_error() {
+        let mut buf = [0u8; PAGE_SIZE];
+        let tmp_dir = setup();
+        let db_file = tmp_dir.path().join("test.db");
+
+        let dm = DiskManagerMmap::new(db_file.clone()).expect("Should create disk manager");
+        dm.write_page(0, &repetitive_page(0)).expect("should write page");
+        dm.sync().expect("should sync");
+
+        {
+            let mut file = OpenOptions::new().write(true).open(&db_file).expect("should open db file");
+            file.seek(SeekFrom::Start(PAGE_SIZE as u64 + 10)).expect("should seek");
+            file.write_all(&[b'!']).expect("should corrupt page");
+        }
+
+        assert!(matches!(dm.read_page(0, &mut buf), Err(DiskError::PageCorrupted { page_id: 0, .. })));
+    }
 }
@@ -1,5 +1,6 @@
 use common::Promise;
 use pages::{PageData, PageId};
+use crate::errors::{DiskError, WriteAndReadError};
 
 /**
  * @brief Represents a Read request for the DiskManager to execute.
@@ -13,12 +14,12 @@ pub struct ReadDiskRequest<'a> {
     /** ID of the page being read from disk. */
     pub page_id: PageId,
 
-    /** Callback used to signal to the request issuer when the request has been completed. */
-    pub callback: Promise<bool>,
+    /** Callback used to signal to the request issuer when the request has been completed, with the read result. */
+    pub callback: Promise<Result<(), DiskError>>,
 }
 
 impl<'a> ReadDiskRequest<'a> {
-    pub fn new(source_page_id: PageId, dest_data: &'a mut PageData, callback: Promise<bool>) -> Self {
+    pub fn new(source_page_id: PageId, dest_data: &'a mut PageData, callback: Promise<Result<(), DiskError>>) -> Self {
         ReadDiskRequest {
             page_id: source_page_id,
             data: dest_data,
@@ -41,13 +42,13 @@ pub struct WriteDiskRequest<'a> {
     /** ID of the page being written to disk. */
     pub(super) page_id: PageId,
 
-    /** Callback used to signal to the request issuer when the request has been completed. */
-    pub(super) callback: Promise<bool>,
+    /** Callback used to signal to the request issuer when the request has been completed, with the write result. */
+    pub(super) callback: Promise<Result<(), DiskError>>,
 }
 
 
 impl<'a> WriteDiskRequest<'a> {
-    pub fn new(dest_page_id: PageId, source_data: &'a PageData, callback: Promise<bool>) -> Self {
+    pub fn new(dest_page_id: PageId, source_data: &'a PageData, callback: Promise<Result<(), DiskError>>) -> Self {
         WriteDiskRequest {
             page_id: dest_page_id,
             data: source_data,
@@ -59,6 +60,8 @@ impl<'a> WriteDiskRequest<'a> {
 /// Single message for both read and write to the same page data buffer
 ///
 /// First writing the data provided to the dest_page_id and then reading from source_page_id into the data (replacing the content)
+///
+/// The read is only done if the write succeeded, so a failed write does not lose the data
 pub struct WriteAndReadDiskRequest<'a> {
     /// Data to write to disk and to read from disk
     pub data: &'a mut PageData,
@@ -69,12 +72,12 @@ pub struct WriteAndReadDiskRequest<'a> {
     // The ID of the page that after write is going to be read from disk
     pub source_page_id: PageId,
 
-    /** Callback used to signal to the request issuer when the request has been completed. */
-    pub callback: Promise<bool>,
+    /** Callback used to signal to the request issuer when the request has been completed, with the write and read result. */
+    pub callback: Promise<Result<(), WriteAndReadError>>,
 }
 
 impl<'a> WriteAndReadDiskRequest<'a> {
-    pub fn new(dest_page_id: PageId, source_page_id: PageId, data: &'a mut PageData, callback: Promise<bool>) -> Self {
+    pub fn new(dest_page_id: PageId, source_page_id: PageId, data: &'a mut PageData, callback: Promise<Result<(), WriteAndReadError>>) -> Self {
         WriteAndReadDiskRequest {
             dest_page_id,
             source_page_id,
@@ -3,15 +3,24 @@ use std::sync::Arc;
 use crate::disk_scheduler::disk_request::WriteAndReadDiskRequest;
 use crate::disk_scheduler::worker::traits::{DiskSchedulerWorker, DiskSchedulerWorkerMessage};
 use crate::{DiskManager, ReadDiskRequest, WriteDiskRequest};
+use crate::errors::{DiskError, WriteAndReadError};
 use common::{Future, Promise};
 use pages::{PageData, PageId, UnderlyingPage};
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::disk_scheduler::worker::different_thread::DifferentThreadDiskSchedulerWorker;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::disk_scheduler::worker::multi_thread::MultiThreadDiskSchedulerWorker;
+
 #[cfg(target_arch = "wasm32")]
 use crate::disk_scheduler::worker::same_thread::SameThreadDiskScheduler;
 
+#[cfg(target_os = "linux")]
+use crate::disk_scheduler::worker::io_uring::IoUringDiskSchedulerWorker;
+#[cfg(target_os = "linux")]
+use crate::DefaultDiskManager;
+
 /**
  * @brief The DiskScheduler schedules disk read and write operations.
  *
@@ -22,10 +31,13 @@ use crate::disk_scheduler::worker::same_thread::SameThreadDiskScheduler;
 pub struct DiskScheduler {
     /** The background thread responsible for issuing scheduled requests to the disk manager. */
     worker: Box<dyn DiskSchedulerWorker>,
+
+    /** The disk manager, used directly for page allocation which does not go through the worker. */
+    disk_manager: Arc<dyn DiskManager>,
 }
 
 
-type DiskSchedulerPromise = Promise<bool>;
+type DiskSchedulerPromise = Promise<Result<(), DiskError>>;
 
 impl DiskScheduler {
     
@@ -34,14 +46,40 @@ impl DiskScheduler {
 
         let scheduler = DiskScheduler {
             #[cfg(not(target_arch = "wasm32"))]
-            worker: DifferentThreadDiskSchedulerWorker::new(disk_manager).boxed(),
+            worker: DifferentThreadDiskSchedulerWorker::new(disk_manager.clone()).boxed(),
             #[cfg(target_arch = "wasm32")]
-            worker: SameThreadDiskScheduler::new(disk_manager).boxed(),
+            worker: SameThreadDiskScheduler::new(disk_manager.clone()).boxed(),
+            disk_manager,
         };
 
         scheduler
     }
 
+    /// Create a disk scheduler with a pool of `num_workers` threads
+    ///
+    /// Requests are picked in elevator order by page offset, duplicate reads of the same page are merged into a single read
+    /// and writes to the same page that are superseded by a later write are dropped
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_multi_threaded<D: DiskManager + 'static>(disk_manager: Arc<D>, num_workers: usize) -> Self {
+        DiskScheduler {
+            worker: MultiThreadDiskSchedulerWorker::new(disk_manager.clone(), num_workers).boxed(),
+            disk_manager,
+        }
+    }
+
+    /// Create a disk scheduler that submits the scheduled requests in batches to io_uring
+    ///
+    /// `queue_depth` is the size of the io_uring submission queue and the max number of requests executed concurrently
+    ///
+    /// Fails if io_uring is not available (e.g. old kernel or disabled by seccomp)
+    #[cfg(target_os = "linux")]
+    pub fn new_io_uring(disk_manager: Arc<DefaultDiskManager>, queue_depth: u32) -> Result<Self, DiskError> {
+        Ok(DiskScheduler {
+            worker: IoUringDiskSchedulerWorker::new(disk_manager.clone(), queue_depth)?.boxed(),
+            disk_manager,
+        })
+    }
+
     /// Schedule read page from disk
     ///
     /// this is not blocking
@@ -52,8 +90,8 @@ impl DiskScheduler {
     /// this only change the page data and nothing more
     ///
     #[must_use]
-    pub unsafe fn schedule_read_page_from_disk(&mut self, dest: &mut UnderlyingPage) -> Future<bool> {
-        // promise value should be set to true once the request is processed.
+    pub unsafe fn schedule_read_page_from_disk(&self, dest: &mut UnderlyingPage) -> Future<Result<(), DiskError>> {
+        // promise value should be set to the read result once the request is processed.
         let promise = Promise::new();
         let future = promise.get_future();
 
@@ -77,8 +115,8 @@ impl DiskScheduler {
     ///
     /// this only change the page data and nothing more
     ///
-    pub fn read_page_from_disk<'a, R, AfterRequestFn: FnOnce() -> R>(self: Arc<Self>, dest: &mut UnderlyingPage, after_request_fn: AfterRequestFn) -> (bool, R) {
-        // promise value should be set to true once the request is processed.
+    pub fn read_page_from_disk<'a, R, AfterRequestFn: FnOnce() -> R>(self: Arc<Self>, dest: &mut UnderlyingPage, after_request_fn: AfterRequestFn) -> (Result<(), DiskError>, R) {
+        // promise value should be set to the read result once the request is processed.
         let promise = Promise::new();
         let future = promise.get_future();
 
@@ -111,8 +149,8 @@ impl DiskScheduler {
     /// You must not drop the page before calling `.wait` on the result as it will cause undefined behavior
     ///
     #[must_use]
-    pub unsafe fn schedule_write_page_to_disk<'a>(&mut self, page_to_write: &UnderlyingPage) -> Future<bool> {
-        // promise value should be set to true once the request is processed.
+    pub unsafe fn schedule_write_page_to_disk<'a>(&self, page_to_write: &UnderlyingPage) -> Future<Result<(), DiskError>> {
+        // promise value should be set to the write result once the request is processed.
 
         let promise = Promise::new();
         let future = promise.get_future();
@@ -134,8 +172,8 @@ impl DiskScheduler {
     /// Write page to disk
     ///
     /// This block until the page is written after the provided callback is called
-    pub fn write_page_to_disk<'a, R, AfterRequestFn: FnOnce() -> R>(self: Arc<Self>, page_to_write: &UnderlyingPage, after_request_fn: AfterRequestFn) -> (bool, R) {
-        // promise value should be set to true once the request is processed.
+    pub fn write_page_to_disk<'a, R, AfterRequestFn: FnOnce() -> R>(self: Arc<Self>, page_to_write: &UnderlyingPage, after_request_fn: AfterRequestFn) -> (Result<(), DiskError>, R) {
+        // promise value should be set to the write result once the request is processed.
 
         let promise = Promise::new();
         let future = promise.get_future();
@@ -165,9 +203,9 @@ impl DiskScheduler {
     ///
     /// This block until the page is read after the provided callback is called
     ///
-    /// this only change the page data and nothing more
-    pub fn write_and_read_page_from_disk<'a, R, AfterRequestFn: FnOnce() -> R>(self: Arc<Self>, page: &mut UnderlyingPage, page_id_to_read: PageId, after_request_fn: AfterRequestFn) -> (bool, R) {
-        // promise value should be set to true once the request is processed.
+    /// this only change the page data and nothing more, if the write failed the page data is not changed
+    pub fn write_and_read_page_from_disk<'a, R, AfterRequestFn: FnOnce() -> R>(self: Arc<Self>, page: &mut UnderlyingPage, page_id_to_read: PageId, after_request_fn: AfterRequestFn) -> (Result<(), WriteAndReadError>, R) {
+        // promise value should be set to the read result once the request is processed.
         let promise = Promise::new();
         let future = promise.get_future();
 
@@ -194,11 +232,33 @@ impl DiskScheduler {
         (future.wait(), r)
     }
 
+    /// Allocate a page on disk, reusing a deallocated page if available
+    ///
+    /// This is blocking and does not wait for the scheduled requests
+    pub fn allocate_page(&self) -> Result<PageId, DiskError> {
+        self.disk_manager.allocate_page()
+    }
+
+    /// Deallocate a page on disk so it can be reused
+    ///
+    /// This is blocking and does not wait for the scheduled requests,
+    /// the caller must make sure there are no scheduled requests for the page
+    pub fn deallocate_page(&self, page_id: PageId) -> Result<(), DiskError> {
+        self.disk_manager.deallocate_page(page_id)
+    }
+
+    /// Check whether a page is allocated on disk
+    ///
+    /// This is blocking and does not wait for the scheduled requests
+    pub fn is_page_allocated(&self, page_id: PageId) -> bool {
+        self.disk_manager.is_page_allocated(page_id)
+    }
+
     /**
      * @brief Create a Promise object. If you want to implement your own version of promise, you can change this function
      * so that our test cases can use your promise implementation.
      *
-     * @return std::promise<bool>
+     * @return std::promise<Result<(), DiskError>>
      */
     pub fn create_promise(&self) -> DiskSchedulerPromise {
         Promise::new()
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use crate::errors::{DiskError, WriteAndReadError};
     use common::{Future, Promise};
     use pages::{AlignToPageData, Page, PageId};
     use parking_lot::Mutex;
@@ -40,7 +41,7 @@ mod tests {
                 || {},
             );
 
-            assert_eq!(finish_writing_result, (true, ()));
+            assert_eq!(finish_writing_result, (Ok(()), ()));
         }
 
         {
@@ -53,7 +54,7 @@ mod tests {
                 || {},
             );
 
-            assert_eq!(finish_writing_result, true);
+            assert_eq!(finish_writing_result, Ok(()));
         };
 
         assert_eq!(page1.read().get_data(), &page1_string.align_to_page_data());
@@ -64,10 +65,12 @@ mod tests {
             let mut guard = page1.write();
             guard.set_page_id(page2.read().get_page_id());
 
-            disk_scheduler.clone().read_page_from_disk(
+            let (read_result, _) = disk_scheduler.clone().read_page_from_disk(
                 &mut guard,
                 || {},
             );
+
+            assert_eq!(read_result, Ok(()));
         };
 
         assert_eq!(page1.read().get_data(), &page2_string.align_to_page_data());
@@ -104,11 +107,15 @@ mod tests {
                 unimplemented!()
             }
 
-            fn write_log(&self, _log_data: &[u8], _size: i32) {
+            fn write_log(&self, _log_data: &[u8], _size: i32) -> Result<(), DiskError> {
                 unimplemented!()
             }
 
-            fn read_log(&self, _log_data: &mut [u8], _size: i32, _offset: i32) -> bool {
+            fn read_log(&self, _log_data: &mut [u8], _size: i32, _offset: i32) -> Result<bool, DiskError> {
+                unimplemented!()
+            }
+
+            fn truncate_log(&self, _size: u64) -> Result<(), DiskError> {
                 unimplemented!()
             }
 
@@ -132,7 +139,31 @@ mod tests {
                 unimplemented!()
             }
 
-            fn write_page(&self, page_id: PageId, _page_data: &[u8]) {
+            fn allocate_page(&self) -> Result<PageId, DiskError> {
+                unimplemented!()
+            }
+
+            fn deallocate_page(&self, _page_id: PageId) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn get_system_root(&self, _root: SystemRoot) -> PageId {
+                unimplemented!()
+            }
+
+            fn set_system_root(&self, _root: SystemRoot, _page_id: PageId) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn get_checkpoint_offset(&self) -> Option<u64> {
+                unimplemented!()
+            }
+
+            fn set_checkpoint_offset(&self, _offset: u64) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn write_page(&self, page_id: PageId, _page_data: &[u8]) -> Result<(), DiskError> {
                 let mut inner = self.0.lock();
                 inner.order_of_page_id_calls.push(page_id);
                 let promise = Promise::new();
@@ -148,9 +179,11 @@ mod tests {
                 inner.order_of_page_id_finish.push(page_id);
 
                 inner.map.remove(&page_id);
+
+                Ok(())
             }
 
-            fn read_page(&self, page_id: PageId, _page_data: &mut [u8]) {
+            fn read_page(&self, page_id: PageId, _page_data: &mut [u8]) -> Result<(), DiskError> {
                 let mut inner = self.0.lock();
 
                 inner.order_of_page_id_calls.push(page_id);
@@ -166,12 +199,14 @@ mod tests {
 
                 inner.order_of_page_id_finish.push(page_id);
                 inner.map.remove(&page_id);
+
+                Ok(())
             }
         }
 
         let manual_manager = Arc::new(ManualDiskManager::default());
 
-        let mut disk_scheduler = DiskScheduler::new(Arc::clone(&manual_manager));
+        let disk_scheduler = DiskScheduler::new(Arc::clone(&manual_manager));
 
         let page1 = Page::new(0);
         let page2 = Page::new(1);
@@ -179,8 +214,8 @@ mod tests {
         let future1 = unsafe { disk_scheduler.schedule_write_page_to_disk(page1.read().deref()) };
         let future2 = unsafe { disk_scheduler.schedule_read_page_from_disk(&mut page2.write()) };
 
-        assert!(future1.wait());
-        assert!(future2.wait());
+        assert_eq!(future1.wait(), Ok(()));
+        assert_eq!(future2.wait(), Ok(()));
 
         assert_eq!(manual_manager.get_current_queries(), vec![], "everything cleaned up");
         assert_eq!(manual_manager.0.lock().order_of_page_id_calls, vec![0, 1], "called with the next page");
@@ -188,4 +223,352 @@ mod tests {
 
         // dm.shut_down();
     }
+
+    #[test]
+    fn write_and_read_should_not_read_when_write_failed() {
+        let page1_string = "some string to write";
+        let page1 = Page::new(1);
+        {
+            let mut underlying_page_data = page1.write();
+
+            underlying_page_data.get_data_mut()[0..page1_string.len()].copy_from_slice(page1_string.as_bytes());
+        }
+
+        let inner = DiskManagerUnlimitedMemory::new();
+        inner.write_page(2, &"page 2".align_to_page_data()).expect("should write page");
+
+        let disk_manager = DiskManagerFaultInjection::new(inner);
+        disk_manager.add_fault(FaultRule::new(DiskFault::FailWrite).always());
+
+        let disk_scheduler = Arc::new(DiskScheduler::new(Arc::new(disk_manager)));
+
+        let (write_result, _) = disk_scheduler.clone().write_page_to_disk(page1.read().deref(), || {});
+        assert!(matches!(write_result, Err(DiskError::Io { .. })), "got {:?}", write_result);
+
+        let (write_and_read_result, _) = disk_scheduler.clone().write_and_read_page_from_disk(
+            &mut page1.write(),
+            2,
+            || {},
+        );
+
+        assert!(matches!(write_and_read_result, Err(WriteAndReadError::Write(DiskError::Io { .. }))), "got {:?}", write_and_read_result);
+        assert_eq!(page1.read().get_data(), &page1_string.align_to_page_data(), "page data should not be replaced");
+    }
+
+    #[test]
+    fn multi_threaded_should_merge_reads_drop_superseded_writes_and_order_by_page() {
+        // Record the calls and block reading page 0 until the gate is released, so all the other requests are queued
+        #[derive(Default)]
+        struct RecordingDiskManager {
+            inner: DiskManagerUnlimitedMemory,
+            gate: Mutex<()>,
+            calls: Mutex<Vec<(&'static str, PageId)>>,
+        }
+
+        impl DiskManager for RecordingDiskManager {
+            fn shut_down(&mut self) {
+                unimplemented!()
+            }
+
+            fn write_log(&self, _log_data: &[u8], _size: i32) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn read_log(&self, _log_data: &mut [u8], _size: i32, _offset: i32) -> Result<bool, DiskError> {
+                unimplemented!()
+            }
+
+            fn truncate_log(&self, _size: u64) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn get_num_flushes(&self) -> i32 {
+                unimplemented!()
+            }
+
+            fn get_flush_state(&self) -> bool {
+                unimplemented!()
+            }
+
+            fn get_num_writes(&self) -> i32 {
+                unimplemented!()
+            }
+
+            fn set_flush_log_future(&mut self, _f: Option<Future<()>>) {
+                unimplemented!()
+            }
+
+            fn has_flush_log_future(&self) -> bool {
+                unimplemented!()
+            }
+
+            fn allocate_page(&self) -> Result<PageId, DiskError> {
+                unimplemented!()
+            }
+
+            fn deallocate_page(&self, _page_id: PageId) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn get_system_root(&self, _root: SystemRoot) -> PageId {
+                unimplemented!()
+            }
+
+            fn set_system_root(&self, _root: SystemRoot, _page_id: PageId) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn get_checkpoint_offset(&self) -> Option<u64> {
+                unimplemented!()
+            }
+
+            fn set_checkpoint_offset(&self, _offset: u64) -> Result<(), DiskError> {
+                unimplemented!()
+            }
+
+            fn write_page(&self, page_id: PageId, page_data: &[u8]) -> Result<(), DiskError> {
+                self.calls.lock().push(("write", page_id));
+
+                self.inner.write_page(page_id, page_data)
+            }
+
+            fn read_page(&self, page_id: PageId, page_data: &mut [u8]) -> Result<(), DiskError> {
+                if page_id == 0 {
+                    drop(self.gate.lock());
+                }
+
+                self.calls.lock().push(("read", page_id));
+
+                self.inner.read_page(page_id, page_data)
+            }
+        }
+
+        let disk_manager = Arc::new(RecordingDiskManager::default());
+        for page_id in [0, 1, 3, 5] {
+            disk_manager.inner.write_page(page_id, &format!("page {}", page_id).as_str().align_to_page_data()).expect("should write page");
+        }
+
+        let disk_scheduler = DiskScheduler::new_multi_threaded(disk_manager.clone(), 1);
+
+        let gate = disk_manager.gate.lock();
+
+        let blocking_page = Page::new(0);
+        let pages_to_read: Vec<Page> = (0..3).map(|_| Page::new(1)).collect();
+        let pages_to_write: Vec<Page> = (0..3)
+            .map(|i| {
+                let page = Page::new(2);
+                page.write().get_data_mut().copy_from_slice(&format!("write {}", i).as_str().align_to_page_data());
+
+                page
+            })
+            .collect();
+        let after_write_page = Page::new(2);
+        let far_page = Page::new(5);
+        let near_page = Page::new(3);
+
+        let mut blocking_guard = blocking_page.write();
+        let mut read_guards: Vec<_> = pages_to_read.iter().map(|page| page.write()).collect();
+        let write_guards: Vec<_> = pages_to_write.iter().map(|page| page.read()).collect();
+        let mut after_write_guard = after_write_page.write();
+        let mut far_guard = far_page.write();
+        let mut near_guard = near_page.write();
+
+        let mut futures = vec![unsafe { disk_scheduler.schedule_read_page_from_disk(&mut blocking_guard) }];
+        futures.push(unsafe { disk_scheduler.schedule_read_page_from_disk(&mut far_guard) });
+        futures.extend(read_guards.iter_mut().map(|guard| unsafe { disk_scheduler.schedule_read_page_from_disk(guard) }));
+        futures.extend(write_guards.iter().map(|guard| unsafe { disk_scheduler.schedule_write_page_to_disk(guard.deref()) }));
+        futures.push(unsafe { disk_scheduler.schedule_read_page_from_disk(&mut after_write_guard) });
+        futures.push(unsafe { disk_scheduler.schedule_read_page_from_disk(&mut near_guard) });
+
+        drop(gate);
+
+        for future in futures {
+            assert_eq!(future.wait(), Ok(()));
+        }
+
+        drop((blocking_guard, read_guards, write_guards, after_write_guard, far_guard, near_guard));
+
+        assert_eq!(
+            disk_manager.calls.lock().clone(),
+            vec![
+                ("read", 0),
+                ("read", 1),
+                // Only the last write is executed and the read after it is not merged with it
+                ("write", 2),
+                ("read", 3),
+                ("read", 5),
+                // The elevator already passed page 2, so its read is executed after wrapping around
+                ("read", 2),
+            ],
+            "should merge the requests of the same page and execute the pages by order"
+        );
+
+        for page in &pages_to_read {
+            assert_eq!(page.read().get_data(), &"page 1".align_to_page_data());
+        }
+        assert_eq!(after_write_page.read().get_data(), &"write 2".align_to_page_data());
+    }
+
+    #[test]
+    fn multi_threaded_should_keep_order_of_scheduled_requests_on_the_same_page() {
+        let disk_manager = Arc::new(DiskManagerUnlimitedMemory::new());
+        disk_manager.write_page(3, &"page 3".align_to_page_data()).expect("should write page");
+
+        let disk_scheduler = DiskScheduler::new_multi_threaded(disk_manager, 4);
+
+        let pages_to_write: Vec<Page> = (0..20)
+            .map(|i| {
+                let page = Page::new(i % 3);
+                page.write().get_data_mut().copy_from_slice(&format!("write {}", i).as_str().align_to_page_data());
+
+                page
+            })
+            .collect();
+        let pages_to_read: Vec<Page> = (0..3).map(Page::new).collect();
+
+        // Write page 4 and read page 3 into it after all the other writes
+        let write_and_read_page = Page::new(4);
+        write_and_read_page.write().get_data_mut().copy_from_slice(&"page 4".align_to_page_data());
+
+        // Keep the guards until the requests finished so the pages are not changed while in flight
+        let write_guards: Vec<_> = pages_to_write.iter().map(|page| page.read()).collect();
+        let mut read_guards: Vec<_> = pages_to_read.iter().map(|page| page.write()).collect();
+
+        let mut futures: Vec<Future<Result<(), DiskError>>> = write_guards
+            .iter()
+            .map(|guard| unsafe { disk_scheduler.schedule_write_page_to_disk(guard.deref()) })
+            .collect();
+        futures.extend(read_guards.iter_mut().map(|guard| unsafe { disk_scheduler.schedule_read_page_from_disk(guard) }));
+
+        let disk_scheduler = Arc::new(disk_scheduler);
+        let (write_and_read_result, _) = disk_scheduler.clone().write_and_read_page_from_disk(&mut write_and_read_page.write(), 3, || {});
+        assert_eq!(write_and_read_result, Ok(()));
+
+        for future in futures {
+            assert_eq!(future.wait(), Ok(()));
+        }
+
+        drop(write_guards);
+        drop(read_guards);
+
+        // The last write of each page is the one that should be read
+        assert_eq!(pages_to_read[0].read().get_data(), &"write 18".align_to_page_data());
+        assert_eq!(pages_to_read[1].read().get_data(), &"write 19".align_to_page_data());
+        assert_eq!(pages_to_read[2].read().get_data(), &"write 17".align_to_page_data());
+        assert_eq!(write_and_read_page.read().get_data(), &"page 3".align_to_page_data());
+
+        let page_4 = Page::new(4);
+        let (read_result, _) = disk_scheduler.clone().read_page_from_disk(&mut page_4.write(), || {});
+        assert_eq!(read_result, Ok(()));
+        assert_eq!(page_4.read().get_data(), &"page 4".align_to_page_data());
+    }
+
+    #[cfg(target_os = "linux")]
+    fn create_io_uring_disk_scheduler(queue_depth: u32) -> (tempdir::TempDir, DiskScheduler) {
+        let dir = tempdir::TempDir::new("disk_scheduler_tests").expect("Should create tmp directory");
+        let disk_manager = DefaultDiskManager::new(dir.path().join("test.db")).expect("should create disk manager");
+
+        let disk_scheduler = DiskScheduler::new_io_uring(Arc::new(disk_manager), queue_depth).expect("io_uring should be available");
+
+        (dir, disk_scheduler)
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn io_uring_write_and_read_pages() {
+        let (_dir, disk_scheduler) = create_io_uring_disk_scheduler(8);
+        let disk_scheduler = Arc::new(disk_scheduler);
+
+        let page1 = Page::new(1);
+        page1.write().get_data_mut().copy_from_slice(&"page 1".align_to_page_data());
+
+        let page2 = Page::new(2);
+        page2.write().get_data_mut().copy_from_slice(&"page 2".align_to_page_data());
+
+        let (write_result, _) = disk_scheduler.clone().write_page_to_disk(page1.read().deref(), || {});
+        assert_eq!(write_result, Ok(()));
+
+        // write page 2 and read page 1 into page 2 buffer
+        let (write_and_read_result, _) = disk_scheduler.clone().write_and_read_page_from_disk(&mut page2.write(), 1, || {});
+        assert_eq!(write_and_read_result, Ok(()));
+        assert_eq!(page2.read().get_data(), &"page 1".align_to_page_data());
+
+        // Read page 2 from disk into page 1 buffer
+        let (read_result, _) = disk_scheduler.clone().read_page_from_disk(&mut page1.write(), || {});
+        assert_eq!(read_result, Ok(()));
+        assert_eq!(page1.read().get_data(), &"page 1".align_to_page_data());
+
+        let page3 = Page::new(2);
+        let (read_result, _) = disk_scheduler.clone().read_page_from_disk(&mut page3.write(), || {});
+        assert_eq!(read_result, Ok(()));
+        assert_eq!(page3.read().get_data(), &"page 2".align_to_page_data());
+
+        // Page that was never written is empty
+        let page4 = Page::new(10);
+        page4.write().get_data_mut().copy_from_slice(&"not empty".align_to_page_data());
+        let (read_result, _) = disk_scheduler.clone().read_page_from_disk(&mut page4.write(), || {});
+        assert_eq!(read_result, Ok(()));
+        assert_eq!(page4.read().get_data(), &[0u8; pages::PAGE_SIZE]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn io_uring_should_return_truncated_page_error_when_the_file_ends_in_the_middle_of_the_page() {
+        let (dir, disk_scheduler) = create_io_uring_disk_scheduler(8);
+        let disk_scheduler = Arc::new(disk_scheduler);
+
+        let page = Page::new(1);
+        page.write().get_data_mut().copy_from_slice(&"page 1".align_to_page_data());
+
+        let (write_result, _) = disk_scheduler.clone().write_page_to_disk(page.read().deref(), || {});
+        assert_eq!(write_result, Ok(()));
+
+        // Cut the write of the page short
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(dir.path().join("test.db"))
+            .and_then(|file| file.set_len(DefaultDiskManager::get_page_offset(1) + 100))
+            .expect("should truncate the db file");
+
+        let (read_result, _) = disk_scheduler.clone().read_page_from_disk(&mut page.write(), || {});
+        assert_eq!(read_result, Err(DiskError::PageTruncated { page_id: 1, read: 100 }));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn io_uring_should_keep_order_of_scheduled_requests_on_the_same_page() {
+        let (_dir, disk_scheduler) = create_io_uring_disk_scheduler(4);
+
+        let pages_to_write: Vec<Page> = (0..20)
+            .map(|i| {
+                let page = Page::new(i % 3);
+                page.write().get_data_mut().copy_from_slice(&format!("write {}", i).as_str().align_to_page_data());
+
+                page
+            })
+            .collect();
+        let pages_to_read: Vec<Page> = (0..3).map(Page::new).collect();
+
+        // Keep the guards until the requests finished so the pages are not changed while in flight
+        let write_guards: Vec<_> = pages_to_write.iter().map(|page| page.read()).collect();
+        let mut read_guards: Vec<_> = pages_to_read.iter().map(|page| page.write()).collect();
+
+        let mut futures: Vec<Future<Result<(), DiskError>>> = write_guards
+            .iter()
+            .map(|guard| unsafe { disk_scheduler.schedule_write_page_to_disk(guard.deref()) })
+            .collect();
+        futures.extend(read_guards.iter_mut().map(|guard| unsafe { disk_scheduler.schedule_read_page_from_disk(guard) }));
+
+        for future in futures {
+            assert_eq!(future.wait(), Ok(()));
+        }
+
+        drop(write_guards);
+        drop(read_guards);
+
+        // The last write of each page is the one that should be read
+        assert_eq!(pages_to_read[0].read().get_data(), &"write 18".align_to_page_data());
+        assert_eq!(pages_to_read[1].read().get_data(), &"write 19".align_to_page_data());
+        assert_eq!(pages_to_read[2].read().get_data(), &"write 17".align_to_page_data());
+    }
 }
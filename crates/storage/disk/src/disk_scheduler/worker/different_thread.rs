@@ -1,5 +1,6 @@
 use crate::disk_scheduler::worker::traits::{DiskSchedulerWorker, DiskSchedulerWorkerMessage};
 use crate::{DiskManager, DiskRequestType};
+use crate::errors::WriteAndReadError;
 use common::{abort_process_on_panic, Channel};
 use std::sync::Arc;
 use std::thread::{Builder, JoinHandle};
@@ -15,7 +16,7 @@ pub(crate) struct DifferentThreadDiskSchedulerWorker {
 
 // Influenced from
 // https://web.mit.edu/rust-lang_v1.25/arch/amd64_ubuntu1404/share/doc/rust/html/book/second-edition/ch20-06-graceful-shutdown-and-cleanup.html
-impl DiskSchedulerWorker for DifferentThreadDiskSchedulerWorker {
+impl DifferentThreadDiskSchedulerWorker {
     /**
      * TODO(P1): Add implementation
      *
@@ -24,7 +25,7 @@ impl DiskSchedulerWorker for DifferentThreadDiskSchedulerWorker {
      * The background thread needs to process requests while the DiskScheduler exists, i.e., this function should not
      * return until ~DiskScheduler() is called. At that point you need to make sure that the function does return.
      */
-    fn new<D: DiskManager + 'static>(disk_manager: Arc<D>) -> Self  {
+    pub(crate) fn new<D: DiskManager + 'static>(disk_manager: Arc<D>) -> Self  {
 
         // let (sender, receiver) = mpsc::channel();
 
@@ -53,20 +54,20 @@ impl DiskSchedulerWorker for DifferentThreadDiskSchedulerWorker {
 
                     match req {
                         DiskRequestType::Read(req) => {
-                            disk_manager.read_page(req.page_id, req.data.as_mut_slice());
-                            req.callback.set_value(true);
+                            let result = disk_manager.read_page(req.page_id, req.data.as_mut_slice());
+                            req.callback.set_value(result);
                         }
                         DiskRequestType::Write(req) => {
-                            disk_manager.write_page(req.page_id, req.data.as_slice());
-                            req.callback.set_value(true);
+                            let result = disk_manager.write_page(req.page_id, req.data.as_slice());
+                            req.callback.set_value(result);
                         }
                         DiskRequestType::WriteAndRead(req) => {
-                            disk_manager.write_page(req.dest_page_id, req.data.as_slice());
-
-                            // TODO - read page if write was successful
-                            //        as otherwise, if the write failed we will read and lose the data
-                            disk_manager.read_page(req.source_page_id, req.data.as_mut_slice());
-                            req.callback.set_value(true);
+                            // Only read the page if the write was successful
+                            // as otherwise, the read will override the data that failed to be written
+                            let result = disk_manager.write_page(req.dest_page_id, req.data.as_slice())
+                                .map_err(WriteAndReadError::Write)
+                                .and_then(|_| disk_manager.read_page(req.source_page_id, req.data.as_mut_slice()).map_err(WriteAndReadError::Read));
+                            req.callback.set_value(result);
                         }
                     }
                 }
@@ -78,7 +79,9 @@ impl DiskSchedulerWorker for DifferentThreadDiskSchedulerWorker {
             sender: channel,
         }
     }
+}
 
+impl DiskSchedulerWorker for DifferentThreadDiskSchedulerWorker {
     fn send(&self, message: DiskSchedulerWorkerMessage<'static>) {
         self.sender.put(message);
     }
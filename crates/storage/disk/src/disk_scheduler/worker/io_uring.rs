@@ -0,0 +1,463 @@
+use crate::disk_scheduler::disk_request::WriteAndReadDiskRequest;
+use crate::disk_scheduler::worker::traits::{DiskSchedulerWorker, DiskSchedulerWorkerMessage};
+use crate::errors::{DiskError, WriteAndReadError};
+use crate::{DefaultDiskManager, DiskRequestType, ReadDiskRequest, WriteDiskRequest};
+use ::io_uring::{opcode, squeue, types, EnterFlags, IoUring};
+use common::{abort_process_on_panic, Channel};
+use pages::{PageData, PageId, PAGE_SIZE};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::thread::{Builder, JoinHandle};
+
+/// Write and read of the same request are linked, so a request needs at most 2 entries
+const MAX_ENTRIES_PER_REQUEST: usize = 2;
+
+const WRITE_PART: u64 = 0;
+const READ_PART: u64 = 1;
+
+/// Disk scheduler worker that submits the queued requests in batches to io_uring
+///
+/// The requests in a batch are executed concurrently by the kernel, so a request that accesses a page
+/// written by an earlier request in the batch (or writes a page read by it) is deferred to the next batch
+/// to keep the same ordering as executing the requests one by one
+pub(crate) struct IoUringDiskSchedulerWorker {
+    thread: Option<JoinHandle<()>>,
+
+    sender: Arc<Channel<DiskSchedulerWorkerMessage<'static>>>,
+}
+
+impl IoUringDiskSchedulerWorker {
+    /// Create the worker with a ring of `queue_depth` entries, which is also the max batch size
+    pub(crate) fn new(disk_manager: Arc<DefaultDiskManager>, queue_depth: u32) -> Result<Self, DiskError> {
+        let queue_depth = queue_depth.max(MAX_ENTRIES_PER_REQUEST as u32);
+        let ring = IoUring::new(queue_depth)?;
+
+        let channel = Arc::new(Channel::new());
+
+        let receiver = channel.clone();
+        let thread = Builder::new()
+            .name("Disk Scheduler io_uring".to_string())
+            .spawn(move || {
+                abort_process_on_panic();
+
+                let mut worker_loop = IoUringWorkerLoop {
+                    ring,
+                    queue_depth,
+                    fd: types::Fd(disk_manager.get_db_raw_fd()),
+                    disk_manager,
+                    stopped: None,
+                };
+
+                worker_loop.run(&receiver);
+            })
+            .expect("failed to spawn disk scheduler thread");
+
+        Ok(IoUringDiskSchedulerWorker {
+            thread: Some(thread),
+            sender: channel,
+        })
+    }
+}
+
+impl DiskSchedulerWorker for IoUringDiskSchedulerWorker {
+    fn send(&self, message: DiskSchedulerWorkerMessage<'static>) {
+        self.sender.put(message);
+    }
+
+    fn stop(&mut self) {
+        self.sender.put(DiskSchedulerWorkerMessage::Terminate);
+
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+/// Request that was submitted to the ring, waiting for its completions
+enum InFlightRequest {
+    Read(ReadDiskRequest<'static>),
+
+    // The page with the stamped checksum is a copy that must live until the write completes
+    Write(WriteDiskRequest<'static>, Box<PageData>),
+    WriteAndRead(WriteAndReadDiskRequest<'static>, Box<PageData>),
+}
+
+struct InFlight {
+    request: InFlightRequest,
+
+    // None until the entry completed
+    write_result: Option<i32>,
+    read_result: Option<i32>,
+
+    // Bytes of the page read so far, a short read is resubmitted for the rest of the page
+    read_bytes: usize,
+}
+
+impl InFlight {
+    /// @return the page that is read and the buffer it is read into
+    fn read_target(&mut self) -> Option<(PageId, &mut PageData)> {
+        match &mut self.request {
+            InFlightRequest::Read(req) => Some((req.page_id, req.data)),
+            InFlightRequest::Write(..) => None,
+            InFlightRequest::WriteAndRead(req, _) => Some((req.source_page_id, req.data)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Batch {
+    requests: Vec<InFlight>,
+
+    // Pages accessed by the batch, true if the page is written
+    pages: HashMap<PageId, bool>,
+
+    num_entries: usize,
+}
+
+impl Batch {
+    fn conflicts_with(&self, request: &DiskRequestType) -> bool {
+        page_accesses(request)
+            .into_iter()
+            .flatten()
+            .any(|(page_id, is_write)| self.pages.get(&page_id).is_some_and(|&written| written || is_write))
+    }
+}
+
+/// @return the pages the request accesses and whether they are written
+fn page_accesses(request: &DiskRequestType) -> [Option<(PageId, bool)>; 2] {
+    match request {
+        DiskRequestType::Read(req) => [Some((req.page_id, false)), None],
+        DiskRequestType::Write(req) => [Some((req.page_id, true)), None],
+        DiskRequestType::WriteAndRead(req) => [Some((req.dest_page_id, true)), Some((req.source_page_id, false))],
+    }
+}
+
+struct IoUringWorkerLoop {
+    ring: IoUring,
+    queue_depth: u32,
+    disk_manager: Arc<DefaultDiskManager>,
+
+    // The db file of the disk manager, taken once as resubmitting a read is done while holding the db file latch
+    fd: types::Fd,
+
+    // Set when the ring can no longer be used, the worker stops and every request fails with this error
+    stopped: Option<DiskError>,
+}
+
+impl IoUringWorkerLoop {
+    fn run(&mut self, receiver: &Channel<DiskSchedulerWorkerMessage<'static>>) {
+        let mut deferred: Option<DiskRequestType<'static>> = None;
+        let mut terminate = false;
+
+        loop {
+            // 1. Wait for the first request of the batch, unless one was deferred from the previous batch
+            let first = match deferred.take() {
+                Some(request) => request,
+                None if terminate => break,
+                None => match receiver.get() {
+                    DiskSchedulerWorkerMessage::Terminate => break,
+                    DiskSchedulerWorkerMessage::NewJob(request) => request,
+                },
+            };
+
+            let mut batch = Batch::default();
+            self.add_to_batch(&mut batch, first);
+
+            // 2. Add all the queued requests that fit in the ring and does not conflict with the batch
+            while !terminate && batch.num_entries + MAX_ENTRIES_PER_REQUEST <= self.queue_depth as usize {
+                match receiver.try_get() {
+                    None => break,
+                    Some(DiskSchedulerWorkerMessage::Terminate) => terminate = true,
+                    Some(DiskSchedulerWorkerMessage::NewJob(request)) => {
+                        if batch.conflicts_with(&request) {
+                            deferred = Some(request);
+                            break;
+                        }
+
+                        self.add_to_batch(&mut batch, request);
+                    }
+                }
+            }
+
+            // 3. Submit and complete the requests once all their entries completed,
+            //    the requests that did not complete get the submit error
+            let disk_manager = self.disk_manager.clone();
+            let submit_error = disk_manager.with_db_file_latch(|| self.submit_and_wait(&mut batch)).err();
+
+            for in_flight in batch.requests {
+                complete_request(in_flight, &submit_error);
+            }
+
+            if let Some(err) = self.stopped.clone() {
+                fail_requests(deferred.take(), receiver, terminate, err);
+
+                break;
+            }
+        }
+    }
+
+    fn add_to_batch(&mut self, batch: &mut Batch, request: DiskRequestType<'static>) {
+        for (page_id, is_write) in page_accesses(&request).into_iter().flatten() {
+            *batch.pages.entry(page_id).or_insert(false) |= is_write;
+        }
+
+        let index = batch.requests.len() as u64;
+        let fd = self.fd;
+
+        let mut entries = vec![];
+
+        let request = match request {
+            DiskRequestType::Read(req) => {
+                entries.push(read_entry(fd, req.page_id, req.data).user_data(index * 2 + READ_PART));
+
+                InFlightRequest::Read(req)
+            }
+            DiskRequestType::Write(req) => {
+                let page = self.prepare_page_for_write(req.page_id, req.data);
+                entries.push(write_entry(fd, req.page_id, &page).user_data(index * 2 + WRITE_PART));
+
+                InFlightRequest::Write(req, page)
+            }
+            DiskRequestType::WriteAndRead(req) => {
+                let page = self.prepare_page_for_write(req.dest_page_id, req.data);
+
+                // Linked so the read is cancelled if the write failed, as otherwise the read will override the data that failed to be written
+                entries.push(write_entry(fd, req.dest_page_id, &page).user_data(index * 2 + WRITE_PART).flags(squeue::Flags::IO_LINK));
+                entries.push(read_entry(fd, req.source_page_id, req.data).user_data(index * 2 + READ_PART));
+
+                InFlightRequest::WriteAndRead(req, page)
+            }
+        };
+
+        for entry in &entries {
+            // SAFETY: the buffers are owned by the in flight request, which lives until the batch completed
+            unsafe {
+                self.ring.submission().push(entry).expect("batch must not exceed the ring size");
+            }
+        }
+
+        batch.num_entries += entries.len();
+        batch.requests.push(InFlight {
+            request,
+            write_result: None,
+            read_result: None,
+            read_bytes: 0,
+        });
+    }
+
+    fn prepare_page_for_write(&self, page_id: PageId, data: &PageData) -> Box<PageData> {
+        let mut page = Box::new(*data);
+        self.disk_manager.prepare_page_for_write(page_id, &mut page);
+
+        page
+    }
+
+    /// Submit the batch and wait until all its entries completed
+    ///
+    /// When the submit fails, the entries that the kernel did not consume are left without a completion
+    /// and the ring is replaced so they are never submitted.
+    /// When the ring can't be replaced or waited for, the worker is stopped
+    fn submit_and_wait(&mut self, batch: &mut Batch) -> Result<(), DiskError> {
+        let mut remaining = batch.num_entries;
+
+        while remaining > 0 {
+            match self.ring.submit_and_wait(remaining) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    // The submit only fails when no entry was consumed, but entries consumed by an earlier
+                    // submit may still be running, and their buffers must not be released until they complete
+                    let consumed = remaining - self.ring.submission().len();
+                    self.wait_for_consumed(batch, consumed)?;
+
+                    self.ring = IoUring::new(self.queue_depth).map_err(|err| self.stop(err.into()))?;
+
+                    return Err(err.into());
+                }
+            }
+
+            remaining -= self.reap_completions(batch, true);
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the entries that were consumed by the kernel without submitting the rest
+    fn wait_for_consumed(&mut self, batch: &mut Batch, mut consumed: usize) -> Result<(), DiskError> {
+        while consumed > 0 {
+            // SAFETY: nothing is submitted, only waiting for the completions
+            let result = unsafe {
+                self.ring.submitter().enter::<()>(0, consumed as u32, EnterFlags::GETEVENTS.bits(), None)
+            };
+
+            match result {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    // The kernel may still access the pages copied for the writes, so they are never released
+                    for in_flight in &mut batch.requests {
+                        if let InFlightRequest::Write(_, page) | InFlightRequest::WriteAndRead(_, page) = &mut in_flight.request {
+                            std::mem::forget(std::mem::replace(page, Box::new([0u8; PAGE_SIZE])));
+                        }
+                    }
+
+                    return Err(self.stop(err.into()));
+                }
+            }
+
+            consumed -= self.reap_completions(batch, false);
+        }
+
+        Ok(())
+    }
+
+    /// Stop the worker as the ring can no longer be used
+    ///
+    /// returns: DiskError the error of the requests of the batch
+    fn stop(&mut self, err: DiskError) -> DiskError {
+        self.stopped = Some(err.clone());
+
+        err
+    }
+
+    /// Record the completed entries, a short read is resubmitted for the rest of the page when `resubmit` is set,
+    /// otherwise it is left without a result
+    ///
+    /// @return the number of completed entries
+    fn reap_completions(&mut self, batch: &mut Batch, resubmit: bool) -> usize {
+        let fd = self.fd;
+        let completions: Vec<_> = self.ring.completion().map(|completion| (completion.user_data(), completion.result())).collect();
+
+        let mut completed = 0;
+
+        for (user_data, result) in completions {
+            let in_flight = &mut batch.requests[(user_data / 2) as usize];
+
+            if user_data % 2 == WRITE_PART {
+                in_flight.write_result = Some(result);
+                completed += 1;
+
+                continue;
+            }
+
+            // Read failed, or reached the end of the file or the end of the page
+            if result <= 0 || in_flight.read_bytes + result as usize == PAGE_SIZE {
+                in_flight.read_result = Some(if result < 0 { result } else { (in_flight.read_bytes + result as usize) as i32 });
+                completed += 1;
+
+                continue;
+            }
+
+            in_flight.read_bytes += result as usize;
+
+            if !resubmit {
+                completed += 1;
+
+                continue;
+            }
+
+            let read_bytes = in_flight.read_bytes;
+            let (page_id, data) = in_flight.read_target().expect("read completion should belong to a read request");
+            let entry = read_entry_from(fd, page_id, data, read_bytes).user_data(user_data);
+
+            // SAFETY: the buffer is owned by the in flight request, which lives until the batch completed
+            unsafe {
+                self.ring.submission().push(&entry).expect("resubmitted read replaces a completed entry so it fits in the ring");
+            }
+        }
+
+        completed
+    }
+}
+
+fn read_entry(fd: types::Fd, page_id: PageId, data: &mut PageData) -> squeue::Entry {
+    read_entry_from(fd, page_id, data, 0)
+}
+
+/// Read the rest of the page, starting at `start` bytes into the page
+fn read_entry_from(fd: types::Fd, page_id: PageId, data: &mut PageData, start: usize) -> squeue::Entry {
+    opcode::Read::new(fd, data[start..].as_mut_ptr(), (PAGE_SIZE - start) as u32)
+        .offset(DefaultDiskManager::get_page_offset(page_id) + start as u64)
+        .build()
+}
+
+fn write_entry(fd: types::Fd, page_id: PageId, data: &PageData) -> squeue::Entry {
+    opcode::Write::new(fd, data.as_ptr(), PAGE_SIZE as u32)
+        .offset(DefaultDiskManager::get_page_offset(page_id))
+        .build()
+}
+
+/// @return the number of bytes of the completed entry, or the submit error if the entry did not complete
+fn entry_result(result: Option<i32>, submit_error: &Option<DiskError>) -> Result<usize, DiskError> {
+    match result {
+        Some(result) if result < 0 => Err(io::Error::from_raw_os_error(-result).into()),
+        Some(result) => Ok(result as usize),
+        None => Err(submit_error.clone().expect("entry should complete when the batch was submitted")),
+    }
+}
+
+/// Same as the reads of `DefaultDiskManager`, a page past the end of the file is empty
+/// and a page that the file ends in the middle of is truncated
+fn read_result(result: Result<usize, DiskError>, page_id: PageId, data: &mut PageData) -> Result<(), DiskError> {
+    match result? {
+        // Page was never written, so it is empty
+        0 => data.fill(0),
+        PAGE_SIZE => {}
+
+        // The write of the page was cut short
+        read => return Err(DiskError::PageTruncated { page_id, read }),
+    }
+
+    DefaultDiskManager::verify_read_page(page_id, data)
+}
+
+fn write_result(result: Result<usize, DiskError>) -> Result<(), DiskError> {
+    if result? < PAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write the entire page").into());
+    }
+
+    Ok(())
+}
+
+/// Fail the deferred request and the queued requests until the worker is terminated
+fn fail_requests(deferred: Option<DiskRequestType<'static>>, receiver: &Channel<DiskSchedulerWorkerMessage<'static>>, terminate: bool, err: DiskError) {
+    let fail = |request: DiskRequestType<'static>| match request {
+        DiskRequestType::Read(req) => req.callback.set_value(Err(err.clone())),
+        DiskRequestType::Write(req) => req.callback.set_value(Err(err.clone())),
+        DiskRequestType::WriteAndRead(req) => req.callback.set_value(Err(WriteAndReadError::Write(err.clone()))),
+    };
+
+    if let Some(request) = deferred {
+        fail(request);
+    }
+
+    if terminate {
+        return;
+    }
+
+    while let DiskSchedulerWorkerMessage::NewJob(request) = receiver.get() {
+        fail(request);
+    }
+}
+
+fn complete_request(in_flight: InFlight, submit_error: &Option<DiskError>) {
+    let write = || entry_result(in_flight.write_result, submit_error);
+    let read = || entry_result(in_flight.read_result, submit_error);
+
+    match in_flight.request {
+        InFlightRequest::Read(req) => {
+            req.callback.set_value(read_result(read(), req.page_id, req.data));
+        }
+        InFlightRequest::Write(req, _page) => {
+            req.callback.set_value(write_result(write()));
+        }
+        InFlightRequest::WriteAndRead(req, _page) => {
+            // When the write failed the read was cancelled, so only the write error is relevant
+            let result = write_result(write())
+                .map_err(WriteAndReadError::Write)
+                .and_then(|_| read_result(read(), req.source_page_id, req.data).map_err(WriteAndReadError::Read));
+            req.callback.set_value(result);
+        }
+    }
+}
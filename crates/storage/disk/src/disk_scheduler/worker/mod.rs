@@ -1,3 +1,9 @@
 pub mod traits;
 pub mod different_thread;
-pub mod same_thread;
\ No newline at end of file
+pub mod same_thread;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod multi_thread;
+
+#[cfg(target_os = "linux")]
+pub mod io_uring;
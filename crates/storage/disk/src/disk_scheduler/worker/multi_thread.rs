@@ -0,0 +1,315 @@
+use crate::disk_scheduler::disk_request::WriteAndReadDiskRequest;
+use crate::disk_scheduler::worker::traits::{DiskSchedulerWorker, DiskSchedulerWorkerMessage};
+use crate::errors::WriteAndReadError;
+use crate::{DiskManager, DiskRequestType, ReadDiskRequest, WriteDiskRequest};
+use common::abort_process_on_panic;
+use pages::PageId;
+use parking_lot::{Condvar, Mutex};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::thread::{Builder, JoinHandle};
+
+/// Disk scheduler worker that processes the requests using a pool of threads
+///
+/// The requests are queued per page and the pages are picked in elevator order (by page offset),
+/// requests of the same page are executed in the order they were scheduled and never concurrently.
+///
+/// When a page is picked:
+/// - consecutive reads of the page are merged into a single read
+/// - consecutive writes of the page are merged into a single write of the last data, the earlier writes are superseded
+pub(crate) struct MultiThreadDiskSchedulerWorker {
+    threads: Vec<JoinHandle<()>>,
+
+    queue: Arc<RequestQueue>,
+}
+
+impl MultiThreadDiskSchedulerWorker {
+    pub(crate) fn new<D: DiskManager + 'static>(disk_manager: Arc<D>, num_workers: usize) -> Self {
+        assert!(num_workers > 0, "must have at least 1 worker");
+
+        let queue = Arc::new(RequestQueue::default());
+
+        let threads = (0..num_workers)
+            .map(|i| {
+                let queue = queue.clone();
+                let disk_manager = disk_manager.clone();
+
+                Builder::new()
+                    .name(format!("Disk Scheduler {}", i))
+                    .spawn(move || {
+                        abort_process_on_panic();
+
+                        while let Some(job) = queue.wait_for_job() {
+                            let pages = job.get_pages();
+
+                            job.execute(disk_manager.as_ref());
+
+                            queue.finish_job(pages);
+                        }
+                    })
+                    .expect("failed to spawn disk scheduler thread")
+            })
+            .collect();
+
+        MultiThreadDiskSchedulerWorker {
+            threads,
+            queue,
+        }
+    }
+}
+
+impl DiskSchedulerWorker for MultiThreadDiskSchedulerWorker {
+    fn send(&self, message: DiskSchedulerWorkerMessage<'static>) {
+        match message {
+            DiskSchedulerWorkerMessage::Terminate => self.queue.terminate(),
+            DiskSchedulerWorkerMessage::NewJob(request) => self.queue.push(request),
+        }
+    }
+
+    fn stop(&mut self) {
+        self.queue.terminate();
+
+        for thread in self.threads.drain(..) {
+            thread.join().unwrap();
+        }
+    }
+}
+
+#[derive(Default)]
+struct RequestQueue {
+    state: Mutex<QueueState>,
+    cv: Condvar,
+}
+
+impl RequestQueue {
+    fn push(&self, request: DiskRequestType<'static>) {
+        self.state.lock().push(request);
+        self.cv.notify_one();
+    }
+
+    /// Wait until there is a job that can be executed
+    ///
+    /// @return None when terminated and all the requests were taken
+    fn wait_for_job(&self) -> Option<Job> {
+        let mut state = self.state.lock();
+
+        loop {
+            if let Some(job) = state.take_next_job() {
+                return Some(job);
+            }
+
+            if state.terminate && state.requests.is_empty() {
+                return None;
+            }
+
+            // Either the queue is empty or all the queued pages are being processed by other workers
+            self.cv.wait(&mut state);
+        }
+    }
+
+    fn finish_job(&self, pages: [Option<PageId>; 2]) {
+        {
+            let mut state = self.state.lock();
+
+            for page_id in pages.into_iter().flatten() {
+                state.in_progress.remove(&page_id);
+            }
+        }
+
+        // The finished pages may have requests that other workers are waiting for
+        self.cv.notify_all();
+    }
+
+    fn terminate(&self) {
+        self.state.lock().terminate = true;
+        self.cv.notify_all();
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_request_id: u64,
+
+    requests: HashMap<u64, DiskRequestType<'static>>,
+
+    // The requests of each page in the order they were scheduled,
+    // write and read request is in the queue of both of its pages
+    pages: BTreeMap<PageId, VecDeque<u64>>,
+
+    // Pages that a worker is currently executing requests for
+    in_progress: HashSet<PageId>,
+
+    // The elevator position, the next page is the first page from here that can be executed
+    head: PageId,
+
+    terminate: bool,
+}
+
+impl QueueState {
+    fn push(&mut self, request: DiskRequestType<'static>) {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+
+        for page_id in get_request_pages(&request).into_iter().flatten() {
+            self.pages.entry(page_id).or_default().push_back(request_id);
+        }
+
+        self.requests.insert(request_id, request);
+    }
+
+    fn take_next_job(&mut self) -> Option<Job> {
+        let (page_id, request_id) = self.pages.range(self.head..)
+            .chain(self.pages.range(..self.head))
+            .map(|(&page_id, request_ids)| (page_id, request_ids[0]))
+            .find(|&(page_id, request_id)| self.can_execute(page_id, request_id))?;
+
+        let job = match &self.requests[&request_id] {
+            DiskRequestType::Read(_) => Job::Reads(
+                page_id,
+                self.take_from_page_while(page_id, |request| match request {
+                    DiskRequestType::Read(req) => Ok(req),
+                    other => Err(other),
+                }),
+            ),
+            DiskRequestType::Write(_) => Job::Writes(
+                page_id,
+                self.take_from_page_while(page_id, |request| match request {
+                    DiskRequestType::Write(req) => Ok(req),
+                    other => Err(other),
+                }),
+            ),
+            DiskRequestType::WriteAndRead(_) => {
+                let request = self.requests.remove(&request_id).expect("queued request must exist");
+
+                for page_id in get_request_pages(&request).into_iter().flatten() {
+                    self.pop_from_page(page_id);
+                }
+
+                let DiskRequestType::WriteAndRead(req) = request else {
+                    unreachable!("request type was checked")
+                };
+
+                Job::WriteAndRead(req)
+            }
+        };
+
+        for page_id in job.get_pages().into_iter().flatten() {
+            self.in_progress.insert(page_id);
+        }
+
+        self.head = page_id.saturating_add(1);
+
+        Some(job)
+    }
+
+    /// A request can be executed when it is the first request of all of its pages and none of them are in progress
+    fn can_execute(&self, page_id: PageId, request_id: u64) -> bool {
+        if self.in_progress.contains(&page_id) {
+            return false;
+        }
+
+        match &self.requests[&request_id] {
+            DiskRequestType::WriteAndRead(req) => [req.dest_page_id, req.source_page_id].iter().all(|page_id| {
+                !self.in_progress.contains(page_id) && self.pages.get(page_id).and_then(|ids| ids.front()) == Some(&request_id)
+            }),
+            _ => true,
+        }
+    }
+
+    /// Take the requests from the start of the page queue as long as they are of the same type
+    fn take_from_page_while<T>(&mut self, page_id: PageId, get: fn(DiskRequestType<'static>) -> Result<T, DiskRequestType<'static>>) -> Vec<T> {
+        let mut taken = vec![];
+
+        while let Some(&request_id) = self.pages.get(&page_id).and_then(|ids| ids.front()) {
+            let request = self.requests.remove(&request_id).expect("queued request must exist");
+
+            match get(request) {
+                Ok(req) => {
+                    taken.push(req);
+                    self.pop_from_page(page_id);
+                }
+                Err(request) => {
+                    self.requests.insert(request_id, request);
+                    break;
+                }
+            }
+        }
+
+        taken
+    }
+
+    fn pop_from_page(&mut self, page_id: PageId) {
+        let request_ids = self.pages.get_mut(&page_id).expect("page must have queued requests");
+        request_ids.pop_front();
+
+        if request_ids.is_empty() {
+            self.pages.remove(&page_id);
+        }
+    }
+}
+
+/// @return the pages the request accesses
+fn get_request_pages(request: &DiskRequestType) -> [Option<PageId>; 2] {
+    match request {
+        DiskRequestType::Read(req) => [Some(req.page_id), None],
+        DiskRequestType::Write(req) => [Some(req.page_id), None],
+        DiskRequestType::WriteAndRead(req) if req.dest_page_id == req.source_page_id => [Some(req.dest_page_id), None],
+        DiskRequestType::WriteAndRead(req) => [Some(req.dest_page_id), Some(req.source_page_id)],
+    }
+}
+
+enum Job {
+    Reads(PageId, Vec<ReadDiskRequest<'static>>),
+    Writes(PageId, Vec<WriteDiskRequest<'static>>),
+    WriteAndRead(WriteAndReadDiskRequest<'static>),
+}
+
+impl Job {
+    fn get_pages(&self) -> [Option<PageId>; 2] {
+        match self {
+            Job::Reads(page_id, _) | Job::Writes(page_id, _) => [Some(*page_id), None],
+            Job::WriteAndRead(req) if req.dest_page_id == req.source_page_id => [Some(req.dest_page_id), None],
+            Job::WriteAndRead(req) => [Some(req.dest_page_id), Some(req.source_page_id)],
+        }
+    }
+
+    fn execute<D: DiskManager + ?Sized>(self, disk_manager: &D) {
+        match self {
+            Job::Reads(page_id, reads) => {
+                let mut reads = reads.into_iter();
+                let first = reads.next().expect("must have at least 1 read");
+
+                let result = disk_manager.read_page(page_id, first.data.as_mut_slice());
+
+                // Copy to the merged reads before completing the first read, as its data can be released once completed
+                for req in reads {
+                    if result.is_ok() {
+                        req.data.copy_from_slice(first.data);
+                    }
+
+                    req.callback.set_value(result.clone());
+                }
+
+                first.callback.set_value(result);
+            }
+            Job::Writes(page_id, writes) => {
+                let last = writes.last().expect("must have at least 1 write");
+
+                // The earlier writes are superseded, they get the result of the last write as their data is not on disk otherwise
+                let result = disk_manager.write_page(page_id, last.data.as_slice());
+
+                for req in writes {
+                    req.callback.set_value(result.clone());
+                }
+            }
+            Job::WriteAndRead(req) => {
+                // Only read the page if the write was successful
+                // as otherwise, the read will override the data that failed to be written
+                let result = disk_manager.write_page(req.dest_page_id, req.data.as_slice())
+                    .map_err(WriteAndReadError::Write)
+                    .and_then(|_| disk_manager.read_page(req.source_page_id, req.data.as_mut_slice()).map_err(WriteAndReadError::Read));
+                req.callback.set_value(result);
+            }
+        }
+    }
+}
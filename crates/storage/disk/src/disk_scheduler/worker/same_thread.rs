@@ -1,5 +1,6 @@
 use crate::disk_scheduler::worker::traits::{DiskSchedulerWorker, DiskSchedulerWorkerMessage};
 use crate::{DiskManager, DiskRequestType};
+use crate::errors::WriteAndReadError;
 use std::sync::Arc;
 
 pub(crate) struct SameThreadDiskScheduler {
@@ -21,36 +22,34 @@ impl SameThreadDiskScheduler {
 
         match req {
             DiskRequestType::Read(req) => {
-                self.manager.read_page(req.page_id, req.data.as_mut_slice());
-                req.callback.set_value(true);
+                let result = self.manager.read_page(req.page_id, req.data.as_mut_slice());
+                req.callback.set_value(result);
             }
             DiskRequestType::Write(req) => {
-                self.manager.write_page(req.page_id, req.data.as_slice());
-                req.callback.set_value(true);
+                let result = self.manager.write_page(req.page_id, req.data.as_slice());
+                req.callback.set_value(result);
             }
             DiskRequestType::WriteAndRead(req) => {
-                self.manager.write_page(req.dest_page_id, req.data.as_slice());
-
-                // TODO - read page if write was successful
-                //        as otherwise, if the write failed we will read and lose the data
-                self.manager.read_page(req.source_page_id, req.data.as_mut_slice());
-                req.callback.set_value(true);
+                // Only read the page if the write was successful
+                // as otherwise, the read will override the data that failed to be written
+                let result = self.manager.write_page(req.dest_page_id, req.data.as_slice())
+                    .map_err(WriteAndReadError::Write)
+                    .and_then(|_| self.manager.read_page(req.source_page_id, req.data.as_mut_slice()).map_err(WriteAndReadError::Read));
+                req.callback.set_value(result);
             }
         }
     }
 }
 
-impl DiskSchedulerWorker for SameThreadDiskScheduler {
-    fn new<D: DiskManager + 'static>(disk_manager: Arc<D>) -> Self
-    where
-        Self: Sized
-    {
+impl SameThreadDiskScheduler {
+    pub(crate) fn new<D: DiskManager + 'static>(disk_manager: Arc<D>) -> Self {
         Self {
             manager: disk_manager
         }
     }
-    
+}
 
+impl DiskSchedulerWorker for SameThreadDiskScheduler {
     fn send(&self, message: DiskSchedulerWorkerMessage<'static>) {
         self.handle(message);
     }
@@ -1,6 +1,4 @@
-use std::sync::Arc;
-
-use crate::{DiskManager, DiskRequestType};
+use crate::DiskRequestType;
 
 pub(crate) enum DiskSchedulerWorkerMessage<'a> {
     Terminate,
@@ -8,8 +6,6 @@ pub(crate) enum DiskSchedulerWorkerMessage<'a> {
 }
 
 pub trait DiskSchedulerWorker: 'static + Sync + Send {
-    fn new<D: DiskManager + 'static>(disk_manager: Arc<D>) -> Self where Self: Sized;
-
     fn send(&self, message: DiskSchedulerWorkerMessage<'static>);
     
     fn stop(&mut self);
@@ -0,0 +1,79 @@
+use pages::PageId;
+use std::io;
+
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum DiskError {
+    #[error("page {page_id} is corrupted, expected checksum {expected:#010x} but found {actual:#010x}")]
+    PageCorrupted {
+        page_id: PageId,
+        expected: u32,
+        actual: u32,
+    },
+
+    /// The file ends in the middle of the page
+    #[error("page {page_id} is truncated, read only {read} bytes")]
+    PageTruncated {
+        page_id: PageId,
+        read: usize,
+    },
+
+    #[error("page {page_id} could not be decompressed")]
+    PageDecompressionFailed {
+        page_id: PageId,
+    },
+
+    #[error("page {page_id} is outside the pages the compression map can hold")]
+    CompressionMapFull {
+        page_id: PageId,
+    },
+
+    // Keeping the kind and message instead of the io::Error itself as it is not cloneable
+    #[error("I/O error ({kind:?}): {message}")]
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl From<io::Error> for DiskError {
+    fn from(err: io::Error) -> Self {
+        DiskError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Error for a write followed by a read on the same page data buffer
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum WriteAndReadError {
+    /// The write failed and the read was not attempted, so the page data was not changed
+    #[error("failed to write page")]
+    Write(#[source] DiskError),
+
+    /// The write succeeded but the read failed, so the page data is not valid
+    #[error("failed to read page after writing")]
+    Read(#[source] DiskError),
+}
+
+/// Error for a database file that cannot be opened by this build
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum SuperblockError {
+    #[error("not a database file, the superblock magic is missing")]
+    InvalidMagic,
+
+    #[error("unsupported database format version {found}, this build supports version {supported}")]
+    UnsupportedFormatVersion {
+        found: u32,
+        supported: u32,
+    },
+
+    #[error("database was created with page size {found} but this build uses page size {expected}")]
+    PageSizeMismatch {
+        found: u32,
+        expected: u32,
+    },
+
+    #[error("database superblock is corrupted")]
+    Corrupted(#[source] DiskError),
+}
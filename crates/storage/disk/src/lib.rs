@@ -1,5 +1,8 @@
 mod disk_scheduler;
 mod disk_manager;
+pub mod errors;
 
-pub use disk_manager::{DiskManager, DefaultDiskManager, DiskManagerUnlimitedMemory};
+pub use disk_manager::{DiskManager, DefaultDiskManager, DiskManagerUnlimitedMemory, DiskManagerFaultInjection, DiskFault, FaultRule, DiskManagerCompression, SystemRoot};
+#[cfg(not(target_arch = "wasm32"))]
+pub use disk_manager::DiskManagerMmap;
 pub use disk_scheduler::*;
@@ -1,4 +1,5 @@
 use crate::{PageData, PageId, UnderlyingPage, INVALID_PAGE_ID, PAGE_SIZE};
+use common::config::{AtomicLSN, INVALID_LSN, LSN};
 use common::ReaderWriterLatch;
 use parking_lot::{RwLockReadGuard, RwLockWriteGuard};
 use std::ops::{Deref, DerefMut};
@@ -27,6 +28,10 @@ struct PageSend {
 
     // True if the page is dirty, i.e. it is different from its corresponding page on disk.
     is_dirty: AtomicBool,
+
+    // The LSN of the first logged change since the page was last written (recovery LSN), INVALID_LSN if there is none.
+    // Redo must start from it to bring the page on disk up to date
+    rec_lsn: AtomicLSN,
 }
 
 impl Page {
@@ -58,6 +63,7 @@ impl Page {
                 ),
             pin_count: AtomicIsize::new(0),
             is_dirty: AtomicBool::new(false),
+            rec_lsn: AtomicLSN::new(INVALID_LSN),
         }))
     }
 
@@ -98,10 +104,25 @@ impl Page {
         self.0.is_dirty.load(Ordering::SeqCst)
     }
 
+    /// Set the dirty flag, the recovery LSN is cleared when the page is clean as all its changes are on disk
     pub fn set_is_dirty(&self, is_dirty: bool) {
+        if !is_dirty {
+            self.0.rec_lsn.store(INVALID_LSN, Ordering::SeqCst);
+        }
+
         self.0.is_dirty.store(is_dirty, Ordering::SeqCst)
     }
 
+    /// The LSN of the first logged change since the page was last written, `INVALID_LSN` if there is none
+    pub fn get_rec_lsn(&self) -> LSN {
+        self.0.rec_lsn.load(Ordering::SeqCst)
+    }
+
+    /// Record the LSN of a logged change to the page, only the first change since the page was last written is kept
+    pub fn set_rec_lsn_if_unset(&self, lsn: LSN) {
+        let _ = self.0.rec_lsn.compare_exchange(INVALID_LSN, lsn, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
     pub fn read(&self) -> PageReadGuard {
         self.0.inner.read()
     }
@@ -1,4 +1,5 @@
 use std::ops::Deref;
+use parking_lot::RwLockUpgradableReadGuard;
 use crate::{Page, PageReadGuard, PageWriteGuard, UnderlyingPage, PageAndWriteGuard, PageAndGuard};
 
 pub struct PageAndReadGuard<'a>(
@@ -25,6 +26,30 @@ impl<'a> PageAndReadGuard<'a> {
     pub(crate) fn read_guard(self) -> PageReadGuard<'a> {
         self.0
     }
+
+    /// Try to replace the read latch with the write latch,
+    /// succeeds only if no one else holds the latch
+    ///
+    /// The upgradable read latch is taken before the read latch is released, so no writer can take the latch in between
+    /// and the page did not change since it was read, whether the upgrade succeeded or not
+    ///
+    /// returns: Result<PageAndWriteGuard, PageAndReadGuard> the write guard or the read guard when failed
+    ///
+    pub fn try_upgrade(self) -> Result<PageAndWriteGuard<'a>, PageAndReadGuard<'a>> {
+        let PageAndReadGuard(read_guard, page) = self;
+
+        // Only a single upgradable reader is allowed, so another upgrade in progress fails this one
+        let Some(upgradable_guard) = PageReadGuard::rwlock(&read_guard).try_upgradable_read() else {
+            return Err(PageAndReadGuard(read_guard, page));
+        };
+
+        drop(read_guard);
+
+        match RwLockUpgradableReadGuard::try_upgrade(upgradable_guard) {
+            Ok(write_guard) => Ok(PageAndWriteGuard::new(write_guard, page)),
+            Err(upgradable_guard) => Err(PageAndReadGuard(RwLockUpgradableReadGuard::downgrade(upgradable_guard), page)),
+        }
+    }
 }
 
 impl<'a> Deref for PageAndReadGuard<'a> {
@@ -15,6 +15,10 @@ impl<'a> PageAndGuard<'a> for PageAndWriteGuard<'a> {
 }
 
 impl<'a> PageAndWriteGuard<'a> {
+    /// Create from write guard that was taken on the page
+    pub(super) fn new(write_guard: PageWriteGuard<'a>, page: Page) -> Self {
+        PageAndWriteGuard(write_guard, page)
+    }
 
     #[inline(always)]
     #[allow(unused)]
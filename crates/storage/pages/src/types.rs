@@ -6,6 +6,21 @@ pub const INVALID_PAGE_ID: PageId = -1;
 /// Size of a data page in byte
 pub const PAGE_SIZE: usize = 4096;
 
+/// Size of the trailer at the end of every page that is reserved for the disk manager checksum
+pub const PAGE_CHECKSUM_SIZE: usize = 4;
+
+/// Offset of the checksum trailer, the checksum covers everything before it
+pub const PAGE_CHECKSUM_OFFSET: usize = PAGE_SIZE - PAGE_CHECKSUM_SIZE;
+
+/// Size of the page LSN that is stored right before the checksum trailer
+pub const PAGE_LSN_SIZE: usize = 4;
+
+/// Offset of the page LSN, the LSN of the last log record that changed the page
+pub const PAGE_LSN_OFFSET: usize = PAGE_CHECKSUM_OFFSET - PAGE_LSN_SIZE;
+
+/// Number of bytes in a page that page layouts can use (everything before the page LSN and the checksum trailer)
+pub const PAGE_USABLE_SIZE: usize = PAGE_LSN_OFFSET;
+
 
 /// The type of the page id (in cpp it was `page_id_t`)
 pub type PageId = i32;
@@ -1,11 +1,11 @@
 use common::config::{LSN};
 use std::mem::size_of;
-use crate::{PageData, PageId, INVALID_PAGE_ID, PAGE_SIZE};
+use crate::{PageData, PageId, INVALID_PAGE_ID, PAGE_LSN_OFFSET, PAGE_LSN_SIZE, PAGE_SIZE};
 
 //noinspection RsAssertEqual
 const _:() = assert!(size_of::<PageId>() == 4);
 //noinspection RsAssertEqual
-const _:() = assert!(size_of::<LSN>() == 4);
+const _:() = assert!(size_of::<LSN>() == PAGE_LSN_SIZE);
 
 
 /**
@@ -30,8 +30,6 @@ impl UnderlyingPage {
     const SIZE_PAGE_HEADER: usize = 8;
     #[allow(unused)]
     const OFFSET_PAGE_START: usize = 0;
-    #[allow(unused)]
-    const OFFSET_LSN: usize = 4;
 
     pub fn new(page_id: PageId, data: PageData) -> Self {
         UnderlyingPage {
@@ -84,16 +82,22 @@ impl UnderlyingPage {
         self.page_id
     }
 
-    /** @return the page LSN. */
+    /** @return the page LSN, the LSN of the last log record that changed the page */
     pub fn get_lsn(&self) -> LSN {
-        // return *reinterpret_cast<lsn_t *>(GetData() + OFFSET_LSN);
-        LSN::from_ne_bytes(self.data[UnderlyingPage::OFFSET_LSN..UnderlyingPage::OFFSET_LSN + size_of::<LSN>()].try_into().unwrap())
+        LSN::from_le_bytes(self.data[PAGE_LSN_OFFSET..PAGE_LSN_OFFSET + PAGE_LSN_SIZE].try_into().unwrap())
     }
 
     /** Sets the page LSN. */
     pub fn set_lsn(&mut self, lsn: LSN) {
-        self.data[Self::OFFSET_LSN..].copy_from_slice(lsn.to_ne_bytes().as_slice());
-        // memcpy(GetData() + OFFSET_LSN, &lsn, sizeof(lsn_t));
+        self.data[PAGE_LSN_OFFSET..PAGE_LSN_OFFSET + PAGE_LSN_SIZE].copy_from_slice(lsn.to_le_bytes().as_slice());
+    }
+
+    /// Set the page LSN unless the page was already changed by a later log record,
+    /// used by changes that are not logged by themselves to order them after a log record of another page (e.g. index entries of a table change)
+    pub fn set_lsn_if_greater(&mut self, lsn: LSN) {
+        if lsn > self.get_lsn() {
+            self.set_lsn(lsn);
+        }
     }
 
     /// Clear page id and data so it will be like a new page
@@ -4,8 +4,9 @@ use buffer_common::AccessType;
 use pages::{PageId, INVALID_PAGE_ID};
 use buffer_pool_manager::{errors, BufferPool, BufferPoolManager, PageReadGuard, PageWriteGuard};
 use buffer_pool_manager::errors::MapErrorToBufferPoolError;
-use common::config::TableOID;
+use common::config::{TableOID, LSN};
 use lock_manager::{LockManager};
+use recovery_log_manager::{LogRecord, LogRecordBody};
 use rid::RID;
 use transaction::Transaction;
 use tuple::{Tuple, TupleMeta};
@@ -53,23 +54,22 @@ impl TableHeap {
     /// * `meta`: tuple meta
     /// * `tuple`: tuple to insert
     /// * `lock_mgr`:
-    /// * `txn`:
+    /// * `txn`: the transaction that inserts the tuple, the insert is logged as part of it
     /// * `oid`:
     ///
-    /// returns: Option<RID> the rid of the inserted tuple
+    /// returns: Result<Option<RID>, BufferPoolError> the rid of the inserted tuple or error if failed to fetch or create the table pages
     pub fn insert_tuple(&self,
                         meta: &TupleMeta,
                         tuple: &Tuple,
                         #[allow(unused_variables)]
                         lock_mgr: &Option<Arc<LockManager>>,
-                        #[allow(unused_variables)]
                         txn: &Arc<Transaction>,
                         #[allow(unused_variables)]
                         oid: Option<TableOID>,
-    ) -> Option<RID> {
+    ) -> Result<Option<RID>, errors::BufferPoolError> {
         // Tuple size is too big
         if tuple.get_length() as usize >= LARGEST_TUPLE_SIZE_WITHOUT_OVERFLOW {
-            return None;
+            return Ok(None);
         }
 
         let bpm = self.bpm.as_ref().unwrap();
@@ -78,7 +78,7 @@ impl TableHeap {
         let oid = oid.unwrap_or(0);
 
         let mut last_page_id_guard = self.last_page_id.lock();
-        let mut page_guard = bpm.fetch_page_write(*last_page_id_guard, AccessType::Unknown).expect("Should fetch page");
+        let mut page_guard = bpm.fetch_page_write(*last_page_id_guard, AccessType::Unknown).map_err_to_buffer_pool_err()?;
         loop {
             let page = page_guard.cast_mut::<TablePage>();
             if let Some(_) = page.get_next_tuple_offset(meta, tuple) {
@@ -88,12 +88,18 @@ impl TableHeap {
             // if there's no tuple in the page, and we can't insert the tuple, then this tuple is too large.
             assert_ne!(page.get_num_tuples(), 0, "tuple is too large, cannot insert");
 
-            let mut npg = bpm.new_page(AccessType::Unknown).expect("cannot allocate page");
+            let mut npg = bpm.new_page(AccessType::Unknown).map_err_to_buffer_pool_err()?;
             page.set_next_page_id(npg.get_page_id());
 
             let next_page = npg.cast_mut::<TablePage>();
             next_page.init();
 
+            // Both pages changed, the previous page points to the new page
+            if let Some(lsn) = self.append_log_record(txn, LogRecordBody::NewPage { prev_page_id: page_guard.get_page_id(), page_id: npg.get_page_id() }) {
+                page_guard.set_lsn(lsn);
+                npg.set_lsn(lsn);
+            }
+
             drop(page_guard);
 
             // acquire latch here as TSAN complains. Given we only have one insertion thread, this is fine.
@@ -104,7 +110,10 @@ impl TableHeap {
         }
 
         let page = page_guard.cast_mut::<TablePage>();
-        let slot_id = page.insert_tuple(meta, tuple)?;
+        let slot_id = match page.insert_tuple(meta, tuple) {
+            Some(slot_id) => slot_id,
+            None => return Ok(None),
+        };
 
         // only allow one insertion at a time, otherwise it will deadlock.
         let last_page_id = *last_page_id_guard;
@@ -112,6 +121,10 @@ impl TableHeap {
 
         let rid = RID::new(last_page_id, slot_id as u32);
 
+        if let Some(lsn) = self.append_log_record(txn, LogRecordBody::Insert { rid, tuple: tuple.clone() }) {
+            page_guard.set_lsn(lsn);
+        }
+
         #[cfg(feature = "lock_manager")]
         if let Some(lock_manager) = lock_mgr {
             assert!(
@@ -120,7 +133,7 @@ impl TableHeap {
             )
         }
 
-        Some(rid)
+        Ok(Some(rid))
     }
 
     /// Update the meta of a tuple.
@@ -143,13 +156,27 @@ impl TableHeap {
     ///
     /// * `meta`: the new tuple meta
     /// * `rid`: the rid to for the tuple
+    /// * `txn`: the transaction that deletes the tuple, the delete is logged as part of it
     ///
     /// returns: `bool` return whether the tuple was deleted (false if it was already deleted or not found)
     ///
     ///
-    pub fn mark_tuple_as_deleted(&self, meta: &TupleMeta, rid: &RID) -> bool {
+    pub fn mark_tuple_as_deleted(&self, meta: &TupleMeta, rid: &RID, txn: &Transaction) -> bool {
         let mut page_guard = self.bpm.as_ref().expect("must have bpm").fetch_page_write(rid.get_page_id(), AccessType::Unknown).expect("should fetch page");
-        page_guard.cast_mut::<TablePage>().mark_tuple_as_deleted(meta, rid)
+        let page = page_guard.cast_mut::<TablePage>();
+
+        let (_, tuple) = page.get_tuple(rid);
+
+        if !page.mark_tuple_as_deleted(meta, rid) {
+            // Nothing changed, undoing a delete that was not done would restore the tuple deleted by another transaction
+            return false;
+        }
+
+        if let Some(lsn) = self.append_log_record(txn, LogRecordBody::MarkDelete { rid: *rid, tuple }) {
+            page_guard.set_lsn(lsn);
+        }
+
+        true
     }
 
     /**
@@ -158,9 +185,14 @@ impl TableHeap {
      * @return the meta and tuple
      */
     pub fn get_tuple(&self, rid: &RID) -> (TupleMeta, Tuple) {
+        self.get_tuple_with_access_type(rid, AccessType::Unknown)
+    }
+
+    /// Same as `get_tuple`, the access type is passed to the buffer pool (e.g. scan to read ahead the next pages)
+    pub(crate) fn get_tuple_with_access_type(&self, rid: &RID, access_type: AccessType) -> (TupleMeta, Tuple) {
         // TODO - return result
 
-        let page_guard = self.bpm.as_ref().expect("must have bpm").fetch_page_read(rid.get_page_id(), AccessType::Unknown).expect("should fetch page");
+        let page_guard = self.bpm.as_ref().expect("must have bpm").fetch_page_read(rid.get_page_id(), access_type).expect("should fetch page");
         let page = page_guard.cast::<TablePage>();
 
         let (meta, mut tuple) = page.get_tuple(rid);
@@ -218,9 +250,10 @@ impl TableHeap {
      * @param meta new tuple meta
      * @param tuple  new tuple
      * @param rid the rid of the tuple to be updated
+     * @param txn the transaction that updates the tuple, the update is logged as part of it
      * @param check the check to run before actually update.
      */
-    pub unsafe fn update_tuple_in_place<CheckFn: Fn(&TupleMeta, &Tuple, &RID) -> bool>(&self, meta: &TupleMeta, tuple: &Tuple, rid: &RID, check: Option<CheckFn>) -> bool {
+    pub unsafe fn update_tuple_in_place<CheckFn: Fn(&TupleMeta, &Tuple, &RID) -> bool>(&self, meta: &TupleMeta, tuple: &Tuple, rid: &RID, txn: &Transaction, check: Option<CheckFn>) -> bool {
 
         // TODO - return result
         let mut page_guard = self.bpm.as_ref().expect("must have bpm").fetch_page_write(rid.get_page_id(), AccessType::Unknown).expect("should fetch page");
@@ -232,11 +265,29 @@ impl TableHeap {
 
         if valid {
             page.update_tuple_in_place(meta, tuple, rid);
+
+            if let Some(lsn) = self.append_log_record(txn, LogRecordBody::Update { rid: *rid, old_tuple: old_tup, new_tuple: tuple.clone() }) {
+                page_guard.set_lsn(lsn);
+            }
         }
 
         valid
     }
 
+    /// Append the log record of a change to this table as the last log record of the transaction
+    ///
+    /// Must be called while holding the write guard of the changed page, so the page LSN is set before the page can be written
+    ///
+    /// returns: Option<LSN> the LSN to set on the changed pages, `None` when the buffer pool has no log manager
+    fn append_log_record(&self, txn: &Transaction, body: LogRecordBody) -> Option<LSN> {
+        let log_manager = self.bpm.as_ref()?.get_log_manager()?;
+
+        let mut log_record = LogRecord::new(txn.get_transaction_id(), txn.get_prev_lsn(), body);
+
+        // A checkpoint that begins after the log record must see it as the last log record of the transaction
+        Some(log_manager.append_log_record_with(&mut log_record, |lsn, _| txn.set_prev_lsn(lsn)))
+    }
+
     /** For binder tests */
     pub fn create_empty_heap(create_table_heap: bool) -> Arc<TableHeap> {
         // The input parameter should be false in order to generate a empty heap
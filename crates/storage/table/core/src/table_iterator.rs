@@ -43,7 +43,7 @@ impl Iterator for TableIterator<'_> {
                 return None;
             }
 
-            item = self.table_heap.get_tuple(&self.rid);
+            item = self.table_heap.get_tuple_with_access_type(&self.rid, AccessType::Scan);
 
             if !item.0.is_deleted {
                 break;
@@ -55,7 +55,7 @@ impl Iterator for TableIterator<'_> {
             self.rid.set_slot_num(next_tuple_id);
         }
 
-        let page_guard = self.table_heap.bpm.as_ref().expect("Must have BPM").fetch_page_read(self.rid.get_page_id(), AccessType::Unknown).expect("Must be able to fetch page");
+        let page_guard = self.table_heap.bpm.as_ref().expect("Must have BPM").fetch_page_read(self.rid.get_page_id(), AccessType::Scan).expect("Must be able to fetch page");
         let page = page_guard.cast::<TablePage>();
         let next_tuple_id = self.rid.get_slot_num() + 1;
 
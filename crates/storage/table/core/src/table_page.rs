@@ -1,5 +1,5 @@
 use std::fmt::{Debug, Formatter};
-use pages::{PageId, INVALID_PAGE_ID, PAGE_SIZE};
+use pages::{PageId, INVALID_PAGE_ID, PAGE_USABLE_SIZE};
 use rid::RID;
 use tuple::{Tuple, TupleMeta};
 
@@ -7,13 +7,13 @@ type TupleInfo = (u16, u16, TupleMeta);
 
 const TABLE_PAGE_HEADER_SIZE: usize = 8;
 const TUPLE_INFO_SIZE: usize = 24;
-const TABLE_PAGE_DATA_WITHOUT_HEADER: usize = PAGE_SIZE - TABLE_PAGE_HEADER_SIZE;
+const TABLE_PAGE_DATA_WITHOUT_HEADER: usize = PAGE_USABLE_SIZE - TABLE_PAGE_HEADER_SIZE;
 
 //noinspection RsAssertEqual
 const _: () = {
     assert!(size_of::<PageId>() == 4);
     assert!(size_of::<TupleInfo>() == TUPLE_INFO_SIZE);
-    assert!(size_of::<TablePage>() == PAGE_SIZE);
+    assert!(size_of::<TablePage>() == PAGE_USABLE_SIZE);
 };
 
 // This is the maximum length of a tuple that can be inserted in a table page when the table page is empty
@@ -22,9 +22,9 @@ pub const LARGEST_TUPLE_SIZE_WITHOUT_OVERFLOW: usize = TABLE_PAGE_DATA_WITHOUT_H
 
 /// ```plain
 /// Slotted page format:
-///  ---------------------------------------------------------
-///  | HEADER | ... FREE SPACE ... | ... INSERTED TUPLES ... |
-///  ---------------------------------------------------------
+///  ------------------------------------------------------------------------
+///  | HEADER | ... FREE SPACE ... | ... INSERTED TUPLES ... | CHECKSUM (4) |
+///  ------------------------------------------------------------------------
 ///                                ^
 ///                                free space pointer
 ///
@@ -62,13 +62,13 @@ impl TablePage {
     }
 
     /** @return number of tuples in this page */
-    pub(crate) fn get_num_tuples(&self) -> u32 { self.num_tuples as u32 }
+    pub fn get_num_tuples(&self) -> u32 { self.num_tuples as u32 }
 
     /** @return the page ID of the next table page */
-    pub(crate) fn get_next_page_id(&self) -> PageId { self.next_page_id }
+    pub fn get_next_page_id(&self) -> PageId { self.next_page_id }
 
     /** Set the page id of the next page in the table. */
-    pub(crate) fn set_next_page_id(&mut self, next_page_id: PageId) { self.next_page_id = next_page_id; }
+    pub fn set_next_page_id(&mut self, next_page_id: PageId) { self.next_page_id = next_page_id; }
 
     /** Get the next offset to insert, return nullopt if this tuple cannot fit in this page */
     pub(crate) fn get_next_tuple_offset(&self, _meta: &TupleMeta, tuple: &Tuple) -> Option<usize> {
@@ -115,11 +115,13 @@ impl TablePage {
 
         assert!(tuple_id < self.num_tuples as u32, "Tuple ID out of range");
         let (offset, size, old_meta) = unsafe {
-            self.get_tuple_info(self.num_tuples as usize - 1)
+            self.get_tuple_info(tuple_id as usize)
         };
 
         if !old_meta.is_deleted && meta.is_deleted {
             self.num_deleted_tuples += 1;
+        } else if old_meta.is_deleted && !meta.is_deleted {
+            self.num_deleted_tuples -= 1;
         }
 
         unsafe {
@@ -137,7 +139,7 @@ impl TablePage {
 
         assert!(tuple_id < self.num_tuples as u32, "Tuple ID out of range");
         let (offset, size, old_meta) = unsafe {
-            self.get_tuple_info(self.num_tuples as usize - 1)
+            self.get_tuple_info(tuple_id as usize)
         };
 
         let deleted = !old_meta.is_deleted && meta.is_deleted;
@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(about, long_about = None)]
@@ -37,4 +37,60 @@ pub struct Args {
     /// LRU-K size
     #[arg(long, default_value_t = 16)]
     pub lru_k_size: usize,
+
+    /// Eviction policy of the buffer pool
+    #[arg(long, value_enum, default_value_t = EvictionPolicyType::LruK)]
+    pub policy: EvictionPolicyType,
+
+    /// Use a file on disk with the io_uring disk scheduler with the given queue depth
+    /// instead of the in memory disk manager
+    #[arg(long)]
+    pub io_uring_queue_depth: Option<u32>,
+
+    /// Number of disk scheduler worker threads, uses the multi threaded disk scheduler when set
+    #[arg(long, conflicts_with = "io_uring_queue_depth")]
+    pub disk_scheduler_workers: Option<usize>,
+
+    /// Disk manager that stores the pages
+    #[arg(long, value_enum, default_value_t = DiskManagerType::Memory, conflicts_with = "io_uring_queue_depth")]
+    pub disk_manager: DiskManagerType,
+
+    /// Number of pages to read ahead after each scanned page (0 to disable)
+    #[arg(long, default_value_t = 0)]
+    pub read_ahead_window: usize,
+
+    /// Number of frames that scans replace instead of evicting from the entire pool (0 to disable)
+    #[arg(long, default_value_t = 0)]
+    pub scan_ring_size: usize,
+
+    /// Run the background writer of dirty pages every n milliseconds
+    #[arg(long)]
+    pub background_writer_interval: Option<u64>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum DiskManagerType {
+    /// Pages are kept in memory
+    Memory,
+
+    /// Pages are read and written to a file on disk
+    Default,
+
+    /// The file on disk is mapped to memory
+    Mmap,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum EvictionPolicyType {
+    /// LRU-K with the K from --lru-k-size
+    LruK,
+
+    /// CLOCK (second chance)
+    Clock,
+
+    /// 2Q with the recommended queue sizes
+    TwoQ,
+
+    /// Adaptive Replacement Cache
+    Arc,
 }
@@ -1,7 +1,7 @@
-use crate::cli::Args;
+use crate::cli::{Args, DiskManagerType, EvictionPolicyType};
 use crate::metrics::bpm_metrics::BpmMetrics;
 use crate::page_process::{check_page_consistent, check_page_consistent_no_seed, modify_page};
-use buffer_pool_manager::{BufferPool, BufferPoolManager};
+use buffer_pool_manager::{BackgroundWriterOptions, BufferPool, BufferPoolManager, BACKGROUND_WRITER_CLEAN_FRAMES_PERCENT, BACKGROUND_WRITER_MAX_PAGES_PER_ROUND};
 use buffer_common::AccessType;
 
 use clap::Parser;
@@ -14,8 +14,9 @@ use std::process::abort;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
-#[allow(unused)]
-use disk_storage::{DefaultDiskManager, DiskManagerUnlimitedMemory};
+use std::time::Duration;
+use disk_storage::{DefaultDiskManager, DiskManager, DiskManagerMmap, DiskManagerUnlimitedMemory, DiskScheduler};
+use eviction_policy::TwoQOptions;
 use tempdir::TempDir;
 
 #[cfg(feature = "tracing")]
@@ -185,6 +186,13 @@ mod metrics;
 // get: 261890.90363654544
 // >>> END
 
+fn create_disk_scheduler<D: DiskManager>(disk_manager: D, num_workers: Option<usize>) -> DiskScheduler {
+    match num_workers {
+        Some(num_workers) => DiskScheduler::new_multi_threaded(Arc::new(disk_manager), num_workers),
+        None => DiskScheduler::new(Arc::new(disk_manager)),
+    }
+}
+
 fn setup() -> TempDir {
     TempDir::new("bpm_bench").expect("Should create tmp directory")
 }
@@ -205,23 +213,52 @@ fn main() {
     let bustub_bpm_size = args.bpm_size;
     let lru_k_size = args.lru_k_size;
 
-    println!("[info] total_page={}, duration_ms={}, latency={}, lru_k_size={}, bpm_size={}, scan_thread_cnt={}, get_thread_cnt={}",
-             bustub_page_cnt, duration_ms, enable_latency, lru_k_size, bustub_bpm_size, scan_thread_n, get_thread_n
+    println!("[info] total_page={}, duration_ms={}, latency={}, policy={:?}, lru_k_size={}, bpm_size={}, scan_thread_cnt={}, get_thread_cnt={}",
+             bustub_page_cnt, duration_ms, enable_latency, args.policy, lru_k_size, bustub_bpm_size, scan_thread_n, get_thread_n
     );
 
 
     let tmpdir = setup();
 
-    #[allow(unused)]
     let db_name = tmpdir.path().join("test.db");
 
 
-    let bpm = BufferPoolManager::builder()
+    let disk_scheduler = match args.disk_manager {
+        DiskManagerType::Memory => create_disk_scheduler(DiskManagerUnlimitedMemory::new(), args.disk_scheduler_workers),
+        DiskManagerType::Default => create_disk_scheduler(DefaultDiskManager::new(db_name.clone()).expect("should create disk manager"), args.disk_scheduler_workers),
+        DiskManagerType::Mmap => create_disk_scheduler(DiskManagerMmap::new(db_name.clone()).expect("should create disk manager"), args.disk_scheduler_workers),
+    };
+
+    let mut bpm_builder = BufferPoolManager::builder()
         .with_pool_size(bustub_bpm_size)
-        .with_disk_manager(DiskManagerUnlimitedMemory::new())
-        // .with_disk_manager(DefaultDiskManager::new(db_name).expect("should create disk manager"))
-        .with_lru_k_eviction_policy(lru_k_size)
-        .build_arc();
+        .with_disk_scheduler(disk_scheduler)
+        .with_read_ahead_window(args.read_ahead_window)
+        .with_scan_ring_size(args.scan_ring_size);
+
+    bpm_builder = match args.policy {
+        EvictionPolicyType::LruK => bpm_builder.with_lru_k_eviction_policy(lru_k_size),
+        EvictionPolicyType::Clock => bpm_builder.with_clock_eviction_policy(),
+        EvictionPolicyType::TwoQ => bpm_builder.with_two_q_eviction_policy(TwoQOptions::default()),
+        EvictionPolicyType::Arc => bpm_builder.with_arc_eviction_policy(),
+    };
+
+    if let Some(queue_depth) = args.io_uring_queue_depth {
+        let disk_manager = DefaultDiskManager::new(db_name).expect("should create disk manager");
+
+        bpm_builder = bpm_builder.with_disk_scheduler(
+            DiskScheduler::new_io_uring(Arc::new(disk_manager), queue_depth).expect("should create io_uring disk scheduler")
+        );
+    }
+
+    let bpm = bpm_builder.build_arc();
+
+    if let Some(interval) = args.background_writer_interval {
+        bpm.start_background_writer(BackgroundWriterOptions::new(
+            Duration::from_millis(interval),
+            BACKGROUND_WRITER_MAX_PAGES_PER_ROUND,
+            BACKGROUND_WRITER_CLEAN_FRAMES_PERCENT,
+        ));
+    }
     let page_ids: Arc<RwLock<Vec<PageId>>> = Arc::new(RwLock::new(vec![]));
 
     init_pages(bustub_page_cnt, &bpm, &page_ids);
@@ -367,6 +404,11 @@ fn main() {
 
     total_metrics.lock().report();
 
+    if args.read_ahead_window > 0 {
+        let read_ahead_stats = bpm.get_read_ahead_stats();
+        println!("[info] read ahead hits={}, misses={}", read_ahead_stats.get_hits(), read_ahead_stats.get_misses());
+    }
+
     #[cfg(feature = "statistics")]
     {
         println!("\n\n");
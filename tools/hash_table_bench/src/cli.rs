@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(about, long_about = None)]
@@ -23,6 +23,10 @@ pub struct Args {
     #[arg(long, default_value_t = 4)]
     pub lru_k_size: usize,
 
+    /// Eviction policy of the buffer pool
+    #[arg(long, value_enum, default_value_t = EvictionPolicyType::LruK)]
+    pub policy: EvictionPolicyType,
+
     /// Number of keys in the hash table
     #[arg(long, default_value_t = 100_000)]
     pub total_keys: usize,
@@ -30,5 +34,34 @@ pub struct Args {
     #[arg(long, default_value_t = 2048)]
     pub key_modify_range: usize,
 
+    /// Disk manager that stores the pages
+    #[arg(long, value_enum, default_value_t = DiskManagerType::Memory)]
+    pub disk_manager: DiskManagerType,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum DiskManagerType {
+    /// Pages are kept in memory
+    Memory,
+
+    /// Pages are read and written to a file on disk
+    Default,
+
+    /// The file on disk is mapped to memory
+    Mmap,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum EvictionPolicyType {
+    /// LRU-K with the K from --lru-k-size
+    LruK,
+
+    /// CLOCK (second chance)
+    Clock,
+
+    /// 2Q with the recommended queue sizes
+    TwoQ,
 
+    /// Adaptive Replacement Cache
+    Arc,
 }
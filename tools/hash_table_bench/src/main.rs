@@ -1,15 +1,17 @@
-use crate::cli::Args;
+use crate::cli::{Args, DiskManagerType, EvictionPolicyType};
 use crate::metrics::metrics::Metrics;
 use buffer_pool_manager::{BufferPoolManager};
 
 use clap::Parser;
-use disk_storage::DiskManagerUnlimitedMemory;
+use disk_storage::{DefaultDiskManager, DiskManagerMmap, DiskManagerUnlimitedMemory};
+use eviction_policy::TwoQOptions;
 use pages::PageId;
 use parking_lot::{Mutex};
 use rand::distributions::Distribution;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
+use tempdir::TempDir;
 
 use crate::metrics::total_metrics::TotalMetrics;
 use catalog_schema::Schema;
@@ -75,15 +77,28 @@ fn main() {
     let total_keys = args.total_keys;
     let key_modify_range = args.key_modify_range;
 
-    println!("[info] total_page={}, duration_ms={}, lru_k_size={}, bpm_size={}, write_thread_n={}, read_thread_n={}, key_modify_range={}",
-             total_keys, duration_ms, lru_k_size, bustub_bpm_size, write_thread_n, read_thread_n, key_modify_range
+    println!("[info] total_page={}, duration_ms={}, policy={:?}, lru_k_size={}, bpm_size={}, write_thread_n={}, read_thread_n={}, key_modify_range={}",
+             total_keys, duration_ms, args.policy, lru_k_size, bustub_bpm_size, write_thread_n, read_thread_n, key_modify_range
     );
 
-    let bpm = BufferPoolManager::builder()
-        .with_pool_size(bustub_bpm_size)
-        .with_disk_manager(DiskManagerUnlimitedMemory::new())
-        .with_lru_k_eviction_policy(lru_k_size)
-        .build_arc();
+    let tmpdir = TempDir::new("hash_table_bench").expect("Should create tmp directory");
+    let db_name = tmpdir.path().join("test.db");
+
+    let bpm_builder = BufferPoolManager::builder()
+        .with_pool_size(bustub_bpm_size);
+
+    let bpm_builder = match args.policy {
+        EvictionPolicyType::LruK => bpm_builder.with_lru_k_eviction_policy(lru_k_size),
+        EvictionPolicyType::Clock => bpm_builder.with_clock_eviction_policy(),
+        EvictionPolicyType::TwoQ => bpm_builder.with_two_q_eviction_policy(TwoQOptions::default()),
+        EvictionPolicyType::Arc => bpm_builder.with_arc_eviction_policy(),
+    };
+
+    let bpm = match args.disk_manager {
+        DiskManagerType::Memory => bpm_builder.with_disk_manager(DiskManagerUnlimitedMemory::new()),
+        DiskManagerType::Default => bpm_builder.with_disk_manager(DefaultDiskManager::new(db_name).expect("should create disk manager")),
+        DiskManagerType::Mmap => bpm_builder.with_disk_manager(DiskManagerMmap::new(db_name).expect("should create disk manager")),
+    }.build_arc();
 
     let key_schema = Schema::parse_create_statement("a bigint").expect("Should create schema");
     let comparator = GenericComparator::<8>::from(key_schema);
@@ -29,7 +29,13 @@ fn main() -> rustyline::Result<()> {
 
     let shell = get_shell(&args)?;
 
-    let mut bustub = BustubInstance::from_file(PathBuf::from("test.db"), None);
+    let mut bustub = match BustubInstance::from_file(PathBuf::from("test.db"), None) {
+        Ok(bustub) => bustub,
+        Err(err) => {
+            eprintln!("{:?}", err);
+            std::process::exit(1);
+        }
+    };
 
     bustub.generate_mock_table();
 